@@ -1,6 +1,8 @@
 from __future__ import annotations
 
-from typing import Any, Literal, Optional, TypedDict, Union
+from typing import Literal, Optional, TypedDict, TypeVar, Union
+
+_T = TypeVar("_T")
 
 class TimeRange:
     def __init__(self, duration: float, start_time: float = 0.0) -> None: ...
@@ -10,16 +12,114 @@ class TimeRange:
     def set_start_time(self, value: float) -> None: ...
     def __str__(self) -> str: ...
 
-TrackKind = Literal["video", "audio", "other"]
-OverlapPolicy = Literal["override", "push"]
-InsertPolicy = Literal[
-    "split_and_insert", "insert_before", "insert_after", "insert_before_or_after"
+class TrackKind:
+    VIDEO: TrackKind
+    AUDIO: TrackKind
+    OTHER: TrackKind
+    @property
+    def value(self) -> Literal["video", "audio", "other"]: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class OverlapPolicy:
+    OVERRIDE: OverlapPolicy
+    PUSH: OverlapPolicy
+
+class InsertPolicy:
+    SPLIT_AND_INSERT: InsertPolicy
+    INSERT_BEFORE: InsertPolicy
+    INSERT_AFTER: InsertPolicy
+    INSERT_BEFORE_OR_AFTER: InsertPolicy
+
+# Enum members or their string names; unknown strings raise ValueError.
+TrackKindLike = Union[TrackKind, Literal["video", "audio", "other"]]
+OverlapPolicyLike = Union[OverlapPolicy, Literal["override", "push"]]
+InsertPolicyLike = Union[
+    InsertPolicy,
+    Literal["split_and_insert", "insert_before", "insert_after", "insert_before_or_after"],
 ]
 
+class SyncedInsertResult:
+    primary_clip_id: str
+    audio_clips: list[tuple[str, int]]
+    linked_video_clip_id: Optional[str]
+    link_group_id: Optional[int]
+    created_track_indices: list[int]
+
+class ItemInRange:
+    item_index: int
+    item: Item
+    start: float
+    end: float
+    overlap_start: float
+    overlap_end: float
+
+class Clipboard:
+    def __len__(self) -> int: ...
+    def duration(self) -> float: ...
+    @staticmethod
+    def parse_json(s: str) -> Clipboard: ...
+    def to_json(self) -> str: ...
+
+class StackIndex:
+    def items_in_range(
+        self, start: float, end: float, track_ids: Optional[list[str]] = None
+    ) -> list[tuple[int, ItemInRange]]: ...
+    def track_items_in_range(
+        self, track_index: int, start: float, end: float
+    ) -> list[ItemInRange]: ...
+    def item_at_time(self, track_index: int, time: float) -> Optional[int]: ...
+    def start_time_of_item(self, track_index: int, item_index: int) -> Optional[float]: ...
+    def track_duration(self, track_index: int) -> Optional[float]: ...
+
 class SyncTrackInfo(TypedDict):
     track_indices: list[int]
     track_ids: list[Optional[str]]
 
+class LinkMemberDrift(TypedDict):
+    item_id: Optional[str]
+    track_index: int
+    timeline_offset: float
+    source_offset: float
+    timeline_offset_frames: float
+    source_offset_frames: float
+
+class LinkGroupDrift(TypedDict):
+    link_group_id: int
+    anchor_item_id: Optional[str]
+    in_sync: bool
+    members: list[LinkMemberDrift]
+
+class DeletedClipInfo(TypedDict):
+    clip_id: str
+    link_group_id: Optional[int]
+
+class SplitClipInfo(TypedDict):
+    old_clip_id: str
+    left_clip_id: Optional[str]
+    right_clip_id: Optional[str]
+    link_group_id: Optional[int]
+    split_time: float
+
+class EditPreview(TypedDict):
+    success: bool
+    deleted_clips: list[DeletedClipInfo]
+    split_clips: list[SplitClipInfo]
+    trimmed_clip_ids: list[str]
+    pushed_clip_ids: list[str]
+    moved_link_partner_ids: list[str]
+    created_track_indices: list[int]
+
+class Snap(TypedDict, total=False):
+    start: float
+    time: float
+    edge_offset: float
+    target: Literal["clip_start", "clip_end", "playhead", "marker"]
+    track_index: int
+    item_index: int
+    item_id: Optional[str]
+    marker_index: int
+
 class MediaReference:
     def __init__(
         self,
@@ -175,16 +275,33 @@ class Track:
     def set_name(self, value: Optional[str]) -> None: ...
     def __init__(
         self,
-        kind: Optional[TrackKind] = None,
+        kind: Optional[TrackKindLike] = None,
         id: Optional[str] = None,
         children: Optional[list[Item]] = None,
     ) -> None: ...
     def get_enabled(self) -> bool: ...
     def set_enabled(self, value: bool) -> None: ...
+    def get_locked(self) -> bool: ...
+    def set_locked(self, value: bool) -> None: ...
+    def get_sync_locked(self) -> bool: ...
+    def set_sync_locked(self, value: bool) -> None: ...
+    def get_solo(self) -> bool: ...
+    def set_solo(self, value: bool) -> None: ...
+    def get_muted(self) -> bool: ...
+    def set_muted(self, value: bool) -> None: ...
+    def get_height(self) -> Optional[float]: ...
+    def set_height(self, value: Optional[float] = None) -> None: ...
+    def get_effects(self) -> list[Effect]: ...
+    def set_effects(self, value: list[Effect]) -> None: ...
+    def get_volume(self) -> float: ...
+    def set_volume(self, volume: float) -> None: ...
+    def get_pan(self) -> float: ...
+    def set_pan(self, pan: float) -> None: ...
+    def effective_volume(self, item_index: int) -> Optional[float]: ...
     @property
     def kind(self) -> TrackKind: ...
     @kind.setter
-    def kind(self, value: TrackKind) -> None: ...
+    def kind(self, value: TrackKindLike) -> None: ...
     def items(self) -> list[Item]: ...
     def get_items(self) -> list[Item]: ...
     def timeline_ids(self) -> list[str]: ...
@@ -193,6 +310,7 @@ class Track:
     def get_id(self) -> Optional[str]: ...
     def total_duration(self) -> float: ...
     def start_time_of_item(self, index: int) -> float: ...
+    def items_in_range(self, start: float, end: float) -> list[ItemInRange]: ...
     def get_metadata_json(self) -> str: ...
     def __str__(self) -> str: ...
 
@@ -209,9 +327,16 @@ class Stack:
     def delete_track(self, id: str) -> Optional[Track]: ...
     def set_tracks(self, tracks: list[Track]) -> None: ...
     def sanitize(self) -> None: ...
+    def is_track_rendered(self, track_index: int) -> bool: ...
+    def effective_volume(self, item_id: str) -> Optional[float]: ...
+    def transaction(self) -> StackTransaction: ...
     def get_track_by_id(self, id: str) -> Optional[tuple[int, Track]]: ...
     def get_track_by_name(self, name: str) -> Optional[tuple[int, Track]]: ...
     def find_tracks_by_name(self, name: str) -> list[tuple[int, Track]]: ...
+    def items_in_range(
+        self, start: float, end: float, track_ids: Optional[list[str]] = None
+    ) -> list[tuple[int, ItemInRange]]: ...
+    def build_index(self) -> StackIndex: ...
     def get_item(self, id: str) -> Optional[tuple[int, int, Item]]: ...
     def delete_item(
         self,
@@ -223,11 +348,11 @@ class Stack:
         dest_track_index: int,
         dest_time: float,
         item: Union[Item, Clip, Gap],
-        overlap_policy: OverlapPolicy,
-        insert_policy: InsertPolicy,
+        overlap_policy: OverlapPolicyLike,
+        insert_policy: InsertPolicyLike,
         linked_audio_clips: Optional[list[Union[Item, Clip]]] = None,
         linked_video_clip: Optional[Union[Item, Clip]] = None,
-    ) -> Optional[Union[str, dict[str, Any]]]: ...
+    ) -> Optional[Union[str, SyncedInsertResult]]: ...
     def get_metadata_json(self) -> str: ...
     def set_metadata_json(self, value: str) -> None: ...
     def __str__(self) -> str: ...
@@ -236,22 +361,88 @@ class Stack:
         dest_track_id: str,
         dest_index: int,
         item: Union[Item, Clip, Gap],
-        overlap_policy: OverlapPolicy,
+        overlap_policy: OverlapPolicyLike,
         linked_audio_clips: Optional[list[Union[Item, Clip]]] = None,
         linked_video_clip: Optional[Union[Item, Clip]] = None,
-    ) -> Optional[Union[str, dict[str, Any]]]: ...
+    ) -> Optional[Union[str, SyncedInsertResult]]: ...
+    def preview_insert_item_at_time(
+        self,
+        dest_track_index: int,
+        dest_time: float,
+        item: Union[Item, Clip, Gap],
+        overlap_policy: OverlapPolicyLike,
+        insert_policy: InsertPolicyLike,
+        linked_audio_clips: Optional[list[Union[Item, Clip]]] = None,
+        linked_video_clip: Optional[Union[Item, Clip]] = None,
+    ) -> EditPreview: ...
+    def preview_move_item_at_time(
+        self,
+        item_id: str,
+        dest_track_id: str,
+        dest_time: float,
+        replace_with_gap: bool,
+        overlap_policy: OverlapPolicyLike,
+        insert_policy: InsertPolicyLike,
+    ) -> EditPreview: ...
+    def preview_move_item_at_index(
+        self,
+        item_id: str,
+        dest_track_id: str,
+        dest_index: int,
+        replace_with_gap: bool,
+        overlap_policy: OverlapPolicyLike,
+    ) -> EditPreview: ...
+    def preview_resize_item(
+        self,
+        item_id: str,
+        new_start_time: float,
+        new_duration: float,
+        overlap_policy: OverlapPolicyLike,
+        clamp_to_media: bool,
+    ) -> EditPreview: ...
+    def preview_split_item_at_time(self, item_id: str, split_time: float) -> EditPreview: ...
+    def preview_delete_item(self, id: str, replace_with_gap: bool) -> EditPreview: ...
+    def snap(
+        self,
+        proposed_start: float,
+        tolerance: float,
+        item_id: Optional[str] = None,
+        range: Optional[tuple[float, float]] = None,
+        clip_edges: bool = True,
+        link_partner_edges: bool = True,
+        playhead: Optional[float] = None,
+        markers: Optional[list[float]] = None,
+    ) -> Optional[Snap]: ...
     def unlink_item(self, item_ids: list[str]) -> int: ...
     def link_item(self, item_ids: list[str]) -> Optional[int]: ...
+    def unsync_item(self, item_ids: list[str]) -> int: ...
+    def sync_item(self, item_ids: list[str]) -> Optional[int]: ...
+    def set_link_offset(self, item_id: str, edge: Literal["in", "out"], offset: float) -> bool: ...
+    def get_link_offsets(self, item_id: str) -> Optional[tuple[float, float]]: ...
+    def get_link_drift(self) -> list[LinkGroupDrift]: ...
+    def get_link_group_drift(
+        self, link_group_id: int, anchor_item_id: Optional[str] = None
+    ) -> Optional[LinkGroupDrift]: ...
+    def resync_link_group(self, link_group_id: int, anchor_item_id: str) -> bool: ...
     def group_item(self, item_ids: list[str]) -> Optional[int]: ...
     def ungroup_item(self, item_ids: list[str]) -> int: ...
+    def get_group_parent(self, group_id: int) -> Optional[int]: ...
+    def copy_selection(self, item_ids: list[str]) -> Clipboard: ...
+    def paste_selection(
+        self,
+        clipboard: Clipboard,
+        dest_time: float,
+        dest_track_index: int,
+        overlap_policy: OverlapPolicyLike,
+    ) -> Optional[list[str]]: ...
     def move_item_at_time(
         self,
         item_id: str,
         dest_track_id: str,
         dest_time: float,
         replace_with_gap: bool,
-        overlap_policy: OverlapPolicy,
-        insert_policy: InsertPolicy,
+        overlap_policy: OverlapPolicyLike,
+        insert_policy: InsertPolicyLike,
     ) -> bool: ...
     def move_item_at_index(
         self,
@@ -259,8 +450,7 @@ class Stack:
         dest_track_id: str,
         dest_index: int,
         replace_with_gap: bool,
-        overlap_policy: OverlapPolicy,
-        insert_policy: InsertPolicy,
+        overlap_policy: OverlapPolicyLike,
     ) -> bool: ...
     def split_item_at_time(self, item_id: str, split_time: float) -> bool: ...
     def resize_item(
@@ -268,9 +458,27 @@ class Stack:
         item_id: str,
         new_start_time: float,
         new_duration: float,
-        overlap_policy: OverlapPolicy,
+        overlap_policy: OverlapPolicyLike,
+        clamp_to_media: bool,
+    ) -> bool: ...
+    def resize_item_with_source_start(
+        self,
+        item_id: str,
+        new_start_time: float,
+        source_start_time: float,
+        new_duration: float,
+        overlap_policy: OverlapPolicyLike,
         clamp_to_media: bool,
     ) -> bool: ...
+    def modify_item(
+        self,
+        item_id: str,
+        source_start_time: float,
+        duration: float,
+        clamp_to_media: bool,
+        resize_from_start: bool,
+        push_following: bool,
+    ) -> bool: ...
     def replace_item(
         self,
         item_id: str,
@@ -278,6 +486,16 @@ class Stack:
         linked_audio_clips: Optional[list[Union[Item, Clip]]] = None,
     ) -> bool: ...
 
+class StackTransaction:
+    def __enter__(self) -> StackTransaction: ...
+    def __exit__(
+        self,
+        exc_type: Optional[type] = None,
+        exc_value: Optional[BaseException] = None,
+        traceback: Optional[object] = None,
+    ) -> bool: ...
+    def check(self, outcome: _T) -> _T: ...
+
 class Timeline:
     def __init__(self, tracks: Optional[Union[Stack, list[Track]]] = None) -> None: ...
     @staticmethod
@@ -295,6 +513,10 @@ class Timeline:
     def sync_track_info(self) -> list[SyncTrackInfo]: ...
     def delete_track(self, id: str) -> Optional[Track]: ...
     def move_item(self, item_id: str, dest_track_id: str, dest_time: float) -> bool: ...
+    def subrange(self, start: float, end: float) -> Optional[Timeline]: ...
+    def append(self, other: Timeline) -> None: ...
+    def splice_at(self, time: float, other: Timeline) -> bool: ...
+    def transaction(self) -> StackTransaction: ...
     def get_metadata_json(self) -> str: ...
     def set_metadata_json(self, value: str) -> None: ...
     def __str__(self) -> str: ...
@@ -4,9 +4,13 @@ use pyo3::types::{PyAny, PyDict};
 use tellers_timeline_core::to_json_with_precision;
 use tellers_timeline_core::track_methods::track_item_insert::{InsertPolicy, OverlapPolicy};
 use tellers_timeline_core::{
-    validate_timeline, Clip, Effect, EffectMetadata, Gap, InsertItemAtTimeResult, Item, MediaReference, MediaReferenceCrop, MediaReferencePosition, RationalTime, Stack, TimeRange, Timeline,
+    validate_timeline, Clip, Effect, EffectMetadata, Gap, InsertItemAtTimeResult, Item, ItemInRange, MediaReference, MediaReferenceCrop, MediaReferencePosition, RationalTime, Stack, StackTimes, SyncedInsertResult, TimeRange, Timeline,
     Track, TrackKind,
 };
+use tellers_timeline_core::{
+    Clipboard, EditPreview, PendingTransaction, Snap, SnapMoving, SnapSources, SnapTarget,
+    SyncEdge, SyncGroupDrift, TransactionError,
+};
 use tellers_timeline_core::{IdMetadataExt, MetadataExt};
 
 #[pyclass(name = "MediaReference")]
@@ -692,11 +696,124 @@ struct PyTrack {
     inner: Track,
 }
 
-fn track_kind_from_str(s: &str) -> TrackKind {
+#[pyclass(name = "TrackKind")]
+#[derive(Clone, Copy, PartialEq)]
+enum PyTrackKind {
+    #[pyo3(name = "VIDEO")]
+    Video,
+    #[pyo3(name = "AUDIO")]
+    Audio,
+    #[pyo3(name = "OTHER")]
+    Other,
+}
+
+#[pymethods]
+impl PyTrackKind {
+    /// The serialized name ("video", "audio" or "other").
+    #[getter]
+    fn value(&self) -> &'static str {
+        match self {
+            PyTrackKind::Video => "video",
+            PyTrackKind::Audio => "audio",
+            PyTrackKind::Other => "other",
+        }
+    }
+    /// Equal to the same kind or to its `value`, so `track.kind == "video"`
+    /// holds as it did when `kind` was a string.
+    fn __eq__(&self, other: &Bound<PyAny>) -> bool {
+        if let Ok(kind) = other.extract::<PyTrackKind>() {
+            return kind == *self;
+        }
+        other
+            .extract::<String>()
+            .is_ok_and(|value| value == self.value())
+    }
+    /// Hashes like `value`, consistent with `__eq__`.
+    fn __hash__(&self, py: Python<'_>) -> PyResult<isize> {
+        pyo3::types::PyString::new_bound(py, self.value())
+            .as_any()
+            .hash()
+    }
+}
+
+impl From<PyTrackKind> for TrackKind {
+    fn from(kind: PyTrackKind) -> Self {
+        match kind {
+            PyTrackKind::Video => TrackKind::Video,
+            PyTrackKind::Audio => TrackKind::Audio,
+            PyTrackKind::Other => TrackKind::Other,
+        }
+    }
+}
+
+impl From<&TrackKind> for PyTrackKind {
+    fn from(kind: &TrackKind) -> Self {
+        match kind {
+            TrackKind::Video => PyTrackKind::Video,
+            TrackKind::Audio => PyTrackKind::Audio,
+            TrackKind::Other => PyTrackKind::Other,
+        }
+    }
+}
+
+#[pyclass(name = "OverlapPolicy")]
+#[derive(Clone, Copy, PartialEq)]
+enum PyOverlapPolicy {
+    #[pyo3(name = "OVERRIDE")]
+    Override,
+    #[pyo3(name = "PUSH")]
+    Push,
+}
+
+impl From<PyOverlapPolicy> for OverlapPolicy {
+    fn from(policy: PyOverlapPolicy) -> Self {
+        match policy {
+            PyOverlapPolicy::Override => OverlapPolicy::Override,
+            PyOverlapPolicy::Push => OverlapPolicy::Push,
+        }
+    }
+}
+
+#[pyclass(name = "InsertPolicy")]
+#[derive(Clone, Copy, PartialEq)]
+enum PyInsertPolicy {
+    #[pyo3(name = "SPLIT_AND_INSERT")]
+    SplitAndInsert,
+    #[pyo3(name = "INSERT_BEFORE")]
+    InsertBefore,
+    #[pyo3(name = "INSERT_AFTER")]
+    InsertAfter,
+    #[pyo3(name = "INSERT_BEFORE_OR_AFTER")]
+    InsertBeforeOrAfter,
+}
+
+impl From<PyInsertPolicy> for InsertPolicy {
+    fn from(policy: PyInsertPolicy) -> Self {
+        match policy {
+            PyInsertPolicy::SplitAndInsert => InsertPolicy::SplitAndInsert,
+            PyInsertPolicy::InsertBefore => InsertPolicy::InsertBefore,
+            PyInsertPolicy::InsertAfter => InsertPolicy::InsertAfter,
+            PyInsertPolicy::InsertBeforeOrAfter => InsertPolicy::InsertBeforeOrAfter,
+        }
+    }
+}
+
+// Policies and kinds are accepted either as the enum or as their string name,
+// so existing callers passing "override" / "video" keep working. Unknown
+// strings are an error rather than a silent default.
+
+fn track_kind_from_py(value: &Bound<PyAny>) -> PyResult<TrackKind> {
+    if let Ok(kind) = value.extract::<PyTrackKind>() {
+        return Ok(kind.into());
+    }
+    let s: String = value.extract()?;
     match s.to_ascii_lowercase().as_str() {
-        "video" => TrackKind::Video,
-        "audio" => TrackKind::Audio,
-        _ => TrackKind::Other,
+        "video" => Ok(TrackKind::Video),
+        "audio" => Ok(TrackKind::Audio),
+        "other" => Ok(TrackKind::Other),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "unknown track kind '{s}' (expected 'video', 'audio' or 'other')"
+        ))),
     }
 }
 
@@ -720,20 +837,44 @@ fn clamp_insertion_index(len: usize, index: isize) -> usize {
     }
 }
 
-fn overlap_policy_from_str(s: &str) -> OverlapPolicy {
+fn overlap_policy_from_py(value: &Bound<PyAny>) -> PyResult<OverlapPolicy> {
+    if let Ok(policy) = value.extract::<PyOverlapPolicy>() {
+        return Ok(policy.into());
+    }
+    let s: String = value.extract()?;
     match s.to_ascii_lowercase().as_str() {
-        "override" => OverlapPolicy::Override,
-        "push" => OverlapPolicy::Push,
-        _ => OverlapPolicy::Override,
+        "override" => Ok(OverlapPolicy::Override),
+        "push" => Ok(OverlapPolicy::Push),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "unknown overlap policy '{s}' (expected 'override' or 'push')"
+        ))),
     }
 }
 
-fn insert_policy_from_str(s: &str) -> InsertPolicy {
+fn sync_edge_from_str(edge: &str) -> PyResult<SyncEdge> {
+    match edge.to_ascii_lowercase().as_str() {
+        "in" => Ok(SyncEdge::In),
+        "out" => Ok(SyncEdge::Out),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "unknown edge '{edge}' (expected 'in' or 'out')"
+        ))),
+    }
+}
+
+fn insert_policy_from_py(value: &Bound<PyAny>) -> PyResult<InsertPolicy> {
+    if let Ok(policy) = value.extract::<PyInsertPolicy>() {
+        return Ok(policy.into());
+    }
+    let s: String = value.extract()?;
     match s.to_ascii_lowercase().as_str() {
-        "split_and_insert" | "split" => InsertPolicy::SplitAndInsert,
-        "insert_before" | "before" => InsertPolicy::InsertBefore,
-        "insert_after" | "after" => InsertPolicy::InsertAfter,
-        _ => InsertPolicy::InsertBeforeOrAfter,
+        "split_and_insert" | "split" => Ok(InsertPolicy::SplitAndInsert),
+        "insert_before" | "before" => Ok(InsertPolicy::InsertBefore),
+        "insert_after" | "after" => Ok(InsertPolicy::InsertAfter),
+        "insert_before_or_after" | "before_or_after" => Ok(InsertPolicy::InsertBeforeOrAfter),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "unknown insert policy '{s}' (expected 'split_and_insert', 'insert_before', \
+             'insert_after' or 'insert_before_or_after')"
+        ))),
     }
 }
 
@@ -751,33 +892,89 @@ impl PyTrack {
     fn set_enabled(&mut self, enabled: bool) {
         self.inner.enabled = enabled;
     }
+    /// Locked tracks are left untouched by edits: an edit that would change
+    /// one fails instead.
+    fn get_locked(&self) -> bool {
+        self.inner.is_locked()
+    }
+    fn set_locked(&mut self, locked: bool) {
+        self.inner.set_locked(locked);
+    }
+    /// Sync-locked tracks follow push inserts, collapsing deletes and ripple
+    /// trims made on other tracks.
+    fn get_sync_locked(&self) -> bool {
+        self.inner.is_sync_locked()
+    }
+    fn set_sync_locked(&mut self, sync_locked: bool) {
+        self.inner.set_sync_locked(sync_locked);
+    }
+    fn get_solo(&self) -> bool {
+        self.inner.is_solo()
+    }
+    fn set_solo(&mut self, solo: bool) {
+        self.inner.set_solo(solo);
+    }
+    fn get_muted(&self) -> bool {
+        self.inner.is_muted()
+    }
+    fn set_muted(&mut self, muted: bool) {
+        self.inner.set_muted(muted);
+    }
+    fn get_height(&self) -> Option<f64> {
+        self.inner.height()
+    }
+    #[pyo3(signature = (height=None))]
+    fn set_height(&mut self, height: Option<f64>) {
+        self.inner.set_height(height);
+    }
+    fn get_effects(&self, py: Python<'_>) -> Vec<Py<PyEffect>> {
+        self.inner.effects.iter().map(|e| Py::new(py, PyEffect { inner: e.clone() }).unwrap()).collect()
+    }
+    fn set_effects(&mut self, effects: Vec<PyEffect>) {
+        self.inner.effects = effects.into_iter().map(|e| e.inner).collect();
+    }
+    /// Track gain; clip volumes are kept as they are.
+    fn get_volume(&self) -> f64 {
+        self.inner.get_volume()
+    }
+    fn set_volume(&mut self, volume: f64) {
+        self.inner.set_volume(volume);
+    }
+    fn get_pan(&self) -> f64 {
+        self.inner.get_pan()
+    }
+    fn set_pan(&mut self, pan: f64) {
+        self.inner.set_pan(pan);
+    }
+    /// Clip volume times track gain for the item at `item_index`.
+    fn effective_volume(&self, item_index: usize) -> Option<f64> {
+        self.inner.effective_volume(item_index)
+    }
     #[new]
     #[pyo3(signature = (kind=None, id=None, children=None))]
     fn new(
-        kind: Option<String>,
+        kind: Option<&Bound<PyAny>>,
         id: Option<String>,
         children: Option<Vec<PyItem>>,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let k = kind
-            .map(|s| track_kind_from_str(&s))
+            .map(track_kind_from_py)
+            .transpose()?
             .unwrap_or(TrackKind::Video);
         let mut inner = Track::new(k, id);
         if let Some(items) = children {
             inner.items = items.into_iter().map(|i| i.inner).collect();
         }
-        Self { inner }
+        Ok(Self { inner })
     }
     #[getter]
-    fn kind(&self) -> String {
-        match &self.inner.kind {
-            TrackKind::Video => "video".to_string(),
-            TrackKind::Audio => "audio".to_string(),
-            TrackKind::Other => "other".to_string(),
-        }
+    fn kind(&self) -> PyTrackKind {
+        PyTrackKind::from(&self.inner.kind)
     }
     #[setter]
-    fn set_kind(&mut self, kind: String) {
-        self.inner.kind = track_kind_from_str(&kind);
+    fn set_kind(&mut self, kind: &Bound<PyAny>) -> PyResult<()> {
+        self.inner.kind = track_kind_from_py(kind)?;
+        Ok(())
     }
     fn items(&self, py: Python<'_>) -> Vec<Py<PyItem>> {
         self.inner
@@ -816,6 +1013,14 @@ impl PyTrack {
     fn start_time_of_item(&self, index: usize) -> f64 {
         self.inner.start_time_of_item(index)
     }
+    /// Items (clips and gaps) overlapping the half-open range `start..end`.
+    fn items_in_range(&self, py: Python<'_>, start: f64, end: f64) -> Vec<PyItemInRange> {
+        self.inner
+            .items_in_range(start, end)
+            .into_iter()
+            .map(|hit| PyItemInRange::new(py, hit))
+            .collect()
+    }
     fn get_metadata_json(&self) -> PyResult<String> {
         serde_json::to_string(&self.inner.metadata)
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
@@ -916,6 +1121,19 @@ impl PyStack {
         to_json_with_precision(&self.inner, None, false)
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
     }
+    /// Whether the track at `track_index` is enabled, not muted and not
+    /// silenced by another track's solo.
+    fn is_track_rendered(&self, track_index: usize) -> bool {
+        self.inner.is_track_rendered(track_index)
+    }
+    /// Clip volume times track gain for the item with `item_id`.
+    fn effective_volume(&self, item_id: &str) -> Option<f64> {
+        self.inner.effective_volume(item_id)
+    }
+    /// Context manager applying the edits made in its block atomically.
+    fn transaction(slf: Py<Self>) -> PyStackTransaction {
+        PyStackTransaction::new(TransactionTarget::Stack(slf))
+    }
     fn get_track_by_id(&self, py: Python<'_>, id: &str) -> Option<(usize, Py<PyTrack>)> {
         self.inner.get_track_by_id(id).map(|(i, _t)| {
             let tr = self.inner.children[i].clone();
@@ -939,6 +1157,27 @@ impl PyStack {
             .map(|(i, tr)| (i, Py::new(py, PyTrack { inner: tr.clone() }).unwrap()))
             .collect()
     }
+    /// `(track_index, hit)` for every item overlapping `start..end` on the
+    /// tracks listed in `track_ids` (all tracks when omitted).
+    #[pyo3(signature = (start, end, track_ids=None))]
+    fn items_in_range(
+        &self,
+        py: Python<'_>,
+        start: f64,
+        end: f64,
+        track_ids: Option<Vec<String>>,
+    ) -> Vec<(usize, PyItemInRange)> {
+        self.inner
+            .items_in_range(start, end, track_ids.as_deref())
+            .into_iter()
+            .map(|(ti, hit)| (ti, PyItemInRange::new(py, hit)))
+            .collect()
+    }
+    /// A snapshot index for repeated time lookups; each `items_in_range` on
+    /// it costs a binary search instead of a walk from the track start.
+    fn build_index(&self) -> PyStackIndex {
+        PyStackIndex::new(self.inner.clone())
+    }
     fn get_item(&self, py: Python<'_>, id: &str) -> Option<(usize, usize, Py<PyItem>)> {
         self.inner.get_item(id).map(|(ti, ii, _it)| {
             let item = self.inner.children[ti].items[ii].clone();
@@ -965,65 +1204,34 @@ impl PyStack {
         dest_track_index: usize,
         dest_time: f64,
         item: &Bound<PyAny>,
-        overlap_policy: &str,
-        insert_policy: &str,
+        overlap_policy: &Bound<PyAny>,
+        insert_policy: &Bound<PyAny>,
         linked_audio_clips: Option<Vec<PyObject>>,
         linked_video_clip: Option<PyObject>,
     ) -> PyResult<Option<PyObject>> {
-        if let Some(inner_item) = extract_item(item) {
-            let linked_video_clip = extract_optional_linked_clip(
-                py,
-                linked_video_clip,
-                "linked_video_clip",
-            )?;
-            if (linked_audio_clips.is_some() || linked_video_clip.is_some())
-                && !matches!(inner_item, Item::Clip(_))
-            {
-                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-                    "linked_audio_clips and linked_video_clip can only be used when item is a Clip",
-                ));
-            }
-            let op = overlap_policy_from_str(overlap_policy);
-            let ip = insert_policy_from_str(insert_policy);
-            let linked_audio_clips = linked_audio_clips
-                .map(|items| {
-                    items
-                        .into_iter()
-                        .map(|item| {
-                            extract_item(item.bind(py)).ok_or_else(|| {
-                                PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-                                    "linked_audio_clips expects Item or Clip values",
-                                )
-                            })
-                        })
-                        .collect::<PyResult<Vec<_>>>()
-                })
-                .transpose()?;
-            match self.inner.insert_item_at_time(
-                dest_track_index,
-                dest_time,
-                inner_item,
-                op,
-                ip,
-                linked_audio_clips,
-                linked_video_clip,
-            ) {
-                Some(InsertItemAtTimeResult::ItemId(id)) => Ok(Some(id.into_py(py))),
-                Some(InsertItemAtTimeResult::Synced(result)) => {
-                    let dict = PyDict::new(py);
-                    dict.set_item("primary_clip_id", result.primary_clip_id)?;
-                    dict.set_item("audio_clips", result.audio_clips)?;
-                    dict.set_item("linked_video_clip_id", result.synced_video_clip_id)?;
-                    dict.set_item("link_group_id", result.sync_clips_id)?;
-                    dict.set_item("created_track_indices", result.created_track_indices)?;
-                    Ok(Some(dict.into_py(py)))
-                }
-                None => Ok(None),
+        let (inner_item, linked_audio_clips, linked_video_clip) = extract_insert_items(
+            py,
+            item,
+            linked_audio_clips,
+            linked_video_clip,
+            "insert_item_at_time",
+        )?;
+        let op = overlap_policy_from_py(overlap_policy)?;
+        let ip = insert_policy_from_py(insert_policy)?;
+        match self.inner.insert_item_at_time(
+            dest_track_index,
+            dest_time,
+            inner_item,
+            op,
+            ip,
+            linked_audio_clips,
+            linked_video_clip,
+        ) {
+            Some(InsertItemAtTimeResult::ItemId(id)) => Ok(Some(id.into_py(py))),
+            Some(InsertItemAtTimeResult::Synced(result)) => {
+                Ok(Some(PySyncedInsertResult::from(result).into_py(py)))
             }
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-                "insert_item_at_time expects an Item, Clip, or Gap",
-            ))
+            None => Ok(None),
         }
     }
     #[pyo3(signature = (dest_track_id, dest_index, item, overlap_policy, linked_audio_clips=None, linked_video_clip=None))]
@@ -1033,65 +1241,34 @@ impl PyStack {
         dest_track_id: &str,
         dest_index: usize,
         item: &Bound<PyAny>,
-        overlap_policy: &str,
+        overlap_policy: &Bound<PyAny>,
         linked_audio_clips: Option<Vec<PyObject>>,
         linked_video_clip: Option<PyObject>,
     ) -> PyResult<Option<PyObject>> {
-        if let Some(inner_item) = extract_item(item) {
-            let linked_video_clip = extract_optional_linked_clip(
-                py,
+        let (inner_item, linked_audio_clips, linked_video_clip) = extract_insert_items(
+            py,
+            item,
+            linked_audio_clips,
+            linked_video_clip,
+            "insert_item_at_index",
+        )?;
+        let op = overlap_policy_from_py(overlap_policy)?;
+        match self
+            .inner
+            .insert_item_at_index(
+                dest_track_id,
+                dest_index,
+                inner_item,
+                op,
+                linked_audio_clips,
                 linked_video_clip,
-                "linked_video_clip",
-            )?;
-            if (linked_audio_clips.is_some() || linked_video_clip.is_some())
-                && !matches!(inner_item, Item::Clip(_))
-            {
-                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-                    "linked_audio_clips and linked_video_clip can only be used when item is a Clip",
-                ));
-            }
-            let op = overlap_policy_from_str(overlap_policy);
-            let linked_audio_clips = linked_audio_clips
-                .map(|items| {
-                    items
-                        .into_iter()
-                        .map(|item| {
-                            extract_item(item.bind(py)).ok_or_else(|| {
-                                PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-                                    "linked_audio_clips expects Item or Clip values",
-                                )
-                            })
-                        })
-                        .collect::<PyResult<Vec<_>>>()
-                })
-                .transpose()?;
-            match self
-                .inner
-                .insert_item_at_index(
-                    dest_track_id,
-                    dest_index,
-                    inner_item,
-                    op,
-                    linked_audio_clips,
-                    linked_video_clip,
-                )
-            {
-                Some(InsertItemAtTimeResult::ItemId(id)) => Ok(Some(id.into_py(py))),
-                Some(InsertItemAtTimeResult::Synced(result)) => {
-                    let dict = PyDict::new(py);
-                    dict.set_item("primary_clip_id", result.primary_clip_id)?;
-                    dict.set_item("audio_clips", result.audio_clips)?;
-                    dict.set_item("linked_video_clip_id", result.synced_video_clip_id)?;
-                    dict.set_item("link_group_id", result.sync_clips_id)?;
-                    dict.set_item("created_track_indices", result.created_track_indices)?;
-                    Ok(Some(dict.into_py(py)))
-                }
-                None => Ok(None),
+            )
+        {
+            Some(InsertItemAtTimeResult::ItemId(id)) => Ok(Some(id.into_py(py))),
+            Some(InsertItemAtTimeResult::Synced(result)) => {
+                Ok(Some(PySyncedInsertResult::from(result).into_py(py)))
             }
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-                "insert_item_at_index expects an Item, Clip, or Gap",
-            ))
+            None => Ok(None),
         }
     }
     fn unlink_item(&mut self, item_ids: Vec<String>) -> usize {
@@ -1100,23 +1277,95 @@ impl PyStack {
     fn link_item(&mut self, item_ids: Vec<String>) -> Option<i64> {
         self.inner.sync_item(&item_ids)
     }
+    fn unsync_item(&mut self, item_ids: Vec<String>) -> usize {
+        self.inner.unsync_item(&item_ids)
+    }
+    fn sync_item(&mut self, item_ids: Vec<String>) -> Option<i64> {
+        self.inner.sync_item(&item_ids)
+    }
+    /// Split edit (J-cut or L-cut): move the "in" or "out" point of the
+    /// linked clip `item_id` to `offset` seconds from its link group's
+    /// column, negative being earlier. The clip stays linked.
+    fn set_link_offset(&mut self, item_id: &str, edge: &str, offset: f64) -> PyResult<bool> {
+        Ok(self
+            .inner
+            .set_sync_offset(item_id, sync_edge_from_str(edge)?, offset))
+    }
+    /// The `(in, out)` split-edit offsets of a clip.
+    fn get_link_offsets(&self, item_id: &str) -> Option<(f64, f64)> {
+        self.inner
+            .sync_offsets(item_id)
+            .map(|offsets| (offsets.in_offset, offsets.out_offset))
+    }
+    /// How far the clips of every link group are from the group's first
+    /// video clip, on the timeline and in their source.
+    fn get_link_drift(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        self.inner
+            .sync_drift()
+            .into_iter()
+            .map(|drift| link_drift_to_python(py, drift))
+            .collect()
+    }
+    /// Drift of one link group, measured from `anchor_item_id` when given.
+    #[pyo3(signature = (link_group_id, anchor_item_id=None))]
+    fn get_link_group_drift(
+        &self,
+        py: Python<'_>,
+        link_group_id: i64,
+        anchor_item_id: Option<&str>,
+    ) -> PyResult<Option<PyObject>> {
+        self.inner
+            .sync_group_drift(link_group_id, anchor_item_id)
+            .map(|drift| link_drift_to_python(py, drift))
+            .transpose()
+    }
+    /// Move and slip the clips of a link group back in line with
+    /// `anchor_item_id`.
+    fn resync_link_group(&mut self, link_group_id: i64, anchor_item_id: &str) -> bool {
+        self.inner.resync_group(link_group_id, anchor_item_id)
+    }
     fn group_item(&mut self, item_ids: Vec<String>) -> Option<i64> {
         self.inner.group_item(&item_ids)
     }
     fn ungroup_item(&mut self, item_ids: Vec<String>) -> usize {
         self.inner.ungroup_item(&item_ids)
     }
+    /// The group `group_id` is nested in, if any.
+    fn get_group_parent(&self, group_id: i64) -> Option<i64> {
+        self.inner.tellers_group_parent(group_id)
+    }
+    /// Copy the clips `item_ids` and their link partners.
+    fn copy_selection(&self, item_ids: Vec<String>) -> PyClipboard {
+        PyClipboard {
+            inner: self.inner.copy_selection(&item_ids),
+        }
+    }
+    /// Paste a copy of `clipboard` with its earliest clip at `dest_time` and
+    /// its first track at `dest_track_index`, with fresh ids and groups.
+    /// Returns the ids of the pasted clips.
+    fn paste_selection(
+        &mut self,
+        clipboard: PyRef<PyClipboard>,
+        dest_time: f64,
+        dest_track_index: usize,
+        overlap_policy: &Bound<PyAny>,
+    ) -> PyResult<Option<Vec<String>>> {
+        let op = overlap_policy_from_py(overlap_policy)?;
+        Ok(self
+            .inner
+            .paste_selection(&clipboard.inner, dest_time, dest_track_index, op))
+    }
     fn move_item_at_time(
         &mut self,
         item_id: &str,
         dest_track_id: &str,
         dest_time: f64,
         replace_with_gap: bool,
-        overlap_policy: &str,
-        insert_policy: &str,
+        overlap_policy: &Bound<PyAny>,
+        insert_policy: &Bound<PyAny>,
     ) -> PyResult<bool> {
-        let op = overlap_policy_from_str(overlap_policy);
-        let ip = insert_policy_from_str(insert_policy);
+        let op = overlap_policy_from_py(overlap_policy)?;
+        let ip = insert_policy_from_py(insert_policy)?;
         Ok(self.inner.move_item_at_time(
             item_id,
             dest_track_id,
@@ -1132,9 +1381,9 @@ impl PyStack {
         dest_track_id: &str,
         dest_index: usize,
         replace_with_gap: bool,
-        overlap_policy: &str,
+        overlap_policy: &Bound<PyAny>,
     ) -> PyResult<bool> {
-        let op = overlap_policy_from_str(overlap_policy);
+        let op = overlap_policy_from_py(overlap_policy)?;
         Ok(self
             .inner
             .move_item_at_index(item_id, dest_track_id, dest_index, replace_with_gap, op))
@@ -1147,12 +1396,51 @@ impl PyStack {
         item_id: &str,
         new_start_time: f64,
         new_duration: f64,
-        overlap_policy: &str,
+        overlap_policy: &Bound<PyAny>,
+        clamp_to_media: bool,
+    ) -> PyResult<bool> {
+        let op = overlap_policy_from_py(overlap_policy)?;
+        Ok(self
+            .inner
+            .resize_item(item_id, new_start_time, new_duration, op, clamp_to_media))
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn resize_item_with_source_start(
+        &mut self,
+        item_id: &str,
+        new_start_time: f64,
+        source_start_time: f64,
+        new_duration: f64,
+        overlap_policy: &Bound<PyAny>,
+        clamp_to_media: bool,
+    ) -> PyResult<bool> {
+        let op = overlap_policy_from_py(overlap_policy)?;
+        Ok(self.inner.resize_item_with_source_start(
+            item_id,
+            new_start_time,
+            source_start_time,
+            new_duration,
+            op,
+            clamp_to_media,
+        ))
+    }
+    fn modify_item(
+        &mut self,
+        item_id: &str,
+        source_start_time: f64,
+        duration: f64,
         clamp_to_media: bool,
+        resize_from_start: bool,
+        push_following: bool,
     ) -> bool {
-        let op = overlap_policy_from_str(overlap_policy);
-        self.inner
-            .resize_item(item_id, new_start_time, new_duration, op, clamp_to_media)
+        self.inner.modify_item(
+            item_id,
+            source_start_time,
+            duration,
+            clamp_to_media,
+            resize_from_start,
+            push_following,
+        )
     }
     #[pyo3(signature = (item_id, item, linked_audio_clips=None))]
     fn replace_item(
@@ -1191,6 +1479,331 @@ impl PyStack {
             ))
         }
     }
+    /// Dry run of `insert_item_at_time`: what it would change, as a dict.
+    #[pyo3(signature = (dest_track_index, dest_time, item, overlap_policy, insert_policy, linked_audio_clips=None, linked_video_clip=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn preview_insert_item_at_time(
+        &self,
+        py: Python<'_>,
+        dest_track_index: usize,
+        dest_time: f64,
+        item: &Bound<PyAny>,
+        overlap_policy: &Bound<PyAny>,
+        insert_policy: &Bound<PyAny>,
+        linked_audio_clips: Option<Vec<PyObject>>,
+        linked_video_clip: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        let (inner_item, linked_audio_clips, linked_video_clip) = extract_insert_items(
+            py,
+            item,
+            linked_audio_clips,
+            linked_video_clip,
+            "preview_insert_item_at_time",
+        )?;
+        let op = overlap_policy_from_py(overlap_policy)?;
+        let ip = insert_policy_from_py(insert_policy)?;
+        edit_preview_to_python(
+            py,
+            self.inner.preview_insert_item_at_time(
+                dest_track_index,
+                dest_time,
+                inner_item,
+                op,
+                ip,
+                linked_audio_clips,
+                linked_video_clip,
+            ),
+        )
+    }
+    /// Dry run of `move_item_at_time`.
+    fn preview_move_item_at_time(
+        &self,
+        py: Python<'_>,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_time: f64,
+        replace_with_gap: bool,
+        overlap_policy: &Bound<PyAny>,
+        insert_policy: &Bound<PyAny>,
+    ) -> PyResult<PyObject> {
+        let op = overlap_policy_from_py(overlap_policy)?;
+        let ip = insert_policy_from_py(insert_policy)?;
+        edit_preview_to_python(
+            py,
+            self.inner.preview_move_item_at_time(
+                item_id,
+                dest_track_id,
+                dest_time,
+                replace_with_gap,
+                ip,
+                op,
+            ),
+        )
+    }
+    /// Dry run of `move_item_at_index`.
+    fn preview_move_item_at_index(
+        &self,
+        py: Python<'_>,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_index: usize,
+        replace_with_gap: bool,
+        overlap_policy: &Bound<PyAny>,
+    ) -> PyResult<PyObject> {
+        let op = overlap_policy_from_py(overlap_policy)?;
+        edit_preview_to_python(
+            py,
+            self.inner.preview_move_item_at_index(
+                item_id,
+                dest_track_id,
+                dest_index,
+                replace_with_gap,
+                op,
+            ),
+        )
+    }
+    /// Dry run of `resize_item`.
+    fn preview_resize_item(
+        &self,
+        py: Python<'_>,
+        item_id: &str,
+        new_start_time: f64,
+        new_duration: f64,
+        overlap_policy: &Bound<PyAny>,
+        clamp_to_media: bool,
+    ) -> PyResult<PyObject> {
+        let op = overlap_policy_from_py(overlap_policy)?;
+        edit_preview_to_python(
+            py,
+            self.inner.preview_resize_item(
+                item_id,
+                new_start_time,
+                new_duration,
+                op,
+                clamp_to_media,
+            ),
+        )
+    }
+    /// Dry run of `split_item_at_time`.
+    fn preview_split_item_at_time(
+        &self,
+        py: Python<'_>,
+        item_id: &str,
+        split_time: f64,
+    ) -> PyResult<PyObject> {
+        edit_preview_to_python(py, self.inner.preview_split_item_at_time(item_id, split_time))
+    }
+    /// Snap a drag that would start the item `item_id`, or the time `range`,
+    /// at `proposed_start`. Returns the snap as a dict, or `None` when nothing
+    /// is within `tolerance`.
+    #[pyo3(signature = (proposed_start, tolerance, item_id=None, range=None, clip_edges=true, link_partner_edges=true, playhead=None, markers=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn snap(
+        &self,
+        py: Python<'_>,
+        proposed_start: f64,
+        tolerance: f64,
+        item_id: Option<String>,
+        range: Option<(f64, f64)>,
+        clip_edges: bool,
+        link_partner_edges: bool,
+        playhead: Option<f64>,
+        markers: Option<Vec<f64>>,
+    ) -> PyResult<Option<PyObject>> {
+        let moving = match (item_id, range) {
+            (Some(item_id), None) => SnapMoving::Item(item_id),
+            (None, Some((start, end))) => SnapMoving::Range { start, end },
+            _ => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "snap expects exactly one of item_id and range",
+                ))
+            }
+        };
+        let sources = SnapSources {
+            clip_edges,
+            sync_partner_edges: link_partner_edges,
+            playhead,
+            markers: markers.unwrap_or_default(),
+        };
+        self.inner
+            .snap(&moving, proposed_start, tolerance, &sources)
+            .map(|snap| snap_to_python(py, snap))
+            .transpose()
+    }
+    /// Dry run of `delete_item`.
+    fn preview_delete_item(
+        &self,
+        py: Python<'_>,
+        id: &str,
+        replace_with_gap: bool,
+    ) -> PyResult<PyObject> {
+        edit_preview_to_python(py, self.inner.preview_delete_item(id, replace_with_gap))
+    }
+}
+
+/// Result of an insert that placed linked audio/video clips alongside the
+/// primary clip. Plain inserts return the inserted item id instead.
+#[pyclass(name = "SyncedInsertResult", get_all)]
+#[derive(Clone)]
+struct PySyncedInsertResult {
+    primary_clip_id: String,
+    /// `(clip_id, track_index)` for each placed audio clip.
+    audio_clips: Vec<(String, usize)>,
+    linked_video_clip_id: Option<String>,
+    link_group_id: Option<i64>,
+    created_track_indices: Vec<usize>,
+}
+
+#[pymethods]
+impl PySyncedInsertResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "SyncedInsertResult(primary_clip_id={:?}, audio_clips={:?}, linked_video_clip_id={:?}, link_group_id={:?}, created_track_indices={:?})",
+            self.primary_clip_id,
+            self.audio_clips,
+            self.linked_video_clip_id,
+            self.link_group_id,
+            self.created_track_indices,
+        )
+    }
+}
+
+impl From<SyncedInsertResult> for PySyncedInsertResult {
+    fn from(result: SyncedInsertResult) -> Self {
+        Self {
+            primary_clip_id: result.primary_clip_id,
+            audio_clips: result.audio_clips,
+            linked_video_clip_id: result.synced_video_clip_id,
+            link_group_id: result.sync_clips_id,
+            created_track_indices: result.created_track_indices,
+        }
+    }
+}
+
+/// An item overlapping a queried range: its absolute `start`/`end` on the
+/// track and the clipped `overlap_start`/`overlap_end` inside the range.
+#[pyclass(name = "ItemInRange", get_all)]
+#[derive(Clone)]
+struct PyItemInRange {
+    item_index: usize,
+    item: Py<PyItem>,
+    start: f64,
+    end: f64,
+    overlap_start: f64,
+    overlap_end: f64,
+}
+
+impl PyItemInRange {
+    fn new(py: Python<'_>, hit: ItemInRange<'_>) -> Self {
+        Self {
+            item_index: hit.item_index,
+            item: Py::new(py, PyItem { inner: hit.item.clone() }).unwrap(),
+            start: hit.start,
+            end: hit.end,
+            overlap_start: hit.overlap_start,
+            overlap_end: hit.overlap_end,
+        }
+    }
+}
+
+#[pymethods]
+impl PyItemInRange {
+    fn __repr__(&self) -> String {
+        format!(
+            "ItemInRange(item_index={}, start={}, end={}, overlap_start={}, overlap_end={})",
+            self.item_index, self.start, self.end, self.overlap_start, self.overlap_end,
+        )
+    }
+}
+
+/// Clips copied by `Stack.copy_selection`, to paste with
+/// `Stack.paste_selection` any number of times.
+#[pyclass(name = "Clipboard")]
+#[derive(Clone)]
+struct PyClipboard {
+    inner: Clipboard,
+}
+
+#[pymethods]
+impl PyClipboard {
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+    /// From the start of the earliest clip to the end of the latest.
+    fn duration(&self) -> f64 {
+        self.inner.duration()
+    }
+    #[staticmethod]
+    fn parse_json(s: &str) -> PyResult<Self> {
+        let inner: Clipboard = serde_json::from_str(s)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        Ok(Self { inner })
+    }
+    fn to_json(&self) -> PyResult<String> {
+        to_json_with_precision(&self.inner, None, false)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+    }
+}
+
+/// Time lookups against a snapshot of a stack, by binary search over item
+/// start times. Built by `Stack.build_index()`; later edits to the stack are
+/// not seen, so build a new index after editing.
+#[pyclass(name = "StackIndex")]
+struct PyStackIndex {
+    stack: Stack,
+    times: StackTimes,
+}
+
+impl PyStackIndex {
+    fn new(stack: Stack) -> Self {
+        let times = StackTimes::new(&stack);
+        Self { stack, times }
+    }
+}
+
+#[pymethods]
+impl PyStackIndex {
+    /// Same as `Stack.items_in_range`.
+    #[pyo3(signature = (start, end, track_ids=None))]
+    fn items_in_range(
+        &self,
+        py: Python<'_>,
+        start: f64,
+        end: f64,
+        track_ids: Option<Vec<String>>,
+    ) -> Vec<(usize, PyItemInRange)> {
+        self.times
+            .items_in_range(&self.stack, start, end, track_ids.as_deref())
+            .into_iter()
+            .map(|(ti, hit)| (ti, PyItemInRange::new(py, hit)))
+            .collect()
+    }
+    /// Same as `Track.items_in_range` on the track at `track_index`.
+    fn track_items_in_range(
+        &self,
+        py: Python<'_>,
+        track_index: usize,
+        start: f64,
+        end: f64,
+    ) -> Vec<PyItemInRange> {
+        match (self.times.track(track_index), self.stack.children.get(track_index)) {
+            (Some(times), Some(track)) => times
+                .items_in_range(track, start, end)
+                .into_iter()
+                .map(|hit| PyItemInRange::new(py, hit))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+    fn item_at_time(&self, track_index: usize, time: f64) -> Option<usize> {
+        self.times.track(track_index)?.item_at_time(time)
+    }
+    fn start_time_of_item(&self, track_index: usize, item_index: usize) -> Option<f64> {
+        Some(self.times.track(track_index)?.start_time_of_item(item_index))
+    }
+    fn track_duration(&self, track_index: usize) -> Option<f64> {
+        Some(self.times.track(track_index)?.duration())
+    }
 }
 
 fn extract_item(item: &Bound<PyAny>) -> Option<Item> {
@@ -1227,6 +1840,137 @@ fn extract_optional_linked_clip(
     .transpose()
 }
 
+/// The item and linked clips of an insert, checked the way every insert
+/// entry point checks them.
+fn extract_insert_items(
+    py: Python<'_>,
+    item: &Bound<PyAny>,
+    linked_audio_clips: Option<Vec<PyObject>>,
+    linked_video_clip: Option<PyObject>,
+    method: &str,
+) -> PyResult<(Item, Option<Vec<Item>>, Option<Item>)> {
+    let Some(inner_item) = extract_item(item) else {
+        return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "{method} expects an Item, Clip, or Gap"
+        )));
+    };
+    let linked_video_clip =
+        extract_optional_linked_clip(py, linked_video_clip, "linked_video_clip")?;
+    if (linked_audio_clips.is_some() || linked_video_clip.is_some())
+        && !matches!(inner_item, Item::Clip(_))
+    {
+        return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+            "linked_audio_clips and linked_video_clip can only be used when item is a Clip",
+        ));
+    }
+    let linked_audio_clips = linked_audio_clips
+        .map(|items| {
+            items
+                .into_iter()
+                .map(|item| {
+                    extract_item(item.bind(py)).ok_or_else(|| {
+                        PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                            "linked_audio_clips expects Item or Clip values",
+                        )
+                    })
+                })
+                .collect::<PyResult<Vec<_>>>()
+        })
+        .transpose()?;
+    Ok((inner_item, linked_audio_clips, linked_video_clip))
+}
+
+fn edit_preview_to_python(py: Python<'_>, preview: EditPreview) -> PyResult<PyObject> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("success", preview.result.success)?;
+    let deleted_clips = preview
+        .result
+        .deleted_clips
+        .into_iter()
+        .map(|clip| {
+            let entry = PyDict::new_bound(py);
+            entry.set_item("clip_id", clip.clip_id)?;
+            entry.set_item("link_group_id", clip.sync_clips_id)?;
+            Ok(entry)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("deleted_clips", deleted_clips)?;
+    let split_clips = preview
+        .result
+        .split_clips
+        .into_iter()
+        .map(|split| {
+            let entry = PyDict::new_bound(py);
+            entry.set_item("old_clip_id", split.old_clip_id)?;
+            entry.set_item("left_clip_id", split.left_clip_id)?;
+            entry.set_item("right_clip_id", split.right_clip_id)?;
+            entry.set_item("link_group_id", split.sync_clips_id)?;
+            entry.set_item("split_time", split.split_time)?;
+            Ok(entry)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("split_clips", split_clips)?;
+    dict.set_item("trimmed_clip_ids", preview.trimmed_clip_ids)?;
+    dict.set_item("pushed_clip_ids", preview.pushed_clip_ids)?;
+    dict.set_item("moved_link_partner_ids", preview.moved_sync_partner_ids)?;
+    dict.set_item("created_track_indices", preview.created_track_indices)?;
+    Ok(dict.into_py(py))
+}
+
+fn link_drift_to_python(py: Python<'_>, drift: SyncGroupDrift) -> PyResult<PyObject> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("link_group_id", drift.sync_clips_id)?;
+    dict.set_item("in_sync", drift.is_in_sync())?;
+    dict.set_item("anchor_item_id", drift.anchor_item_id)?;
+    let members = drift
+        .members
+        .into_iter()
+        .map(|member| {
+            let entry = PyDict::new_bound(py);
+            entry.set_item("item_id", member.item_id)?;
+            entry.set_item("track_index", member.track_index)?;
+            entry.set_item("timeline_offset", member.timeline_offset)?;
+            entry.set_item("source_offset", member.source_offset)?;
+            entry.set_item("timeline_offset_frames", member.timeline_offset_frames)?;
+            entry.set_item("source_offset_frames", member.source_offset_frames)?;
+            Ok(entry)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("members", members)?;
+    Ok(dict.into_py(py))
+}
+
+fn snap_to_python(py: Python<'_>, snap: Snap) -> PyResult<PyObject> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("start", snap.start)?;
+    dict.set_item("time", snap.time)?;
+    dict.set_item("edge_offset", snap.edge_offset)?;
+    let (target, clip) = match snap.target {
+        SnapTarget::ClipStart {
+            track_index,
+            item_index,
+            item_id,
+        } => ("clip_start", Some((track_index, item_index, item_id))),
+        SnapTarget::ClipEnd {
+            track_index,
+            item_index,
+            item_id,
+        } => ("clip_end", Some((track_index, item_index, item_id))),
+        SnapTarget::Playhead => ("playhead", None),
+        SnapTarget::Marker { index } => {
+            dict.set_item("marker_index", index)?;
+            ("marker", None)
+        }
+    };
+    dict.set_item("target", target)?;
+    if let Some((track_index, item_index, item_id)) = clip {
+        dict.set_item("track_index", track_index)?;
+        dict.set_item("item_index", item_index)?;
+        dict.set_item("item_id", item_id)?;
+    }
+    Ok(dict.into_py(py))
+}
+
 fn sync_track_info_to_python(
     py: Python<'_>,
     groups: Vec<tellers_timeline_core::SyncTrackInfo>,
@@ -1350,6 +2094,18 @@ impl PyTimeline {
             OverlapPolicy::Override,
         ))
     }
+    /// New timeline of `start..end` of this one, with fresh ids.
+    fn subrange(&self, start: f64, end: f64) -> Option<PyTimeline> {
+        self.inner
+            .subrange(start, end)
+            .map(|inner| PyTimeline { inner })
+    }
+    fn append(&mut self, other: &PyTimeline) {
+        self.inner.append(&other.inner);
+    }
+    fn splice_at(&mut self, time: f64, other: &PyTimeline) -> bool {
+        self.inner.splice_at(time, &other.inner)
+    }
     fn get_metadata_json(&self) -> PyResult<String> {
         serde_json::to_string(&self.inner.metadata)
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
@@ -1371,6 +2127,100 @@ impl PyTimeline {
             .to_json_with_options(None, true)
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
     }
+    /// Context manager applying the edits made in its block atomically.
+    fn transaction(slf: Py<Self>) -> PyStackTransaction {
+        PyStackTransaction::new(TransactionTarget::Timeline(slf))
+    }
+}
+
+enum TransactionTarget {
+    Stack(Py<PyStack>),
+    Timeline(Py<PyTimeline>),
+}
+
+impl TransactionTarget {
+    fn with_stack<R>(&self, py: Python<'_>, f: impl FnOnce(&mut Stack) -> R) -> R {
+        match self {
+            TransactionTarget::Stack(stack) => f(&mut stack.borrow_mut(py).inner),
+            TransactionTarget::Timeline(timeline) => f(&mut timeline.borrow_mut(py).inner.tracks),
+        }
+    }
+}
+
+/// `with stack.transaction() as tx:` keeps the block's edits only if it exits
+/// normally; an exception, or a failed edit passed to `tx.check`, rolls them
+/// all back. Sanitizing is deferred to the end of the block.
+#[pyclass(name = "StackTransaction", unsendable)]
+struct PyStackTransaction {
+    target: TransactionTarget,
+    pending: Option<PendingTransaction>,
+    checked: usize,
+    failed: Option<TransactionError>,
+}
+
+impl PyStackTransaction {
+    fn new(target: TransactionTarget) -> Self {
+        Self {
+            target,
+            pending: None,
+            checked: 0,
+            failed: None,
+        }
+    }
+}
+
+#[pymethods]
+impl PyStackTransaction {
+    fn __enter__<'py>(mut slf: PyRefMut<'py, Self>, py: Python<'py>) -> PyResult<PyRefMut<'py, Self>> {
+        if slf.pending.is_some() {
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "transaction is already open",
+            ));
+        }
+        let pending = slf.target.with_stack(py, |stack| stack.begin_transaction());
+        slf.pending = Some(pending);
+        slf.checked = 0;
+        slf.failed = None;
+        Ok(slf)
+    }
+    #[pyo3(signature = (exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<PyAny>>,
+        _exc_value: Option<&Bound<PyAny>>,
+        _traceback: Option<&Bound<PyAny>>,
+    ) -> PyResult<bool> {
+        if let Some(pending) = self.pending.take() {
+            let keep = exc_type.is_none_or(|t| t.is_none()) && self.failed.is_none();
+            self.target
+                .with_stack(py, |stack| {
+                    if keep {
+                        pending.commit(stack)
+                    } else {
+                        pending.rollback(stack)
+                    }
+                })
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        }
+        Ok(false)
+    }
+    /// Return `outcome` if it is truthy; otherwise mark the transaction as
+    /// failed and raise `ValueError`, so the block rolls back.
+    fn check<'py>(&mut self, outcome: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        if let Some(error) = self.failed {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(error.to_string()));
+        }
+        let index = self.checked;
+        self.checked += 1;
+        if outcome.is_truthy()? {
+            Ok(outcome)
+        } else {
+            let error = TransactionError::EditFailed { index };
+            self.failed = Some(error);
+            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(error.to_string()))
+        }
+    }
 }
 
 #[pymodule]
@@ -1385,5 +2235,13 @@ fn tellers_timeline(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<PyTrack>()?;
     m.add_class::<PyStack>()?;
     m.add_class::<PyTimeline>()?;
+    m.add_class::<PyTrackKind>()?;
+    m.add_class::<PyOverlapPolicy>()?;
+    m.add_class::<PyInsertPolicy>()?;
+    m.add_class::<PySyncedInsertResult>()?;
+    m.add_class::<PyItemInRange>()?;
+    m.add_class::<PyStackIndex>()?;
+    m.add_class::<PyClipboard>()?;
+    m.add_class::<PyStackTransaction>()?;
     Ok(())
 }
@@ -1,6 +1,18 @@
 import json
 
-from tellers_timeline import Clip, Gap, Item, MediaReference, Stack, Timeline, Track
+from tellers_timeline import (
+    Clip,
+    Gap,
+    InsertPolicy,
+    Item,
+    MediaReference,
+    OverlapPolicy,
+    Stack,
+    SyncedInsertResult,
+    Timeline,
+    Track,
+    TrackKind,
+)
 
 # These tests cover ONLY the Python <-> Rust translation layer: argument
 # marshalling, result shapes, metadata round-tripping, and error mapping.
@@ -53,7 +65,7 @@ def test_track_timeline_ids_returns_child_item_ids_in_order():
     assert track.timeline_ids() == ["clip-1", "gap-1", "clip-2"]
 
 
-def test_insert_with_linked_audio_translates_to_synced_result_dict():
+def test_insert_with_linked_audio_translates_to_synced_result():
     stack = Stack([Track(kind="video")])
     primary = Clip(2.0, {"DEFAULT_MEDIA": MediaReference("file:///video.mov")}, id="primary")
     audio_ref = MediaReference(
@@ -66,26 +78,21 @@ def test_insert_with_linked_audio_translates_to_synced_result_dict():
         0, 0.0, primary, "override", "split_and_insert", [audio]
     )
 
-    # The Rust Synced result enum is translated into a dict with these keys.
-    assert isinstance(result, dict)
-    assert set(result) == {
-        "primary_clip_id",
-        "audio_clips",
-        "linked_video_clip_id",
-        "link_group_id",
-        "created_track_indices",
-    }
-    assert result["primary_clip_id"] == "primary"
-    assert isinstance(result["audio_clips"], list)
-    assert len(result["audio_clips"]) == 1
-    assert result["linked_video_clip_id"] is None
-    assert result["link_group_id"] is not None
-    assert isinstance(result["created_track_indices"], list)
+    # The Rust Synced result enum is translated into a SyncedInsertResult.
+    assert isinstance(result, SyncedInsertResult)
+    assert result.primary_clip_id == "primary"
+    assert isinstance(result.audio_clips, list)
+    assert len(result.audio_clips) == 1
+    assert result.audio_clips[0][0] == "audio"
+    assert isinstance(result.audio_clips[0][1], int)
+    assert result.linked_video_clip_id is None
+    assert result.link_group_id is not None
+    assert isinstance(result.created_track_indices, list)
 
     # The generated sync id is written into the clip metadata, and media
     # reference metadata round-trips through the binding unchanged.
     primary_item = stack.get_item("primary")[2]
-    assert maybe_link_group_id(primary_item) == result["link_group_id"]
+    assert maybe_link_group_id(primary_item) == result.link_group_id
     audio_item = stack.get_item("audio")[2]
     media = audio_item.get_media_references()["DEFAULT_MEDIA"]
     media_metadata = json.loads(media.get_metadata_json())
@@ -107,7 +114,7 @@ def test_insert_without_linked_audio_translates_to_item_id_str():
     assert result == "primary"
 
 
-def test_insert_item_at_index_translates_to_synced_result_dict():
+def test_insert_item_at_index_translates_to_synced_result():
     stack = Stack([Track(kind="video", id="v", children=[Item.from_gap(Gap(5.0))])])
 
     result = stack.insert_item_at_index(
@@ -118,12 +125,12 @@ def test_insert_item_at_index_translates_to_synced_result_dict():
         [Clip(3.0, {"DEFAULT_MEDIA": MediaReference("file:///audio.wav")}, id="audio")],
     )
 
-    assert isinstance(result, dict)
-    assert result["primary_clip_id"] == "primary"
-    assert len(result["audio_clips"]) == 1
+    assert isinstance(result, SyncedInsertResult)
+    assert result.primary_clip_id == "primary"
+    assert len(result.audio_clips) == 1
 
 
-def test_insert_with_linked_video_translates_to_synced_result_dict():
+def test_insert_with_linked_video_translates_to_synced_result():
     stack = Stack(
         [
             Track(kind="audio", id="a", children=[Item.from_gap(Gap(10.0))]),
@@ -142,11 +149,11 @@ def test_insert_with_linked_video_translates_to_synced_result_dict():
         linked_video_clip=video,
     )
 
-    assert isinstance(result, dict)
-    assert result["primary_clip_id"] == "audio"
-    assert result["linked_video_clip_id"] == "video"
-    assert result["audio_clips"] == []
-    assert result["link_group_id"] is not None
+    assert isinstance(result, SyncedInsertResult)
+    assert result.primary_clip_id == "audio"
+    assert result.linked_video_clip_id == "video"
+    assert result.audio_clips == []
+    assert result.link_group_id is not None
     assert stack.get_item("video") is not None
 
 
@@ -282,13 +289,74 @@ def test_stack_edit_methods_translate_return_types():
         ),
         bool,
     )
+    assert isinstance(
+        stack.resize_item_with_source_start("primary", 0.0, 0.5, 1.0, "override", True),
+        bool,
+    )
+    assert isinstance(stack.modify_item("primary", 0.0, 1.5, True, False, False), bool)
     assert isinstance(
         stack.move_item_at_time("primary", "a", 0.0, True, "override", "split_and_insert"),
         bool,
     )
+    assert isinstance(stack.unsync_item(["primary"]), int)
+    assert stack.sync_item(["primary"]) is None
     assert isinstance(stack.delete_item("primary", True), list)
 
 
+def test_policies_accept_enums_and_strings():
+    stack = Stack([Track(kind=TrackKind.VIDEO, id="v")])
+    assert stack.tracks()[0].kind == TrackKind.VIDEO
+    assert stack.tracks()[0].kind.value == "video"
+
+    result = stack.insert_item_at_time(
+        0,
+        0.0,
+        Clip(2.0, {"DEFAULT_MEDIA": MediaReference("file:///video.mov")}, id="primary"),
+        OverlapPolicy.OVERRIDE,
+        InsertPolicy.SPLIT_AND_INSERT,
+    )
+    assert result == "primary"
+    assert stack.move_item_at_index("primary", "v", 0, True, OverlapPolicy.PUSH)
+    assert stack.move_item_at_index("primary", "v", 0, True, "push")
+
+
+def assert_value_error_message(fn, message):
+    try:
+        fn()
+    except ValueError as exc:
+        assert message in str(exc)
+    else:
+        raise AssertionError("expected ValueError")
+
+
+def test_track_kind_compares_equal_to_its_string_value():
+    track = Track(kind="video")
+    assert track.kind == "video"
+    assert track.kind != "audio"
+    assert track.kind == TrackKind.VIDEO
+    assert track.kind != TrackKind.AUDIO
+    assert Track(kind=TrackKind.AUDIO).kind == "audio"
+    assert {TrackKind.VIDEO: 1}["video"] == 1
+    assert "other" in {Track(kind="other").kind}
+
+
+def test_unknown_policy_and_kind_strings_raise_value_error():
+    stack = Stack([Track(kind="video", id="v")])
+    clip = Clip(2.0, {"DEFAULT_MEDIA": MediaReference("file:///video.mov")}, id="primary")
+
+    assert_value_error_message(
+        lambda: stack.insert_item_at_time(0, 0.0, clip, "overwrite", "split_and_insert"),
+        "unknown overlap policy 'overwrite'",
+    )
+    assert_value_error_message(
+        lambda: stack.insert_item_at_time(0, 0.0, clip, "override", "middle"),
+        "unknown insert policy 'middle'",
+    )
+    assert_value_error_message(lambda: Track(kind="subtitles"), "unknown track kind 'subtitles'")
+    # Nothing was inserted by the rejected calls.
+    assert stack.get_item("primary") is None
+
+
 def test_timeline_delegates_to_stack():
     timeline = Timeline(Stack([Track(kind="video", id="v")]))
 
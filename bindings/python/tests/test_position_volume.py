@@ -1,6 +1,14 @@
 """Tests for position and volume getter/setter methods."""
 import json
-from tellers_timeline import Clip, MediaReference, MediaReferencePosition, Timeline
+from tellers_timeline import (
+    Clip,
+    Item,
+    MediaReference,
+    MediaReferencePosition,
+    Stack,
+    Timeline,
+    Track,
+)
 
 
 def test_get_set_position():
@@ -329,3 +337,20 @@ def test_parse_json_with_position_and_volume():
 
     volume2 = clip2.get_volume()
     assert abs(volume2 - (-12.5)) < 0.001
+
+
+def test_track_volume_keeps_clip_volumes():
+    """Track gain and pan live on the track; clips keep their own volume."""
+    clip = Clip(2.0, {"DEFAULT_MEDIA": MediaReference("file:///a.wav")}, id="A")
+    clip.set_volume(0.5)
+    track = Track(kind="audio", id="a", children=[Item.from_clip(clip)])
+
+    track.set_volume(0.5)
+    track.set_pan(2.0)
+
+    assert track.get_volume() == 0.5
+    assert track.get_pan() == 1.0
+    assert track.items()[0].get_volume() == 0.5
+    assert track.effective_volume(0) == 0.25
+    assert len(track.get_effects()) == 2
+    assert Stack([track]).effective_volume("A") == 0.25
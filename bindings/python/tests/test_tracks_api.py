@@ -1,9 +1,9 @@
 import json
 
-from tellers_timeline import Clip, Item, MediaReference, Stack, Timeline, Track
+from tellers_timeline import Clip, Clipboard, Item, MediaReference, Stack, Timeline, Track
 
 # Binding methods used by tellers-backend (Python) that were thinly covered:
-# set_tracks, insert_item_at_index (placement), to_json.
+# set_tracks, insert_item_at_index (placement), to_json, items_in_range.
 
 
 def test_set_tracks_replaces_all_tracks():
@@ -108,3 +108,328 @@ def test_get_track_by_name_returns_none_when_name_is_ambiguous():
 
 def test_find_tracks_by_name_returns_empty_list_when_nothing_matches():
     assert _named_stack().find_tracks_by_name("nope") == []
+
+
+def test_items_in_range_returns_spans_and_filters_tracks():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    stack = Stack(
+        [
+            Track(kind="audio", id="a", children=[clip(5.0, "A0")]),
+            Track(kind="video", id="v", children=[clip(2.0, "V0"), clip(4.0, "V1")]),
+        ]
+    )
+
+    hits = stack.tracks()[1].items_in_range(1.0, 3.0)
+    assert [(h.item_index, h.start, h.end) for h in hits] == [(0, 0.0, 2.0), (1, 2.0, 6.0)]
+    assert (hits[1].overlap_start, hits[1].overlap_end) == (2.0, 3.0)
+    assert hits[1].item.get_id() == "V1"
+
+    assert [ti for ti, _ in stack.items_in_range(1.0, 3.0)] == [0, 1, 1]
+    only_video = stack.items_in_range(1.0, 3.0, track_ids=["v"])
+    assert [(ti, hit.item_index) for ti, hit in only_video] == [(1, 0), (1, 1)]
+
+
+def test_stack_index_answers_range_queries_on_a_snapshot():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    stack = Stack(
+        [
+            Track(kind="audio", id="a", children=[clip(5.0, "A0")]),
+            Track(kind="video", id="v", children=[clip(2.0, "V0"), clip(4.0, "V1")]),
+        ]
+    )
+    index = stack.build_index()
+
+    def spans(hits):
+        return [(ti, h.item_index, h.overlap_start, h.overlap_end) for ti, h in hits]
+
+    assert spans(index.items_in_range(1.0, 3.0)) == spans(stack.items_in_range(1.0, 3.0))
+    assert spans(index.items_in_range(1.0, 3.0, track_ids=["v"])) == [
+        (1, 0, 1.0, 2.0),
+        (1, 1, 2.0, 3.0),
+    ]
+    assert [h.item.get_id() for h in index.track_items_in_range(1, 5.0, 9.0)] == ["V1"]
+    assert index.item_at_time(1, 2.0) == 1
+    assert index.start_time_of_item(1, 1) == 2.0
+    assert index.track_duration(0) == 5.0
+    assert index.track_duration(5) is None
+
+    # The index is a snapshot: edits made afterwards are not seen.
+    stack.delete_item("V0", False)
+    assert index.item_at_time(1, 0.5) == 0
+    assert stack.build_index().track_duration(1) == 4.0
+
+
+def test_track_state_locks_edits_and_solo_decides_rendering():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    locked = Track(kind="video", id="v1", children=[clip(2.0, "V1")])
+    locked.set_locked(True)
+    locked.set_height(40.0)
+    soloed = Track(kind="video", id="v2", children=[clip(2.0, "V2")])
+    soloed.set_solo(True)
+    stack = Stack([locked, soloed])
+
+    assert stack.tracks()[0].get_locked()
+    assert stack.tracks()[0].get_height() == 40.0
+    assert json.loads(stack.tracks()[0].get_metadata_json())["tellers.ai"]["locked"] is True
+    assert stack.delete_item("V1", True) == []
+    assert stack.delete_track("v1") is None
+    assert len(stack.delete_item("V2", True)) == 1
+
+    assert not stack.is_track_rendered(0)
+    assert stack.is_track_rendered(1)
+
+
+def test_transaction_rolls_back_on_failure_and_commits_otherwise():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    stack = Stack([Track(kind="video", id="v", children=[clip(2.0, "A"), clip(2.0, "B")])])
+    before = str(stack)
+
+    try:
+        with stack.transaction() as tx:
+            tx.check(stack.delete_item("A", False))
+            tx.check(stack.split_item_at_time("missing", 1.0))
+    except ValueError as error:
+        assert str(error) == "edit 1 of the transaction failed"
+    else:
+        raise AssertionError("a failed edit should raise")
+    assert str(stack) == before
+
+    with stack.transaction() as tx:
+        tx.check(stack.split_item_at_time("A", 1.0))
+        tx.check(stack.resize_item("B", 2.0, 1.0, "override", False))
+    assert len(stack.tracks()[0].items()) == 3
+
+    timeline = Timeline(stack)
+    try:
+        with timeline.transaction():
+            timeline.delete_track("v")
+            raise KeyError("abort")
+    except KeyError:
+        pass
+    assert timeline.get_stack().tracks()[0].get_id() == "v"
+
+
+def test_preview_reports_an_edit_without_applying_it():
+    def clip(duration, url, clip_id):
+        return Item.from_clip(Clip(duration, {"DEFAULT_MEDIA": MediaReference(url)}, id=clip_id))
+
+    stack = Stack(
+        [
+            Track(
+                kind="video",
+                id="v",
+                children=[clip(4.0, "file:///a.mov", "A"), clip(2.0, "file:///b.mov", "B")],
+            )
+        ]
+    )
+    before = str(stack)
+
+    trim = stack.preview_resize_item("B", 3.0, 3.0, "override", False)
+    assert trim["success"]
+    assert trim["trimmed_clip_ids"] == ["A"]
+    push = stack.preview_insert_item_at_time(
+        0, 4.0, Clip(1.0, {"DEFAULT_MEDIA": MediaReference("file:///n.mov")}), "push", "insert_before"
+    )
+    assert push["pushed_clip_ids"] == ["B"]
+    split = stack.preview_split_item_at_time("A", 1.0)["split_clips"]
+    assert [(s["old_clip_id"], s["left_clip_id"], s["split_time"]) for s in split] == [("A", "A", 1.0)]
+    assert stack.preview_delete_item("missing", True)["success"] is False
+    assert str(stack) == before
+
+
+def test_snap_pulls_drags_to_clip_edges_playhead_and_markers():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    stack = Stack(
+        [
+            Track(kind="video", id="v1", children=[clip(2.0, "A")]),
+            Track(kind="video", id="v2", children=[clip(1.0, "M")]),
+        ]
+    )
+
+    snap = stack.snap(1.1, 0.2, item_id="M")
+    assert snap is not None
+    assert (snap["start"], snap["target"], snap["item_id"]) == (1.0, "clip_end", "A")
+    assert snap["edge_offset"] == 1.0
+    assert stack.snap(1.5, 0.2, item_id="M") is None
+
+    snap = stack.snap(3.0, 0.2, range=(0.0, 1.0), clip_edges=False, playhead=4.15, markers=[3.1])
+    assert (snap["target"], snap["marker_index"], snap["start"]) == ("marker", 0, 3.1)
+    try:
+        stack.snap(0.0, 0.2)
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("snap needs item_id or range")
+
+
+def test_sync_locked_tracks_follow_push_inserts():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    titles = Track(kind="video", id="titles", children=[clip(4.0, "T")])
+    titles.set_sync_locked(True)
+    stack = Stack([Track(kind="video", id="v1", children=[clip(2.0, "A")]), titles])
+    assert stack.tracks()[1].get_sync_locked()
+
+    stack.insert_item_at_time(0, 1.0, clip(1.0, "N"), "push", "split_and_insert")
+    assert stack.tracks()[1].total_duration() == 5.0
+    assert len(stack.tracks()[1].items()) == 3
+
+
+def test_link_offsets_make_split_edits():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    stack = Stack(
+        [
+            Track(kind="video", id="v", children=[clip(4.0, "V")]),
+            Track(kind="audio", id="a", children=[clip(4.0, "A")]),
+        ]
+    )
+    assert stack.link_item(["V", "A"]) is not None
+
+    assert stack.set_link_offset("A", "out", 1.0)
+    assert stack.get_link_offsets("A") == (0.0, 1.0)
+    assert stack.tracks()[1].total_duration() == 5.0
+    # The audio's media has nothing before its start.
+    assert not stack.set_link_offset("A", "in", -1.0)
+    try:
+        stack.set_link_offset("A", "middle", 0.0)
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("edge must be 'in' or 'out'")
+
+
+def test_link_drift_is_reported_and_resynced():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    stack = Stack(
+        [
+            Track(kind="video", id="v", children=[clip(4.0, "V")]),
+            Track(kind="audio", id="a", children=[clip(1.0, "X"), clip(4.0, "A")]),
+        ]
+    )
+    link_group_id = stack.link_item(["V", "A"])
+    assert link_group_id is not None
+
+    [drift] = stack.get_link_drift()
+    assert drift["anchor_item_id"] == "V"
+    assert not drift["in_sync"]
+    [member] = drift["members"]
+    assert member["item_id"] == "A"
+    assert member["timeline_offset"] == 1.0
+    assert member["source_offset"] == 0.0
+
+    assert stack.resync_link_group(link_group_id, "V")
+    assert stack.get_link_group_drift(link_group_id, "A")["in_sync"]
+    assert not stack.resync_link_group(link_group_id, "missing")
+
+
+def test_groups_nest_and_ungroup_one_level():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    stack = Stack(
+        [
+            Track(kind="video", id="v1", children=[clip(2.0, "A"), clip(2.0, "C")]),
+            Track(kind="video", id="v2", children=[clip(2.0, "B")]),
+        ]
+    )
+    inner = stack.group_item(["A", "B"])
+    outer = stack.group_item(["B", "C"])
+    assert stack.get_group_parent(inner) == outer
+    assert stack.get_group_parent(outer) is None
+
+    assert stack.ungroup_item(["C"]) == 3
+    assert stack.get_group_parent(inner) is None
+
+
+def test_copy_paste_selection_relinks_copies():
+    def clip(duration, clip_id):
+        return Item.from_clip(
+            Clip(duration, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        )
+
+    stack = Stack(
+        [
+            Track(kind="video", id="v", children=[clip(2.0, "V")]),
+            Track(kind="audio", id="a", children=[clip(2.0, "A")]),
+        ]
+    )
+    link_group_id = stack.link_item(["V", "A"])
+
+    clipboard = stack.copy_selection(["V"])
+    assert len(clipboard) == 2
+    restored = Clipboard.parse_json(clipboard.to_json())
+    pasted = stack.paste_selection(restored, 4.0, 0, "override")
+    assert len(pasted) == 2
+    assert "V" not in pasted and "A" not in pasted
+    assert [track.total_duration() for track in stack.tracks()] == [6.0, 6.0]
+    [original, copy] = stack.get_link_drift()
+    assert original["link_group_id"] == link_group_id
+    assert copy["link_group_id"] != link_group_id
+    assert copy["anchor_item_id"] == pasted[0]
+
+
+def test_timeline_subrange_cuts_clips_and_timed_words():
+    clip = Clip(4.0, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id="C")
+    timeline = Timeline(Stack([Track(kind="video", id="v", children=[Item.from_clip(clip)])]))
+    timeline.set_metadata_json(
+        json.dumps({"tellers_timed_words": [{"text": "hi", "start": 0.5, "end": 1.5}]})
+    )
+
+    cut = timeline.subrange(1.0, 3.0)
+    [track] = cut.get_stack().tracks()
+    assert track.total_duration() == 2.0
+    assert track.get_id() != "v"
+    words = json.loads(cut.get_metadata_json())["tellers_timed_words"]
+    assert words == [{"text": "hi", "start": 0.0, "end": 0.5}]
+    assert timeline.get_stack().tracks()[0].total_duration() == 4.0
+    assert timeline.subrange(3.0, 1.0) is None
+
+
+def test_timeline_append_and_splice_match_tracks():
+    def segment(clip_id):
+        clip = Clip(2.0, {"DEFAULT_MEDIA": MediaReference("file:///a.mov")}, id=clip_id)
+        track = Track(kind="video", id="v", children=[Item.from_clip(clip)])
+        track.set_name("V1")
+        return Timeline(Stack([track]))
+
+    timeline = segment("first")
+    timeline.append(segment("second"))
+    assert timeline.splice_at(2.0, segment("middle"))
+    assert not timeline.splice_at(-1.0, segment("never"))
+
+    [track] = timeline.get_stack().tracks()
+    assert track.total_duration() == 6.0
+    assert [item.get_id() for item in track.items()] == ["first", "middle", "second"]
@@ -0,0 +1,1994 @@
+//! WebAssembly bindings for `tellers-timeline-core`.
+//!
+//! Mirrors the Python bindings: each JS class wraps a core value, getters hand
+//! out copies, and all edit operations live on `Stack` so a browser editor runs
+//! exactly the same edit logic as the backend. Build with
+//! `wasm-pack build bindings/wasm` to get the JS glue and `.d.ts` declarations.
+
+use tellers_timeline_core::to_json_with_precision;
+use tellers_timeline_core::track_methods::track_item_insert::{InsertPolicy, OverlapPolicy};
+use tellers_timeline_core::{
+    validate_timeline, Clip, Clipboard, Gap, InsertItemAtTimeResult, Item, MediaReference, Snap,
+    SnapMoving, SnapSources, SnapTarget, Stack, SyncEdge, SyncGroupDrift, SyncTrackInfo, TimeRange,
+    Timeline, Track, TrackKind,
+};
+use tellers_timeline_core::{IdMetadataExt, MetadataExt};
+use wasm_bindgen::prelude::*;
+
+fn js_err(e: impl std::fmt::Display) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+fn metadata_from_json(metadata_json: &str) -> Result<serde_json::Value, JsError> {
+    let v: serde_json::Value = serde_json::from_str(metadata_json).map_err(js_err)?;
+    Ok(if v.is_object() {
+        v
+    } else {
+        serde_json::Value::Object(serde_json::Map::new())
+    })
+}
+
+#[wasm_bindgen(js_name = OverlapPolicy)]
+#[derive(Clone, Copy)]
+pub enum WasmOverlapPolicy {
+    Override = "override",
+    Push = "push",
+}
+
+impl WasmOverlapPolicy {
+    fn to_core(self) -> Result<OverlapPolicy, JsError> {
+        match self {
+            Self::Override => Ok(OverlapPolicy::Override),
+            Self::Push => Ok(OverlapPolicy::Push),
+            _ => Err(JsError::new("unknown overlap policy")),
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = SyncEdge)]
+#[derive(Clone, Copy)]
+pub enum WasmSyncEdge {
+    In = "in",
+    Out = "out",
+}
+
+impl WasmSyncEdge {
+    fn to_core(self) -> Result<SyncEdge, JsError> {
+        match self {
+            Self::In => Ok(SyncEdge::In),
+            Self::Out => Ok(SyncEdge::Out),
+            _ => Err(JsError::new("unknown sync edge")),
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = InsertPolicy)]
+#[derive(Clone, Copy)]
+pub enum WasmInsertPolicy {
+    SplitAndInsert = "split_and_insert",
+    InsertBefore = "insert_before",
+    InsertAfter = "insert_after",
+    InsertBeforeOrAfter = "insert_before_or_after",
+}
+
+impl WasmInsertPolicy {
+    fn to_core(self) -> Result<InsertPolicy, JsError> {
+        match self {
+            Self::SplitAndInsert => Ok(InsertPolicy::SplitAndInsert),
+            Self::InsertBefore => Ok(InsertPolicy::InsertBefore),
+            Self::InsertAfter => Ok(InsertPolicy::InsertAfter),
+            Self::InsertBeforeOrAfter => Ok(InsertPolicy::InsertBeforeOrAfter),
+            _ => Err(JsError::new("unknown insert policy")),
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = TrackKind)]
+#[derive(Clone, Copy)]
+pub enum WasmTrackKind {
+    Video = "video",
+    Audio = "audio",
+    Other = "other",
+}
+
+impl WasmTrackKind {
+    fn to_core(self) -> Result<TrackKind, JsError> {
+        match self {
+            Self::Video => Ok(TrackKind::Video),
+            Self::Audio => Ok(TrackKind::Audio),
+            Self::Other => Ok(TrackKind::Other),
+            _ => Err(JsError::new("unknown track kind")),
+        }
+    }
+
+    fn from_core(kind: &TrackKind) -> Self {
+        match kind {
+            TrackKind::Video => Self::Video,
+            TrackKind::Audio => Self::Audio,
+            TrackKind::Other => Self::Other,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = Clip)]
+#[derive(Clone)]
+pub struct WasmClip {
+    inner: Clip,
+}
+
+#[wasm_bindgen(js_class = Clip)]
+impl WasmClip {
+    /// A clip playing `duration` seconds from the start of `targetUrl`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(duration: f64, target_url: String, name: Option<String>, id: Option<String>) -> Self {
+        let reference = MediaReference::ExternalReference {
+            target_url,
+            available_range: None,
+            name: None,
+            available_image_bounds: None,
+            metadata: serde_json::Value::Object(serde_json::Map::new()),
+        };
+        Self {
+            inner: Clip::new_single_media_reference(
+                TimeRange::new(duration, 0.0),
+                reference,
+                name,
+                id,
+            ),
+        }
+    }
+    #[wasm_bindgen(js_name = parseJson)]
+    pub fn parse_json(s: &str) -> Result<WasmClip, JsError> {
+        let inner: Clip = serde_json::from_str(s).map_err(js_err)?;
+        Ok(Self { inner })
+    }
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self, precision: Option<usize>, pretty: Option<bool>) -> Result<String, JsError> {
+        to_json_with_precision(&self.inner, precision, pretty.unwrap_or(false)).map_err(js_err)
+    }
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> Option<String> {
+        self.inner.get_id()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_id(&mut self, id: Option<String>) {
+        self.inner.set_id(id);
+    }
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> Option<String> {
+        self.inner.name.clone()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.inner.name = name;
+    }
+    #[wasm_bindgen(getter)]
+    pub fn enabled(&self) -> bool {
+        self.inner.enabled
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.inner.enabled = enabled;
+    }
+    #[wasm_bindgen(getter)]
+    pub fn duration(&self) -> f64 {
+        self.inner.source_range.get_duration()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_duration(&mut self, duration: f64) {
+        self.inner.source_range.set_duration(duration);
+    }
+    #[wasm_bindgen(getter, js_name = sourceStart)]
+    pub fn source_start(&self) -> f64 {
+        self.inner.source_range.get_start_time()
+    }
+    #[wasm_bindgen(setter, js_name = sourceStart)]
+    pub fn set_source_start(&mut self, start: f64) {
+        self.inner.source_range.set_start_time(start);
+    }
+    #[wasm_bindgen(getter)]
+    pub fn volume(&self) -> f64 {
+        self.inner.get_volume()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_volume(&mut self, volume: f64) {
+        self.inner.set_volume(volume);
+    }
+    #[wasm_bindgen(getter, js_name = activeMediaReferenceKey)]
+    pub fn active_media_reference_key(&self) -> Option<String> {
+        self.inner.active_media_reference_key.clone()
+    }
+    #[wasm_bindgen(setter, js_name = activeMediaReferenceKey)]
+    pub fn set_active_media_reference_key(&mut self, key: Option<String>) -> Result<(), JsError> {
+        if let Some(k) = &key {
+            if !self.inner.media_references.contains_key(k) {
+                return Err(JsError::new(&format!(
+                    "active media reference key '{k}' is not in media references"
+                )));
+            }
+        }
+        self.inner.active_media_reference_key = key;
+        Ok(())
+    }
+    #[wasm_bindgen(getter, js_name = metadataJson)]
+    pub fn metadata_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(self.inner.get_metadata()).map_err(js_err)
+    }
+    #[wasm_bindgen(setter, js_name = metadataJson)]
+    pub fn set_metadata_json(&mut self, metadata_json: &str) -> Result<(), JsError> {
+        self.inner.set_metadata(metadata_from_json(metadata_json)?);
+        Ok(())
+    }
+}
+
+#[wasm_bindgen(js_name = Gap)]
+#[derive(Clone)]
+pub struct WasmGap {
+    inner: Gap,
+}
+
+#[wasm_bindgen(js_class = Gap)]
+impl WasmGap {
+    #[wasm_bindgen(constructor)]
+    pub fn new(duration: f64, id: Option<String>) -> Self {
+        Self {
+            inner: Gap::new(duration, id),
+        }
+    }
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self, precision: Option<usize>, pretty: Option<bool>) -> Result<String, JsError> {
+        to_json_with_precision(&self.inner, precision, pretty.unwrap_or(false)).map_err(js_err)
+    }
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> Option<String> {
+        self.inner.get_id()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_id(&mut self, id: Option<String>) {
+        self.inner.set_id(id);
+    }
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> Option<String> {
+        self.inner.name.clone()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.inner.name = name;
+    }
+    #[wasm_bindgen(getter)]
+    pub fn duration(&self) -> f64 {
+        self.inner.source_range.get_duration()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_duration(&mut self, duration: f64) {
+        self.inner.source_range.set_duration(duration);
+    }
+    #[wasm_bindgen(getter, js_name = metadataJson)]
+    pub fn metadata_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(self.inner.get_metadata()).map_err(js_err)
+    }
+    #[wasm_bindgen(setter, js_name = metadataJson)]
+    pub fn set_metadata_json(&mut self, metadata_json: &str) -> Result<(), JsError> {
+        self.inner.set_metadata(metadata_from_json(metadata_json)?);
+        Ok(())
+    }
+}
+
+/// A track item: either a `Clip` or a `Gap`.
+#[wasm_bindgen(js_name = Item)]
+#[derive(Clone)]
+pub struct WasmItem {
+    inner: Item,
+}
+
+#[wasm_bindgen(js_class = Item)]
+impl WasmItem {
+    #[wasm_bindgen(js_name = fromClip)]
+    pub fn from_clip(clip: &WasmClip) -> Self {
+        Self {
+            inner: Item::Clip(clip.inner.clone()),
+        }
+    }
+    #[wasm_bindgen(js_name = fromGap)]
+    pub fn from_gap(gap: &WasmGap) -> Self {
+        Self {
+            inner: Item::Gap(gap.inner.clone()),
+        }
+    }
+    #[wasm_bindgen(js_name = isClip)]
+    pub fn is_clip(&self) -> bool {
+        matches!(self.inner, Item::Clip(_))
+    }
+    #[wasm_bindgen(js_name = isGap)]
+    pub fn is_gap(&self) -> bool {
+        matches!(self.inner, Item::Gap(_))
+    }
+    #[wasm_bindgen(js_name = asClip)]
+    pub fn as_clip(&self) -> Option<WasmClip> {
+        match &self.inner {
+            Item::Clip(clip) => Some(WasmClip {
+                inner: clip.clone(),
+            }),
+            Item::Gap(_) => None,
+        }
+    }
+    #[wasm_bindgen(js_name = asGap)]
+    pub fn as_gap(&self) -> Option<WasmGap> {
+        match &self.inner {
+            Item::Gap(gap) => Some(WasmGap { inner: gap.clone() }),
+            Item::Clip(_) => None,
+        }
+    }
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self, precision: Option<usize>, pretty: Option<bool>) -> Result<String, JsError> {
+        to_json_with_precision(&self.inner, precision, pretty.unwrap_or(false)).map_err(js_err)
+    }
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> Option<String> {
+        self.inner.get_id()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn duration(&self) -> f64 {
+        self.inner.duration()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn enabled(&self) -> bool {
+        self.inner.get_enabled()
+    }
+    #[wasm_bindgen(getter, js_name = metadataJson)]
+    pub fn metadata_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(self.inner.get_metadata()).map_err(js_err)
+    }
+}
+
+#[wasm_bindgen(js_name = Track)]
+#[derive(Clone)]
+pub struct WasmTrack {
+    inner: Track,
+}
+
+#[wasm_bindgen(js_class = Track)]
+impl WasmTrack {
+    #[wasm_bindgen(constructor)]
+    pub fn new(kind: Option<WasmTrackKind>, id: Option<String>) -> Result<WasmTrack, JsError> {
+        let kind = kind.map(WasmTrackKind::to_core).transpose()?;
+        Ok(Self {
+            inner: Track::new(kind.unwrap_or(TrackKind::Video), id),
+        })
+    }
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self, precision: Option<usize>, pretty: Option<bool>) -> Result<String, JsError> {
+        to_json_with_precision(&self.inner, precision, pretty.unwrap_or(false)).map_err(js_err)
+    }
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> Option<String> {
+        self.inner.get_id()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> WasmTrackKind {
+        WasmTrackKind::from_core(&self.inner.kind)
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_kind(&mut self, kind: WasmTrackKind) -> Result<(), JsError> {
+        self.inner.kind = kind.to_core()?;
+        Ok(())
+    }
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> Option<String> {
+        self.inner.name.clone()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.inner.name = name;
+    }
+    #[wasm_bindgen(getter)]
+    pub fn enabled(&self) -> bool {
+        self.inner.enabled
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.inner.enabled = enabled;
+    }
+    #[wasm_bindgen(getter)]
+    pub fn locked(&self) -> bool {
+        self.inner.is_locked()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_locked(&mut self, locked: bool) {
+        self.inner.set_locked(locked);
+    }
+    #[wasm_bindgen(getter, js_name = syncLocked)]
+    pub fn sync_locked(&self) -> bool {
+        self.inner.is_sync_locked()
+    }
+    #[wasm_bindgen(setter, js_name = syncLocked)]
+    pub fn set_sync_locked(&mut self, sync_locked: bool) {
+        self.inner.set_sync_locked(sync_locked);
+    }
+    #[wasm_bindgen(getter)]
+    pub fn solo(&self) -> bool {
+        self.inner.is_solo()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_solo(&mut self, solo: bool) {
+        self.inner.set_solo(solo);
+    }
+    #[wasm_bindgen(getter)]
+    pub fn muted(&self) -> bool {
+        self.inner.is_muted()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_muted(&mut self, muted: bool) {
+        self.inner.set_muted(muted);
+    }
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> Option<f64> {
+        self.inner.height()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_height(&mut self, height: Option<f64>) {
+        self.inner.set_height(height);
+    }
+    /// Track gain; clip volumes are kept as they are.
+    #[wasm_bindgen(getter)]
+    pub fn volume(&self) -> f64 {
+        self.inner.get_volume()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_volume(&mut self, volume: f64) {
+        self.inner.set_volume(volume);
+    }
+    #[wasm_bindgen(getter)]
+    pub fn pan(&self) -> f64 {
+        self.inner.get_pan()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_pan(&mut self, pan: f64) {
+        self.inner.set_pan(pan);
+    }
+    /// Clip volume times track gain for the item at `itemIndex`.
+    #[wasm_bindgen(js_name = effectiveVolume)]
+    pub fn effective_volume(&self, item_index: usize) -> Option<f64> {
+        self.inner.effective_volume(item_index)
+    }
+    /// Copies of the track's items, in order.
+    pub fn items(&self) -> Vec<WasmItem> {
+        self.inner
+            .items
+            .iter()
+            .cloned()
+            .map(|inner| WasmItem { inner })
+            .collect()
+    }
+    /// Append an item (a copy of it; `item` stays usable).
+    #[wasm_bindgen(js_name = pushItem)]
+    pub fn push_item(&mut self, item: &WasmItem) {
+        self.inner.items.push(item.inner.clone());
+    }
+    #[wasm_bindgen(js_name = timelineIds)]
+    pub fn timeline_ids(&self) -> Vec<String> {
+        self.inner.timeline_ids()
+    }
+    #[wasm_bindgen(js_name = itemIndexAtTime)]
+    pub fn item_index_at_time(&self, time: f64) -> Option<usize> {
+        self.inner.get_item_at_time(time)
+    }
+    #[wasm_bindgen(js_name = totalDuration)]
+    pub fn total_duration(&self) -> f64 {
+        self.inner.total_duration()
+    }
+    #[wasm_bindgen(js_name = startTimeOfItem)]
+    pub fn start_time_of_item(&self, index: usize) -> f64 {
+        self.inner.start_time_of_item(index)
+    }
+    #[wasm_bindgen(getter, js_name = metadataJson)]
+    pub fn metadata_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.inner.metadata).map_err(js_err)
+    }
+    #[wasm_bindgen(setter, js_name = metadataJson)]
+    pub fn set_metadata_json(&mut self, metadata_json: &str) -> Result<(), JsError> {
+        self.inner.set_metadata(metadata_from_json(metadata_json)?);
+        Ok(())
+    }
+}
+
+/// Where `Stack.getItem` found an item.
+#[wasm_bindgen]
+pub struct ItemLocation {
+    #[wasm_bindgen(js_name = trackIndex)]
+    pub track_index: usize,
+    #[wasm_bindgen(js_name = itemIndex)]
+    pub item_index: usize,
+    item: Item,
+}
+
+#[wasm_bindgen]
+impl ItemLocation {
+    #[wasm_bindgen(getter)]
+    pub fn item(&self) -> WasmItem {
+        WasmItem {
+            inner: self.item.clone(),
+        }
+    }
+}
+
+/// Tracks occupied by one set of synced clips.
+#[wasm_bindgen(js_name = SyncTrackInfo)]
+pub struct WasmSyncTrackInfo {
+    inner: SyncTrackInfo,
+}
+
+#[wasm_bindgen(js_class = SyncTrackInfo)]
+impl WasmSyncTrackInfo {
+    #[wasm_bindgen(getter, js_name = trackIndices)]
+    pub fn track_indices(&self) -> Vec<usize> {
+        self.inner.track_indices.clone()
+    }
+    #[wasm_bindgen(getter, js_name = trackIds, unchecked_return_type = "(string | undefined)[]")]
+    pub fn track_ids(&self) -> Vec<JsValue> {
+        self.inner
+            .track_ids
+            .iter()
+            .map(|id| id.as_deref().map_or(JsValue::UNDEFINED, JsValue::from_str))
+            .collect()
+    }
+}
+
+/// Audio clip placed by a synced insert.
+#[wasm_bindgen]
+pub struct SyncedAudioClip {
+    #[wasm_bindgen(getter_with_clone, js_name = clipId)]
+    pub clip_id: String,
+    #[wasm_bindgen(js_name = trackIndex)]
+    pub track_index: usize,
+}
+
+/// Outcome of `Stack.insertItemAtTime` / `insertItemAtIndex`. `itemId` is the
+/// inserted item; the other fields are only set for synced inserts (when
+/// linked audio or video clips were passed).
+#[wasm_bindgen]
+pub struct InsertResult {
+    #[wasm_bindgen(getter_with_clone, js_name = itemId)]
+    pub item_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = linkedVideoClipId)]
+    pub linked_video_clip_id: Option<String>,
+    #[wasm_bindgen(js_name = linkGroupId)]
+    pub link_group_id: Option<i64>,
+    audio_clips: Vec<(String, usize)>,
+    created_track_indices: Vec<usize>,
+}
+
+#[wasm_bindgen]
+impl InsertResult {
+    #[wasm_bindgen(getter, js_name = audioClips)]
+    pub fn audio_clips(&self) -> Vec<SyncedAudioClip> {
+        self.audio_clips
+            .iter()
+            .map(|(clip_id, track_index)| SyncedAudioClip {
+                clip_id: clip_id.clone(),
+                track_index: *track_index,
+            })
+            .collect()
+    }
+    #[wasm_bindgen(getter, js_name = createdTrackIndices)]
+    pub fn created_track_indices(&self) -> Vec<usize> {
+        self.created_track_indices.clone()
+    }
+}
+
+impl From<InsertItemAtTimeResult> for InsertResult {
+    fn from(result: InsertItemAtTimeResult) -> Self {
+        match result {
+            InsertItemAtTimeResult::ItemId(item_id) => InsertResult {
+                item_id,
+                linked_video_clip_id: None,
+                link_group_id: None,
+                audio_clips: Vec::new(),
+                created_track_indices: Vec::new(),
+            },
+            InsertItemAtTimeResult::Synced(result) => InsertResult {
+                item_id: result.primary_clip_id,
+                linked_video_clip_id: result.synced_video_clip_id,
+                link_group_id: result.sync_clips_id,
+                audio_clips: result.audio_clips,
+                created_track_indices: result.created_track_indices,
+            },
+        }
+    }
+}
+
+/// Clip an edit would remove, in an `EditPreview`.
+#[wasm_bindgen]
+pub struct DeletedClip {
+    #[wasm_bindgen(getter_with_clone, js_name = clipId)]
+    pub clip_id: String,
+    #[wasm_bindgen(js_name = linkGroupId)]
+    pub link_group_id: Option<i64>,
+}
+
+/// Clip an edit would cut, in an `EditPreview`. `rightClipId` is the new
+/// piece from `splitTime` on; `leftClipId` is unset when the part before it
+/// would be overwritten.
+#[wasm_bindgen]
+pub struct SplitClip {
+    #[wasm_bindgen(getter_with_clone, js_name = oldClipId)]
+    pub old_clip_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = leftClipId)]
+    pub left_clip_id: Option<String>,
+    #[wasm_bindgen(getter_with_clone, js_name = rightClipId)]
+    pub right_clip_id: Option<String>,
+    #[wasm_bindgen(js_name = linkGroupId)]
+    pub link_group_id: Option<i64>,
+    #[wasm_bindgen(js_name = splitTime)]
+    pub split_time: f64,
+}
+
+/// Outcome of the `Stack.preview*` methods: what the edit would change,
+/// without changing anything.
+#[wasm_bindgen]
+pub struct EditPreview {
+    inner: tellers_timeline_core::EditPreview,
+}
+
+#[wasm_bindgen]
+impl EditPreview {
+    #[wasm_bindgen(getter)]
+    pub fn success(&self) -> bool {
+        self.inner.result.success
+    }
+    #[wasm_bindgen(getter, js_name = deletedClips)]
+    pub fn deleted_clips(&self) -> Vec<DeletedClip> {
+        self.inner
+            .result
+            .deleted_clips
+            .iter()
+            .map(|clip| DeletedClip {
+                clip_id: clip.clip_id.clone(),
+                link_group_id: clip.sync_clips_id,
+            })
+            .collect()
+    }
+    #[wasm_bindgen(getter, js_name = splitClips)]
+    pub fn split_clips(&self) -> Vec<SplitClip> {
+        self.inner
+            .result
+            .split_clips
+            .iter()
+            .map(|split| SplitClip {
+                old_clip_id: split.old_clip_id.clone(),
+                left_clip_id: split.left_clip_id.clone(),
+                right_clip_id: split.right_clip_id.clone(),
+                link_group_id: split.sync_clips_id,
+                split_time: split.split_time,
+            })
+            .collect()
+    }
+    #[wasm_bindgen(getter, js_name = trimmedClipIds)]
+    pub fn trimmed_clip_ids(&self) -> Vec<String> {
+        self.inner.trimmed_clip_ids.clone()
+    }
+    #[wasm_bindgen(getter, js_name = pushedClipIds)]
+    pub fn pushed_clip_ids(&self) -> Vec<String> {
+        self.inner.pushed_clip_ids.clone()
+    }
+    #[wasm_bindgen(getter, js_name = movedLinkPartnerIds)]
+    pub fn moved_link_partner_ids(&self) -> Vec<String> {
+        self.inner.moved_sync_partner_ids.clone()
+    }
+    #[wasm_bindgen(getter, js_name = createdTrackIndices)]
+    pub fn created_track_indices(&self) -> Vec<usize> {
+        self.inner.created_track_indices.clone()
+    }
+}
+
+impl From<tellers_timeline_core::EditPreview> for EditPreview {
+    fn from(inner: tellers_timeline_core::EditPreview) -> Self {
+        EditPreview { inner }
+    }
+}
+
+/// How far one clip of a link group is from the group's anchor, in seconds
+/// and in frames of the clip's rate; positive is later.
+#[wasm_bindgen]
+pub struct LinkMemberDrift {
+    #[wasm_bindgen(getter_with_clone, js_name = itemId)]
+    pub item_id: Option<String>,
+    #[wasm_bindgen(js_name = trackIndex)]
+    pub track_index: usize,
+    #[wasm_bindgen(js_name = timelineOffset)]
+    pub timeline_offset: f64,
+    #[wasm_bindgen(js_name = sourceOffset)]
+    pub source_offset: f64,
+    #[wasm_bindgen(js_name = timelineOffsetFrames)]
+    pub timeline_offset_frames: f64,
+    #[wasm_bindgen(js_name = sourceOffsetFrames)]
+    pub source_offset_frames: f64,
+}
+
+/// Clips copied by `Stack.copySelection`, to paste with
+/// `Stack.pasteSelection` any number of times.
+#[wasm_bindgen(js_name = Clipboard)]
+pub struct WasmClipboard {
+    inner: Clipboard,
+}
+
+#[wasm_bindgen(js_class = Clipboard)]
+impl WasmClipboard {
+    #[wasm_bindgen(js_name = parseJson)]
+    pub fn parse_json(s: &str) -> Result<WasmClipboard, JsError> {
+        let inner: Clipboard = serde_json::from_str(s).map_err(js_err)?;
+        Ok(Self { inner })
+    }
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        to_json_with_precision(&self.inner, None, false).map_err(js_err)
+    }
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.inner.len()
+    }
+    /// From the start of the earliest clip to the end of the latest.
+    #[wasm_bindgen(getter)]
+    pub fn duration(&self) -> f64 {
+        self.inner.duration()
+    }
+}
+
+/// Outcome of `Stack.linkDrift` / `linkGroupDrift`.
+#[wasm_bindgen]
+pub struct LinkGroupDrift {
+    inner: SyncGroupDrift,
+}
+
+#[wasm_bindgen]
+impl LinkGroupDrift {
+    #[wasm_bindgen(getter, js_name = linkGroupId)]
+    pub fn link_group_id(&self) -> i64 {
+        self.inner.sync_clips_id
+    }
+    #[wasm_bindgen(getter, js_name = anchorItemId)]
+    pub fn anchor_item_id(&self) -> Option<String> {
+        self.inner.anchor_item_id.clone()
+    }
+    #[wasm_bindgen(getter, js_name = inSync)]
+    pub fn in_sync(&self) -> bool {
+        self.inner.is_in_sync()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn members(&self) -> Vec<LinkMemberDrift> {
+        self.inner
+            .members
+            .iter()
+            .map(|member| LinkMemberDrift {
+                item_id: member.item_id.clone(),
+                track_index: member.track_index,
+                timeline_offset: member.timeline_offset,
+                source_offset: member.source_offset,
+                timeline_offset_frames: member.timeline_offset_frames,
+                source_offset_frames: member.source_offset_frames,
+            })
+            .collect()
+    }
+}
+
+impl From<SyncGroupDrift> for LinkGroupDrift {
+    fn from(inner: SyncGroupDrift) -> Self {
+        LinkGroupDrift { inner }
+    }
+}
+
+/// What `Stack.snapItem` / `snapRange` may snap to. Clip edges and link
+/// partner edges are on by default; set `playhead` and `markers` to snap to
+/// them too.
+#[wasm_bindgen(js_name = SnapSources)]
+pub struct WasmSnapSources {
+    inner: SnapSources,
+}
+
+#[wasm_bindgen(js_class = SnapSources)]
+impl WasmSnapSources {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: SnapSources::default(),
+        }
+    }
+    #[wasm_bindgen(getter, js_name = clipEdges)]
+    pub fn clip_edges(&self) -> bool {
+        self.inner.clip_edges
+    }
+    #[wasm_bindgen(setter, js_name = clipEdges)]
+    pub fn set_clip_edges(&mut self, enabled: bool) {
+        self.inner.clip_edges = enabled;
+    }
+    #[wasm_bindgen(getter, js_name = linkPartnerEdges)]
+    pub fn link_partner_edges(&self) -> bool {
+        self.inner.sync_partner_edges
+    }
+    #[wasm_bindgen(setter, js_name = linkPartnerEdges)]
+    pub fn set_link_partner_edges(&mut self, enabled: bool) {
+        self.inner.sync_partner_edges = enabled;
+    }
+    #[wasm_bindgen(getter)]
+    pub fn playhead(&self) -> Option<f64> {
+        self.inner.playhead
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_playhead(&mut self, playhead: Option<f64>) {
+        self.inner.playhead = playhead;
+    }
+    #[wasm_bindgen(getter)]
+    pub fn markers(&self) -> Vec<f64> {
+        self.inner.markers.clone()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_markers(&mut self, markers: Vec<f64>) {
+        self.inner.markers = markers;
+    }
+}
+
+impl Default for WasmSnapSources {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Outcome of `Stack.snapItem` / `snapRange`. `target` is `"clipStart"`,
+/// `"clipEnd"`, `"playhead"` or `"marker"`; the clip fields are set for clip
+/// targets and `markerIndex` for markers.
+#[wasm_bindgen]
+pub struct SnapResult {
+    pub start: f64,
+    pub time: f64,
+    #[wasm_bindgen(js_name = edgeOffset)]
+    pub edge_offset: f64,
+    #[wasm_bindgen(getter_with_clone)]
+    pub target: String,
+    #[wasm_bindgen(js_name = trackIndex)]
+    pub track_index: Option<usize>,
+    #[wasm_bindgen(js_name = itemIndex)]
+    pub item_index: Option<usize>,
+    #[wasm_bindgen(getter_with_clone, js_name = itemId)]
+    pub item_id: Option<String>,
+    #[wasm_bindgen(js_name = markerIndex)]
+    pub marker_index: Option<usize>,
+}
+
+impl From<Snap> for SnapResult {
+    fn from(snap: Snap) -> Self {
+        let mut result = SnapResult {
+            start: snap.start,
+            time: snap.time,
+            edge_offset: snap.edge_offset,
+            target: String::new(),
+            track_index: None,
+            item_index: None,
+            item_id: None,
+            marker_index: None,
+        };
+        let clip = match snap.target {
+            SnapTarget::ClipStart {
+                track_index,
+                item_index,
+                item_id,
+            } => {
+                result.target = "clipStart".to_string();
+                Some((track_index, item_index, item_id))
+            }
+            SnapTarget::ClipEnd {
+                track_index,
+                item_index,
+                item_id,
+            } => {
+                result.target = "clipEnd".to_string();
+                Some((track_index, item_index, item_id))
+            }
+            SnapTarget::Playhead => {
+                result.target = "playhead".to_string();
+                None
+            }
+            SnapTarget::Marker { index } => {
+                result.target = "marker".to_string();
+                result.marker_index = Some(index);
+                None
+            }
+        };
+        if let Some((track_index, item_index, item_id)) = clip {
+            result.track_index = Some(track_index);
+            result.item_index = Some(item_index);
+            result.item_id = item_id;
+        }
+        result
+    }
+}
+
+fn linked_clips(
+    item: &Item,
+    linked_audio_clips: Option<Vec<WasmItem>>,
+    linked_video_clip: Option<WasmItem>,
+) -> Result<(Option<Vec<Item>>, Option<Item>), JsError> {
+    if (linked_audio_clips.is_some() || linked_video_clip.is_some())
+        && !matches!(item, Item::Clip(_))
+    {
+        return Err(JsError::new(
+            "linkedAudioClips and linkedVideoClip can only be used when item is a Clip",
+        ));
+    }
+    let linked_video_clip = linked_video_clip.map(|i| i.inner);
+    if matches!(linked_video_clip, Some(Item::Gap(_))) {
+        return Err(JsError::new("linkedVideoClip cannot be a Gap"));
+    }
+    let linked_audio_clips =
+        linked_audio_clips.map(|items| items.into_iter().map(|i| i.inner).collect());
+    Ok((linked_audio_clips, linked_video_clip))
+}
+
+#[wasm_bindgen(js_name = Stack)]
+#[derive(Clone)]
+pub struct WasmStack {
+    inner: Stack,
+}
+
+#[wasm_bindgen(js_class = Stack)]
+impl WasmStack {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: Stack::default(),
+        }
+    }
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self, precision: Option<usize>, pretty: Option<bool>) -> Result<String, JsError> {
+        to_json_with_precision(&self.inner, precision, pretty.unwrap_or(false)).map_err(js_err)
+    }
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> Option<String> {
+        self.inner.name.clone()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.inner.name = name;
+    }
+    /// Copies of the tracks, in order.
+    pub fn tracks(&self) -> Vec<WasmTrack> {
+        self.inner
+            .children
+            .iter()
+            .cloned()
+            .map(|inner| WasmTrack { inner })
+            .collect()
+    }
+    /// Insert a copy of `track` (default: at the end; negative indices count
+    /// from the end like Python's `list.insert`) and return its id.
+    #[wasm_bindgen(js_name = addTrack)]
+    pub fn add_track(
+        &mut self,
+        track: &WasmTrack,
+        insertion_index: Option<isize>,
+    ) -> Result<String, JsError> {
+        add_track_at(&mut self.inner, track, insertion_index)
+    }
+    #[wasm_bindgen(js_name = reorderTrack)]
+    pub fn reorder_track(&mut self, id: &str, insertion_index: isize) -> bool {
+        self.inner.reorder_track(id, insertion_index)
+    }
+    #[wasm_bindgen(js_name = deleteTrack)]
+    pub fn delete_track(&mut self, id: &str) -> Option<WasmTrack> {
+        self.inner.delete_track(id).map(|inner| WasmTrack { inner })
+    }
+    #[wasm_bindgen(js_name = syncTrackInfo)]
+    pub fn sync_track_info(&self) -> Vec<WasmSyncTrackInfo> {
+        sync_track_info_to_js(self.inner.sync_track_info())
+    }
+    /// Whether the track at `trackIndex` is enabled, not muted and not
+    /// silenced by another track's solo.
+    #[wasm_bindgen(js_name = isTrackRendered)]
+    pub fn is_track_rendered(&self, track_index: usize) -> bool {
+        self.inner.is_track_rendered(track_index)
+    }
+    /// Clip volume times track gain for the item with `itemId`.
+    #[wasm_bindgen(js_name = effectiveVolume)]
+    pub fn effective_volume(&self, item_id: &str) -> Option<f64> {
+        self.inner.effective_volume(item_id)
+    }
+    #[wasm_bindgen(js_name = getTrackById)]
+    pub fn get_track_by_id(&self, id: &str) -> Option<WasmTrack> {
+        self.inner.get_track_by_id(id).map(|(_, t)| WasmTrack {
+            inner: t.clone(),
+        })
+    }
+    #[wasm_bindgen(js_name = trackIndex)]
+    pub fn track_index(&self, id: &str) -> Option<usize> {
+        self.inner.get_track_by_id(id).map(|(i, _)| i)
+    }
+    #[wasm_bindgen(js_name = getItem)]
+    pub fn get_item(&self, id: &str) -> Option<ItemLocation> {
+        self.inner.get_item(id).map(|(ti, ii, item)| ItemLocation {
+            track_index: ti,
+            item_index: ii,
+            item: item.clone(),
+        })
+    }
+    pub fn sanitize(&mut self) {
+        self.inner.sanitize();
+    }
+    /// Remove an item (and its synced partners); returns the removed items.
+    #[wasm_bindgen(js_name = deleteItem)]
+    pub fn delete_item(&mut self, id: &str, replace_with_gap: bool) -> Vec<WasmItem> {
+        self.inner
+            .delete_item(id, replace_with_gap)
+            .into_iter()
+            .map(|(_, inner)| WasmItem { inner })
+            .collect()
+    }
+    /// Insert a copy of `item` at `destTime` on track `destTrackIndex`.
+    /// `linkedAudioClips` / `linkedVideoClip` are consumed: the JS objects
+    /// passed there can't be used afterwards.
+    #[wasm_bindgen(js_name = insertItemAtTime)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_item_at_time(
+        &mut self,
+        dest_track_index: usize,
+        dest_time: f64,
+        item: &WasmItem,
+        overlap_policy: WasmOverlapPolicy,
+        insert_policy: WasmInsertPolicy,
+        linked_audio_clips: Option<Vec<WasmItem>>,
+        linked_video_clip: Option<WasmItem>,
+    ) -> Result<Option<InsertResult>, JsError> {
+        let (audio, video) = linked_clips(&item.inner, linked_audio_clips, linked_video_clip)?;
+        Ok(self
+            .inner
+            .insert_item_at_time(
+                dest_track_index,
+                dest_time,
+                item.inner.clone(),
+                overlap_policy.to_core()?,
+                insert_policy.to_core()?,
+                audio,
+                video,
+            )
+            .map(InsertResult::from))
+    }
+    /// Insert a copy of `item` at `destIndex` on track `destTrackId`. See
+    /// `insertItemAtTime` for the linked clip arguments.
+    #[wasm_bindgen(js_name = insertItemAtIndex)]
+    pub fn insert_item_at_index(
+        &mut self,
+        dest_track_id: &str,
+        dest_index: usize,
+        item: &WasmItem,
+        overlap_policy: WasmOverlapPolicy,
+        linked_audio_clips: Option<Vec<WasmItem>>,
+        linked_video_clip: Option<WasmItem>,
+    ) -> Result<Option<InsertResult>, JsError> {
+        let (audio, video) = linked_clips(&item.inner, linked_audio_clips, linked_video_clip)?;
+        Ok(self
+            .inner
+            .insert_item_at_index(
+                dest_track_id,
+                dest_index,
+                item.inner.clone(),
+                overlap_policy.to_core()?,
+                audio,
+                video,
+            )
+            .map(InsertResult::from))
+    }
+    #[wasm_bindgen(js_name = moveItemAtTime)]
+    pub fn move_item_at_time(
+        &mut self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_time: f64,
+        replace_with_gap: bool,
+        overlap_policy: WasmOverlapPolicy,
+        insert_policy: WasmInsertPolicy,
+    ) -> Result<bool, JsError> {
+        Ok(self.inner.move_item_at_time(
+            item_id,
+            dest_track_id,
+            dest_time,
+            replace_with_gap,
+            insert_policy.to_core()?,
+            overlap_policy.to_core()?,
+        ))
+    }
+    #[wasm_bindgen(js_name = moveItemAtIndex)]
+    pub fn move_item_at_index(
+        &mut self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_index: usize,
+        replace_with_gap: bool,
+        overlap_policy: WasmOverlapPolicy,
+    ) -> Result<bool, JsError> {
+        Ok(self.inner.move_item_at_index(
+            item_id,
+            dest_track_id,
+            dest_index,
+            replace_with_gap,
+            overlap_policy.to_core()?,
+        ))
+    }
+    #[wasm_bindgen(js_name = splitItemAtTime)]
+    pub fn split_item_at_time(&mut self, item_id: &str, split_time: f64) -> bool {
+        self.inner.split_item_at_time(item_id, split_time)
+    }
+    #[wasm_bindgen(js_name = resizeItem)]
+    pub fn resize_item(
+        &mut self,
+        item_id: &str,
+        new_start_time: f64,
+        new_duration: f64,
+        overlap_policy: WasmOverlapPolicy,
+        clamp_to_media: bool,
+    ) -> Result<bool, JsError> {
+        Ok(self.inner.resize_item(
+            item_id,
+            new_start_time,
+            new_duration,
+            overlap_policy.to_core()?,
+            clamp_to_media,
+        ))
+    }
+    #[wasm_bindgen(js_name = resizeItemWithSourceStart)]
+    pub fn resize_item_with_source_start(
+        &mut self,
+        item_id: &str,
+        new_start_time: f64,
+        source_start_time: f64,
+        new_duration: f64,
+        overlap_policy: WasmOverlapPolicy,
+        clamp_to_media: bool,
+    ) -> Result<bool, JsError> {
+        Ok(self.inner.resize_item_with_source_start(
+            item_id,
+            new_start_time,
+            source_start_time,
+            new_duration,
+            overlap_policy.to_core()?,
+            clamp_to_media,
+        ))
+    }
+    #[wasm_bindgen(js_name = modifyItem)]
+    pub fn modify_item(
+        &mut self,
+        item_id: &str,
+        source_start_time: f64,
+        duration: f64,
+        clamp_to_media: bool,
+        resize_from_start: bool,
+        push_following: bool,
+    ) -> bool {
+        self.inner.modify_item(
+            item_id,
+            source_start_time,
+            duration,
+            clamp_to_media,
+            resize_from_start,
+            push_following,
+        )
+    }
+    /// Replace an item with a copy of `item`; `linkedAudioClips` is consumed.
+    #[wasm_bindgen(js_name = replaceItem)]
+    pub fn replace_item(
+        &mut self,
+        item_id: &str,
+        item: &WasmItem,
+        linked_audio_clips: Option<Vec<WasmItem>>,
+    ) -> Result<bool, JsError> {
+        let (audio, _) = linked_clips(&item.inner, linked_audio_clips, None)?;
+        Ok(self.inner.replace_item(item_id, item.inner.clone(), audio))
+    }
+    #[wasm_bindgen(js_name = syncItem)]
+    pub fn sync_item(&mut self, item_ids: Vec<String>) -> Option<i64> {
+        self.inner.sync_item(&item_ids)
+    }
+    #[wasm_bindgen(js_name = unsyncItem)]
+    pub fn unsync_item(&mut self, item_ids: Vec<String>) -> usize {
+        self.inner.unsync_item(&item_ids)
+    }
+    /// Split edit (J-cut or L-cut): move the `edge` of the linked clip
+    /// `itemId` to `offset` seconds from its link group's column, negative
+    /// being earlier. The clip stays linked.
+    #[wasm_bindgen(js_name = setLinkOffset)]
+    pub fn set_link_offset(
+        &mut self,
+        item_id: &str,
+        edge: WasmSyncEdge,
+        offset: f64,
+    ) -> Result<bool, JsError> {
+        Ok(self.inner.set_sync_offset(item_id, edge.to_core()?, offset))
+    }
+    /// The `[in, out]` split-edit offsets of a clip.
+    #[wasm_bindgen(js_name = linkOffsets)]
+    pub fn link_offsets(&self, item_id: &str) -> Option<Vec<f64>> {
+        self.inner
+            .sync_offsets(item_id)
+            .map(|offsets| vec![offsets.in_offset, offsets.out_offset])
+    }
+    /// How far the clips of every link group are from the group's first
+    /// video clip, on the timeline and in their source.
+    #[wasm_bindgen(js_name = linkDrift)]
+    pub fn link_drift(&self) -> Vec<LinkGroupDrift> {
+        self.inner
+            .sync_drift()
+            .into_iter()
+            .map(LinkGroupDrift::from)
+            .collect()
+    }
+    /// Drift of one link group, measured from `anchorItemId` when given.
+    #[wasm_bindgen(js_name = linkGroupDrift)]
+    pub fn link_group_drift(
+        &self,
+        link_group_id: i64,
+        anchor_item_id: Option<String>,
+    ) -> Option<LinkGroupDrift> {
+        self.inner
+            .sync_group_drift(link_group_id, anchor_item_id.as_deref())
+            .map(LinkGroupDrift::from)
+    }
+    /// Move and slip the clips of a link group back in line with
+    /// `anchorItemId`.
+    #[wasm_bindgen(js_name = resyncLinkGroup)]
+    pub fn resync_link_group(&mut self, link_group_id: i64, anchor_item_id: &str) -> bool {
+        self.inner.resync_group(link_group_id, anchor_item_id)
+    }
+    #[wasm_bindgen(js_name = groupItem)]
+    pub fn group_item(&mut self, item_ids: Vec<String>) -> Option<i64> {
+        self.inner.group_item(&item_ids)
+    }
+    #[wasm_bindgen(js_name = ungroupItem)]
+    pub fn ungroup_item(&mut self, item_ids: Vec<String>) -> usize {
+        self.inner.ungroup_item(&item_ids)
+    }
+    /// The group `groupId` is nested in, if any.
+    #[wasm_bindgen(js_name = groupParent)]
+    pub fn group_parent(&self, group_id: i64) -> Option<i64> {
+        self.inner.tellers_group_parent(group_id)
+    }
+    /// Copy the clips `itemIds` and their link partners.
+    #[wasm_bindgen(js_name = copySelection)]
+    pub fn copy_selection(&self, item_ids: Vec<String>) -> WasmClipboard {
+        WasmClipboard {
+            inner: self.inner.copy_selection(&item_ids),
+        }
+    }
+    /// Paste a copy of `clipboard` with its earliest clip at `destTime` and
+    /// its first track at `destTrackIndex`, with fresh ids and groups.
+    /// Returns the ids of the pasted clips.
+    #[wasm_bindgen(js_name = pasteSelection)]
+    pub fn paste_selection(
+        &mut self,
+        clipboard: &WasmClipboard,
+        dest_time: f64,
+        dest_track_index: usize,
+        overlap_policy: WasmOverlapPolicy,
+    ) -> Result<Option<Vec<String>>, JsError> {
+        Ok(self.inner.paste_selection(
+            &clipboard.inner,
+            dest_time,
+            dest_track_index,
+            overlap_policy.to_core()?,
+        ))
+    }
+    /// What `insertItemAtTime` would do, without doing it.
+    #[wasm_bindgen(js_name = previewInsertItemAtTime)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn preview_insert_item_at_time(
+        &self,
+        dest_track_index: usize,
+        dest_time: f64,
+        item: &WasmItem,
+        overlap_policy: WasmOverlapPolicy,
+        insert_policy: WasmInsertPolicy,
+        linked_audio_clips: Option<Vec<WasmItem>>,
+        linked_video_clip: Option<WasmItem>,
+    ) -> Result<EditPreview, JsError> {
+        let (audio, video) = linked_clips(&item.inner, linked_audio_clips, linked_video_clip)?;
+        Ok(self
+            .inner
+            .preview_insert_item_at_time(
+                dest_track_index,
+                dest_time,
+                item.inner.clone(),
+                overlap_policy.to_core()?,
+                insert_policy.to_core()?,
+                audio,
+                video,
+            )
+            .into())
+    }
+    /// What `moveItemAtTime` would do, without doing it.
+    #[wasm_bindgen(js_name = previewMoveItemAtTime)]
+    pub fn preview_move_item_at_time(
+        &self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_time: f64,
+        replace_with_gap: bool,
+        overlap_policy: WasmOverlapPolicy,
+        insert_policy: WasmInsertPolicy,
+    ) -> Result<EditPreview, JsError> {
+        Ok(self
+            .inner
+            .preview_move_item_at_time(
+                item_id,
+                dest_track_id,
+                dest_time,
+                replace_with_gap,
+                insert_policy.to_core()?,
+                overlap_policy.to_core()?,
+            )
+            .into())
+    }
+    /// What `moveItemAtIndex` would do, without doing it.
+    #[wasm_bindgen(js_name = previewMoveItemAtIndex)]
+    pub fn preview_move_item_at_index(
+        &self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_index: usize,
+        replace_with_gap: bool,
+        overlap_policy: WasmOverlapPolicy,
+    ) -> Result<EditPreview, JsError> {
+        Ok(self
+            .inner
+            .preview_move_item_at_index(
+                item_id,
+                dest_track_id,
+                dest_index,
+                replace_with_gap,
+                overlap_policy.to_core()?,
+            )
+            .into())
+    }
+    /// What `resizeItem` would do, without doing it.
+    #[wasm_bindgen(js_name = previewResizeItem)]
+    pub fn preview_resize_item(
+        &self,
+        item_id: &str,
+        new_start_time: f64,
+        new_duration: f64,
+        overlap_policy: WasmOverlapPolicy,
+        clamp_to_media: bool,
+    ) -> Result<EditPreview, JsError> {
+        Ok(self
+            .inner
+            .preview_resize_item(
+                item_id,
+                new_start_time,
+                new_duration,
+                overlap_policy.to_core()?,
+                clamp_to_media,
+            )
+            .into())
+    }
+    /// What `splitItemAtTime` would do, without doing it.
+    #[wasm_bindgen(js_name = previewSplitItemAtTime)]
+    pub fn preview_split_item_at_time(&self, item_id: &str, split_time: f64) -> EditPreview {
+        self.inner
+            .preview_split_item_at_time(item_id, split_time)
+            .into()
+    }
+    /// What `deleteItem` would do, without doing it.
+    #[wasm_bindgen(js_name = previewDeleteItem)]
+    pub fn preview_delete_item(&self, id: &str, replace_with_gap: bool) -> EditPreview {
+        self.inner.preview_delete_item(id, replace_with_gap).into()
+    }
+    /// Snap a drag that would start item `itemId` at `proposedStart`.
+    /// `undefined` when nothing is within `tolerance`.
+    #[wasm_bindgen(js_name = snapItem)]
+    pub fn snap_item(
+        &self,
+        item_id: String,
+        proposed_start: f64,
+        tolerance: f64,
+        sources: &WasmSnapSources,
+    ) -> Option<SnapResult> {
+        self.inner
+            .snap(
+                &SnapMoving::Item(item_id),
+                proposed_start,
+                tolerance,
+                &sources.inner,
+            )
+            .map(SnapResult::from)
+    }
+    /// Snap a drag of the time range `start..end` (e.g. media dragged in
+    /// from outside the timeline) that would start at `proposedStart`.
+    #[wasm_bindgen(js_name = snapRange)]
+    pub fn snap_range(
+        &self,
+        start: f64,
+        end: f64,
+        proposed_start: f64,
+        tolerance: f64,
+        sources: &WasmSnapSources,
+    ) -> Option<SnapResult> {
+        self.inner
+            .snap(
+                &SnapMoving::Range { start, end },
+                proposed_start,
+                tolerance,
+                &sources.inner,
+            )
+            .map(SnapResult::from)
+    }
+    #[wasm_bindgen(getter, js_name = metadataJson)]
+    pub fn metadata_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.inner.metadata).map_err(js_err)
+    }
+    #[wasm_bindgen(setter, js_name = metadataJson)]
+    pub fn set_metadata_json(&mut self, metadata_json: &str) -> Result<(), JsError> {
+        self.inner.set_metadata(metadata_from_json(metadata_json)?);
+        Ok(())
+    }
+}
+
+impl Default for WasmStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn add_track_at(
+    stack: &mut Stack,
+    track: &WasmTrack,
+    insertion_index: Option<isize>,
+) -> Result<String, JsError> {
+    let len = stack.children.len() as isize;
+    let insertion_index = insertion_index.unwrap_or(len);
+    // Same index semantics as `Stack::add_track_at` (Python-style negatives).
+    let index = if insertion_index < 0 {
+        (len + insertion_index).clamp(0, len)
+    } else {
+        insertion_index.min(len)
+    };
+    if !stack.add_track_at(track.inner.clone(), insertion_index) {
+        return Err(JsError::new("Failed to add track"));
+    }
+    stack.children[index as usize]
+        .get_id()
+        .ok_or_else(|| JsError::new("Inserted track has no id"))
+}
+
+fn sync_track_info_to_js(groups: Vec<SyncTrackInfo>) -> Vec<WasmSyncTrackInfo> {
+    groups
+        .into_iter()
+        .map(|inner| WasmSyncTrackInfo { inner })
+        .collect()
+}
+
+#[wasm_bindgen(js_name = Timeline)]
+#[derive(Clone)]
+pub struct WasmTimeline {
+    inner: Timeline,
+}
+
+#[wasm_bindgen(js_class = Timeline)]
+impl WasmTimeline {
+    #[wasm_bindgen(constructor)]
+    pub fn new(stack: Option<WasmStack>) -> Self {
+        let mut inner = Timeline::default();
+        if let Some(stack) = stack {
+            inner.tracks = stack.inner;
+        }
+        Self { inner }
+    }
+    #[wasm_bindgen(js_name = parseJson)]
+    pub fn parse_json(s: &str) -> Result<WasmTimeline, JsError> {
+        let inner: Timeline = serde_json::from_str(s).map_err(js_err)?;
+        Ok(Self { inner })
+    }
+    /// Serialize with floats rounded to `precision` decimals (default: full
+    /// precision), compact unless `pretty` is `true`, like the other types.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self, precision: Option<usize>, pretty: Option<bool>) -> Result<String, JsError> {
+        self.inner
+            .to_json_with_options(precision, pretty.unwrap_or(false))
+            .map_err(js_err)
+    }
+    pub fn validate(&self) -> Vec<String> {
+        validate_timeline(&self.inner)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+    pub fn sanitize(&mut self) {
+        self.inner.tracks.sanitize();
+    }
+    #[wasm_bindgen(js_name = clearTargetUrls)]
+    pub fn clear_target_urls(&mut self) {
+        self.inner.clear_target_urls();
+    }
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> Option<String> {
+        self.inner.name.clone()
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.inner.name = name;
+    }
+    /// A copy of the stack: edits made on it only reach the timeline once it
+    /// is assigned back. The item edit methods on `Timeline` apply in place.
+    #[wasm_bindgen(getter)]
+    pub fn stack(&self) -> WasmStack {
+        WasmStack {
+            inner: self.inner.tracks.clone(),
+        }
+    }
+    #[wasm_bindgen(setter)]
+    pub fn set_stack(&mut self, stack: &WasmStack) {
+        self.inner.tracks = stack.inner.clone();
+    }
+    #[wasm_bindgen(js_name = addTrack)]
+    pub fn add_track(
+        &mut self,
+        track: &WasmTrack,
+        insertion_index: Option<isize>,
+    ) -> Result<String, JsError> {
+        add_track_at(&mut self.inner.tracks, track, insertion_index)
+    }
+    #[wasm_bindgen(js_name = reorderTrack)]
+    pub fn reorder_track(&mut self, id: &str, insertion_index: isize) -> bool {
+        self.inner.reorder_track(id, insertion_index)
+    }
+    #[wasm_bindgen(js_name = deleteTrack)]
+    pub fn delete_track(&mut self, id: &str) -> Option<WasmTrack> {
+        self.inner.delete_track(id).map(|inner| WasmTrack { inner })
+    }
+    #[wasm_bindgen(js_name = syncTrackInfo)]
+    pub fn sync_track_info(&self) -> Vec<WasmSyncTrackInfo> {
+        sync_track_info_to_js(self.inner.sync_track_info())
+    }
+    /// New timeline of `start..end` of this one, with fresh ids; `undefined`
+    /// unless `0 <= start < end`.
+    pub fn subrange(&self, start: f64, end: f64) -> Option<WasmTimeline> {
+        self.inner
+            .subrange(start, end)
+            .map(|inner| WasmTimeline { inner })
+    }
+    /// Add `other` after the end of the longest track.
+    pub fn append(&mut self, other: &WasmTimeline) {
+        self.inner.append(&other.inner);
+    }
+    /// Insert `other` at `time`, pushing everything after it on every track.
+    #[wasm_bindgen(js_name = spliceAt)]
+    pub fn splice_at(&mut self, time: f64, other: &WasmTimeline) -> bool {
+        self.inner.splice_at(time, &other.inner)
+    }
+    #[wasm_bindgen(getter, js_name = metadataJson)]
+    pub fn metadata_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.inner.metadata).map_err(js_err)
+    }
+    #[wasm_bindgen(setter, js_name = metadataJson)]
+    pub fn set_metadata_json(&mut self, metadata_json: &str) -> Result<(), JsError> {
+        self.inner.set_metadata(metadata_from_json(metadata_json)?);
+        Ok(())
+    }
+}
+
+// Track lookups and item edits on the timeline's own stack. The edits run the
+// matching `Stack` method on the stack moved out of the timeline, so nothing is
+// copied and the two APIs can't drift apart.
+#[wasm_bindgen(js_class = Timeline)]
+impl WasmTimeline {
+    /// Copies of the tracks, in order.
+    pub fn tracks(&self) -> Vec<WasmTrack> {
+        self.inner
+            .tracks
+            .children
+            .iter()
+            .cloned()
+            .map(|inner| WasmTrack { inner })
+            .collect()
+    }
+    #[wasm_bindgen(js_name = getTrackById)]
+    pub fn get_track_by_id(&self, id: &str) -> Option<WasmTrack> {
+        self.inner
+            .tracks
+            .get_track_by_id(id)
+            .map(|(_, t)| WasmTrack { inner: t.clone() })
+    }
+    #[wasm_bindgen(js_name = trackIndex)]
+    pub fn track_index(&self, id: &str) -> Option<usize> {
+        self.inner.tracks.get_track_by_id(id).map(|(i, _)| i)
+    }
+    #[wasm_bindgen(js_name = getItem)]
+    pub fn get_item(&self, id: &str) -> Option<ItemLocation> {
+        self.inner
+            .tracks
+            .get_item(id)
+            .map(|(ti, ii, item)| ItemLocation {
+                track_index: ti,
+                item_index: ii,
+                item: item.clone(),
+            })
+    }
+    #[wasm_bindgen(js_name = deleteItem)]
+    pub fn delete_item(&mut self, id: &str, replace_with_gap: bool) -> Vec<WasmItem> {
+        self.with_stack(|stack| stack.delete_item(id, replace_with_gap))
+    }
+    #[wasm_bindgen(js_name = insertItemAtTime)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_item_at_time(
+        &mut self,
+        dest_track_index: usize,
+        dest_time: f64,
+        item: &WasmItem,
+        overlap_policy: WasmOverlapPolicy,
+        insert_policy: WasmInsertPolicy,
+        linked_audio_clips: Option<Vec<WasmItem>>,
+        linked_video_clip: Option<WasmItem>,
+    ) -> Result<Option<InsertResult>, JsError> {
+        self.with_stack(|stack| {
+            stack.insert_item_at_time(
+                dest_track_index,
+                dest_time,
+                item,
+                overlap_policy,
+                insert_policy,
+                linked_audio_clips,
+                linked_video_clip,
+            )
+        })
+    }
+    #[wasm_bindgen(js_name = insertItemAtIndex)]
+    pub fn insert_item_at_index(
+        &mut self,
+        dest_track_id: &str,
+        dest_index: usize,
+        item: &WasmItem,
+        overlap_policy: WasmOverlapPolicy,
+        linked_audio_clips: Option<Vec<WasmItem>>,
+        linked_video_clip: Option<WasmItem>,
+    ) -> Result<Option<InsertResult>, JsError> {
+        self.with_stack(|stack| {
+            stack.insert_item_at_index(
+                dest_track_id,
+                dest_index,
+                item,
+                overlap_policy,
+                linked_audio_clips,
+                linked_video_clip,
+            )
+        })
+    }
+    #[wasm_bindgen(js_name = moveItemAtTime)]
+    pub fn move_item_at_time(
+        &mut self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_time: f64,
+        replace_with_gap: bool,
+        overlap_policy: WasmOverlapPolicy,
+        insert_policy: WasmInsertPolicy,
+    ) -> Result<bool, JsError> {
+        self.with_stack(|stack| {
+            stack.move_item_at_time(
+                item_id,
+                dest_track_id,
+                dest_time,
+                replace_with_gap,
+                overlap_policy,
+                insert_policy,
+            )
+        })
+    }
+    #[wasm_bindgen(js_name = moveItemAtIndex)]
+    pub fn move_item_at_index(
+        &mut self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_index: usize,
+        replace_with_gap: bool,
+        overlap_policy: WasmOverlapPolicy,
+    ) -> Result<bool, JsError> {
+        self.with_stack(|stack| {
+            stack.move_item_at_index(
+                item_id,
+                dest_track_id,
+                dest_index,
+                replace_with_gap,
+                overlap_policy,
+            )
+        })
+    }
+    #[wasm_bindgen(js_name = splitItemAtTime)]
+    pub fn split_item_at_time(&mut self, item_id: &str, split_time: f64) -> bool {
+        self.with_stack(|stack| stack.split_item_at_time(item_id, split_time))
+    }
+    #[wasm_bindgen(js_name = resizeItem)]
+    pub fn resize_item(
+        &mut self,
+        item_id: &str,
+        new_start_time: f64,
+        new_duration: f64,
+        overlap_policy: WasmOverlapPolicy,
+        clamp_to_media: bool,
+    ) -> Result<bool, JsError> {
+        self.with_stack(|stack| {
+            stack.resize_item(
+                item_id,
+                new_start_time,
+                new_duration,
+                overlap_policy,
+                clamp_to_media,
+            )
+        })
+    }
+    #[wasm_bindgen(js_name = resizeItemWithSourceStart)]
+    pub fn resize_item_with_source_start(
+        &mut self,
+        item_id: &str,
+        new_start_time: f64,
+        source_start_time: f64,
+        new_duration: f64,
+        overlap_policy: WasmOverlapPolicy,
+        clamp_to_media: bool,
+    ) -> Result<bool, JsError> {
+        self.with_stack(|stack| {
+            stack.resize_item_with_source_start(
+                item_id,
+                new_start_time,
+                source_start_time,
+                new_duration,
+                overlap_policy,
+                clamp_to_media,
+            )
+        })
+    }
+    #[wasm_bindgen(js_name = modifyItem)]
+    pub fn modify_item(
+        &mut self,
+        item_id: &str,
+        source_start_time: f64,
+        duration: f64,
+        clamp_to_media: bool,
+        resize_from_start: bool,
+        push_following: bool,
+    ) -> bool {
+        self.with_stack(|stack| {
+            stack.modify_item(
+                item_id,
+                source_start_time,
+                duration,
+                clamp_to_media,
+                resize_from_start,
+                push_following,
+            )
+        })
+    }
+    #[wasm_bindgen(js_name = replaceItem)]
+    pub fn replace_item(
+        &mut self,
+        item_id: &str,
+        item: &WasmItem,
+        linked_audio_clips: Option<Vec<WasmItem>>,
+    ) -> Result<bool, JsError> {
+        self.with_stack(|stack| stack.replace_item(item_id, item, linked_audio_clips))
+    }
+    #[wasm_bindgen(js_name = syncItem)]
+    pub fn sync_item(&mut self, item_ids: Vec<String>) -> Option<i64> {
+        self.inner.tracks.sync_item(&item_ids)
+    }
+    #[wasm_bindgen(js_name = unsyncItem)]
+    pub fn unsync_item(&mut self, item_ids: Vec<String>) -> usize {
+        self.inner.tracks.unsync_item(&item_ids)
+    }
+    #[wasm_bindgen(js_name = groupItem)]
+    pub fn group_item(&mut self, item_ids: Vec<String>) -> Option<i64> {
+        self.inner.tracks.group_item(&item_ids)
+    }
+    #[wasm_bindgen(js_name = ungroupItem)]
+    pub fn ungroup_item(&mut self, item_ids: Vec<String>) -> usize {
+        self.inner.tracks.ungroup_item(&item_ids)
+    }
+}
+
+impl WasmTimeline {
+    fn with_stack<R>(&mut self, edit: impl FnOnce(&mut WasmStack) -> R) -> R {
+        let mut stack = WasmStack {
+            inner: std::mem::take(&mut self.inner.tracks),
+        };
+        let result = edit(&mut stack);
+        self.inner.tracks = stack.inner;
+        result
+    }
+}
+
+// Native tests: they stay on the success paths, since building a `JsError`
+// needs a JS host.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tellers_timeline_core::SyncedInsertResult;
+
+    fn ok<T>(result: Result<T, JsError>) -> T {
+        match result {
+            Ok(value) => value,
+            Err(_) => panic!("unexpected JsError"),
+        }
+    }
+
+    fn clip(id: &str) -> WasmItem {
+        let reference = MediaReference::ExternalReference {
+            target_url: "file:///media.mov".to_string(),
+            available_range: Some(TimeRange::new(100.0, 0.0)),
+            name: None,
+            available_image_bounds: None,
+            metadata: serde_json::json!({}),
+        };
+        WasmItem {
+            inner: Item::Clip(Clip::new_single_media_reference(
+                TimeRange::new(2.0, 0.0),
+                reference,
+                None,
+                Some(id.to_string()),
+            )),
+        }
+    }
+
+    fn track(id: &str) -> WasmTrack {
+        WasmTrack {
+            inner: Track::new(TrackKind::Video, Some(id.to_string())),
+        }
+    }
+
+    fn track_ids(stack: &Stack) -> Vec<String> {
+        stack.children.iter().filter_map(|t| t.get_id()).collect()
+    }
+
+    #[test]
+    fn policies_and_kinds_map_to_core() {
+        assert_eq!(
+            ok(WasmOverlapPolicy::Override.to_core()),
+            OverlapPolicy::Override
+        );
+        assert_eq!(ok(WasmOverlapPolicy::Push.to_core()), OverlapPolicy::Push);
+        assert_eq!(
+            ok(WasmInsertPolicy::SplitAndInsert.to_core()),
+            InsertPolicy::SplitAndInsert
+        );
+        assert_eq!(
+            ok(WasmInsertPolicy::InsertBefore.to_core()),
+            InsertPolicy::InsertBefore
+        );
+        assert_eq!(
+            ok(WasmInsertPolicy::InsertAfter.to_core()),
+            InsertPolicy::InsertAfter
+        );
+        assert_eq!(
+            ok(WasmInsertPolicy::InsertBeforeOrAfter.to_core()),
+            InsertPolicy::InsertBeforeOrAfter
+        );
+        assert_eq!(ok(WasmSyncEdge::In.to_core()), SyncEdge::In);
+        assert_eq!(ok(WasmSyncEdge::Out.to_core()), SyncEdge::Out);
+        for kind in [TrackKind::Video, TrackKind::Audio, TrackKind::Other] {
+            assert_eq!(ok(WasmTrackKind::from_core(&kind).to_core()), kind);
+        }
+    }
+
+    #[test]
+    fn insert_result_converts_plain_and_synced_outcomes() {
+        let plain = InsertResult::from(InsertItemAtTimeResult::ItemId("c".to_string()));
+        assert_eq!(plain.item_id, "c");
+        assert_eq!(plain.linked_video_clip_id, None);
+        assert_eq!(plain.link_group_id, None);
+        assert!(plain.audio_clips().is_empty());
+        assert!(plain.created_track_indices().is_empty());
+
+        let synced = InsertResult::from(InsertItemAtTimeResult::Synced(SyncedInsertResult {
+            primary_clip_id: "v".to_string(),
+            audio_clips: vec![("a0".to_string(), 0), ("a1".to_string(), 3)],
+            synced_video_clip_id: Some("v2".to_string()),
+            sync_clips_id: Some(7),
+            created_track_indices: vec![3],
+        }));
+        assert_eq!(synced.item_id, "v");
+        assert_eq!(synced.linked_video_clip_id.as_deref(), Some("v2"));
+        assert_eq!(synced.link_group_id, Some(7));
+        let audio: Vec<_> = synced
+            .audio_clips()
+            .into_iter()
+            .map(|clip| (clip.clip_id, clip.track_index))
+            .collect();
+        assert_eq!(audio, vec![("a0".to_string(), 0), ("a1".to_string(), 3)]);
+        assert_eq!(synced.created_track_indices(), vec![3]);
+    }
+
+    #[test]
+    fn edit_preview_reports_without_editing() {
+        let mut stack = Stack::default();
+        let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+        video.items.push(clip("A").inner);
+        stack.children.push(video);
+        let wasm = WasmStack {
+            inner: stack.clone(),
+        };
+
+        let preview = wasm.preview_split_item_at_time("A", 1.0);
+        assert!(preview.success());
+        let splits: Vec<_> = preview
+            .split_clips()
+            .into_iter()
+            .map(|split| (split.old_clip_id, split.left_clip_id, split.split_time))
+            .collect();
+        assert_eq!(splits, vec![("A".to_string(), Some("A".to_string()), 1.0)]);
+        let deleted = wasm.preview_delete_item("A", false).deleted_clips();
+        assert_eq!(deleted[0].clip_id, "A");
+        assert_eq!(wasm.inner, stack);
+    }
+
+    #[test]
+    fn snap_result_names_the_target() {
+        let mut stack = Stack::default();
+        for id in ["A", "M"] {
+            let mut video = Track::new(TrackKind::Video, Some(format!("v{id}")));
+            video.items.push(clip(id).inner);
+            stack.children.push(video);
+        }
+        let wasm = WasmStack { inner: stack };
+        let mut sources = WasmSnapSources::new();
+
+        let snap = wasm.snap_item("M".to_string(), 2.1, 0.2, &sources).unwrap();
+        assert_eq!((snap.start, snap.target.as_str()), (2.0, "clipEnd"));
+        assert_eq!(snap.item_id.as_deref(), Some("A"));
+        assert_eq!(snap.marker_index, None);
+
+        sources.set_clip_edges(false);
+        sources.set_markers(vec![5.0]);
+        let snap = wasm.snap_range(0.0, 1.0, 3.9, 0.2, &sources).unwrap();
+        assert_eq!((snap.target.as_str(), snap.marker_index), ("marker", Some(0)));
+        assert_eq!(snap.edge_offset, 1.0);
+        assert_eq!(snap.track_index, None);
+    }
+
+    #[test]
+    fn add_track_returns_the_id_at_the_resolved_index() {
+        let mut stack = Stack::default();
+        assert_eq!(ok(add_track_at(&mut stack, &track("a"), None)), "a");
+        assert_eq!(ok(add_track_at(&mut stack, &track("b"), Some(0))), "b");
+        // Past the end appends; negative counts from the end, clamped at 0.
+        assert_eq!(ok(add_track_at(&mut stack, &track("c"), Some(10))), "c");
+        assert_eq!(ok(add_track_at(&mut stack, &track("d"), Some(-1))), "d");
+        assert_eq!(ok(add_track_at(&mut stack, &track("e"), Some(-10))), "e");
+        assert_eq!(track_ids(&stack), ["e", "b", "a", "d", "c"]);
+    }
+
+    #[test]
+    fn timeline_edits_apply_to_its_own_stack() {
+        let mut timeline = WasmTimeline::new(None);
+        ok(timeline.add_track(&track("v"), None));
+        let inserted = ok(timeline.insert_item_at_time(
+            0,
+            0.0,
+            &clip("c"),
+            WasmOverlapPolicy::Override,
+            WasmInsertPolicy::SplitAndInsert,
+            None,
+            None,
+        ));
+        assert_eq!(inserted.map(|result| result.item_id).as_deref(), Some("c"));
+        assert!(timeline.get_item("c").is_some());
+        assert!(timeline.split_item_at_time("c", 1.0));
+        assert_eq!(timeline.inner.tracks.children[0].items.len(), 2);
+        assert_eq!(timeline.delete_item("c", false).len(), 1);
+        assert_eq!(timeline.inner.tracks.children[0].items.len(), 1);
+
+        // The getter is a snapshot: editing it leaves the timeline untouched.
+        let mut copy = timeline.stack();
+        copy.delete_track("v");
+        assert_eq!(timeline.track_index("v"), Some(0));
+    }
+
+    #[test]
+    fn json_is_compact_unless_asked_for_on_every_type() {
+        let timeline = WasmTimeline::new(None);
+        assert!(!ok(timeline.to_json(None, None)).contains('\n'));
+        assert!(ok(timeline.to_json(None, Some(true))).contains('\n'));
+        assert!(!ok(timeline.stack().to_json(None, None)).contains('\n'));
+    }
+}
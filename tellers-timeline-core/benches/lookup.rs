@@ -0,0 +1,183 @@
+//! Lookups and synced edits on a documentary-sized timeline: two video and two
+//! audio tracks, 2 000 clips each, every video clip synced with the audio clip
+//! below it.
+//!
+//! Run with `cargo bench -p tellers-timeline-core --bench lookup`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use tellers_timeline_core::{
+    Clip, IdMetadataExt, InsertPolicy, Item, MediaReference, OverlapPolicy, Stack, TimeRange,
+    Track, TrackKind,
+};
+
+const CLIPS_PER_TRACK: usize = 2_000;
+const CLIP_DURATION: f64 = 2.5;
+
+fn clip(id: String) -> Item {
+    let reference = MediaReference::ExternalReference {
+        target_url: "file:///media.mov".to_string(),
+        available_range: Some(TimeRange::new(10_000.0, 0.0)),
+        name: None,
+        available_image_bounds: None,
+        metadata: serde_json::json!({}),
+    };
+    Item::Clip(Clip::new_single_media_reference(
+        TimeRange::new(CLIP_DURATION, 0.0),
+        reference,
+        None,
+        Some(id),
+    ))
+}
+
+fn large_stack() -> Stack {
+    let mut stack = Stack::default();
+    for (prefix, kind) in [
+        ("a2", TrackKind::Audio),
+        ("a1", TrackKind::Audio),
+        ("v1", TrackKind::Video),
+        ("v2", TrackKind::Video),
+    ] {
+        let mut track = Track::new(kind, Some(prefix.to_string()));
+        for i in 0..CLIPS_PER_TRACK {
+            track.items.push(clip(format!("{prefix}-{i}")));
+        }
+        stack.children.push(track);
+    }
+    for i in 0..CLIPS_PER_TRACK {
+        for (video, audio) in [("v1", "a1"), ("v2", "a2")] {
+            stack.sync_item(&[format!("{video}-{i}"), format!("{audio}-{i}")]);
+        }
+    }
+    stack
+}
+
+fn lookups(c: &mut Criterion) {
+    let stack = large_stack();
+    let last = format!("v2-{}", CLIPS_PER_TRACK - 1);
+    let track = &stack.children[3];
+    let end = track.total_duration() - 1.0;
+
+    c.bench_function("scan/get_item/last", |b| {
+        b.iter(|| stack.get_item(black_box(&last)).map(|(ti, ii, _)| (ti, ii)))
+    });
+    c.bench_function("scan/get_item/missing", |b| {
+        b.iter(|| stack.get_item(black_box("missing")).is_some())
+    });
+    c.bench_function("scan/start_time_of_item/last", |b| {
+        b.iter(|| track.start_time_of_item(black_box(CLIPS_PER_TRACK - 1)))
+    });
+    c.bench_function("scan/get_item_at_time/end", |b| {
+        b.iter(|| track.get_item_at_time(black_box(end)))
+    });
+    // Building the id table is one pass; it pays off from the second lookup.
+    c.bench_function("index/build_ids", |b| b.iter(|| stack.build_index().len()));
+    let index = stack.build_index();
+    c.bench_function("index/get_item/last", |b| {
+        b.iter(|| index.get_item(black_box(&last)).map(|(ti, ii, _)| (ti, ii)))
+    });
+    c.bench_function("index/start_time_of_item/last", |b| {
+        b.iter(|| index.start_time_of_item(black_box(3), black_box(CLIPS_PER_TRACK - 1)))
+    });
+    c.bench_function("scan/items_in_range/end", |b| {
+        b.iter(|| {
+            track
+                .items_in_range(black_box(end - 10.0), black_box(end))
+                .len()
+        })
+    });
+    c.bench_function("index/items_in_range/end", |b| {
+        b.iter(|| {
+            index
+                .track_items_in_range(3, black_box(end - 10.0), black_box(end))
+                .len()
+        })
+    });
+    c.bench_function("index/item_at_time/end", |b| {
+        b.iter(|| index.item_at_time(black_box(3), black_box(end)))
+    });
+}
+
+fn synced_move(c: &mut Criterion) {
+    let stack = large_stack();
+    let id = format!("v1-{}", CLIPS_PER_TRACK / 2);
+    let dest = CLIP_DURATION * (CLIPS_PER_TRACK as f64 + 10.0);
+    assert!(stack
+        .get_item(&id)
+        .is_some_and(|(_, _, item)| item.get_id().is_some()));
+
+    c.bench_function("move_item_at_time/synced", |b| {
+        b.iter_batched(
+            || stack.clone(),
+            |mut stack| {
+                stack.move_item_at_time(
+                    &id,
+                    "v1",
+                    dest,
+                    true,
+                    InsertPolicy::InsertBefore,
+                    OverlapPolicy::Override,
+                )
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+// Edits that ripple every sync group after the touched clip.
+fn synced_ripples(c: &mut Criterion) {
+    let stack = large_stack();
+    let id = format!("v1-{}", CLIPS_PER_TRACK / 2);
+    let start = stack
+        .get_item(&id)
+        .map(|(ti, ii, _)| stack.children[ti].start_time_of_item(ii))
+        .expect("clip");
+
+    c.bench_function("delete_item/collapse", |b| {
+        b.iter_batched(
+            || stack.clone(),
+            |mut stack| stack.delete_item(&id, false).len(),
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("resize_item/shrink", |b| {
+        b.iter_batched(
+            || stack.clone(),
+            |mut stack| {
+                stack.resize_item(
+                    &id,
+                    start,
+                    CLIP_DURATION / 2.0,
+                    OverlapPolicy::Override,
+                    false,
+                )
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("insert_item_at_time/push", |b| {
+        b.iter_batched(
+            || stack.clone(),
+            |mut stack| {
+                stack
+                    .insert_item_at_time(
+                        2,
+                        start,
+                        clip("inserted".to_string()),
+                        OverlapPolicy::Push,
+                        InsertPolicy::InsertBefore,
+                        Some(vec![clip("inserted-audio".to_string())]),
+                        None,
+                    )
+                    .is_some()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = lookups, synced_move, synced_ripples
+}
+criterion_main!(benches);
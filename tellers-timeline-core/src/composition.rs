@@ -0,0 +1,258 @@
+//! A flattened, render-ready view of a timeline.
+//!
+//! `Timeline::composition_at` answers "what is on screen and in the mix at
+//! time t": the active video layers, top-most first, and every audio clip to
+//! mix, each resolved to the media and source time a renderer needs.
+//! `Timeline::segments` walks the whole timeline as contiguous spans over
+//! which that answer does not change, so a renderer can emit one graph per
+//! segment instead of sampling frames.
+//!
+//! Track order follows OTIO: later tracks sit on top, so the video track with
+//! the highest index wins where layers overlap. Only clips produce layers;
+//! gaps and `Other` tracks are skipped.
+
+use crate::{
+    Clip, IdMetadataExt, Item, MediaReferenceCrop, MediaReferencePosition, Seconds, Stack,
+    StackIndex, Timeline, Track, TrackKind,
+};
+
+/// One clip contributing to the picture or the mix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositionLayer {
+    pub track_index: usize,
+    pub item_index: usize,
+    pub clip_id: Option<String>,
+    /// `target_url` of the active reference; `None` for generator references
+    /// (see `generator_kind`) and clips without a resolvable reference.
+    pub media_url: Option<String>,
+    pub generator_kind: Option<String>,
+    /// Where the clip sits on the timeline.
+    pub start: Seconds,
+    pub end: Seconds,
+    /// The time in the media that plays at the queried time (for segments,
+    /// at the segment start); advances one-to-one with timeline time.
+    pub source_time: Seconds,
+    pub position: MediaReferencePosition,
+    pub crop: MediaReferenceCrop,
+    /// The clip's own volume; see `track_volume` and
+    /// [`CompositionLayer::effective_volume`].
+    pub volume: f64,
+    /// Gain and pan of the clip's track.
+    pub track_volume: f64,
+    pub track_pan: f64,
+    pub clip_enabled: bool,
+    pub track_enabled: bool,
+    /// The track is enabled, not muted and not silenced by another track's
+    /// solo; see [`Stack::is_track_rendered`].
+    pub track_rendered: bool,
+}
+
+impl CompositionLayer {
+    /// Whether the layer should be rendered: the clip is enabled and its
+    /// track is rendered. Disabled, muted and soloed-out layers are still
+    /// reported so callers can show them.
+    pub fn is_active(&self) -> bool {
+        self.clip_enabled && self.track_rendered
+    }
+
+    /// Volume the layer plays at: clip volume times track gain.
+    pub fn effective_volume(&self) -> f64 {
+        self.volume * self.track_volume
+    }
+
+    fn key(&self) -> (usize, usize) {
+        (self.track_index, self.item_index)
+    }
+}
+
+/// The layers active at one instant.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Composition {
+    /// Video layers, top-most first: `video[0]` covers everything below it.
+    pub video: Vec<CompositionLayer>,
+    /// Audio clips to mix, in track order.
+    pub audio: Vec<CompositionLayer>,
+}
+
+impl Composition {
+    fn layer_keys(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.video
+            .iter()
+            .chain(&self.audio)
+            .map(CompositionLayer::key)
+    }
+}
+
+/// A span of the timeline over which the set of layers is constant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositionSegment {
+    pub start: Seconds,
+    pub end: Seconds,
+    /// The layers in `start..end`, with source times taken at `start`.
+    pub composition: Composition,
+}
+
+impl Timeline {
+    /// The video layers and audio clips active at `time`.
+    pub fn composition_at(&self, time: Seconds) -> Composition {
+        compose(&self.tracks, time, |track_index, time| {
+            let track = &self.tracks.children[track_index];
+            let item_index = track.get_item_at_time(time)?;
+            Some((item_index, track.start_time_of_item(item_index)))
+        })
+    }
+
+    /// Contiguous segments covering `0..duration`, split wherever the set of
+    /// layers changes. Spans with nothing on any track are yielded too (with
+    /// an empty composition) so the segments tile the timeline.
+    pub fn segments(&self) -> CompositionSegments<'_> {
+        CompositionSegments::new(&self.tracks)
+    }
+}
+
+/// Iterator returned by [`Timeline::segments`].
+pub struct CompositionSegments<'a> {
+    stack: &'a Stack,
+    index: StackIndex<'a>,
+    /// Every item boundary on a video or audio track, sorted and deduplicated.
+    boundaries: Vec<Seconds>,
+    next: usize,
+}
+
+impl<'a> CompositionSegments<'a> {
+    fn new(stack: &'a Stack) -> Self {
+        let index = stack.build_index();
+        let mut boundaries = Vec::new();
+        for (track_index, track) in stack.children.iter().enumerate() {
+            if !is_layer_track(&track.kind) {
+                continue;
+            }
+            for item_index in 0..=track.items.len() {
+                if let Some(time) = index.start_time_of_item(track_index, item_index) {
+                    boundaries.push(time);
+                }
+            }
+        }
+        boundaries.sort_by(|a, b| a.total_cmp(b));
+        boundaries.dedup();
+        Self {
+            stack,
+            index,
+            boundaries,
+            next: 0,
+        }
+    }
+
+    fn composition_at(&self, time: Seconds) -> Composition {
+        compose(self.stack, time, |track_index, time| {
+            let item_index = self.index.item_at_time(track_index, time)?;
+            Some((
+                item_index,
+                self.index.start_time_of_item(track_index, item_index)?,
+            ))
+        })
+    }
+}
+
+impl Iterator for CompositionSegments<'_> {
+    type Item = CompositionSegment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = *self.boundaries.get(self.next)?;
+        let mut end = *self.boundaries.get(self.next + 1)?;
+        let composition = self.composition_at(start);
+        self.next += 1;
+        // Boundaries where no layer changes (a gap meeting another gap or the
+        // end of its track) don't start a new segment.
+        while let Some(&following) = self.boundaries.get(self.next + 1) {
+            if !composition
+                .layer_keys()
+                .eq(self.composition_at(end).layer_keys())
+            {
+                break;
+            }
+            end = following;
+            self.next += 1;
+        }
+        Some(CompositionSegment {
+            start,
+            end,
+            composition,
+        })
+    }
+}
+
+fn is_layer_track(kind: &TrackKind) -> bool {
+    matches!(kind, TrackKind::Video | TrackKind::Audio)
+}
+
+/// Build the composition at `time`; `item_at` resolves (item_index, start)
+/// of the item under `time` on a track.
+fn compose(
+    stack: &Stack,
+    time: Seconds,
+    item_at: impl Fn(usize, Seconds) -> Option<(usize, Seconds)>,
+) -> Composition {
+    let mut composition = Composition::default();
+    for (track_index, track) in stack.children.iter().enumerate().rev() {
+        if !is_layer_track(&track.kind) {
+            continue;
+        }
+        let Some((item_index, start)) = item_at(track_index, time) else {
+            continue;
+        };
+        let Some(Item::Clip(clip)) = track.items.get(item_index) else {
+            continue;
+        };
+        let layer = layer(
+            clip,
+            track,
+            track_index,
+            item_index,
+            start,
+            time,
+            stack.is_track_rendered(track_index),
+        );
+        match track.kind {
+            TrackKind::Video => composition.video.push(layer),
+            _ => composition.audio.push(layer),
+        }
+    }
+    composition.audio.reverse();
+    composition
+}
+
+fn layer(
+    clip: &Clip,
+    track: &Track,
+    track_index: usize,
+    item_index: usize,
+    start: Seconds,
+    time: Seconds,
+    track_rendered: bool,
+) -> CompositionLayer {
+    let reference = clip.active_media_reference();
+    CompositionLayer {
+        track_index,
+        item_index,
+        clip_id: clip.get_id(),
+        media_url: reference
+            .and_then(|reference| reference.target_url())
+            .filter(|url| !url.is_empty())
+            .cloned(),
+        generator_kind: reference
+            .and_then(|reference| reference.generator_kind())
+            .cloned(),
+        start,
+        end: start + clip.source_range.duration.to_seconds().max(0.0),
+        source_time: clip.source_range.start_time.to_seconds() + (time - start),
+        position: clip.get_position(),
+        crop: clip.get_crop(),
+        volume: clip.get_volume(),
+        track_volume: track.get_volume(),
+        track_pan: track.get_pan(),
+        clip_enabled: clip.enabled,
+        track_enabled: track.enabled,
+        track_rendered,
+    }
+}
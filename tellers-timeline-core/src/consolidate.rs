@@ -0,0 +1,225 @@
+//! Trim a timeline down to the media it uses.
+//!
+//! Archiving a cut should not mean archiving every rush in full.
+//! [`Timeline::consolidate`] takes, for each media of [`Timeline::media_usage`],
+//! the span from the first to the last used second plus handles, points the
+//! clips at a trimmed copy of the file holding just that span, and returns the
+//! transcode jobs that produce those copies. The timeline is rewritten as if
+//! the jobs had already run; running them is up to the caller. Only the
+//! active media reference is trimmed, so unless timecode is preserved, clips
+//! holding other renditions are skipped and reported.
+
+use crate::{IdMetadataExt, Item, MediaReference, MediaUsageEntry, Seconds, Timeline};
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsolidateOptions {
+    /// Extra media kept on each side of the used span, clamped to what the
+    /// source has.
+    pub handles: Seconds,
+    /// Directory (or URL prefix) the trimmed files are written to.
+    pub target_dir: String,
+    /// Extension of the trimmed files; the source's when `None`.
+    pub extension: Option<String>,
+    /// Keep source times: the trimmed file is expected to carry the source
+    /// timecode, so clips keep their `source_range` and only the available
+    /// range shrinks. Otherwise trimmed files start at 0 and every clip's
+    /// source start moves back by the trimmed head.
+    pub preserve_timecode: bool,
+}
+
+impl Default for ConsolidateOptions {
+    fn default() -> Self {
+        Self {
+            handles: 1.0,
+            target_dir: "consolidated".to_string(),
+            extension: None,
+            preserve_timecode: false,
+        }
+    }
+}
+
+/// One file for the transcoding farm: cut `source_in..source_out` out of
+/// `source_url` and write it to `target_url`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscodeJob {
+    pub media_id: Option<String>,
+    pub source_url: String,
+    pub source_in: Seconds,
+    pub source_out: Seconds,
+    pub target_url: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConsolidateReport {
+    /// One job per consolidated media, in order of first use.
+    pub jobs: Vec<TranscodeJob>,
+    /// Media known only by id (no URL to cut from); their clips are left as
+    /// they were.
+    pub skipped_media_ids: Vec<String>,
+    /// Clips with more than one media reference, left as they were when
+    /// timecode is not preserved: moving their source start would misplace
+    /// the renditions that are not trimmed.
+    pub skipped_clip_ids: Vec<String>,
+}
+
+impl Timeline {
+    /// Point every clip at trimmed media and return the jobs producing it.
+    /// See the module docs.
+    pub fn consolidate(&mut self, options: &ConsolidateOptions) -> ConsolidateReport {
+        let mut report = ConsolidateReport::default();
+        for mut entry in self.media_usage().media {
+            let Some(source_url) = entry.target_urls.first().cloned() else {
+                report.skipped_media_ids.extend(entry.media_id.clone());
+                continue;
+            };
+            if !options.preserve_timecode {
+                entry.clips.retain(|clip_use| {
+                    let location = &clip_use.location;
+                    match self
+                        .tracks
+                        .children
+                        .get(location.track_index)
+                        .and_then(|track| track.items.get(location.item_index))
+                    {
+                        Some(Item::Clip(clip)) if clip.media_references.len() > 1 => {
+                            report.skipped_clip_ids.extend(clip.get_id());
+                            false
+                        }
+                        _ => true,
+                    }
+                });
+                if entry.clips.is_empty() {
+                    continue;
+                }
+            }
+            let (source_in, source_out) = trimmed_span(&entry, options.handles);
+            let target_url = trimmed_url(&entry, &source_url, source_in, source_out, options);
+            let shift = if options.preserve_timecode {
+                0.0
+            } else {
+                source_in
+            };
+            for clip_use in &entry.clips {
+                let location = &clip_use.location;
+                let Some(Item::Clip(clip)) = self
+                    .tracks
+                    .children
+                    .get_mut(location.track_index)
+                    .and_then(|track| track.items.get_mut(location.item_index))
+                else {
+                    continue;
+                };
+                clip.source_range
+                    .start_time
+                    .set_from_seconds(clip_use.source_start - shift);
+                if let Some(reference) = clip.media_references.get_mut(&location.reference_key) {
+                    if let MediaReference::ExternalReference {
+                        target_url: url, ..
+                    } = reference
+                    {
+                        url.clone_from(&target_url);
+                    }
+                    reference.set_media_start(source_in - shift);
+                    reference.set_media_duration(Some(source_out - source_in));
+                }
+            }
+            report.jobs.push(TranscodeJob {
+                media_id: entry.media_id,
+                source_url,
+                source_in,
+                source_out,
+                target_url,
+            });
+        }
+        report
+    }
+}
+
+/// First to last second used by the entry's clips, widened by `handles`
+/// without leaving the available range (or going below 0 when the range is
+/// unknown).
+fn trimmed_span(entry: &MediaUsageEntry, handles: Seconds) -> (Seconds, Seconds) {
+    let first = entry
+        .clips
+        .iter()
+        .map(|clip_use| clip_use.source_start)
+        .reduce(Seconds::min)
+        .unwrap_or(0.0);
+    let last = entry
+        .clips
+        .iter()
+        .map(|clip_use| clip_use.source_end)
+        .reduce(Seconds::max)
+        .unwrap_or(0.0);
+    let (floor, ceiling) = entry.available_range.unwrap_or((0.0, Seconds::INFINITY));
+    let handles = handles.max(0.0);
+    (
+        (first - handles).max(floor).min(first),
+        (last + handles).min(ceiling).max(last),
+    )
+}
+
+/// `{target_dir}/{name}_{in_ms}-{out_ms}.{ext}`, where `name` is the media id,
+/// or the source file stem plus a hash of the source URL so that same-named
+/// files from different folders stay apart.
+fn trimmed_url(
+    entry: &MediaUsageEntry,
+    source_url: &str,
+    source_in: Seconds,
+    source_out: Seconds,
+    options: &ConsolidateOptions,
+) -> String {
+    let path = source_url.split(['?', '#']).next().unwrap_or_default();
+    let file = path.rsplit('/').next().unwrap_or_default();
+    let (stem, source_extension) = match file.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+        _ => (file, None),
+    };
+    let name = match &entry.media_id {
+        Some(media_id) => sanitize(media_id),
+        None => format!("{}_{:08x}", sanitize(stem), fnv1a(source_url)),
+    };
+    let mut url = String::new();
+    let dir = options.target_dir.trim_end_matches('/');
+    if !dir.is_empty() {
+        url.push_str(dir);
+        url.push('/');
+    }
+    let _ = write!(
+        url,
+        "{name}_{}-{}",
+        (source_in * 1000.0).round() as i64,
+        (source_out * 1000.0).round() as i64
+    );
+    if let Some(ext) = options.extension.as_deref().or(source_extension) {
+        url.push('.');
+        url.push_str(ext.trim_start_matches('.'));
+    }
+    url
+}
+
+fn sanitize(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if cleaned.is_empty() {
+        "media".to_string()
+    } else {
+        cleaned
+    }
+}
+
+// Stable across runs and platforms, unlike `DefaultHasher`.
+fn fnv1a(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
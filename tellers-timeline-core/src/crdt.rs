@@ -0,0 +1,1023 @@
+//! Optional CRDT layer for real-time collaborative editing.
+//!
+//! A [`Replica`] wraps a [`Timeline`] and keeps, next to it, a conflict-free
+//! replicated state from which the timeline is derived:
+//!
+//! * the track list and every `Track.items` are sequence CRDTs (RGA): each
+//!   insertion is an element anchored after an existing element, deletions
+//!   leave tombstones, and concurrent insertions at the same anchor are
+//!   ordered by their [`Stamp`];
+//! * every top-level field of the timeline, stack, tracks and items is a
+//!   last-writer-wins register keyed on `tellers.ai.timeline_id`.
+//!
+//! Edits still go through the regular `Stack` API: [`Replica::edit`] runs the
+//! edit on the current timeline, compares before and after, and turns the
+//! difference into [`CrdtOp`]s to broadcast. Applying the same set of ops in
+//! any order, any number of times, yields the same timeline on every replica.
+//!
+//! Moving an item re-inserts it under the same timeline id; if two replicas
+//! move it concurrently, the insertion with the greater stamp wins and the
+//! other is hidden. All ops of one edit share a Lamport counter, so a whole
+//! edit wins or loses together against a concurrent one.
+//!
+//! The derived timeline enforces the grouping invariants after merge:
+//!
+//! * members of a `Link Group ID` keep the relative layout (offsets and
+//!   durations) recorded by the last edit that touched the group; members
+//!   that concurrent edits pushed out of that layout are moved back into it,
+//!   or leave the group when another clip now holds their place (see
+//!   [`SyncRepair`]);
+//! * neither a Link Group nor a Tellers Group is left with a single member.
+
+use crate::{IdMetadataExt, InsertPolicy, Item, MetadataExt, OverlapPolicy, Seconds, Timeline};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const EPS: Seconds = 1e-9;
+
+/// Replica id used for the elements of the initial timeline, so replicas
+/// forked from the same timeline agree on them.
+const ORIGIN_REPLICA: u64 = 0;
+
+/// Totally ordered operation id: Lamport counter, then replica id, then the
+/// op's position within its edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Stamp {
+    pub counter: u64,
+    pub replica: u64,
+    pub seq: u32,
+}
+
+/// What a register belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CrdtTarget {
+    Timeline,
+    Stack,
+    Track(String),
+    Item(String),
+    /// The recorded member layout of a Link Group ID.
+    SyncGroup(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CrdtOp {
+    InsertTrack {
+        elem: Stamp,
+        after: Option<Stamp>,
+        track_id: String,
+    },
+    InsertItem {
+        elem: Stamp,
+        after: Option<Stamp>,
+        track_id: String,
+        item_id: String,
+    },
+    /// Tombstone a track or item element.
+    Delete { elem: Stamp },
+    /// Write a register; `None` removes the field.
+    Set {
+        stamp: Stamp,
+        target: CrdtTarget,
+        key: String,
+        value: Option<Value>,
+    },
+}
+
+/// How the derived timeline restored a Link Group that concurrent edits
+/// pushed out of its recorded layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncRepair {
+    /// The member was moved (and trimmed) back to its place in the layout.
+    Realigned { group_id: i64, item_id: String },
+    /// Another clip now covers the member's place in the layout, so the
+    /// member left the group rather than overwrite that clip.
+    Unlinked { group_id: i64, item_id: String },
+}
+
+type Registers = BTreeMap<String, (Stamp, Option<Value>)>;
+
+#[derive(Debug, Clone)]
+struct Element {
+    /// `None` for a track element, the owning track id for an item element.
+    track_id: Option<String>,
+    /// Timeline id of the track or item this element places.
+    key: String,
+    deleted: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CrdtState {
+    elements: HashMap<Stamp, Element>,
+    /// Children of each anchor, per sequence (`None` = the track list).
+    children: HashMap<Option<String>, HashMap<Option<Stamp>, Vec<Stamp>>>,
+    /// Field registers, grouped by target so deriving one track or item
+    /// only visits its own fields.
+    registers: HashMap<CrdtTarget, Registers>,
+    pending: Vec<CrdtOp>,
+    /// Items whose registers were written since the last derive.
+    changed_items: HashSet<String>,
+}
+
+impl CrdtState {
+    fn knows(&self, elem: &Option<Stamp>) -> bool {
+        elem.is_none_or(|stamp| self.elements.contains_key(&stamp))
+    }
+
+    /// Apply one op; returns false when it depends on an element not yet seen.
+    fn try_apply(&mut self, op: &CrdtOp) -> bool {
+        match op {
+            CrdtOp::InsertTrack {
+                elem,
+                after,
+                track_id,
+            } => self.insert(*elem, *after, None, track_id),
+            CrdtOp::InsertItem {
+                elem,
+                after,
+                track_id,
+                item_id,
+            } => self.insert(*elem, *after, Some(track_id.clone()), item_id),
+            CrdtOp::Delete { elem } => match self.elements.get_mut(elem) {
+                Some(element) => {
+                    element.deleted = true;
+                    true
+                }
+                None => false,
+            },
+            CrdtOp::Set {
+                stamp,
+                target,
+                key,
+                value,
+            } => {
+                if let CrdtTarget::Item(item_id) = target {
+                    self.changed_items.insert(item_id.clone());
+                }
+                let slot = self
+                    .registers
+                    .entry(target.clone())
+                    .or_default()
+                    .entry(key.clone());
+                match slot {
+                    std::collections::btree_map::Entry::Occupied(mut existing) => {
+                        if *stamp > existing.get().0 {
+                            existing.insert((*stamp, value.clone()));
+                        }
+                    }
+                    std::collections::btree_map::Entry::Vacant(empty) => {
+                        empty.insert((*stamp, value.clone()));
+                    }
+                }
+                true
+            }
+        }
+    }
+
+    fn insert(
+        &mut self,
+        elem: Stamp,
+        after: Option<Stamp>,
+        track_id: Option<String>,
+        key: &str,
+    ) -> bool {
+        if self.elements.contains_key(&elem) {
+            return true;
+        }
+        if !self.knows(&after) {
+            return false;
+        }
+        self.children
+            .entry(track_id.clone())
+            .or_default()
+            .entry(after)
+            .or_default()
+            .push(elem);
+        self.elements.insert(
+            elem,
+            Element {
+                track_id,
+                key: key.to_string(),
+                deleted: false,
+            },
+        );
+        true
+    }
+
+    fn apply(&mut self, op: CrdtOp) {
+        if !self.try_apply(&op) {
+            self.pending.push(op);
+            return;
+        }
+        // Retry ops that were waiting on an element until none makes progress.
+        loop {
+            let before = self.pending.len();
+            let pending = std::mem::take(&mut self.pending);
+            for op in pending {
+                if !self.try_apply(&op) {
+                    self.pending.push(op);
+                }
+            }
+            if self.pending.len() == before {
+                break;
+            }
+        }
+    }
+
+    /// Elements of one sequence in RGA order: depth-first from the root, with
+    /// later insertions at the same anchor first.
+    fn sequence(&self, track_id: Option<&str>) -> Vec<Stamp> {
+        let mut out = Vec::new();
+        let Some(anchors) = self.children.get(&track_id.map(str::to_string)) else {
+            return out;
+        };
+        let sorted_children = |anchor: Option<Stamp>| {
+            let mut children = anchors.get(&anchor).cloned().unwrap_or_default();
+            children.sort_by(|a, b| b.cmp(a));
+            children
+        };
+        let mut stack: Vec<Stamp> = sorted_children(None);
+        stack.reverse();
+        while let Some(elem) = stack.pop() {
+            out.push(elem);
+            let mut next = sorted_children(Some(elem));
+            next.reverse();
+            stack.extend(next);
+        }
+        out
+    }
+
+    /// The visible element for each key: the live element with the greatest
+    /// stamp. Earlier live elements of the same key are concurrent moves that
+    /// lost.
+    fn winners(&self, tracks: bool) -> HashMap<String, Stamp> {
+        let mut winners: HashMap<String, Stamp> = HashMap::new();
+        for (stamp, element) in &self.elements {
+            if element.deleted || element.track_id.is_none() != tracks {
+                continue;
+            }
+            let entry = winners.entry(element.key.clone()).or_insert(*stamp);
+            if *stamp > *entry {
+                *entry = *stamp;
+            }
+        }
+        winners
+    }
+
+    fn fields(&self, target: &CrdtTarget) -> Map<String, Value> {
+        self.registers
+            .get(target)
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, (_, value))| Some((key.clone(), value.clone()?)))
+            .collect()
+    }
+
+    fn register(&self, target: &CrdtTarget, key: &str) -> Option<&Value> {
+        self.registers.get(target)?.get(key)?.1.as_ref()
+    }
+
+    /// Greatest stamp among an item's element and field writes.
+    fn last_write(&self, item_id: &str, elem: Stamp) -> Stamp {
+        self.registers
+            .get(&CrdtTarget::Item(item_id.to_string()))
+            .into_iter()
+            .flatten()
+            .map(|(_, (stamp, _))| *stamp)
+            .fold(elem, Stamp::max)
+    }
+}
+
+/// One participant in a collaborative session.
+#[derive(Debug, Clone)]
+pub struct Replica {
+    replica_id: u64,
+    clock: u64,
+    state: CrdtState,
+    timeline: Timeline,
+    repairs: Vec<SyncRepair>,
+    /// Parsed items by id, so a derive only deserializes the items whose
+    /// registers changed (`None`: the merged fields are not a valid item).
+    item_cache: HashMap<String, Option<Item>>,
+}
+
+impl Replica {
+    /// Start a session from `timeline`. The initial elements are stamped with
+    /// a reserved origin replica, so every replica built from the same
+    /// timeline (or [`forked`](Replica::fork) from this one) starts from
+    /// identical state. `replica_id` must be unique per participant and
+    /// non-zero.
+    pub fn new(replica_id: u64, timeline: &Timeline) -> Self {
+        // Round-trip so every field is in its normalized form. Missing ids are
+        // filled in first, from positions, so that they match across replicas.
+        let timeline = with_positional_ids(timeline);
+        let timeline: Timeline = serde_json::to_value(&timeline)
+            .and_then(serde_json::from_value)
+            .unwrap_or(timeline);
+        let timeline = &timeline;
+        let mut origin = Replica {
+            replica_id: ORIGIN_REPLICA,
+            clock: 0,
+            state: CrdtState::default(),
+            timeline: Timeline {
+                tracks: crate::Stack {
+                    children: Vec::new(),
+                    ..timeline.tracks.clone()
+                },
+                ..timeline.clone()
+            },
+            repairs: Vec::new(),
+            item_cache: HashMap::new(),
+        };
+        let blank = Timeline {
+            name: None,
+            metadata: Value::Object(Map::new()),
+            tracks: crate::Stack {
+                name: None,
+                children: Vec::new(),
+                metadata: Value::Object(Map::new()),
+                ..timeline.tracks.clone()
+            },
+            ..timeline.clone()
+        };
+        let ops = origin.ops_between(&blank, timeline);
+        for op in ops {
+            origin.state.apply(op);
+        }
+        origin.rederive();
+        origin.replica_id = replica_id;
+        origin
+    }
+
+    /// A new replica with the same state under another id, as when a client
+    /// joins a session and receives a snapshot.
+    pub fn fork(&self, replica_id: u64) -> Self {
+        Replica {
+            replica_id,
+            ..self.clone()
+        }
+    }
+
+    pub fn replica_id(&self) -> u64 {
+        self.replica_id
+    }
+
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Link Group repairs made while deriving the current timeline, so the
+    /// caller can tell users their linked clips were moved back or unlinked.
+    pub fn sync_repairs(&self) -> &[SyncRepair] {
+        &self.repairs
+    }
+
+    /// Ops received but not yet applied because they anchor on elements this
+    /// replica has not seen.
+    pub fn pending_ops(&self) -> usize {
+        self.state.pending.len()
+    }
+
+    /// Run a `Stack` edit, apply it locally and return its result together
+    /// with the ops to broadcast to the other replicas.
+    pub fn edit<R>(&mut self, edit: impl FnOnce(&mut crate::Stack) -> R) -> (R, Vec<CrdtOp>) {
+        let mut after = self.timeline.clone();
+        let result = edit(&mut after.tracks);
+        self.clock += 1;
+        let ops = self.ops_between(&self.timeline, &after);
+        for op in &ops {
+            self.state.apply(op.clone());
+        }
+        self.rederive();
+        (result, ops)
+    }
+
+    /// Apply ops from another replica. Duplicates and out-of-order delivery
+    /// are fine.
+    pub fn apply_remote(&mut self, ops: &[CrdtOp]) {
+        for op in ops {
+            let counter = match op {
+                CrdtOp::InsertTrack { elem, .. }
+                | CrdtOp::InsertItem { elem, .. }
+                | CrdtOp::Delete { elem } => elem.counter,
+                CrdtOp::Set { stamp, .. } => stamp.counter,
+            };
+            self.clock = self.clock.max(counter);
+            self.state.apply(op.clone());
+        }
+        self.rederive();
+    }
+
+    fn rederive(&mut self) {
+        for item_id in std::mem::take(&mut self.state.changed_items) {
+            self.item_cache.remove(&item_id);
+        }
+        let (timeline, repairs) = self.materialize();
+        self.timeline = timeline;
+        self.repairs = repairs;
+    }
+
+    fn ops_between(&self, before: &Timeline, after: &Timeline) -> Vec<CrdtOp> {
+        let mut gen = OpGenerator {
+            counter: self.clock,
+            replica: self.replica_id,
+            seq: 0,
+            ops: Vec::new(),
+        };
+        let track_winners = self.state.winners(true);
+        let item_winners = self.state.winners(false);
+
+        let before_json = timeline_fields(before);
+        let after_json = timeline_fields(after);
+        gen.set_fields(CrdtTarget::Timeline, Some(&before_json.0), &after_json.0);
+        gen.set_fields(CrdtTarget::Stack, Some(&before_json.1), &after_json.1);
+
+        // Tracks.
+        let before_tracks: Vec<String> = before
+            .tracks
+            .children
+            .iter()
+            .filter_map(|t| t.get_id())
+            .collect();
+        let after_tracks: Vec<String> = after
+            .tracks
+            .children
+            .iter()
+            .filter_map(|t| t.get_id())
+            .collect();
+        let kept = common_subsequence(&before_tracks, &after_tracks);
+        let kept_before: HashSet<usize> = kept.iter().map(|(b, _)| *b).collect();
+        let kept_after: HashSet<usize> = kept.iter().map(|(_, a)| *a).collect();
+        for (index, track_id) in before_tracks.iter().enumerate() {
+            if !kept_before.contains(&index) {
+                if let Some(elem) = track_winners.get(track_id) {
+                    gen.push(|_| CrdtOp::Delete { elem: *elem });
+                }
+            }
+        }
+        let mut prev: Option<Stamp> = None;
+        for (index, track_id) in after_tracks.iter().enumerate() {
+            let elem = if kept_after.contains(&index) {
+                track_winners[track_id]
+            } else {
+                let after = prev;
+                let track_id = track_id.clone();
+                gen.push_insert(|elem| CrdtOp::InsertTrack {
+                    elem,
+                    after,
+                    track_id,
+                })
+            };
+            prev = Some(elem);
+        }
+
+        let before_track_fields: HashMap<String, Map<String, Value>> = before
+            .tracks
+            .children
+            .iter()
+            .filter_map(|t| Some((t.get_id()?, track_fields(t))))
+            .collect();
+        for track in &after.tracks.children {
+            let Some(track_id) = track.get_id() else {
+                continue;
+            };
+            gen.set_fields(
+                CrdtTarget::Track(track_id.clone()),
+                before_track_fields.get(&track_id),
+                &track_fields(track),
+            );
+        }
+
+        // Items.
+        let item_ids = |track: Option<&crate::Track>| -> Vec<String> {
+            track
+                .map(|t| t.items.iter().filter_map(|i| i.get_id()).collect())
+                .unwrap_or_default()
+        };
+        let mut before_items: HashMap<String, &Item> = HashMap::new();
+        for track in &before.tracks.children {
+            for item in &track.items {
+                if let Some(id) = item.get_id() {
+                    before_items.insert(id, item);
+                }
+            }
+        }
+        let mut kept_items: HashSet<(String, String)> = HashSet::new();
+        for track in &after.tracks.children {
+            let Some(track_id) = track.get_id() else {
+                continue;
+            };
+            let old = item_ids(before.tracks.get_track_by_id(&track_id).map(|(_, t)| t));
+            let new = item_ids(Some(track));
+            for (o, _) in common_subsequence(&old, &new) {
+                kept_items.insert((track_id.clone(), old[o].clone()));
+            }
+        }
+        for track in &before.tracks.children {
+            let Some(track_id) = track.get_id() else {
+                continue;
+            };
+            for item_id in item_ids(Some(track)) {
+                if kept_items.contains(&(track_id.clone(), item_id.clone())) {
+                    continue;
+                }
+                if let Some(elem) = item_winners.get(&item_id) {
+                    gen.push(|_| CrdtOp::Delete { elem: *elem });
+                }
+            }
+        }
+        for track in &after.tracks.children {
+            let Some(track_id) = track.get_id() else {
+                continue;
+            };
+            let mut prev: Option<Stamp> = None;
+            for item in &track.items {
+                let Some(item_id) = item.get_id() else {
+                    continue;
+                };
+                let kept = kept_items.contains(&(track_id.clone(), item_id.clone()));
+                let elem = match (kept, item_winners.get(&item_id)) {
+                    (true, Some(elem)) => *elem,
+                    _ => {
+                        let after = prev;
+                        let (track_id, item_id) = (track_id.clone(), item_id.clone());
+                        gen.push_insert(|elem| CrdtOp::InsertItem {
+                            elem,
+                            after,
+                            track_id,
+                            item_id,
+                        })
+                    }
+                };
+                prev = Some(elem);
+                // Only serialize items that changed; most edits touch a few
+                // clips of a long timeline.
+                let before_item = before_items.get(&item_id).copied();
+                if before_item != Some(item) {
+                    gen.set_fields(
+                        CrdtTarget::Item(item_id.clone()),
+                        before_item.map(item_fields).as_ref(),
+                        &item_fields(item),
+                    );
+                }
+            }
+        }
+
+        // Link group layouts.
+        let before_layouts = sync_layouts(before);
+        for (group_id, layout) in sync_layouts(after) {
+            if before_layouts.get(&group_id) != Some(&layout) {
+                gen.push(|stamp| CrdtOp::Set {
+                    stamp,
+                    target: CrdtTarget::SyncGroup(group_id),
+                    key: "layout".to_string(),
+                    value: Some(layout_to_value(&layout)),
+                });
+            }
+        }
+
+        gen.ops
+    }
+
+    /// Derive the timeline from the CRDT state and enforce the grouping
+    /// invariants. Pure function of the state, so every replica that has
+    /// applied the same ops derives the same timeline.
+    fn materialize(&mut self) -> (Timeline, Vec<SyncRepair>) {
+        let state = &self.state;
+        let cache = &mut self.item_cache;
+        let track_winners = state.winners(true);
+        let item_winners = state.winners(false);
+
+        // Tracks or items whose merged fields no longer deserialize are
+        // dropped rather than failing the whole document.
+        let mut tracks = Vec::new();
+        for elem in state.sequence(None) {
+            let element = &state.elements[&elem];
+            if element.deleted || track_winners.get(&element.key) != Some(&elem) {
+                continue;
+            }
+            let mut fields = state.fields(&CrdtTarget::Track(element.key.clone()));
+            fields.insert("children".to_string(), Value::Array(Vec::new()));
+            let Ok(mut track) = serde_json::from_value::<crate::Track>(Value::Object(fields))
+            else {
+                continue;
+            };
+            for item_elem in state.sequence(Some(&element.key)) {
+                let item_element = &state.elements[&item_elem];
+                if item_element.deleted || item_winners.get(&item_element.key) != Some(&item_elem) {
+                    continue;
+                }
+                let item = cache.entry(item_element.key.clone()).or_insert_with(|| {
+                    let fields = state.fields(&CrdtTarget::Item(item_element.key.clone()));
+                    serde_json::from_value(Value::Object(fields)).ok()
+                });
+                track.items.extend(item.clone());
+            }
+            tracks.push(track);
+        }
+        let mut stack_fields = state.fields(&CrdtTarget::Stack);
+        stack_fields.insert("children".to_string(), Value::Array(Vec::new()));
+        let mut timeline_fields = state.fields(&CrdtTarget::Timeline);
+        timeline_fields.insert("tracks".to_string(), Value::Object(stack_fields));
+        let mut timeline: Timeline =
+            serde_json::from_value(Value::Object(timeline_fields)).unwrap_or_default();
+        timeline.tracks.children = tracks;
+
+        let repairs = self.enforce_sync_layouts(&mut timeline, &item_winners);
+        drop_singleton_groups(&mut timeline);
+        (timeline, repairs)
+    }
+
+    fn enforce_sync_layouts(
+        &self,
+        timeline: &mut Timeline,
+        item_winners: &HashMap<String, Stamp>,
+    ) -> Vec<SyncRepair> {
+        let mut members: BTreeMap<i64, Vec<(String, Seconds, Seconds)>> = BTreeMap::new();
+        for track in &timeline.tracks.children {
+            let mut start = 0.0;
+            for item in &track.items {
+                let duration = item.duration().max(0.0);
+                if let (Some(group), Some(id)) = (crate::item_link_group_id(item), item.get_id()) {
+                    members
+                        .entry(group)
+                        .or_default()
+                        .push((id, start, duration));
+                }
+                start += duration;
+            }
+        }
+
+        let mut repairs = Vec::new();
+        let mut evicted: HashSet<String> = HashSet::new();
+        for (group_id, mut members) in members {
+            members.sort_by(|a, b| a.0.cmp(&b.0));
+            let layout = self
+                .state
+                .register(&CrdtTarget::SyncGroup(group_id), "layout")
+                .map(layout_from_value)
+                .unwrap_or_default();
+
+            // Keep the largest set of members that fits the recorded layout
+            // under one common shift; ties go to the most recently written
+            // origin member.
+            let mut best: Option<(usize, Stamp, Seconds, Vec<String>)> = None;
+            for (origin_id, origin_start, _) in &members {
+                let Some((origin_offset, _)) = layout.get(origin_id) else {
+                    continue;
+                };
+                let shift = origin_start - origin_offset;
+                let fitting: Vec<String> = members
+                    .iter()
+                    .filter(|(id, start, duration)| {
+                        layout.get(id).is_some_and(|(offset, expected)| {
+                            (start - (offset + shift)).abs() <= EPS
+                                && (duration - expected).abs() <= EPS
+                        })
+                    })
+                    .map(|(id, _, _)| id.clone())
+                    .collect();
+                let written = item_winners
+                    .get(origin_id)
+                    .map(|elem| self.state.last_write(origin_id, *elem))
+                    .unwrap_or(Stamp {
+                        counter: 0,
+                        replica: 0,
+                        seq: 0,
+                    });
+                let better = match &best {
+                    None => true,
+                    Some((count, stamp, _, _)) => {
+                        fitting.len() > *count || (fitting.len() == *count && written > *stamp)
+                    }
+                };
+                if better {
+                    best = Some((fitting.len(), written, shift, fitting));
+                }
+            }
+            let Some((_, _, shift, keep)) = best else {
+                // No member is in the recorded layout (or there is none):
+                // nothing to align to, leave the group as it is.
+                continue;
+            };
+            // Move the others back under the winning shift, so concurrent
+            // nudges never silently split the user's A/V pair.
+            for (id, _, _) in members {
+                if keep.contains(&id) {
+                    continue;
+                }
+                let realigned = layout.get(&id).is_some_and(|(offset, duration)| {
+                    realign_member(timeline, &id, offset + shift, *duration)
+                });
+                if realigned {
+                    repairs.push(SyncRepair::Realigned {
+                        group_id,
+                        item_id: id,
+                    });
+                } else {
+                    evicted.insert(id.clone());
+                    repairs.push(SyncRepair::Unlinked {
+                        group_id,
+                        item_id: id,
+                    });
+                }
+            }
+        }
+
+        for track in &mut timeline.tracks.children {
+            for item in &mut track.items {
+                if item.get_id().is_some_and(|id| evicted.contains(&id)) {
+                    remove_link_group_id(item.get_metadata_mut());
+                }
+            }
+        }
+        repairs
+    }
+}
+
+struct OpGenerator {
+    counter: u64,
+    replica: u64,
+    seq: u32,
+    ops: Vec<CrdtOp>,
+}
+
+impl OpGenerator {
+    fn next_stamp(&mut self) -> Stamp {
+        let stamp = Stamp {
+            counter: self.counter,
+            replica: self.replica,
+            seq: self.seq,
+        };
+        self.seq += 1;
+        stamp
+    }
+
+    fn push(&mut self, op: impl FnOnce(Stamp) -> CrdtOp) {
+        let stamp = self.next_stamp();
+        self.ops.push(op(stamp));
+    }
+
+    fn push_insert(&mut self, op: impl FnOnce(Stamp) -> CrdtOp) -> Stamp {
+        let stamp = self.next_stamp();
+        self.ops.push(op(stamp));
+        stamp
+    }
+
+    fn set_fields(
+        &mut self,
+        target: CrdtTarget,
+        before: Option<&Map<String, Value>>,
+        after: &Map<String, Value>,
+    ) {
+        for (key, value) in after {
+            if before.and_then(|b| b.get(key)) != Some(value) {
+                let (target, key, value) = (target.clone(), key.clone(), value.clone());
+                self.push(|stamp| CrdtOp::Set {
+                    stamp,
+                    target,
+                    key,
+                    value: Some(value),
+                });
+            }
+        }
+        for key in before.into_iter().flat_map(|b| b.keys()) {
+            if !after.contains_key(key) {
+                let (target, key) = (target.clone(), key.clone());
+                self.push(|stamp| CrdtOp::Set {
+                    stamp,
+                    target,
+                    key,
+                    value: None,
+                });
+            }
+        }
+    }
+}
+
+/// A copy of `timeline` where the timeline, the stack and every track and
+/// item without a timeline id get one derived from their position.
+fn with_positional_ids(timeline: &Timeline) -> Timeline {
+    fn has_id(metadata: &Value) -> bool {
+        metadata["tellers.ai"]["timeline_id"].is_string() || metadata["tellers_id"].is_string()
+    }
+    let mut timeline = timeline.clone();
+    let mut used: HashSet<String> = timeline
+        .tracks
+        .children
+        .iter()
+        .flat_map(|track| {
+            std::iter::once(track.get_id()).chain(track.items.iter().map(IdMetadataExt::get_id))
+        })
+        .flatten()
+        .collect();
+    let mut fill = |metadata: &mut Value, position: String| {
+        if has_id(metadata) {
+            return;
+        }
+        let mut id = format!("origin-{position}");
+        let mut suffix = 1;
+        while !used.insert(id.clone()) {
+            id = format!("origin-{position}.{suffix}");
+            suffix += 1;
+        }
+        crate::metadata::tellers_ai_mut(metadata)
+            .insert("timeline_id".to_string(), Value::String(id));
+    };
+    fill(&mut timeline.metadata, "timeline".to_string());
+    fill(&mut timeline.tracks.metadata, "stack".to_string());
+    for (track_index, track) in timeline.tracks.children.iter_mut().enumerate() {
+        fill(&mut track.metadata, track_index.to_string());
+        for (item_index, item) in track.items.iter_mut().enumerate() {
+            fill(
+                item.get_metadata_mut(),
+                format!("{track_index}-{item_index}"),
+            );
+        }
+    }
+    timeline
+}
+
+fn object_without(value: Value, key: &str) -> Map<String, Value> {
+    match value {
+        Value::Object(mut map) => {
+            map.remove(key);
+            map
+        }
+        _ => Map::new(),
+    }
+}
+
+/// The timeline's and the stack's own fields. Serialized from copies without
+/// tracks, so the cost does not grow with the number of clips.
+fn timeline_fields(timeline: &Timeline) -> (Map<String, Value>, Map<String, Value>) {
+    let stack = crate::Stack {
+        otio_schema: timeline.tracks.otio_schema.clone(),
+        name: timeline.tracks.name.clone(),
+        children: Vec::new(),
+        metadata: timeline.tracks.metadata.clone(),
+        transactions: crate::OpenTransactions::default(),
+    };
+    let shell = Timeline {
+        otio_schema: timeline.otio_schema.clone(),
+        name: timeline.name.clone(),
+        tracks: crate::Stack::default(),
+        metadata: timeline.metadata.clone(),
+    };
+    (
+        object_without(
+            serde_json::to_value(&shell).unwrap_or(Value::Null),
+            "tracks",
+        ),
+        object_without(
+            serde_json::to_value(&stack).unwrap_or(Value::Null),
+            "children",
+        ),
+    )
+}
+
+fn track_fields(track: &crate::Track) -> Map<String, Value> {
+    let shell = crate::Track {
+        otio_schema: track.otio_schema.clone(),
+        enabled: track.enabled,
+        kind: track.kind.clone(),
+        name: track.name.clone(),
+        items: Vec::new(),
+        metadata: track.metadata.clone(),
+        effects: track.effects.clone(),
+    };
+    object_without(
+        serde_json::to_value(&shell).unwrap_or(Value::Null),
+        "children",
+    )
+}
+
+fn item_fields(item: &Item) -> Map<String, Value> {
+    match serde_json::to_value(item) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+type SyncLayout = BTreeMap<String, (Seconds, Seconds)>;
+
+/// Per Link Group ID, each member's offset from the group's earliest start
+/// and its duration.
+fn sync_layouts(timeline: &Timeline) -> BTreeMap<i64, SyncLayout> {
+    let mut raw: BTreeMap<i64, Vec<(String, Seconds, Seconds)>> = BTreeMap::new();
+    for track in &timeline.tracks.children {
+        let mut start = 0.0;
+        for item in &track.items {
+            let duration = item.duration().max(0.0);
+            if let (Some(group), Some(id)) = (crate::item_link_group_id(item), item.get_id()) {
+                raw.entry(group).or_default().push((id, start, duration));
+            }
+            start += duration;
+        }
+    }
+    raw.into_iter()
+        .map(|(group, members)| {
+            let origin = members
+                .iter()
+                .map(|(_, start, _)| *start)
+                .fold(f64::INFINITY, f64::min);
+            let layout = members
+                .into_iter()
+                .map(|(id, start, duration)| (id, (start - origin, duration)))
+                .collect();
+            (group, layout)
+        })
+        .collect()
+}
+
+fn layout_to_value(layout: &SyncLayout) -> Value {
+    serde_json::to_value(layout).unwrap_or(Value::Null)
+}
+
+fn layout_from_value(value: &Value) -> SyncLayout {
+    serde_json::from_value(value.clone()).unwrap_or_default()
+}
+
+/// Move item `id` on its own track to `start..start + duration`, provided
+/// that span holds only gaps once the item is lifted out.
+fn realign_member(timeline: &mut Timeline, id: &str, start: Seconds, duration: Seconds) -> bool {
+    if start < -EPS {
+        return false;
+    }
+    let Some((track_index, item_index, _)) = timeline.tracks.get_item(id) else {
+        return false;
+    };
+    let track = &mut timeline.tracks.children[track_index];
+    let original = track.clone();
+    let Some(mut item) = track.delete_clip_at(item_index, true) else {
+        return false;
+    };
+    let free = track
+        .items_in_range(start + EPS, start + duration - EPS)
+        .iter()
+        .all(|hit| matches!(hit.item, Item::Gap(_)));
+    if !free {
+        *track = original;
+        return false;
+    }
+    item.set_duration(duration);
+    track.insert_at_time(
+        start.max(0.0),
+        item,
+        OverlapPolicy::Override,
+        InsertPolicy::SplitAndInsert,
+    );
+    true
+}
+
+fn remove_link_group_id(metadata: &mut Value) {
+    for key in ["Resolve_OTIO", "resolve"] {
+        if let Some(resolve) = metadata.get_mut(key).and_then(Value::as_object_mut) {
+            resolve.remove("Link Group ID");
+        }
+    }
+}
+
+pub(crate) fn drop_singleton_groups(timeline: &mut Timeline) {
+    let mut link_counts: HashMap<i64, usize> = HashMap::new();
+    let mut tellers_counts: HashMap<i64, usize> = HashMap::new();
+    for item in timeline.tracks.children.iter().flat_map(|t| &t.items) {
+        if let Some(group) = crate::item_link_group_id(item) {
+            *link_counts.entry(group).or_default() += 1;
+        }
+        for group in crate::item_tellers_group_ids(item) {
+            *tellers_counts.entry(group).or_default() += 1;
+        }
+    }
+    for item in timeline
+        .tracks
+        .children
+        .iter_mut()
+        .flat_map(|t| &mut t.items)
+    {
+        if crate::item_link_group_id(item).is_some_and(|g| link_counts[&g] < 2) {
+            remove_link_group_id(item.get_metadata_mut());
+        }
+        for group in crate::item_tellers_group_ids(item) {
+            if tellers_counts[&group] < 2 {
+                crate::metadata::remove_tellers_group_level(item.get_metadata_mut(), group);
+            }
+        }
+    }
+}
+
+/// Index pairs of a longest common subsequence of two id lists. Ids are
+/// unique within a list, so this is the longest run of shared ids in the
+/// same order on both sides, found without a quadratic table.
+fn common_subsequence(old: &[String], new: &[String]) -> Vec<(usize, usize)> {
+    let mut new_index: HashMap<&str, usize> = HashMap::new();
+    for (j, id) in new.iter().enumerate() {
+        new_index.entry(id.as_str()).or_insert(j);
+    }
+    let shared: Vec<(usize, usize)> = old
+        .iter()
+        .enumerate()
+        .filter_map(|(i, id)| Some((i, *new_index.get(id.as_str())?)))
+        .collect();
+    crate::patch::longest_increasing_run(&shared)
+}
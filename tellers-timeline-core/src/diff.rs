@@ -0,0 +1,941 @@
+//! Structural diff and three-way merge of timelines.
+//!
+//! Both are keyed on the `tellers.ai.timeline_id` of tracks and clips
+//! ([`IdMetadataExt::get_id`]), not on positions: a clip that was moved to
+//! another track is the same clip, and two edits to different clips never
+//! collide just because the clips sit at the same index. Gaps are filler
+//! between clips and carry no identity of their own, so they are not diffed;
+//! clip placement is compared through absolute start times instead.
+//!
+//! Tracks and clips without a timeline id are keyed by their position
+//! instead, as `@<track index>` and `@<track index>:<item index>`, so that
+//! id-less content still round-trips through a merge.
+
+use crate::{
+    Effect, IdMetadataExt, Item, MediaReference, Seconds, Timeline, TimeRange, Track, TrackKind,
+};
+use std::collections::{HashMap, HashSet};
+
+const EPS: Seconds = 1e-9;
+
+/// One semantic change between two timelines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelineChange {
+    TimelineRenamed {
+        before: Option<String>,
+        after: Option<String>,
+    },
+    TimelineMetadataChanged {
+        before: serde_json::Value,
+        after: serde_json::Value,
+    },
+    TrackAdded {
+        track_id: String,
+        index: usize,
+    },
+    TrackRemoved {
+        track_id: String,
+        index: usize,
+    },
+    /// The relative order of the tracks present on both sides changed. Both
+    /// lists only hold those common track ids.
+    TracksReordered {
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+    TrackMetadataChanged {
+        track_id: String,
+        before: serde_json::Value,
+        after: serde_json::Value,
+    },
+    /// The track name, kind or enabled flag changed.
+    TrackPropertiesChanged {
+        track_id: String,
+        before: TrackProperties,
+        after: TrackProperties,
+    },
+    TrackEffectsChanged {
+        track_id: String,
+        before: Vec<Effect>,
+        after: Vec<Effect>,
+    },
+    ItemAdded {
+        item_id: String,
+        track_id: String,
+        start: Seconds,
+        item: Item,
+    },
+    ItemRemoved {
+        item_id: String,
+        track_id: String,
+        start: Seconds,
+        item: Item,
+    },
+    /// The item changed track and/or timeline start time.
+    ItemMoved {
+        item_id: String,
+        from_track_id: String,
+        to_track_id: String,
+        from_start: Seconds,
+        to_start: Seconds,
+    },
+    /// The item's `source_range` (in point and/or duration) changed.
+    ItemTrimmed {
+        item_id: String,
+        track_id: String,
+        before: TimeRange,
+        after: TimeRange,
+    },
+    ItemMetadataChanged {
+        item_id: String,
+        before: serde_json::Value,
+        after: serde_json::Value,
+    },
+    ItemEffectsChanged {
+        item_id: String,
+        before: Vec<Effect>,
+        after: Vec<Effect>,
+    },
+    /// The item name, enabled flag, media references or active media
+    /// reference key changed.
+    ItemPropertiesChanged {
+        item_id: String,
+        before: ItemProperties,
+        after: ItemProperties,
+    },
+}
+
+/// The plain fields of a track that `TrackPropertiesChanged` compares.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackProperties {
+    pub name: Option<String>,
+    pub kind: TrackKind,
+    pub enabled: bool,
+}
+
+impl TrackProperties {
+    fn of(track: &Track) -> Self {
+        Self {
+            name: track.name.clone(),
+            kind: track.kind.clone(),
+            enabled: track.enabled,
+        }
+    }
+}
+
+/// The fields of a clip that `ItemPropertiesChanged` compares: everything
+/// except timing, metadata and effects, which have their own changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemProperties {
+    pub name: Option<String>,
+    pub enabled: bool,
+    pub media_references: HashMap<String, MediaReference>,
+    pub active_media_reference_key: Option<String>,
+}
+
+impl ItemProperties {
+    fn of(item: &Item) -> Self {
+        match item {
+            Item::Clip(clip) => Self {
+                name: clip.name.clone(),
+                enabled: clip.enabled,
+                media_references: clip.media_references.clone(),
+                active_media_reference_key: clip.active_media_reference_key.clone(),
+            },
+            Item::Gap(gap) => Self {
+                name: gap.name.clone(),
+                enabled: true,
+                media_references: HashMap::new(),
+                active_media_reference_key: None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimelineDiff {
+    pub changes: Vec<TimelineChange>,
+}
+
+impl TimelineDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Which part of an item both sides of a merge changed differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemConflictAspect {
+    /// One side removed the item while the other edited it.
+    Presence,
+    /// Track or start time: both sides moved the clip to different places.
+    Placement,
+    /// In point or duration: both sides trimmed the clip differently.
+    SourceRange,
+    /// Metadata, effects, name, enabled flag or media references.
+    Content,
+}
+
+/// Which part of a track both sides of a merge changed differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackConflictAspect {
+    /// One side removed the track while the other edited it or its clips.
+    Presence,
+    /// Name, kind, enabled flag, metadata or effects.
+    Properties,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeSide {
+    Ours,
+    Theirs,
+}
+
+/// A change that could not be applied automatically. The merged timeline
+/// always keeps "ours" for the conflicting part, so callers can surface the
+/// conflict and re-apply "theirs" by hand if wanted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeConflict {
+    Item {
+        item_id: String,
+        aspect: ItemConflictAspect,
+    },
+    /// Both sides put clips on the same range of a track. `reverted_item_id`
+    /// is the clip whose "theirs" placement was dropped; `kept_item_id` the
+    /// clip that keeps the range.
+    OverlappingRange {
+        track_id: String,
+        start: Seconds,
+        end: Seconds,
+        kept_item_id: String,
+        reverted_item_id: String,
+    },
+    Track {
+        track_id: String,
+        aspect: TrackConflictAspect,
+        /// For presence conflicts, the side that removed the track.
+        removed_by: Option<MergeSide>,
+    },
+    /// Both sides reordered the tracks differently. The lists hold the
+    /// tracks present on all three sides, in each side's order; ours is kept.
+    TrackOrder {
+        ours: Vec<String>,
+        theirs: Vec<String>,
+    },
+    /// Both sides changed the timeline name or metadata differently.
+    TimelineProperties,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    pub timeline: Timeline,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ItemSnapshot {
+    track_id: String,
+    start: Seconds,
+    item: Item,
+}
+
+impl ItemSnapshot {
+    fn end(&self) -> Seconds {
+        self.start + self.item.duration().max(0.0)
+    }
+
+    fn placement_eq(&self, other: &ItemSnapshot) -> bool {
+        self.track_id == other.track_id && (self.start - other.start).abs() <= EPS
+    }
+
+    fn source_range_eq(&self, other: &ItemSnapshot) -> bool {
+        time_range_eq(&self.item.get_source_range(), &other.item.get_source_range())
+    }
+
+    fn timing_eq(&self, other: &ItemSnapshot) -> bool {
+        self.placement_eq(other) && self.source_range_eq(other)
+    }
+
+    fn content_eq(&self, other: &ItemSnapshot) -> bool {
+        item_content_eq(&self.item, &other.item)
+    }
+
+    fn eq(&self, other: &ItemSnapshot) -> bool {
+        self.timing_eq(other) && self.content_eq(other)
+    }
+}
+
+/// Clips of a timeline keyed by timeline id, plus their id order (track
+/// order, then item order) so output stays deterministic.
+struct TimelineSnapshot {
+    order: Vec<String>,
+    items: HashMap<String, ItemSnapshot>,
+    track_order: Vec<String>,
+    tracks: HashMap<String, Track>,
+}
+
+impl TimelineSnapshot {
+    fn new(timeline: &Timeline) -> Self {
+        let mut order = Vec::new();
+        let mut items = HashMap::new();
+        let mut track_order = Vec::new();
+        let mut tracks = HashMap::new();
+        for (track_index, track) in timeline.tracks.children.iter().enumerate() {
+            let track_id = track.get_id().unwrap_or_else(|| format!("@{track_index}"));
+            if tracks.contains_key(&track_id) {
+                continue;
+            }
+            let mut start = 0.0;
+            for (item_index, item) in track.items.iter().enumerate() {
+                let duration = item.duration().max(0.0);
+                if let Item::Clip(_) = item {
+                    let item_id = item
+                        .get_id()
+                        .unwrap_or_else(|| format!("@{track_index}:{item_index}"));
+                    if let std::collections::hash_map::Entry::Vacant(entry) =
+                        items.entry(item_id.clone())
+                    {
+                        order.push(item_id);
+                        entry.insert(ItemSnapshot {
+                            track_id: track_id.clone(),
+                            start,
+                            item: item.clone(),
+                        });
+                    }
+                }
+                start += duration;
+            }
+            let mut shell = track.clone();
+            shell.items.clear();
+            track_order.push(track_id.clone());
+            tracks.insert(track_id, shell);
+        }
+        Self {
+            order,
+            items,
+            track_order,
+            tracks,
+        }
+    }
+
+    fn track_clips(&self, track_id: &str) -> Vec<(&String, &ItemSnapshot)> {
+        self.order
+            .iter()
+            .filter_map(|id| Some((id, self.items.get(id)?)))
+            .filter(|(_, snap)| snap.track_id == track_id)
+            .collect()
+    }
+
+    /// Whether this snapshot changed the track or any clip on it relative to
+    /// `base`.
+    fn track_edited(&self, base: &TimelineSnapshot, track_id: &str) -> bool {
+        match (self.tracks.get(track_id), base.tracks.get(track_id)) {
+            (Some(ours), Some(theirs)) if ours != theirs => return true,
+            (None, None) => return false,
+            _ => {}
+        }
+        let mine = self.track_clips(track_id);
+        let base_clips = base.track_clips(track_id);
+        if mine.len() != base_clips.len() {
+            return true;
+        }
+        mine.iter()
+            .any(|(id, snap)| base.items.get(*id).is_none_or(|b| !b.eq(snap)))
+    }
+}
+
+fn time_range_eq(a: &TimeRange, b: &TimeRange) -> bool {
+    (a.get_start_time() - b.get_start_time()).abs() <= EPS
+        && (a.get_duration() - b.get_duration()).abs() <= EPS
+}
+
+fn item_content_eq(a: &Item, b: &Item) -> bool {
+    let mut a = a.clone();
+    let mut b = b.clone();
+    a.set_source_range(TimeRange::default());
+    b.set_source_range(TimeRange::default());
+    a == b
+}
+
+fn common_order(order: &[String], other: &HashMap<String, Track>) -> Vec<String> {
+    order
+        .iter()
+        .filter(|id| other.contains_key(*id))
+        .cloned()
+        .collect()
+}
+
+/// Compute the semantic changes that turn `before` into `after`.
+///
+/// Changes are ordered: timeline-level changes first, then track changes,
+/// then removed clips (in `before` order), then added and changed clips (in
+/// `after` order).
+pub fn diff_timelines(before: &Timeline, after: &Timeline) -> TimelineDiff {
+    let old = TimelineSnapshot::new(before);
+    let new = TimelineSnapshot::new(after);
+    let mut changes = Vec::new();
+
+    if before.name != after.name {
+        changes.push(TimelineChange::TimelineRenamed {
+            before: before.name.clone(),
+            after: after.name.clone(),
+        });
+    }
+    if before.metadata != after.metadata {
+        changes.push(TimelineChange::TimelineMetadataChanged {
+            before: before.metadata.clone(),
+            after: after.metadata.clone(),
+        });
+    }
+
+    for (index, track_id) in old.track_order.iter().enumerate() {
+        if !new.tracks.contains_key(track_id) {
+            changes.push(TimelineChange::TrackRemoved {
+                track_id: track_id.clone(),
+                index,
+            });
+        }
+    }
+    for (index, track_id) in new.track_order.iter().enumerate() {
+        if !old.tracks.contains_key(track_id) {
+            changes.push(TimelineChange::TrackAdded {
+                track_id: track_id.clone(),
+                index,
+            });
+        }
+    }
+    let common_before = common_order(&old.track_order, &new.tracks);
+    let common_after = common_order(&new.track_order, &old.tracks);
+    if common_before != common_after {
+        changes.push(TimelineChange::TracksReordered {
+            before: common_before,
+            after: common_after,
+        });
+    }
+    for track_id in &new.track_order {
+        let (Some(old_track), Some(new_track)) = (old.tracks.get(track_id), new.tracks.get(track_id))
+        else {
+            continue;
+        };
+        if old_track.metadata != new_track.metadata {
+            changes.push(TimelineChange::TrackMetadataChanged {
+                track_id: track_id.clone(),
+                before: old_track.metadata.clone(),
+                after: new_track.metadata.clone(),
+            });
+        }
+        let (before, after) = (TrackProperties::of(old_track), TrackProperties::of(new_track));
+        if before != after {
+            changes.push(TimelineChange::TrackPropertiesChanged {
+                track_id: track_id.clone(),
+                before,
+                after,
+            });
+        }
+        if old_track.effects != new_track.effects {
+            changes.push(TimelineChange::TrackEffectsChanged {
+                track_id: track_id.clone(),
+                before: old_track.effects.clone(),
+                after: new_track.effects.clone(),
+            });
+        }
+    }
+
+    for item_id in &old.order {
+        if new.items.contains_key(item_id) {
+            continue;
+        }
+        let snap = &old.items[item_id];
+        changes.push(TimelineChange::ItemRemoved {
+            item_id: item_id.clone(),
+            track_id: snap.track_id.clone(),
+            start: snap.start,
+            item: snap.item.clone(),
+        });
+    }
+    for item_id in &new.order {
+        let snap = &new.items[item_id];
+        let Some(prev) = old.items.get(item_id) else {
+            changes.push(TimelineChange::ItemAdded {
+                item_id: item_id.clone(),
+                track_id: snap.track_id.clone(),
+                start: snap.start,
+                item: snap.item.clone(),
+            });
+            continue;
+        };
+        if prev.track_id != snap.track_id || (prev.start - snap.start).abs() > EPS {
+            changes.push(TimelineChange::ItemMoved {
+                item_id: item_id.clone(),
+                from_track_id: prev.track_id.clone(),
+                to_track_id: snap.track_id.clone(),
+                from_start: prev.start,
+                to_start: snap.start,
+            });
+        }
+        let prev_range = prev.item.get_source_range();
+        let range = snap.item.get_source_range();
+        if !time_range_eq(&prev_range, &range) {
+            changes.push(TimelineChange::ItemTrimmed {
+                item_id: item_id.clone(),
+                track_id: snap.track_id.clone(),
+                before: prev_range,
+                after: range,
+            });
+        }
+        let prev_metadata = crate::MetadataExt::get_metadata(&prev.item);
+        let metadata = crate::MetadataExt::get_metadata(&snap.item);
+        if prev_metadata != metadata {
+            changes.push(TimelineChange::ItemMetadataChanged {
+                item_id: item_id.clone(),
+                before: prev_metadata.clone(),
+                after: metadata.clone(),
+            });
+        }
+        let prev_effects = prev.item.get_effects();
+        let effects = snap.item.get_effects();
+        if prev_effects != effects {
+            changes.push(TimelineChange::ItemEffectsChanged {
+                item_id: item_id.clone(),
+                before: prev_effects,
+                after: effects,
+            });
+        }
+        let (before, after) = (ItemProperties::of(&prev.item), ItemProperties::of(&snap.item));
+        if before != after {
+            changes.push(TimelineChange::ItemPropertiesChanged {
+                item_id: item_id.clone(),
+                before,
+                after,
+            });
+        }
+    }
+
+    TimelineDiff { changes }
+}
+
+/// Three-way value merge: take whichever side changed `base`, "ours" when
+/// neither or both did. The flag reports a conflict (both changed, and to
+/// different values).
+fn merge_value<T: Clone>(
+    base: &T,
+    ours: &T,
+    theirs: &T,
+    eq: impl Fn(&T, &T) -> bool,
+) -> (T, bool) {
+    if eq(ours, base) {
+        (theirs.clone(), false)
+    } else if eq(theirs, base) || eq(ours, theirs) {
+        (ours.clone(), false)
+    } else {
+        (ours.clone(), true)
+    }
+}
+
+/// The merged state of one clip. `theirs_placed` marks a placement that
+/// differs from "ours" and may therefore collide with clips placed by ours.
+struct MergedItem {
+    snap: Option<ItemSnapshot>,
+    ours: Option<ItemSnapshot>,
+    theirs_placed: bool,
+}
+
+impl MergedItem {
+    /// Drop the "theirs" placement, keeping the merged content on ours'
+    /// timing (or removing the clip if ours does not have it).
+    fn revert_placement(&mut self) {
+        self.snap = match (self.snap.take(), &self.ours) {
+            (Some(mut merged), Some(ours)) => {
+                merged.track_id = ours.track_id.clone();
+                merged.start = ours.start;
+                merged.item.set_source_range(ours.item.get_source_range());
+                Some(merged)
+            }
+            _ => None,
+        };
+        self.theirs_placed = false;
+    }
+}
+
+fn merge_item(
+    item_id: &str,
+    base: Option<&ItemSnapshot>,
+    ours: Option<&ItemSnapshot>,
+    theirs: Option<&ItemSnapshot>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> MergedItem {
+    let conflict = |aspect| MergeConflict::Item {
+        item_id: item_id.to_string(),
+        aspect,
+    };
+    let snap = match (base, ours, theirs) {
+        (_, None, None) => None,
+        (None, Some(ours), Some(theirs)) => {
+            if !ours.eq(theirs) {
+                conflicts.push(conflict(ItemConflictAspect::Presence));
+            }
+            Some(ours.clone())
+        }
+        (None, Some(side), None) | (None, None, Some(side)) => Some(side.clone()),
+        (Some(base), None, Some(theirs)) => {
+            if !theirs.eq(base) {
+                conflicts.push(conflict(ItemConflictAspect::Presence));
+            }
+            None
+        }
+        (Some(base), Some(ours), None) => {
+            if ours.eq(base) {
+                None
+            } else {
+                conflicts.push(conflict(ItemConflictAspect::Presence));
+                Some(ours.clone())
+            }
+        }
+        (Some(base), Some(ours), Some(theirs)) => {
+            // Placement and source range merge independently: a ripple on
+            // one side moves a clip without touching its trim, so it must
+            // not clash with a trim of the same clip on the other side.
+            let (placement, placement_conflict) =
+                merge_value(base, ours, theirs, ItemSnapshot::placement_eq);
+            let (range, range_conflict) =
+                merge_value(base, ours, theirs, ItemSnapshot::source_range_eq);
+            let (content, content_conflict) =
+                merge_value(base, ours, theirs, ItemSnapshot::content_eq);
+            for (conflicted, aspect) in [
+                (placement_conflict, ItemConflictAspect::Placement),
+                (range_conflict, ItemConflictAspect::SourceRange),
+                (content_conflict, ItemConflictAspect::Content),
+            ] {
+                if conflicted {
+                    conflicts.push(conflict(aspect));
+                }
+            }
+            let mut item = content.item;
+            item.set_source_range(range.item.get_source_range());
+            Some(ItemSnapshot {
+                track_id: placement.track_id,
+                start: placement.start,
+                item,
+            })
+        }
+    };
+    let theirs_placed = match (&snap, ours) {
+        (Some(merged), Some(ours)) => !merged.timing_eq(ours),
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    MergedItem {
+        snap,
+        ours: ours.cloned(),
+        theirs_placed,
+    }
+}
+
+fn merge_tracks(
+    base: &TimelineSnapshot,
+    ours: &TimelineSnapshot,
+    theirs: &TimelineSnapshot,
+    conflicts: &mut Vec<MergeConflict>,
+) -> (Vec<String>, HashMap<String, Track>) {
+    let mut all_ids: Vec<String> = Vec::new();
+    for id in base
+        .track_order
+        .iter()
+        .chain(&ours.track_order)
+        .chain(&theirs.track_order)
+    {
+        if !all_ids.contains(id) {
+            all_ids.push(id.clone());
+        }
+    }
+
+    let mut merged = HashMap::new();
+    for track_id in &all_ids {
+        let track = match (
+            base.tracks.get(track_id),
+            ours.tracks.get(track_id),
+            theirs.tracks.get(track_id),
+        ) {
+            (_, None, None) => None,
+            (None, Some(track), _) | (None, None, Some(track)) => Some(track.clone()),
+            (Some(_), None, Some(_)) => {
+                if theirs.track_edited(base, track_id) {
+                    conflicts.push(MergeConflict::Track {
+                        track_id: track_id.clone(),
+                        aspect: TrackConflictAspect::Presence,
+                        removed_by: Some(MergeSide::Ours),
+                    });
+                }
+                None
+            }
+            (Some(_), Some(track), None) => {
+                if ours.track_edited(base, track_id) {
+                    conflicts.push(MergeConflict::Track {
+                        track_id: track_id.clone(),
+                        aspect: TrackConflictAspect::Presence,
+                        removed_by: Some(MergeSide::Theirs),
+                    });
+                    Some(track.clone())
+                } else {
+                    None
+                }
+            }
+            (Some(base_track), Some(ours_track), Some(theirs_track)) => {
+                let (track, conflict) =
+                    merge_value(base_track, ours_track, theirs_track, |a, b| a == b);
+                if conflict {
+                    conflicts.push(MergeConflict::Track {
+                        track_id: track_id.clone(),
+                        aspect: TrackConflictAspect::Properties,
+                        removed_by: None,
+                    });
+                }
+                Some(track)
+            }
+        };
+        if let Some(track) = track {
+            merged.insert(track_id.clone(), track);
+        }
+    }
+
+    // Reorders merge as a whole: keep ours' order unless only theirs
+    // reordered the tracks both sides share with the base.
+    let ours_reordered =
+        common_order(&ours.track_order, &base.tracks) != common_order(&base.track_order, &ours.tracks);
+    let theirs_reordered = common_order(&theirs.track_order, &base.tracks)
+        != common_order(&base.track_order, &theirs.tracks);
+    if ours_reordered && theirs_reordered {
+        let on_all_sides = |order: &[String]| -> Vec<String> {
+            order
+                .iter()
+                .filter(|id| {
+                    base.tracks.contains_key(*id)
+                        && ours.tracks.contains_key(*id)
+                        && theirs.tracks.contains_key(*id)
+                })
+                .cloned()
+                .collect()
+        };
+        let (ours_order, theirs_order) =
+            (on_all_sides(&ours.track_order), on_all_sides(&theirs.track_order));
+        if ours_order != theirs_order {
+            conflicts.push(MergeConflict::TrackOrder {
+                ours: ours_order,
+                theirs: theirs_order,
+            });
+        }
+    }
+    let (primary, secondary) = if ours_reordered {
+        (&ours.track_order, &theirs.track_order)
+    } else {
+        (&theirs.track_order, &ours.track_order)
+    };
+    let mut order: Vec<String> = primary
+        .iter()
+        .filter(|id| merged.contains_key(*id))
+        .cloned()
+        .collect();
+    for (index, id) in secondary.iter().enumerate() {
+        if merged.contains_key(id) && !order.contains(id) {
+            order.insert(index.min(order.len()), id.clone());
+        }
+    }
+    for id in &all_ids {
+        if merged.contains_key(id) && !order.contains(id) {
+            order.push(id.clone());
+        }
+    }
+    (order, merged)
+}
+
+/// Revert "theirs" placements that land on a removed track or overlap another
+/// clip, until every track is free of overlaps. Each pass reverts at least
+/// one clip to ours' placement, which never overlaps other ours placements,
+/// so this terminates.
+fn resolve_overlaps(
+    order: &[String],
+    merged_items: &mut HashMap<String, MergedItem>,
+    tracks: &HashMap<String, Track>,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    for item_id in order {
+        let Some(entry) = merged_items.get_mut(item_id) else {
+            continue;
+        };
+        let on_missing_track = entry
+            .snap
+            .as_ref()
+            .is_some_and(|snap| !tracks.contains_key(&snap.track_id));
+        if on_missing_track {
+            entry.revert_placement();
+            if entry
+                .snap
+                .as_ref()
+                .is_some_and(|snap| !tracks.contains_key(&snap.track_id))
+            {
+                entry.snap = None;
+            }
+        }
+    }
+
+    loop {
+        let mut by_track: HashMap<&str, Vec<&str>> = HashMap::new();
+        for item_id in order {
+            if let Some(snap) = merged_items.get(item_id).and_then(|m| m.snap.as_ref()) {
+                by_track
+                    .entry(snap.track_id.as_str())
+                    .or_default()
+                    .push(item_id.as_str());
+            }
+        }
+        let mut collision: Option<(String, String, String)> = None;
+        let mut track_ids: Vec<&&str> = by_track.keys().collect();
+        track_ids.sort();
+        'tracks: for track_id in track_ids {
+            let mut ids = by_track[*track_id].clone();
+            ids.sort_by(|a, b| {
+                let sa = merged_items[*a].snap.as_ref().unwrap().start;
+                let sb = merged_items[*b].snap.as_ref().unwrap().start;
+                sa.partial_cmp(&sb)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.cmp(b))
+            });
+            let mut prev: Option<&str> = None;
+            for id in ids {
+                let snap = merged_items[id].snap.as_ref().unwrap();
+                if let Some(prev_id) = prev {
+                    let prev_snap = merged_items[prev_id].snap.as_ref().unwrap();
+                    if snap.start < prev_snap.end() - EPS {
+                        let (kept, reverted) = if merged_items[id].theirs_placed {
+                            (prev_id, id)
+                        } else {
+                            (id, prev_id)
+                        };
+                        collision = Some((
+                            track_id.to_string(),
+                            kept.to_string(),
+                            reverted.to_string(),
+                        ));
+                        break 'tracks;
+                    }
+                }
+                prev = Some(id);
+            }
+        }
+        let Some((track_id, kept, reverted)) = collision else {
+            break;
+        };
+        let reverted_snap = merged_items[&reverted].snap.clone().unwrap();
+        let kept_snap = merged_items[&kept].snap.clone().unwrap();
+        conflicts.push(MergeConflict::OverlappingRange {
+            track_id,
+            start: reverted_snap.start.max(kept_snap.start),
+            end: reverted_snap.end().min(kept_snap.end()),
+            kept_item_id: kept,
+            reverted_item_id: reverted.clone(),
+        });
+        let entry = merged_items.get_mut(&reverted).unwrap();
+        if !entry.theirs_placed {
+            // Two ours placements cannot overlap; guard against looping on
+            // malformed input by dropping the later clip.
+            entry.snap = None;
+        } else {
+            entry.revert_placement();
+        }
+    }
+}
+
+/// Three-way merge of `ours` and `theirs` against their common ancestor
+/// `base`.
+///
+/// Non-conflicting changes from both sides are applied. Where both sides
+/// changed the same thing differently, "ours" wins and a [`MergeConflict`]
+/// is reported; that includes "theirs" moving, trimming or adding a clip
+/// onto a track range that "ours" occupies. Clips are laid back out on their
+/// tracks at their merged start times, with gaps filling the holes.
+pub fn merge_timelines(base: &Timeline, ours: &Timeline, theirs: &Timeline) -> MergeResult {
+    let base_snap = TimelineSnapshot::new(base);
+    let ours_snap = TimelineSnapshot::new(ours);
+    let theirs_snap = TimelineSnapshot::new(theirs);
+    let mut conflicts = Vec::new();
+
+    let mut timeline = ours.clone();
+    let (name, name_conflict) = merge_value(&base.name, &ours.name, &theirs.name, |a, b| a == b);
+    let (metadata, metadata_conflict) =
+        merge_value(&base.metadata, &ours.metadata, &theirs.metadata, |a, b| a == b);
+    if name_conflict || metadata_conflict {
+        conflicts.push(MergeConflict::TimelineProperties);
+    }
+    timeline.name = name;
+    timeline.metadata = metadata;
+
+    let (track_order, mut tracks) = merge_tracks(&base_snap, &ours_snap, &theirs_snap, &mut conflicts);
+
+    let mut item_order: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    for id in ours_snap
+        .order
+        .iter()
+        .chain(&theirs_snap.order)
+        .chain(&base_snap.order)
+    {
+        if seen.insert(id.clone()) {
+            item_order.push(id.clone());
+        }
+    }
+    let mut merged_items: HashMap<String, MergedItem> = HashMap::new();
+    for item_id in &item_order {
+        let merged = merge_item(
+            item_id,
+            base_snap.items.get(item_id),
+            ours_snap.items.get(item_id),
+            theirs_snap.items.get(item_id),
+            &mut conflicts,
+        );
+        merged_items.insert(item_id.clone(), merged);
+    }
+    resolve_overlaps(&item_order, &mut merged_items, &tracks, &mut conflicts);
+
+    let mut per_track: HashMap<String, Vec<ItemSnapshot>> = HashMap::new();
+    for item_id in &item_order {
+        if let Some(snap) = merged_items.remove(item_id).and_then(|m| m.snap) {
+            per_track.entry(snap.track_id.clone()).or_default().push(snap);
+        }
+    }
+    timeline.tracks.children = track_order
+        .iter()
+        .filter_map(|track_id| {
+            let mut track = tracks.remove(track_id)?;
+            let mut clips = per_track.remove(track_id).unwrap_or_default();
+            clips.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap_or(std::cmp::Ordering::Equal));
+            let mut pos = 0.0;
+            for clip in clips {
+                if clip.start > pos + EPS {
+                    track.items.push(Item::Gap(crate::Gap::make_gap(clip.start - pos)));
+                }
+                pos = clip.end();
+                track.items.push(clip.item);
+            }
+            Some(track)
+        })
+        .collect();
+    timeline.tracks.sanitize_preserving_all_gap_tracks();
+
+    MergeResult {
+        timeline,
+        conflicts,
+    }
+}
+
+impl Timeline {
+    /// See [`diff_timelines`].
+    pub fn diff(&self, other: &Timeline) -> TimelineDiff {
+        diff_timelines(self, other)
+    }
+}
@@ -0,0 +1,516 @@
+//! Export a timeline as an ffmpeg `-filter_complex` graph.
+//!
+//! The graph is built per track, the way an editor lays the timeline out:
+//! every clip is trimmed to its source range and every span without a
+//! renderable clip becomes blank (transparent video, silence), then the
+//! pieces of a track are joined with `concat`. Video tracks are overlaid
+//! bottom to top on a black canvas, so the highest track wins like in
+//! [`Timeline::composition_at`]; audio tracks are summed with `amix`.
+//!
+//! Nothing here runs ffmpeg: the export is plain text plus the list of
+//! inputs, so it can be golden-tested and handed to whatever launches the
+//! render.
+
+use crate::{Clip, Item, MediaReference, Seconds, Timeline, Track, TrackKind};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Output format of the rendered graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FfmpegExportOptions {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+    pub sample_rate: u32,
+}
+
+impl Default for FfmpegExportOptions {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            frame_rate: 30.0,
+            sample_rate: 48000,
+        }
+    }
+}
+
+/// One `-i` input of the render.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FfmpegInput {
+    /// `target_url` of the external reference, passed to ffmpeg unchanged.
+    pub url: String,
+    /// Still images are looped (`-loop 1`) so `trim` can cut any duration
+    /// out of them.
+    pub still_image: bool,
+}
+
+/// A timeline rendered as an ffmpeg filtergraph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FfmpegExport {
+    /// Inputs in `-i` order; the graph refers to them as `[n:v]` / `[n:a]`.
+    pub inputs: Vec<FfmpegInput>,
+    /// The `-filter_complex` script, one filter chain per line.
+    pub filter_complex: String,
+    /// Output pad carrying the composited picture.
+    pub video_output: String,
+    /// Output pad carrying the mix.
+    pub audio_output: String,
+    /// Length of the longest video or audio track.
+    pub duration: Seconds,
+}
+
+impl FfmpegExport {
+    /// Arguments for an ffmpeg invocation writing to `output` (the program
+    /// name itself is not included).
+    pub fn to_args(&self, output: &str) -> Vec<String> {
+        let mut args = vec!["-y".to_string()];
+        for input in &self.inputs {
+            if input.still_image {
+                args.extend(["-loop".to_string(), "1".to_string()]);
+            }
+            args.extend(["-i".to_string(), input.url.clone()]);
+        }
+        args.extend([
+            "-filter_complex".to_string(),
+            self.filter_complex.clone(),
+            "-map".to_string(),
+            self.video_output.clone(),
+            "-map".to_string(),
+            self.audio_output.clone(),
+            "-t".to_string(),
+            number(self.duration),
+            output.to_string(),
+        ]);
+        args
+    }
+
+    /// A POSIX shell script running the render, one argument group per line.
+    pub fn to_shell_script(&self, output: &str) -> String {
+        let mut script = String::from("#!/bin/sh\nffmpeg");
+        let args = self.to_args(output);
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            script.push_str(" \\\n  ");
+            script.push_str(&shell_quote(arg));
+            // Keep each flag on the same line as its value.
+            if arg.starts_with('-') && arg != "-y" {
+                if let Some(value) = args.next() {
+                    script.push(' ');
+                    script.push_str(&shell_quote(value));
+                }
+            }
+        }
+        script.push('\n');
+        script
+    }
+}
+
+impl Timeline {
+    /// Render the timeline as an ffmpeg filtergraph.
+    ///
+    /// Clips on video tracks contribute picture only and clips on audio
+    /// tracks sound only, matching the Resolve layout where linked audio
+    /// lives on its own track. Tracks that are not rendered (disabled, muted
+    /// or soloed out, see [`crate::Stack::is_track_rendered`]) and `Other`
+    /// tracks are left out; disabled clips, generator references (titles)
+    /// and clips without a `target_url` render as blank. Trims are taken
+    /// relative to the reference's `available_range` start, since decoded
+    /// media starts at 0.
+    pub fn to_ffmpeg(&self, options: &FfmpegExportOptions) -> FfmpegExport {
+        let mut graph = Graph::new(options);
+        let duration = self
+            .tracks
+            .children
+            .iter()
+            .filter(|track| matches!(track.kind, TrackKind::Video | TrackKind::Audio))
+            .map(Track::total_duration)
+            .fold(0.0, Seconds::max);
+
+        let mut video_tracks = Vec::new();
+        let mut audio_tracks = Vec::new();
+        for (track_index, track) in self.tracks.children.iter().enumerate() {
+            if !self.tracks.is_track_rendered(track_index) {
+                continue;
+            }
+            match track.kind {
+                TrackKind::Video => {
+                    video_tracks.extend(graph.track(track_index, track, Kind::Video))
+                }
+                TrackKind::Audio => {
+                    audio_tracks.extend(graph.track(track_index, track, Kind::Audio))
+                }
+                _ => {}
+            }
+        }
+
+        let (width, height, rate) = (options.width, options.height, number(options.frame_rate));
+        let mut below = "vbase".to_string();
+        graph.chain(format!(
+            "color=c=black:s={width}x{height}:r={rate}:d={}[{below}]",
+            number(duration)
+        ));
+        for (n, track) in video_tracks.iter().enumerate() {
+            let label = format!("vmix{n}");
+            graph.chain(format!(
+                "[{below}][{track}]overlay=eof_action=pass[{label}]"
+            ));
+            below = label;
+        }
+        graph.chain(format!("[{below}]format=yuv420p[vout]"));
+
+        let silence = graph.silence(duration);
+        if audio_tracks.is_empty() {
+            graph.chain(format!("{silence}[aout]"));
+        } else {
+            graph.chain(format!("{silence}[abase]"));
+            let mut mix = "[abase]".to_string();
+            for track in &audio_tracks {
+                let _ = write!(mix, "[{track}]");
+            }
+            let _ = write!(
+                mix,
+                "amix=inputs={}:duration=first:normalize=0[aout]",
+                audio_tracks.len() + 1
+            );
+            graph.chain(mix);
+        }
+
+        FfmpegExport {
+            inputs: graph.inputs,
+            filter_complex: graph.chains.join(";\n"),
+            video_output: "[vout]".to_string(),
+            audio_output: "[aout]".to_string(),
+            duration,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Video,
+    Audio,
+}
+
+struct Graph<'a> {
+    options: &'a FfmpegExportOptions,
+    inputs: Vec<FfmpegInput>,
+    input_index: HashMap<String, usize>,
+    chains: Vec<String>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(options: &'a FfmpegExportOptions) -> Self {
+        Self {
+            options,
+            inputs: Vec::new(),
+            input_index: HashMap::new(),
+            chains: Vec::new(),
+        }
+    }
+
+    fn chain(&mut self, chain: String) {
+        self.chains.push(chain);
+    }
+
+    fn input(&mut self, url: &str) -> usize {
+        if let Some(&index) = self.input_index.get(url) {
+            return index;
+        }
+        let index = self.inputs.len();
+        self.inputs.push(FfmpegInput {
+            url: url.to_string(),
+            still_image: is_still_image(url),
+        });
+        self.input_index.insert(url.to_string(), index);
+        index
+    }
+
+    /// Emit one track as a `concat` of its pieces and return the output
+    /// label, or `None` when nothing on the track would render.
+    fn track(&mut self, track_index: usize, track: &Track, kind: Kind) -> Option<String> {
+        let mut pieces = Vec::new();
+        // Blank spans are accumulated so consecutive gaps become one source.
+        let mut blank: Seconds = 0.0;
+        let mut blank_from = 0;
+        let mut any_clip = false;
+        for (item_index, item) in track.items.iter().enumerate() {
+            let duration = item.duration().max(0.0);
+            if duration <= 0.0 {
+                continue;
+            }
+            let source = match item {
+                Item::Clip(clip) => renderable_source(clip).map(|source| (clip, source)),
+                Item::Gap(_) => None,
+            };
+            let Some((clip, (url, trim_start))) = source else {
+                if blank == 0.0 {
+                    blank_from = item_index;
+                }
+                blank += duration;
+                continue;
+            };
+            if blank > 0.0 {
+                pieces.push(self.blank(kind, track_index, blank_from, blank));
+                blank = 0.0;
+            }
+            let input = self.input(url);
+            pieces.push(match kind {
+                Kind::Video => {
+                    self.video_clip(clip, input, track_index, item_index, trim_start, duration)
+                }
+                Kind::Audio => {
+                    self.audio_clip(clip, input, track_index, item_index, trim_start, duration)
+                }
+            });
+            any_clip = true;
+        }
+        if !any_clip {
+            return None;
+        }
+        // Trailing blanks are dropped: the track simply ends, and the
+        // overlay/mix below fills the rest.
+        let (label, streams) = match kind {
+            Kind::Video => (format!("vt{track_index}"), "v=1:a=0"),
+            Kind::Audio => (format!("at{track_index}"), "v=0:a=1"),
+        };
+        let mut chain = String::new();
+        for piece in &pieces {
+            let _ = write!(chain, "[{piece}]");
+        }
+        let _ = write!(chain, "concat=n={}:{streams}", pieces.len());
+        if matches!(kind, Kind::Audio) {
+            // Track gain and pan apply to the whole track, after the clip
+            // volumes.
+            let (volume, pan) = (track.get_volume(), track.get_pan());
+            if volume != 1.0 {
+                let _ = write!(chain, ",volume={}", number(volume));
+            }
+            if pan != 0.0 {
+                let _ = write!(chain, ",stereotools=balance_out={}", number(pan));
+            }
+        }
+        let _ = write!(chain, "[{label}]");
+        self.chain(chain);
+        Some(label)
+    }
+
+    fn blank(
+        &mut self,
+        kind: Kind,
+        track_index: usize,
+        item_index: usize,
+        duration: Seconds,
+    ) -> String {
+        let source = match kind {
+            Kind::Video => self.transparent(duration),
+            Kind::Audio => self.silence(duration),
+        };
+        let label = format!("{}blank{track_index}_{item_index}", kind.prefix());
+        self.chain(format!("{source}[{label}]"));
+        label
+    }
+
+    /// Cut the clip out of its input, apply crop and transform, and place it
+    /// on a transparent canvas the size of the output.
+    fn video_clip(
+        &mut self,
+        clip: &Clip,
+        input: usize,
+        track_index: usize,
+        item_index: usize,
+        trim_start: Seconds,
+        duration: Seconds,
+    ) -> String {
+        let FfmpegExportOptions { width, height, .. } = *self.options;
+        let mut filters = vec![
+            format!(
+                "trim=start={}:duration={}",
+                number(trim_start),
+                number(duration)
+            ),
+            "setpts=PTS-STARTPTS".to_string(),
+            format!("fps={}", number(self.options.frame_rate)),
+            "format=rgba".to_string(),
+        ];
+
+        let crop = clip.get_crop();
+        if [
+            crop.crop_left,
+            crop.crop_right,
+            crop.crop_top,
+            crop.crop_bottom,
+        ] != [0.0; 4]
+        {
+            filters.push(format!(
+                "crop=w=iw*{}:h=ih*{}:x=iw*{}:y=ih*{}",
+                number((1.0 - crop.crop_left - crop.crop_right).max(0.0)),
+                number((1.0 - crop.crop_top - crop.crop_bottom).max(0.0)),
+                number(crop.crop_left),
+                number(crop.crop_top)
+            ));
+        }
+        // Fit the media inside the frame, then apply the clip's zoom.
+        filters.push(format!(
+            "scale={width}:{height}:force_original_aspect_ratio=decrease"
+        ));
+        let position = clip.get_position();
+        if (position.zoom_x, position.zoom_y) != (1.0, 1.0) {
+            filters.push(format!(
+                "scale=iw*{}:ih*{}",
+                number(position.zoom_x),
+                number(position.zoom_y)
+            ));
+        }
+        if position.rotation != 0.0 {
+            // Resolve rotates counter-clockwise in degrees, ffmpeg clockwise
+            // in radians.
+            let angle = number(-position.rotation.to_radians());
+            filters.push(format!(
+                "rotate=a={angle}:ow=rotw({angle}):oh=roth({angle}):c=none"
+            ));
+        }
+
+        let media = format!("v{track_index}_{item_index}");
+        let canvas = format!("vcanvas{track_index}_{item_index}");
+        let placed = format!("vclip{track_index}_{item_index}");
+        self.chain(format!("[{input}:v]{}[{media}]", filters.join(",")));
+        let transparent = self.transparent(duration);
+        self.chain(format!("{transparent}[{canvas}]"));
+        // Position is in frame units around the center, y pointing up.
+        self.chain(format!(
+            "[{canvas}][{media}]overlay=x={}:y={}:eof_action=pass[{placed}]",
+            offset("(W-w)/2", position.x * f64::from(width)),
+            offset("(H-h)/2", -position.y * f64::from(height))
+        ));
+        placed
+    }
+
+    fn audio_clip(
+        &mut self,
+        clip: &Clip,
+        input: usize,
+        track_index: usize,
+        item_index: usize,
+        trim_start: Seconds,
+        duration: Seconds,
+    ) -> String {
+        let mut filters = vec![
+            format!(
+                "atrim=start={}:duration={}",
+                number(trim_start),
+                number(duration)
+            ),
+            "asetpts=PTS-STARTPTS".to_string(),
+            self.audio_format(),
+        ];
+        let volume = clip.get_volume();
+        if volume != 1.0 {
+            filters.push(format!("volume={}", number(volume)));
+        }
+        let label = format!("a{track_index}_{item_index}");
+        self.chain(format!("[{input}:a]{}[{label}]", filters.join(",")));
+        label
+    }
+
+    fn transparent(&self, duration: Seconds) -> String {
+        let FfmpegExportOptions {
+            width,
+            height,
+            frame_rate,
+            ..
+        } = *self.options;
+        format!(
+            "color=c=black@0.0:s={width}x{height}:r={}:d={},format=rgba",
+            number(frame_rate),
+            number(duration)
+        )
+    }
+
+    fn silence(&self, duration: Seconds) -> String {
+        format!(
+            "anullsrc=r={}:cl=stereo,atrim=duration={},{}",
+            self.options.sample_rate,
+            number(duration),
+            self.audio_format()
+        )
+    }
+
+    /// Common format for every audio piece; `concat` and `amix` need their
+    /// inputs to agree.
+    fn audio_format(&self) -> String {
+        format!(
+            "aresample={},aformat=sample_fmts=fltp:channel_layouts=stereo",
+            self.options.sample_rate
+        )
+    }
+}
+
+impl Kind {
+    fn prefix(self) -> &'static str {
+        match self {
+            Kind::Video => "v",
+            Kind::Audio => "a",
+        }
+    }
+}
+
+/// The input url and trim start of a clip that produces picture or sound.
+fn renderable_source(clip: &Clip) -> Option<(&str, Seconds)> {
+    if !clip.enabled {
+        return None;
+    }
+    let reference = clip.active_media_reference()?;
+    let MediaReference::ExternalReference { target_url, .. } = reference else {
+        return None;
+    };
+    if target_url.is_empty() {
+        return None;
+    }
+    let trim_start = (clip.source_range.start_time.to_seconds() - reference.media_start()).max(0.0);
+    Some((target_url, trim_start))
+}
+
+fn is_still_image(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let Some((_, extension)) = path.rsplit_once('.') else {
+        return false;
+    };
+    matches!(
+        extension.to_ascii_lowercase().as_str(),
+        "jpg" | "jpeg" | "png" | "webp" | "bmp" | "tif" | "tiff"
+    )
+}
+
+/// Format a number for the graph: at most six decimals, no trailing zeros,
+/// so the output is stable across platforms.
+fn number(value: f64) -> String {
+    let text = format!("{value:.6}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn offset(base: &str, value: f64) -> String {
+    let text = number(value);
+    match text.strip_prefix('-') {
+        _ if text == "0" => base.to_string(),
+        Some(magnitude) => format!("{base}-{magnitude}"),
+        None => format!("{base}+{text}"),
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
@@ -1,13 +1,35 @@
+pub mod composition;
+pub mod consolidate;
+pub mod crdt;
+pub mod diff;
+pub mod ffmpeg;
+pub mod media_usage;
 pub mod metadata;
+pub mod patch;
+pub mod relink;
+pub mod representation;
 pub mod sanitize;
 pub mod serialize;
+pub mod snapping;
+pub mod splice;
 pub mod stack_methods;
+pub mod subrange;
 pub mod track_methods;
 pub mod types;
 pub mod validate;
 
+pub use composition::*;
+pub use consolidate::*;
+pub use crdt::*;
+pub use diff::*;
+pub use ffmpeg::*;
+pub use media_usage::*;
 pub use metadata::*;
+pub use patch::*;
+pub use relink::*;
+pub use representation::*;
 pub use serialize::*;
+pub use snapping::*;
 pub use stack_methods::*;
 pub use track_methods::*;
 pub use types::*;
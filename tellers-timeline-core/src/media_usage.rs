@@ -0,0 +1,226 @@
+//! Which media a timeline uses, and which parts of it.
+//!
+//! Licensing needs the list of assets and how many seconds of each end up in
+//! the cut; archiving needs the source intervals to keep. [`Timeline::media_usage`]
+//! walks the active reference of every clip and groups them per media: by
+//! `metadata["tellers.ai"]["media_id"]` when the reference has one (the same
+//! asset may be served under several URLs), by `target_url` otherwise.
+//!
+//! Only external references count; generators (titles, solids) are not media.
+
+use crate::{item_timeline_id, Item, MediaReference, MediaReferenceLocation, Seconds, Timeline};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+const EPS: Seconds = 1e-9;
+
+/// One clip playing a media.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaClipUse {
+    pub location: MediaReferenceLocation,
+    pub timeline_start: Seconds,
+    pub source_start: Seconds,
+    pub source_end: Seconds,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaUsageEntry {
+    pub media_id: Option<String>,
+    /// Every URL the media is referenced by, in order of first use.
+    pub target_urls: Vec<String>,
+    pub clips: Vec<MediaClipUse>,
+    /// Used source intervals, sorted, with overlapping and touching ones
+    /// merged.
+    pub used_intervals: Vec<(Seconds, Seconds)>,
+    /// Length of `used_intervals`: the seconds of media the cut needs.
+    pub used_duration: Seconds,
+    /// Union of the references' available ranges, when they declare one.
+    pub available_range: Option<(Seconds, Seconds)>,
+    /// Available media before the first used second and after the last one,
+    /// when the available range is known.
+    pub head_handle: Option<Seconds>,
+    pub tail_handle: Option<Seconds>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MediaUsage {
+    /// One entry per media, in order of first use (track, then item).
+    pub media: Vec<MediaUsageEntry>,
+}
+
+impl Timeline {
+    /// Per-media usage of this timeline. See the module docs.
+    pub fn media_usage(&self) -> MediaUsage {
+        let mut usage = MediaUsage::default();
+        let mut by_key: HashMap<String, usize> = HashMap::new();
+        for (track_index, track) in self.tracks.children.iter().enumerate() {
+            let mut timeline_start = 0.0;
+            for (item_index, item) in track.items.iter().enumerate() {
+                let item_start = timeline_start;
+                timeline_start += item.duration().max(0.0);
+                let Item::Clip(clip) = item else {
+                    continue;
+                };
+                let reference_key = clip
+                    .active_media_reference_key
+                    .clone()
+                    .unwrap_or_else(|| "DEFAULT_MEDIA".to_string());
+                let Some(
+                    reference @ MediaReference::ExternalReference {
+                        target_url,
+                        available_range,
+                        ..
+                    },
+                ) = clip.media_references.get(&reference_key)
+                else {
+                    continue;
+                };
+                let media_id = media_id(reference);
+                let Some(key) = media_id
+                    .clone()
+                    .or_else(|| (!target_url.is_empty()).then(|| format!("url:{target_url}")))
+                else {
+                    continue;
+                };
+                let index = *by_key.entry(key).or_insert_with(|| {
+                    usage.media.push(MediaUsageEntry {
+                        media_id: media_id.clone(),
+                        target_urls: Vec::new(),
+                        clips: Vec::new(),
+                        used_intervals: Vec::new(),
+                        used_duration: 0.0,
+                        available_range: None,
+                        head_handle: None,
+                        tail_handle: None,
+                    });
+                    usage.media.len() - 1
+                });
+                let entry = &mut usage.media[index];
+                if !target_url.is_empty() && !entry.target_urls.contains(target_url) {
+                    entry.target_urls.push(target_url.clone());
+                }
+                if let Some(range) = available_range {
+                    let start = range.start_time.to_seconds();
+                    let end = start + range.duration.to_seconds();
+                    entry.available_range = Some(match entry.available_range {
+                        Some((s, e)) => (s.min(start), e.max(end)),
+                        None => (start, end),
+                    });
+                }
+                let source_start = clip.source_range.start_time.to_seconds();
+                entry.clips.push(MediaClipUse {
+                    location: MediaReferenceLocation {
+                        track_index,
+                        item_index,
+                        clip_id: item_timeline_id(item).map(str::to_string),
+                        reference_key,
+                    },
+                    timeline_start: item_start,
+                    source_start,
+                    source_end: source_start + clip.source_range.duration.to_seconds().max(0.0),
+                });
+            }
+        }
+        for entry in &mut usage.media {
+            entry.used_intervals = merge_intervals(
+                entry
+                    .clips
+                    .iter()
+                    .map(|clip| (clip.source_start, clip.source_end))
+                    .collect(),
+            );
+            entry.used_duration = entry.used_intervals.iter().map(|(s, e)| e - s).sum();
+            if let (Some((start, end)), Some(first), Some(last)) = (
+                entry.available_range,
+                entry.used_intervals.first(),
+                entry.used_intervals.last(),
+            ) {
+                entry.head_handle = Some((first.0 - start).max(0.0));
+                entry.tail_handle = Some((end - last.1).max(0.0));
+            }
+        }
+        usage
+    }
+}
+
+impl MediaUsage {
+    /// The usage as a JSON manifest (the serialized [`MediaUsage`]).
+    pub fn to_manifest_json(&self, pretty: bool) -> serde_json::Result<String> {
+        if pretty {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        }
+    }
+
+    /// The usage as CSV, one row per media. Lists (URLs, intervals) are
+    /// `;`-separated inside their field; times are in seconds with
+    /// millisecond precision, and unknown values are left empty.
+    pub fn to_manifest_csv(&self) -> String {
+        let mut out = String::from(
+            "media_id,target_urls,clip_count,used_duration,used_intervals,\
+             available_start,available_end,head_handle,tail_handle\n",
+        );
+        for entry in &self.media {
+            let intervals = entry
+                .used_intervals
+                .iter()
+                .map(|(s, e)| format!("{s:.3}-{e:.3}"))
+                .collect::<Vec<_>>()
+                .join(";");
+            let seconds =
+                |value: Option<Seconds>| value.map_or(String::new(), |v| format!("{v:.3}"));
+            let _ = writeln!(
+                out,
+                "{},{},{},{:.3},{},{},{},{},{}",
+                csv_field(entry.media_id.as_deref().unwrap_or("")),
+                csv_field(&entry.target_urls.join(";")),
+                entry.clips.len(),
+                entry.used_duration,
+                csv_field(&intervals),
+                seconds(entry.available_range.map(|(s, _)| s)),
+                seconds(entry.available_range.map(|(_, e)| e)),
+                seconds(entry.head_handle),
+                seconds(entry.tail_handle),
+            );
+        }
+        out
+    }
+}
+
+/// `metadata["tellers.ai"]["media_id"]`, or the legacy root `media_id` for
+/// references built in memory rather than deserialized (deserializing copies
+/// the legacy field under `tellers.ai`).
+pub(crate) fn media_id(reference: &MediaReference) -> Option<String> {
+    let metadata = reference.metadata();
+    metadata
+        .get("tellers.ai")
+        .and_then(|ai| ai.get("media_id"))
+        .or_else(|| metadata.get("media_id"))
+        .and_then(|id| match id {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+}
+
+pub(crate) fn merge_intervals(mut intervals: Vec<(Seconds, Seconds)>) -> Vec<(Seconds, Seconds)> {
+    intervals.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let mut merged: Vec<(Seconds, Seconds)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + EPS => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -6,10 +6,13 @@ pub trait IdMetadataExt {
 }
 
 fn read_id_from_metadata(meta: &serde_json::Value) -> Option<String> {
+    borrow_id_from_metadata(meta).map(|s| s.to_string())
+}
+
+fn borrow_id_from_metadata(meta: &serde_json::Value) -> Option<&str> {
     meta.get("tellers.ai")
         .and_then(|v| v.get("timeline_id"))
         .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
 }
 
 fn write_id_to_metadata(meta: &mut serde_json::Value, id: Option<String>) {
@@ -218,11 +221,23 @@ impl MetadataExt for Item {
 // * the Resolve "Link Group ID" (`metadata["Resolve_OTIO"]["Link Group ID"]`),
 //   which ties synchronised clips together, and
 // * the Tellers Group ID (`metadata["tellers.ai"]["Tellers Group ID"]`), the
-//   Tellers-native "move together" grouping.
+//   Tellers-native "move together" grouping. Groups nest: the groups a clip's
+//   group sits in are listed, innermost first, under
+//   `metadata["tellers.ai"]["Tellers Parent Group IDs"]`.
 //
 // These accessors expose the read/write conventions for both so callers (the
 // editor bridge, the stack methods) don't re-implement the metadata layout.
 
+/// The timeline id of an item, borrowed from its metadata. Unlike
+/// `IdMetadataExt::get_id` this does not allocate, so id scans over large
+/// tracks stay cheap.
+pub fn item_timeline_id(item: &Item) -> Option<&str> {
+    match item {
+        Item::Clip(clip) => borrow_id_from_metadata(&clip.metadata),
+        Item::Gap(gap) => borrow_id_from_metadata(&gap.metadata),
+    }
+}
+
 /// The Resolve "Link Group ID" of an item (the sync-clip group), or `None` for
 /// an ungrouped clip or a gap.
 pub fn item_link_group_id(item: &Item) -> Option<i64> {
@@ -284,8 +299,27 @@ pub fn set_tellers_group_id(metadata: &mut serde_json::Value, group_id: i64) {
     );
 }
 
+/// The `metadata["tellers.ai"]` object, created (replacing anything that is
+/// not an object on the way) when missing.
+pub(crate) fn tellers_ai_mut(
+    metadata: &mut serde_json::Value,
+) -> &mut serde_json::Map<String, serde_json::Value> {
+    if !metadata.is_object() {
+        *metadata = serde_json::Value::Object(serde_json::Map::new());
+    }
+    let ai = metadata
+        .as_object_mut()
+        .unwrap()
+        .entry("tellers.ai".to_string())
+        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+    if !ai.is_object() {
+        *ai = serde_json::Value::Object(serde_json::Map::new());
+    }
+    ai.as_object_mut().unwrap()
+}
+
 /// Remove the Tellers Group ID from `metadata`, returning whether one was
-/// present.
+/// present. The groups it was nested in go with it.
 pub fn remove_tellers_group_id(metadata: &mut serde_json::Value) -> bool {
     let Some(ai) = metadata
         .get_mut("tellers.ai")
@@ -293,5 +327,180 @@ pub fn remove_tellers_group_id(metadata: &mut serde_json::Value) -> bool {
     else {
         return false;
     };
+    ai.remove("Tellers Parent Group IDs");
     ai.remove("Tellers Group ID").is_some()
 }
+
+/// The Tellers groups an item's group is nested in, innermost first, from
+/// `metadata["tellers.ai"]["Tellers Parent Group IDs"]`. Values read back the
+/// same way as [`resolve_tellers_group_id`].
+pub fn resolve_tellers_parent_group_ids(metadata: &serde_json::Value) -> Vec<i64> {
+    metadata
+        .get("tellers.ai")
+        .and_then(|v| v.get("Tellers Parent Group IDs"))
+        .and_then(|v| v.as_array())
+        .map(|raw| {
+            raw.iter()
+                .filter_map(|value| {
+                    value
+                        .as_i64()
+                        .or_else(|| value.as_u64().and_then(|value| i64::try_from(value).ok()))
+                        .or_else(|| value.as_str().and_then(|value| value.parse::<i64>().ok()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Write the groups an item's group is nested in, innermost first. An empty
+/// list removes the key.
+pub fn set_tellers_parent_group_ids(metadata: &mut serde_json::Value, parent_ids: &[i64]) {
+    if parent_ids.is_empty() {
+        if let Some(ai) = metadata
+            .get_mut("tellers.ai")
+            .and_then(|value| value.as_object_mut())
+        {
+            ai.remove("Tellers Parent Group IDs");
+        }
+        return;
+    }
+    tellers_ai_mut(metadata).insert(
+        "Tellers Parent Group IDs".to_string(),
+        serde_json::Value::from(parent_ids.to_vec()),
+    );
+}
+
+/// The outermost Tellers group of `metadata`: the group itself when it is not
+/// nested, else its last parent.
+pub fn resolve_tellers_root_group_id(metadata: &serde_json::Value) -> Option<i64> {
+    let group_id = resolve_tellers_group_id(metadata)?;
+    Some(
+        resolve_tellers_parent_group_ids(metadata)
+            .last()
+            .copied()
+            .unwrap_or(group_id),
+    )
+}
+
+/// The Tellers Group ID of an item followed by the groups it is nested in,
+/// innermost first; empty for an ungrouped clip or a gap.
+pub fn item_tellers_group_ids(item: &Item) -> Vec<i64> {
+    match item {
+        Item::Clip(clip) => match resolve_tellers_group_id(&clip.metadata) {
+            Some(group_id) => std::iter::once(group_id)
+                .chain(resolve_tellers_parent_group_ids(&clip.metadata))
+                .collect(),
+            None => Vec::new(),
+        },
+        Item::Gap(_) => Vec::new(),
+    }
+}
+
+/// The outermost Tellers group of an item, which moves, deletes and ungroups
+/// act on as a whole.
+pub fn item_tellers_root_group_id(item: &Item) -> Option<i64> {
+    match item {
+        Item::Clip(clip) => resolve_tellers_root_group_id(&clip.metadata),
+        Item::Gap(_) => None,
+    }
+}
+
+/// Take `group_id` out of the groups `metadata` belongs to, keeping the rest
+/// of the nesting: a clip directly in the group moves up to its parent.
+/// Returns whether the clip was in the group.
+pub(crate) fn remove_tellers_group_level(metadata: &mut serde_json::Value, group_id: i64) -> bool {
+    let Some(leaf) = resolve_tellers_group_id(metadata) else {
+        return false;
+    };
+    let mut parents = resolve_tellers_parent_group_ids(metadata);
+    if leaf == group_id {
+        if parents.is_empty() {
+            return remove_tellers_group_id(metadata);
+        }
+        set_tellers_group_id(metadata, parents.remove(0));
+    } else if let Some(position) = parents.iter().position(|&id| id == group_id) {
+        parents.remove(position);
+    } else {
+        return false;
+    }
+    set_tellers_parent_group_ids(metadata, &parents);
+    true
+}
+
+/// How far a synced clip's in and out points sit from the column its link
+/// group shares: a split edit (J-cut or L-cut), where e.g. the audio starts
+/// before or ends after its video. Negative offsets are earlier. A clip
+/// plays `duration - in_offset + out_offset` of a column of `duration`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SyncOffsets {
+    pub in_offset: f64,
+    pub out_offset: f64,
+}
+
+impl SyncOffsets {
+    pub fn is_zero(&self) -> bool {
+        self.in_offset == 0.0 && self.out_offset == 0.0
+    }
+
+    /// How much longer the clip is than its group's column.
+    pub(crate) fn extra_duration(&self) -> f64 {
+        self.out_offset - self.in_offset
+    }
+}
+
+/// Read the split-edit offsets from `metadata["tellers.ai"]["sync_offset"]`
+/// (`{"in": .., "out": ..}`). Missing values read as zero.
+pub fn resolve_sync_offsets(metadata: &serde_json::Value) -> SyncOffsets {
+    let offset = |key: &str| {
+        metadata
+            .get("tellers.ai")
+            .and_then(|ai| ai.get("sync_offset"))
+            .and_then(|offset| offset.get(key))
+            .and_then(serde_json::Value::as_f64)
+            .unwrap_or(0.0)
+    };
+    SyncOffsets {
+        in_offset: offset("in"),
+        out_offset: offset("out"),
+    }
+}
+
+/// The split-edit offsets of an item; zero for clips without any and gaps.
+pub fn item_sync_offsets(item: &Item) -> SyncOffsets {
+    match item {
+        Item::Clip(clip) => resolve_sync_offsets(&clip.metadata),
+        Item::Gap(_) => SyncOffsets::default(),
+    }
+}
+
+/// Write split-edit offsets to `metadata["tellers.ai"]["sync_offset"]`.
+/// Zero offsets remove the key.
+pub fn set_sync_offsets(metadata: &mut serde_json::Value, offsets: SyncOffsets) {
+    let values = (
+        serde_json::Number::from_f64(offsets.in_offset),
+        serde_json::Number::from_f64(offsets.out_offset),
+    );
+    match values {
+        (Some(in_offset), Some(out_offset)) if !offsets.is_zero() => {
+            tellers_ai_mut(metadata).insert(
+                "sync_offset".to_string(),
+                serde_json::json!({ "in": in_offset, "out": out_offset }),
+            );
+        }
+        _ => {
+            remove_sync_offsets(metadata);
+        }
+    }
+}
+
+/// Remove the split-edit offsets from `metadata`, returning whether any were
+/// present.
+pub fn remove_sync_offsets(metadata: &mut serde_json::Value) -> bool {
+    let Some(ai) = metadata
+        .get_mut("tellers.ai")
+        .and_then(|value| value.as_object_mut())
+    else {
+        return false;
+    };
+    ai.remove("sync_offset").is_some()
+}
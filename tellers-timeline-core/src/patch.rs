@@ -0,0 +1,441 @@
+//! RFC 6902 JSON Patch export and application.
+//!
+//! Clients hold the serialized OTIO tree; after a server-side edit they only
+//! need the operations that turn their copy into the new one. Patches are
+//! computed by comparing the serialized tree before and after an edit, so
+//! they describe exactly what changed on the wire, whichever `Stack` method
+//! made the change.
+//!
+//! Arrays are matched element by element on `tellers.ai.timeline_id` where
+//! elements carry one (tracks, clips, gaps), so inserting a clip emits one
+//! `add` rather than a `replace` of every following item.
+
+use crate::{Stack, Timeline};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum JsonPatchOp {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// An RFC 6902 document: an ordered list of operations. Serializes as the
+/// bare JSON array the RFC specifies.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonPatch(pub Vec<JsonPatchOp>);
+
+impl JsonPatch {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn ops(&self) -> &[JsonPatchOp] {
+        &self.0
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PatchError {
+    #[error("invalid JSON pointer {0:?}")]
+    InvalidPointer(String),
+    #[error("path {0:?} does not exist")]
+    PathNotFound(String),
+    #[error("test failed at {0:?}")]
+    TestFailed(String),
+    #[error("serialization failed: {0}")]
+    Serialization(String),
+}
+
+/// The operations that turn `before` into `after`.
+pub fn json_patch_between(before: &Value, after: &Value) -> JsonPatch {
+    let mut ops = Vec::new();
+    diff_values(before, after, String::new(), &mut ops);
+    JsonPatch(ops)
+}
+
+/// Apply `patch` to `target` in place. On error `target` is left unchanged.
+pub fn apply_json_patch(target: &mut Value, patch: &JsonPatch) -> Result<(), PatchError> {
+    let mut working = target.clone();
+    for op in &patch.0 {
+        apply_op(&mut working, op)?;
+    }
+    *target = working;
+    Ok(())
+}
+
+fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn element_key(value: &Value) -> Option<&str> {
+    value
+        .get("metadata")
+        .and_then(|m| m.get("tellers.ai"))
+        .and_then(|ai| ai.get("timeline_id"))
+        .and_then(Value::as_str)
+}
+
+fn elements_match(a: &Value, b: &Value) -> bool {
+    match (element_key(a), element_key(b)) {
+        (Some(ka), Some(kb)) => ka == kb,
+        (None, None) => a == b,
+        _ => false,
+    }
+}
+
+fn diff_values(before: &Value, after: &Value, path: String, ops: &mut Vec<JsonPatchOp>) {
+    if before == after {
+        return;
+    }
+    match (before, after) {
+        (Value::Object(old), Value::Object(new)) => {
+            for key in old.keys() {
+                if !new.contains_key(key) {
+                    ops.push(JsonPatchOp::Remove {
+                        path: format!("{path}/{}", escape_token(key)),
+                    });
+                }
+            }
+            for (key, value) in new {
+                let child = format!("{path}/{}", escape_token(key));
+                match old.get(key) {
+                    Some(previous) => diff_values(previous, value, child, ops),
+                    None => ops.push(JsonPatchOp::Add {
+                        path: child,
+                        value: value.clone(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => diff_arrays(old, new, &path, ops),
+        _ => ops.push(JsonPatchOp::Replace {
+            path,
+            value: after.clone(),
+        }),
+    }
+}
+
+/// Remove the unmatched old elements (back to front), add the unmatched new
+/// ones (front to back), then recurse into matched pairs, which by then sit
+/// at their new indices.
+fn diff_arrays(old: &[Value], new: &[Value], path: &str, ops: &mut Vec<JsonPatchOp>) {
+    let pairs = match_elements(old, new);
+    let mut matched_old = vec![false; old.len()];
+    let mut matched_new = vec![false; new.len()];
+    for &(o, n) in &pairs {
+        matched_old[o] = true;
+        matched_new[n] = true;
+    }
+    for index in (0..old.len()).rev().filter(|i| !matched_old[*i]) {
+        ops.push(JsonPatchOp::Remove {
+            path: format!("{path}/{index}"),
+        });
+    }
+    for index in (0..new.len()).filter(|j| !matched_new[*j]) {
+        ops.push(JsonPatchOp::Add {
+            path: format!("{path}/{index}"),
+            value: new[index].clone(),
+        });
+    }
+    for (o, n) in pairs {
+        diff_values(&old[o], &new[n], format!("{path}/{n}"), ops);
+    }
+}
+
+/// Matched (old_index, new_index) pairs, increasing in both indices.
+///
+/// Keyed elements are paired through an id map, keeping the longest run
+/// whose order is the same on both sides; a full LCS table over a track
+/// with thousands of items would cost tens of megabytes per edit. Only the
+/// stretches between those anchors, where unkeyed elements live, go through
+/// the quadratic LCS.
+fn match_elements(old: &[Value], new: &[Value]) -> Vec<(usize, usize)> {
+    let mut new_by_key: HashMap<&str, usize> = HashMap::new();
+    for (j, value) in new.iter().enumerate() {
+        if let Some(key) = element_key(value) {
+            new_by_key.entry(key).or_insert(j);
+        }
+    }
+    let keyed: Vec<(usize, usize)> = old
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| Some((i, *new_by_key.get(element_key(value)?)?)))
+        .collect();
+
+    let mut pairs = Vec::new();
+    let (mut i0, mut j0) = (0, 0);
+    for (i, j) in longest_increasing_run(&keyed) {
+        lcs_pairs(&old[i0..i], &new[j0..j], (i0, j0), &mut pairs);
+        pairs.push((i, j));
+        (i0, j0) = (i + 1, j + 1);
+    }
+    lcs_pairs(&old[i0..], &new[j0..], (i0, j0), &mut pairs);
+    pairs
+}
+
+/// The longest subsequence of `pairs` (already increasing in the old index)
+/// that also increases in the new index, by patience sorting.
+pub(crate) fn longest_increasing_run(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // tails[k]: index into `pairs` of the smallest tail of a run of length k+1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (k, &(_, j)) in pairs.iter().enumerate() {
+        let length = tails.partition_point(|&t| pairs[t].1 < j);
+        previous[k] = length.checked_sub(1).map(|l| tails[l]);
+        if length == tails.len() {
+            tails.push(k);
+        } else {
+            tails[length] = k;
+        }
+    }
+    let mut run = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(k) = next {
+        run.push(pairs[k]);
+        next = previous[k];
+    }
+    run.reverse();
+    run
+}
+
+/// Longest common subsequence of two short slices, pushing matched pairs
+/// shifted by `offset`.
+fn lcs_pairs(
+    old: &[Value],
+    new: &[Value],
+    offset: (usize, usize),
+    pairs: &mut Vec<(usize, usize)>,
+) {
+    let n = old.len();
+    let m = new.len();
+    if n == 0 || m == 0 {
+        return;
+    }
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if elements_match(&old[i], &new[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if elements_match(&old[i], &new[j]) {
+            pairs.push((offset.0 + i, offset.1 + j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+}
+
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, PatchError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(PatchError::InvalidPointer(pointer.to_string()));
+    };
+    Ok(rest.split('/').map(unescape_token).collect())
+}
+
+fn array_index(token: &str, len: usize, allow_end: bool, path: &str) -> Result<usize, PatchError> {
+    if allow_end && token == "-" {
+        return Ok(len);
+    }
+    let index: usize = token
+        .parse()
+        .map_err(|_| PatchError::InvalidPointer(path.to_string()))?;
+    let limit = if allow_end { len } else { len.saturating_sub(1) };
+    if index > limit || (!allow_end && len == 0) {
+        return Err(PatchError::PathNotFound(path.to_string()));
+    }
+    Ok(index)
+}
+
+fn parent_mut<'a>(
+    root: &'a mut Value,
+    tokens: &[String],
+    path: &str,
+) -> Result<&'a mut Value, PatchError> {
+    let mut current = root;
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => map
+                .get_mut(token)
+                .ok_or_else(|| PatchError::PathNotFound(path.to_string()))?,
+            Value::Array(items) => {
+                let index = array_index(token, items.len(), false, path)?;
+                &mut items[index]
+            }
+            _ => return Err(PatchError::PathNotFound(path.to_string())),
+        };
+    }
+    Ok(current)
+}
+
+fn get_value(root: &Value, path: &str) -> Result<Value, PatchError> {
+    root.pointer(path)
+        .cloned()
+        .ok_or_else(|| PatchError::PathNotFound(path.to_string()))
+}
+
+fn add_value(root: &mut Value, path: &str, value: Value) -> Result<(), PatchError> {
+    let tokens = parse_pointer(path)?;
+    let Some((last, parents)) = tokens.split_last() else {
+        *root = value;
+        return Ok(());
+    };
+    match parent_mut(root, parents, path)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+        }
+        Value::Array(items) => {
+            let index = array_index(last, items.len(), true, path)?;
+            items.insert(index, value);
+        }
+        _ => return Err(PatchError::PathNotFound(path.to_string())),
+    }
+    Ok(())
+}
+
+fn remove_value(root: &mut Value, path: &str) -> Result<Value, PatchError> {
+    let tokens = parse_pointer(path)?;
+    let Some((last, parents)) = tokens.split_last() else {
+        return Err(PatchError::InvalidPointer(path.to_string()));
+    };
+    match parent_mut(root, parents, path)? {
+        Value::Object(map) => map
+            .remove(last)
+            .ok_or_else(|| PatchError::PathNotFound(path.to_string())),
+        Value::Array(items) => {
+            let index = array_index(last, items.len(), false, path)?;
+            Ok(items.remove(index))
+        }
+        _ => Err(PatchError::PathNotFound(path.to_string())),
+    }
+}
+
+fn apply_op(root: &mut Value, op: &JsonPatchOp) -> Result<(), PatchError> {
+    match op {
+        JsonPatchOp::Add { path, value } => add_value(root, path, value.clone()),
+        JsonPatchOp::Remove { path } => remove_value(root, path).map(|_| ()),
+        JsonPatchOp::Replace { path, value } => {
+            let tokens = parse_pointer(path)?;
+            let target = parent_mut(root, &tokens, path)?;
+            *target = value.clone();
+            Ok(())
+        }
+        JsonPatchOp::Move { from, path } => {
+            let value = remove_value(root, from)?;
+            add_value(root, path, value)
+        }
+        JsonPatchOp::Copy { from, path } => {
+            let value = get_value(root, from)?;
+            add_value(root, path, value)
+        }
+        JsonPatchOp::Test { path, value } => {
+            if &get_value(root, path)? == value {
+                Ok(())
+            } else {
+                Err(PatchError::TestFailed(path.clone()))
+            }
+        }
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, PatchError> {
+    serde_json::to_value(value).map_err(|e| PatchError::Serialization(e.to_string()))
+}
+
+impl Stack {
+    /// Run `edit` and return its result together with the JSON Patch that
+    /// turns the stack's serialized form before the edit into the one after.
+    /// Paths are rooted at the stack object.
+    ///
+    /// Fails without running `edit` if the stack cannot be serialized. If
+    /// only the edited stack fails to serialize, the edit stays applied and
+    /// the error is returned, so callers resync clients instead of sending a
+    /// patch that would replace their whole document.
+    pub fn edit_with_patch<R>(
+        &mut self,
+        edit: impl FnOnce(&mut Stack) -> R,
+    ) -> Result<(R, JsonPatch), PatchError> {
+        let before = to_value(&*self)?;
+        let result = edit(self);
+        let after = to_value(&*self)?;
+        Ok((result, json_patch_between(&before, &after)))
+    }
+}
+
+impl Timeline {
+    /// Run a stack `edit` on this timeline's tracks and return its result with
+    /// the JSON Patch describing the change. Paths are rooted at the timeline
+    /// object (`/tracks/children/...`), ready for [`Timeline::apply_patch`] on
+    /// a client copy.
+    pub fn edit_with_patch<R>(
+        &mut self,
+        edit: impl FnOnce(&mut Stack) -> R,
+    ) -> Result<(R, JsonPatch), PatchError> {
+        let (result, patch) = self.tracks.edit_with_patch(edit)?;
+        let ops = patch
+            .0
+            .into_iter()
+            .map(|op| prefix_op(op, "/tracks"))
+            .collect();
+        Ok((result, JsonPatch(ops)))
+    }
+
+    /// The patch that turns this timeline's serialized form into `other`'s.
+    pub fn patch_to(&self, other: &Timeline) -> Result<JsonPatch, PatchError> {
+        Ok(json_patch_between(&to_value(self)?, &to_value(other)?))
+    }
+
+    /// Apply an RFC 6902 patch to the serialized timeline and read it back.
+    /// The timeline is left unchanged if any operation fails or the patched
+    /// document is not a valid timeline.
+    pub fn apply_patch(&mut self, patch: &JsonPatch) -> Result<(), PatchError> {
+        let mut value = to_value(self)?;
+        apply_json_patch(&mut value, patch)?;
+        *self = serde_json::from_value(value)
+            .map_err(|e| PatchError::Serialization(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn prefix_op(op: JsonPatchOp, prefix: &str) -> JsonPatchOp {
+    let p = |path: String| format!("{prefix}{path}");
+    match op {
+        JsonPatchOp::Add { path, value } => JsonPatchOp::Add { path: p(path), value },
+        JsonPatchOp::Remove { path } => JsonPatchOp::Remove { path: p(path) },
+        JsonPatchOp::Replace { path, value } => JsonPatchOp::Replace { path: p(path), value },
+        JsonPatchOp::Move { from, path } => JsonPatchOp::Move {
+            from: p(from),
+            path: p(path),
+        },
+        JsonPatchOp::Copy { from, path } => JsonPatchOp::Copy {
+            from: p(from),
+            path: p(path),
+        },
+        JsonPatchOp::Test { path, value } => JsonPatchOp::Test { path: p(path), value },
+    }
+}
@@ -0,0 +1,331 @@
+//! Rewrite where media references point.
+//!
+//! Projects move between machines and storage: the backend serves presigned
+//! S3 URLs, an editing station wants local proxy paths, an archive wants
+//! paths relative to its own root. [`Timeline::relink`] rewrites the
+//! `target_url` of every external reference through an ordered list of
+//! [`RelinkRule`]s (first match wins), optionally asks a callback about the
+//! URLs no rule matched, resolves what is still relative against a base, and
+//! reports every reference it could not turn into a playable location.
+//!
+//! Generator references have no URL and are left alone.
+
+use crate::{item_timeline_id, Item, MediaReference, Timeline};
+use regex::Regex;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RelinkError {
+    #[error("invalid relink pattern {pattern:?}: {source}")]
+    InvalidPattern {
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+}
+
+/// One rewrite of a `target_url`.
+#[derive(Debug, Clone)]
+pub enum RelinkRule {
+    /// Replace a leading `from` with `to`.
+    Prefix { from: String, to: String },
+    /// Replace the first match of `pattern` with `replacement`, which may
+    /// refer to capture groups as `$1` or `${name}`.
+    Regex { pattern: Regex, replacement: String },
+}
+
+impl RelinkRule {
+    pub fn prefix(from: impl Into<String>, to: impl Into<String>) -> Self {
+        RelinkRule::Prefix {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    pub fn regex(pattern: &str, replacement: impl Into<String>) -> Result<Self, RelinkError> {
+        let compiled = Regex::new(pattern).map_err(|source| RelinkError::InvalidPattern {
+            pattern: pattern.to_string(),
+            source,
+        })?;
+        Ok(RelinkRule::Regex {
+            pattern: compiled,
+            replacement: replacement.into(),
+        })
+    }
+
+    /// The rewritten URL, or `None` when the rule does not apply to `url`.
+    pub fn apply(&self, url: &str) -> Option<String> {
+        match self {
+            RelinkRule::Prefix { from, to } => url
+                .strip_prefix(from.as_str())
+                .map(|rest| format!("{to}{rest}")),
+            RelinkRule::Regex {
+                pattern,
+                replacement,
+            } => pattern
+                .is_match(url)
+                .then(|| pattern.replace(url, replacement.as_str()).into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RelinkOptions {
+    /// Tried in order; the first rule that applies rewrites the URL.
+    pub rules: Vec<RelinkRule>,
+    /// Directory that relative URLs are resolved against, after the rules.
+    /// `.` and `..` segments are collapsed; `..` never climbs above the
+    /// base's root.
+    pub base_url: Option<String>,
+    /// Only relink references stored under these keys (e.g. `["proxy"]`);
+    /// every reference when `None`.
+    pub reference_keys: Option<Vec<String>>,
+    /// Report references that no rule nor the callback rewrote as
+    /// [`UnresolvedReason::NoMatch`], for relinks that must move every URL
+    /// (say, off an expiring host).
+    pub require_match: bool,
+}
+
+/// Where a media reference lives in the timeline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaReferenceLocation {
+    pub track_index: usize,
+    pub item_index: usize,
+    pub clip_id: Option<String>,
+    /// Key of the reference in the clip's `media_references`.
+    pub reference_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelinkedReference {
+    pub location: MediaReferenceLocation,
+    pub old_url: String,
+    pub new_url: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnresolvedReason {
+    /// The reference has no URL (e.g. imported as a `MissingReference`).
+    MissingUrl,
+    /// The URL is still relative and there is no `base_url` to resolve it.
+    RelativeUrl,
+    /// `require_match` is set and nothing rewrote the URL.
+    NoMatch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnresolvedReference {
+    pub location: MediaReferenceLocation,
+    /// The URL as left in the timeline.
+    pub target_url: String,
+    pub reason: UnresolvedReason,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RelinkReport {
+    /// References whose URL changed, in track, item, then key order.
+    pub relinked: Vec<RelinkedReference>,
+    pub unresolved: Vec<UnresolvedReference>,
+}
+
+impl Timeline {
+    /// Rewrite external reference URLs with `options`. See the module docs.
+    pub fn relink(&mut self, options: &RelinkOptions) -> RelinkReport {
+        self.relink_with(options, |_| None)
+    }
+
+    /// Like [`Timeline::relink`], asking `resolve` for a new URL whenever no
+    /// rule applies. `resolve` sees the reference as it is before the relink,
+    /// so it can look the media up by its metadata (e.g. `tellers.ai.media_id`)
+    /// rather than by URL.
+    pub fn relink_with(
+        &mut self,
+        options: &RelinkOptions,
+        mut resolve: impl FnMut(&MediaReference) -> Option<String>,
+    ) -> RelinkReport {
+        let mut report = RelinkReport::default();
+        for (track_index, track) in self.tracks.children.iter_mut().enumerate() {
+            for (item_index, item) in track.items.iter_mut().enumerate() {
+                let clip_id = item_timeline_id(item).map(str::to_string);
+                let Item::Clip(clip) = item else {
+                    continue;
+                };
+                let mut keys: Vec<&String> = clip
+                    .media_references
+                    .keys()
+                    .filter(|key| {
+                        options
+                            .reference_keys
+                            .as_ref()
+                            .is_none_or(|wanted| wanted.contains(key))
+                    })
+                    .collect();
+                keys.sort();
+                let keys: Vec<String> = keys.into_iter().cloned().collect();
+                for reference_key in keys {
+                    let Some(reference) = clip.media_references.get_mut(&reference_key) else {
+                        continue;
+                    };
+                    let MediaReference::ExternalReference { target_url, .. } = &*reference else {
+                        continue;
+                    };
+                    let old_url = target_url.clone();
+                    let location = MediaReferenceLocation {
+                        track_index,
+                        item_index,
+                        clip_id: clip_id.clone(),
+                        reference_key,
+                    };
+                    let outcome = relink_url(&old_url, reference, options, &mut resolve);
+                    if let MediaReference::ExternalReference { target_url, .. } = reference {
+                        target_url.clone_from(&outcome.url);
+                    }
+                    if let Some(reason) = outcome.unresolved {
+                        report.unresolved.push(UnresolvedReference {
+                            location: location.clone(),
+                            target_url: outcome.url.clone(),
+                            reason,
+                        });
+                    }
+                    if outcome.url != old_url {
+                        report.relinked.push(RelinkedReference {
+                            location,
+                            old_url,
+                            new_url: outcome.url,
+                        });
+                    }
+                }
+            }
+        }
+        report
+    }
+
+    /// Make `key` the active media reference of every clip that has a
+    /// reference under that key, e.g. to flip the whole timeline between
+    /// `DEFAULT_MEDIA` and a proxy. Clips without it keep their current
+    /// reference and are returned (with `reference_key` set to `key`).
+    pub fn switch_media_reference_key(&mut self, key: &str) -> Vec<MediaReferenceLocation> {
+        let mut missing = Vec::new();
+        for (track_index, track) in self.tracks.children.iter_mut().enumerate() {
+            for (item_index, item) in track.items.iter_mut().enumerate() {
+                let clip_id = item_timeline_id(item).map(str::to_string);
+                let Item::Clip(clip) = item else {
+                    continue;
+                };
+                if clip.media_references.contains_key(key) {
+                    clip.active_media_reference_key = Some(key.to_string());
+                } else {
+                    missing.push(MediaReferenceLocation {
+                        track_index,
+                        item_index,
+                        clip_id,
+                        reference_key: key.to_string(),
+                    });
+                }
+            }
+        }
+        missing
+    }
+}
+
+struct RelinkOutcome {
+    url: String,
+    unresolved: Option<UnresolvedReason>,
+}
+
+fn relink_url(
+    url: &str,
+    reference: &MediaReference,
+    options: &RelinkOptions,
+    resolve: &mut impl FnMut(&MediaReference) -> Option<String>,
+) -> RelinkOutcome {
+    if url.is_empty() {
+        return RelinkOutcome {
+            url: String::new(),
+            unresolved: Some(UnresolvedReason::MissingUrl),
+        };
+    }
+    let rewritten = options
+        .rules
+        .iter()
+        .find_map(|rule| rule.apply(url))
+        .or_else(|| resolve(reference));
+    let matched = rewritten.is_some();
+    let mut url = rewritten.unwrap_or_else(|| url.to_string());
+    if is_relative_url(&url) {
+        match &options.base_url {
+            Some(base) => url = join_url(base, &url),
+            None => {
+                return RelinkOutcome {
+                    url,
+                    unresolved: Some(UnresolvedReason::RelativeUrl),
+                }
+            }
+        }
+    }
+    let unresolved = if url.is_empty() {
+        Some(UnresolvedReason::MissingUrl)
+    } else if options.require_match && !matched {
+        Some(UnresolvedReason::NoMatch)
+    } else {
+        None
+    };
+    RelinkOutcome { url, unresolved }
+}
+
+fn scheme_len(url: &str) -> Option<usize> {
+    let colon = url.find(':')?;
+    let scheme = &url[..colon];
+    // A single letter is a Windows drive (`C:\media`), not a scheme.
+    let is_scheme = scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    is_scheme.then_some(colon)
+}
+
+fn is_relative_url(url: &str) -> bool {
+    let is_windows_path =
+        url.len() >= 2 && url.as_bytes()[0].is_ascii_alphabetic() && url.as_bytes()[1] == b':';
+    !url.is_empty()
+        && scheme_len(url).is_none()
+        && !is_windows_path
+        && !url.starts_with('/')
+        && !url.starts_with('\\')
+}
+
+/// Append `relative` to the directory `base`, collapsing `.` and `..`.
+fn join_url(base: &str, relative: &str) -> String {
+    // Past this byte the base is path; `..` may not remove the root before it.
+    let root = match scheme_len(base) {
+        Some(colon) if base[colon..].starts_with("://") => {
+            let authority = colon + 3;
+            base[authority..]
+                .find('/')
+                .map_or(base.len(), |slash| authority + slash)
+        }
+        Some(colon) => colon + 1,
+        None => 0,
+    };
+    let mut out = base.trim_end_matches('/').to_string();
+    if out.len() < root {
+        out = base[..root].to_string();
+    }
+    for segment in relative.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                if let Some(slash) = out.rfind('/').filter(|&slash| slash >= root) {
+                    out.truncate(slash);
+                }
+            }
+            segment => {
+                out.push('/');
+                out.push_str(segment);
+            }
+        }
+    }
+    out
+}
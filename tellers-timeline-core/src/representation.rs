@@ -0,0 +1,165 @@
+//! Timeline-wide switching between media representations.
+//!
+//! A clip can carry several renditions of the same media in
+//! `media_references`, keyed by name (the original under `DEFAULT_MEDIA`, a
+//! `proxy`, ...). [`Timeline::set_active_representation`] points every clip
+//! at the requested rendition in one go and records the choice on the
+//! timeline, so an editor can cut on proxies and render on the originals.
+//!
+//! A clip only switches when its `source_range` still lies inside the new
+//! reference's `available_range`: a proxy transcoded without the source
+//! timecode starts at 0, and moving a clip that reads from 3600s onto it
+//! would point past the end of the file.
+
+use crate::metadata::tellers_ai_mut;
+use crate::{
+    item_timeline_id, Clip, Item, MediaReference, MediaReferenceLocation, Seconds, Timeline,
+};
+
+const EPS: Seconds = 1e-9;
+
+/// A named rendition, stored as a key of `Clip::media_references`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Representation {
+    /// `proxy`
+    Proxy,
+    /// `high_res`
+    HighRes,
+    Custom(String),
+}
+
+impl Representation {
+    pub fn key(&self) -> &str {
+        match self {
+            Representation::Proxy => "proxy",
+            Representation::HighRes => "high_res",
+            Representation::Custom(key) => key,
+        }
+    }
+}
+
+impl From<&str> for Representation {
+    fn from(key: &str) -> Self {
+        match key {
+            "proxy" => Representation::Proxy,
+            "high_res" => Representation::HighRes,
+            other => Representation::Custom(other.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepresentationIssueKind {
+    /// The clip has no reference under the requested key.
+    Missing,
+    /// The requested reference doesn't cover the clip's source range
+    /// (`available_start..available_end`, in seconds).
+    IncompatibleRange {
+        available_start: f64,
+        available_end: f64,
+    },
+}
+
+/// A clip that could not switch to the requested representation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepresentationIssue {
+    /// `reference_key` is the requested key.
+    pub location: MediaReferenceLocation,
+    pub kind: RepresentationIssueKind,
+    /// The key the clip plays after the fallback.
+    pub active_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepresentationReport {
+    /// Clips now playing the requested representation.
+    pub switched: usize,
+    pub issues: Vec<RepresentationIssue>,
+}
+
+impl Timeline {
+    /// Play `representation` on every clip that has a compatible reference
+    /// under its key. Other clips fall back to `DEFAULT_MEDIA` when that one
+    /// is compatible, keep their current reference otherwise, and are
+    /// reported. Clips playing a generator (titles, solids) have no
+    /// renditions and are skipped.
+    pub fn set_active_representation(
+        &mut self,
+        representation: impl Into<Representation>,
+    ) -> RepresentationReport {
+        let representation = representation.into();
+        let key = representation.key();
+        let mut report = RepresentationReport::default();
+        for (track_index, track) in self.tracks.children.iter_mut().enumerate() {
+            for (item_index, item) in track.items.iter_mut().enumerate() {
+                let clip_id = item_timeline_id(item).map(str::to_string);
+                let Item::Clip(clip) = item else {
+                    continue;
+                };
+                if matches!(
+                    clip.active_media_reference(),
+                    Some(MediaReference::GeneratorReference { .. })
+                ) {
+                    continue;
+                }
+                let kind = match clip.media_references.get(key) {
+                    None => RepresentationIssueKind::Missing,
+                    Some(reference) => match incompatible_range(clip, reference) {
+                        None => {
+                            clip.active_media_reference_key = Some(key.to_string());
+                            report.switched += 1;
+                            continue;
+                        }
+                        Some(kind) => kind,
+                    },
+                };
+                let fallback_fits = clip
+                    .media_references
+                    .get("DEFAULT_MEDIA")
+                    .is_some_and(|reference| incompatible_range(clip, reference).is_none());
+                if key != "DEFAULT_MEDIA" && fallback_fits {
+                    clip.active_media_reference_key = Some("DEFAULT_MEDIA".to_string());
+                }
+                report.issues.push(RepresentationIssue {
+                    location: MediaReferenceLocation {
+                        track_index,
+                        item_index,
+                        clip_id,
+                        reference_key: key.to_string(),
+                    },
+                    kind,
+                    active_key: clip.active_media_reference_key.clone(),
+                });
+            }
+        }
+        tellers_ai_mut(&mut self.metadata).insert(
+            "representation".to_string(),
+            serde_json::Value::String(key.to_string()),
+        );
+        report
+    }
+
+    /// The representation last set with
+    /// [`Timeline::set_active_representation`], if any.
+    pub fn active_representation(&self) -> Option<Representation> {
+        self.metadata
+            .get("tellers.ai")
+            .and_then(|ai| ai.get("representation"))
+            .and_then(|key| key.as_str())
+            .map(Representation::from)
+    }
+}
+
+/// `None` when `reference` covers the clip's source range (or has no
+/// available range to check against).
+fn incompatible_range(clip: &Clip, reference: &MediaReference) -> Option<RepresentationIssueKind> {
+    let available_start = reference.media_start();
+    let available_end = available_start + reference.media_duration()?;
+    let source_start = clip.source_range.start_time.to_seconds();
+    let source_end = source_start + clip.source_range.duration.to_seconds();
+    let fits = source_start >= available_start - EPS && source_end <= available_end + EPS;
+    (!fits).then_some(RepresentationIssueKind::IncompatibleRange {
+        available_start,
+        available_end,
+    })
+}
@@ -83,6 +83,9 @@ impl Stack {
     }
 
     pub(crate) fn sanitize_preserving_all_gap_tracks(&mut self) {
+        if self.sanitize_deferred() {
+            return;
+        }
         for t in &mut self.children {
             t.sanitize_preserving_all_gap_track();
         }
@@ -90,7 +93,7 @@ impl Stack {
         self.cleanup_dangling_sync_clips();
     }
 
-    fn ensure_unique_timeline_ids(&mut self) {
+    pub(crate) fn ensure_unique_timeline_ids(&mut self) {
         let mut used_ids = HashSet::new();
         for track in &mut self.children {
             ensure_unique_timeline_id(track, &mut used_ids);
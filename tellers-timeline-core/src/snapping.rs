@@ -0,0 +1,196 @@
+//! Snapping for interactive drags.
+//!
+//! `Stack::snap` takes where a drag would put the moving item (or a bare time
+//! range) and looks for a nearby time to pull it to: another clip's edge, the
+//! playhead or a marker. Any edge of the moving thing may snap: its start,
+//! its end and, when enabled, the edges of its sync partners, which move
+//! with it. Clips that move along (the item, its sync partners and its
+//! Tellers group) are never snap targets.
+
+use crate::metadata::{item_link_group_id, item_tellers_root_group_id};
+use crate::{IdMetadataExt, Item, Seconds, Stack, StackIndex};
+use std::collections::HashSet;
+
+/// What a drag snaps. `Range` is for drags that are not an item of the
+/// stack, e.g. a clip dragged in from the media pool.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapMoving {
+    Item(String),
+    Range { start: Seconds, end: Seconds },
+}
+
+/// The snap candidates a drag may use. The playhead and markers are not part
+/// of the stack, so their times are passed in; leaving them out disables them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapSources {
+    /// Starts and ends of the clips that do not move.
+    pub clip_edges: bool,
+    /// Let the edges of the moving item's sync partners snap too, not only
+    /// the item's own.
+    pub sync_partner_edges: bool,
+    pub playhead: Option<Seconds>,
+    pub markers: Vec<Seconds>,
+}
+
+impl Default for SnapSources {
+    fn default() -> Self {
+        Self {
+            clip_edges: true,
+            sync_partner_edges: true,
+            playhead: None,
+            markers: Vec::new(),
+        }
+    }
+}
+
+/// What a drag snapped to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapTarget {
+    ClipStart {
+        track_index: usize,
+        item_index: usize,
+        item_id: Option<String>,
+    },
+    ClipEnd {
+        track_index: usize,
+        item_index: usize,
+        item_id: Option<String>,
+    },
+    Playhead,
+    /// `index` into [`SnapSources::markers`].
+    Marker {
+        index: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snap {
+    /// Where the moving item (or range) starts once snapped.
+    pub start: Seconds,
+    /// The time snapped to.
+    pub time: Seconds,
+    /// Offset of the edge that snapped from the moving start: 0 for the
+    /// start itself, the duration for the end.
+    pub edge_offset: Seconds,
+    pub target: SnapTarget,
+}
+
+impl Stack {
+    /// Snap a drag that would start `moving` at `proposed_start` to the
+    /// closest candidate within `tolerance` of any of its edges. `None` when
+    /// nothing is close enough, or the moving item does not exist. Ties go to
+    /// the playhead, then markers, then clips in track order.
+    pub fn snap(
+        &self,
+        moving: &SnapMoving,
+        proposed_start: Seconds,
+        tolerance: Seconds,
+        sources: &SnapSources,
+    ) -> Option<Snap> {
+        let index = self.build_index();
+        let moving = match moving {
+            SnapMoving::Item(item_id) => moving_item(self, &index, item_id, sources)?,
+            SnapMoving::Range { start, end } => MovingEdges {
+                offsets: vec![0.0, (end - start).max(0.0)],
+                clips: HashSet::new(),
+            },
+        };
+
+        let mut candidates: Vec<(Seconds, SnapTarget)> = Vec::new();
+        candidates.extend(sources.playhead.map(|time| (time, SnapTarget::Playhead)));
+        candidates.extend(
+            sources
+                .markers
+                .iter()
+                .enumerate()
+                .map(|(index, &time)| (time, SnapTarget::Marker { index })),
+        );
+        if sources.clip_edges {
+            for (track_index, track) in self.children.iter().enumerate() {
+                let mut start = 0.0;
+                for (item_index, item) in track.items.iter().enumerate() {
+                    let end = start + item.duration().max(0.0);
+                    if matches!(item, Item::Clip(_))
+                        && !moving.clips.contains(&(track_index, item_index))
+                    {
+                        let item_id = item.get_id();
+                        candidates.push((
+                            start,
+                            SnapTarget::ClipStart {
+                                track_index,
+                                item_index,
+                                item_id: item_id.clone(),
+                            },
+                        ));
+                        candidates.push((
+                            end,
+                            SnapTarget::ClipEnd {
+                                track_index,
+                                item_index,
+                                item_id,
+                            },
+                        ));
+                    }
+                    start = end;
+                }
+            }
+        }
+
+        let mut best: Option<(Seconds, Snap)> = None;
+        for (time, target) in candidates {
+            for &edge_offset in &moving.offsets {
+                let distance = (time - (proposed_start + edge_offset)).abs();
+                if distance > tolerance || best.as_ref().is_some_and(|(d, _)| *d <= distance) {
+                    continue;
+                }
+                best = Some((
+                    distance,
+                    Snap {
+                        start: time - edge_offset,
+                        time,
+                        edge_offset,
+                        target: target.clone(),
+                    },
+                ));
+            }
+        }
+        best.map(|(_, snap)| snap)
+    }
+}
+
+/// The edges that may snap, as offsets from the moving start, and the
+/// `(track_index, item_index)` of the clips that move along.
+struct MovingEdges {
+    offsets: Vec<Seconds>,
+    clips: HashSet<(usize, usize)>,
+}
+
+fn moving_item(
+    stack: &Stack,
+    index: &StackIndex<'_>,
+    item_id: &str,
+    sources: &SnapSources,
+) -> Option<MovingEdges> {
+    let (track_index, item_index, item) = index.get_item(item_id)?;
+    let item_start = index.start_time_of_item(track_index, item_index)?;
+    let mut offsets = vec![0.0, item.duration().max(0.0)];
+    let mut clips = HashSet::from([(track_index, item_index)]);
+
+    if let Some(sync_clips_id) = item_link_group_id(item) {
+        for &(ti, ii) in index.sync_members(sync_clips_id) {
+            if !clips.insert((ti, ii)) || !sources.sync_partner_edges {
+                continue;
+            }
+            let Some(start) = index.start_time_of_item(ti, ii) else {
+                continue;
+            };
+            let end = start + stack.children[ti].items[ii].duration().max(0.0);
+            offsets.push(start - item_start);
+            offsets.push(end - item_start);
+        }
+    }
+    if let Some(group_id) = item_tellers_root_group_id(item) {
+        clips.extend(index.tellers_group_members(group_id));
+    }
+    Some(MovingEdges { offsets, clips })
+}
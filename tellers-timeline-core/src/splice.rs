@@ -0,0 +1,241 @@
+//! Assembling timelines from separately edited segments.
+//!
+//! [`Timeline::splice_at`] opens room for the other timeline on every track
+//! and drops its tracks into it. Each of its tracks lands on the first unused,
+//! unlocked track of the same kind and name, or on a new track placed after
+//! the last track of that kind. New tracks keep the source track's name,
+//! metadata and effects under a numbered id (`V2`, `A3`, ...). Splicing fails
+//! when it would change a locked track. Link and Tellers group ids
+//! are renumbered past the ones already in use, and item ids only when they
+//! collide. Timed words and subtitles are merged on the same clock, and
+//! entries spanning the splice point are split around the inserted segment.
+
+use crate::metadata::{
+    resolve_tellers_group_id, resolve_tellers_parent_group_ids, set_tellers_group_id,
+    set_tellers_parent_group_ids,
+};
+use crate::stack_methods::{fresh_group, replace_track_range_with_item, set_resolve_sync_clips_id};
+use crate::subrange::TIMED_METADATA_KEYS;
+use crate::{Gap, IdMetadataExt, Item, Seconds, Stack, Timeline, Track};
+use std::collections::{HashMap, HashSet};
+
+const EPS: Seconds = 1e-9;
+
+impl Timeline {
+    /// Add `other` after the end of the longest track.
+    pub fn append(&mut self, other: &Timeline) {
+        let end = stack_duration(&self.tracks);
+        self.splice_at(end, other);
+    }
+
+    /// Insert `other` at `time`, pushing everything from `time` on every track
+    /// later by its duration. `false` when `time` is negative or not finite.
+    pub fn splice_at(&mut self, time: Seconds, other: &Timeline) -> bool {
+        if !time.is_finite() || time < -EPS {
+            return false;
+        }
+        let time = time.max(0.0);
+        let duration = stack_duration(&other.tracks);
+        if duration <= EPS {
+            return true;
+        }
+
+        let mut inserted = other.tracks.children.clone();
+        let mut used_ids: HashSet<String> = self
+            .tracks
+            .children
+            .iter()
+            .flat_map(|track| track.items.iter().map(IdMetadataExt::get_id))
+            .flatten()
+            .collect();
+        let mut next_sync_clips_id = self.tracks.next_sync_clips_id();
+        let mut next_tellers_group_id = self.tracks.next_tellers_group_id();
+        let mut sync_clips_ids = HashMap::new();
+        let mut tellers_group_ids = HashMap::new();
+        for item in inserted.iter_mut().flat_map(|track| &mut track.items) {
+            if item.get_id().is_some_and(|id| !used_ids.insert(id)) {
+                item.set_id(None);
+            }
+            let Item::Clip(clip) = item else {
+                continue;
+            };
+            if let Some(old) = clip.sync_clips_id() {
+                let new = fresh_group(&mut sync_clips_ids, &mut next_sync_clips_id, old);
+                set_resolve_sync_clips_id(&mut clip.metadata, new);
+            }
+            if let Some(old) = resolve_tellers_group_id(&clip.metadata) {
+                let new = fresh_group(&mut tellers_group_ids, &mut next_tellers_group_id, old);
+                set_tellers_group_id(&mut clip.metadata, new);
+                let parent_ids: Vec<i64> = resolve_tellers_parent_group_ids(&clip.metadata)
+                    .into_iter()
+                    .map(|old| fresh_group(&mut tellers_group_ids, &mut next_tellers_group_id, old))
+                    .collect();
+                set_tellers_parent_group_ids(&mut clip.metadata, &parent_ids);
+            }
+        }
+
+        let spliced = self.tracks.refusing_locked_track_changes(|stack| {
+            for track in &mut stack.children {
+                track.insert_gap_at_time(time, duration);
+            }
+            let mut matched: HashSet<usize> = HashSet::new();
+            for source in inserted {
+                let same = stack
+                    .children
+                    .iter()
+                    .enumerate()
+                    .find(|(track_index, track)| {
+                        !matched.contains(track_index)
+                            && !track.is_locked()
+                            && track.kind == source.kind
+                            && track.name == source.name
+                    });
+                let track_index = match same {
+                    Some((track_index, _)) => track_index,
+                    None => {
+                        let track_index = stack
+                            .children
+                            .iter()
+                            .rposition(|track| track.kind == source.kind)
+                            .map_or(stack.children.len(), |last| last + 1);
+                        let track = new_track_like(stack, &source);
+                        stack.children.insert(track_index, track);
+                        matched = matched
+                            .into_iter()
+                            .map(|index| index + usize::from(index >= track_index))
+                            .collect();
+                        track_index
+                    }
+                };
+                matched.insert(track_index);
+
+                let track = &mut stack.children[track_index];
+                let mut start = time;
+                for item in source.items {
+                    let end = start + item.duration().max(0.0);
+                    if matches!(item, Item::Clip(_)) {
+                        let track_end = track.total_duration();
+                        if start > track_end + EPS {
+                            track
+                                .items
+                                .push(Item::Gap(Gap::make_gap(start - track_end)));
+                        }
+                        replace_track_range_with_item(track, start, end, item);
+                    }
+                    start = end;
+                }
+            }
+            stack.sanitize_preserving_all_gap_tracks();
+            true
+        });
+        if !spliced {
+            return false;
+        }
+
+        merge_metadata(&mut self.metadata, &other.metadata, time, duration);
+        true
+    }
+}
+
+/// An empty track for `source` with its name, metadata and effects, under a
+/// fresh numbered id. Unnamed sources take the numbered name.
+fn new_track_like(stack: &Stack, source: &Track) -> Track {
+    let mut track = stack.new_numbered_track(source.kind.clone());
+    let id = track.get_id();
+    if source.name.is_some() {
+        track.name = source.name.clone();
+    }
+    track.metadata = source.metadata.clone();
+    track.set_id(id);
+    track.effects = source.effects.clone();
+    track
+}
+
+fn stack_duration(stack: &Stack) -> Seconds {
+    stack
+        .children
+        .iter()
+        .map(|track| track.total_duration())
+        .fold(0.0, Seconds::max)
+}
+
+/// Shift this timeline's timed entries from `time` on by `duration`, add the
+/// other timeline's entries shifted to `time`, and take the other keys this
+/// timeline does not have yet.
+fn merge_metadata(
+    metadata: &mut serde_json::Value,
+    other: &serde_json::Value,
+    time: Seconds,
+    duration: Seconds,
+) {
+    let (Some(metadata), Some(other)) = (metadata.as_object_mut(), other.as_object()) else {
+        return;
+    };
+    for key in TIMED_METADATA_KEYS {
+        let inserted = other.get(key).and_then(serde_json::Value::as_array);
+        let Some(entries) = metadata
+            .get_mut(key)
+            .and_then(serde_json::Value::as_array_mut)
+        else {
+            if let Some(inserted) = inserted {
+                let mut entries = inserted.clone();
+                shift_entries(&mut entries, Seconds::NEG_INFINITY, time);
+                metadata.insert(key.to_string(), serde_json::Value::Array(entries));
+            }
+            continue;
+        };
+        shift_entries(entries, time, duration);
+        if let Some(inserted) = inserted {
+            let mut inserted = inserted.clone();
+            shift_entries(&mut inserted, Seconds::NEG_INFINITY, time);
+            entries.extend(inserted);
+            entries.sort_by(|a, b| entry_start(a).total_cmp(&entry_start(b)));
+        }
+    }
+    for (key, value) in other {
+        if key != "tellers.ai" && !metadata.contains_key(key) {
+            metadata.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// Move the entries starting at or after `from` by `delta`. Entries running
+/// across `from` are split there, their second part moved.
+fn shift_entries(entries: &mut Vec<serde_json::Value>, from: Seconds, delta: Seconds) {
+    let mut shifted = Vec::with_capacity(entries.len());
+    for mut entry in entries.drain(..) {
+        let start = entry_start(&entry);
+        let end = entry.get("end").and_then(serde_json::Value::as_f64);
+        if !start.is_finite() {
+            shifted.push(entry);
+            continue;
+        }
+        if start < from - EPS {
+            if let Some(end) = end.filter(|end| *end > from + EPS) {
+                let mut tail = entry.clone();
+                tail["start"] = serde_json::Value::from(from + delta);
+                tail["end"] = serde_json::Value::from(end + delta);
+                entry["end"] = serde_json::Value::from(from);
+                shifted.push(entry);
+                shifted.push(tail);
+            } else {
+                shifted.push(entry);
+            }
+            continue;
+        }
+        entry["start"] = serde_json::Value::from(start + delta);
+        if let Some(end) = end {
+            entry["end"] = serde_json::Value::from(end + delta);
+        }
+        shifted.push(entry);
+    }
+    *entries = shifted;
+}
+
+/// Untimed entries sort last.
+fn entry_start(entry: &serde_json::Value) -> Seconds {
+    entry
+        .get("start")
+        .and_then(serde_json::Value::as_f64)
+        .unwrap_or(Seconds::INFINITY)
+}
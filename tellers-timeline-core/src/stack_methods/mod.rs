@@ -1,10 +1,15 @@
-use crate::metadata::{item_link_group_id, item_tellers_group_id, resolve_tellers_group_id};
+use crate::metadata::{
+    item_link_group_id, item_sync_offsets, item_tellers_group_ids, item_tellers_root_group_id,
+    resolve_tellers_root_group_id,
+};
 use crate::{
     Clip, Gap, IdMetadataExt, InsertPolicy, Item, OverlapPolicy, Seconds, Stack, Track, TrackKind,
     TrackInsertResult,
 };
 use std::collections::{HashMap, HashSet};
 
+mod stack_clipboard;
+mod stack_index;
 mod stack_insert_propagate;
 mod stack_item_delete;
 mod stack_item_get;
@@ -13,9 +18,25 @@ mod stack_item_link;
 mod stack_item_move;
 mod stack_item_replace;
 mod stack_item_split;
+mod stack_lock;
+mod stack_preview;
+mod stack_split_edit;
+mod stack_sync_drift;
+mod stack_sync_lock;
 mod stack_track;
-
+mod stack_transaction;
+
+pub use stack_clipboard::{Clipboard, ClipboardItem};
+pub use stack_index::{StackIndex, StackTimes, TrackTimes};
+pub use stack_lock::EditOutcome;
+pub use stack_preview::EditPreview;
+pub use stack_split_edit::SyncEdge;
+pub use stack_sync_drift::{SyncGroupDrift, SyncMemberDrift};
+pub use stack_transaction::{
+    OpenTransactions, PendingTransaction, StackTransaction, TransactionError,
+};
 use stack_item_split::SyncSplitIdPolicy;
+use stack_sync_lock::Ripple;
 
 const EPS: Seconds = 1e-9;
 
@@ -146,7 +167,7 @@ impl Stack {
         }
     }
 
-    fn next_sync_clips_id(&self) -> i64 {
+    pub(crate) fn next_sync_clips_id(&self) -> i64 {
         self.children
             .iter()
             .flat_map(|track| track.items.iter())
@@ -159,14 +180,11 @@ impl Stack {
             + 1
     }
 
-    fn next_tellers_group_id(&self) -> i64 {
+    pub(crate) fn next_tellers_group_id(&self) -> i64 {
         self.children
             .iter()
             .flat_map(|track| track.items.iter())
-            .filter_map(|item| match item {
-                Item::Clip(clip) => resolve_tellers_group_id(&clip.metadata),
-                Item::Gap(_) => None,
-            })
+            .flat_map(item_tellers_group_ids)
             .max()
             .unwrap_or(0)
             + 1
@@ -256,15 +274,11 @@ impl Stack {
         item: Item,
         overlap_policy: OverlapPolicy,
         insert_policy: InsertPolicy,
-    ) {
+    ) -> bool {
         if overlap_policy == OverlapPolicy::Push {
-            let _ = self.children[track_index].insert_at_time(
-                insert_time,
-                item,
-                overlap_policy,
-                insert_policy,
-            );
-            return;
+            return self.children[track_index]
+                .insert_at_time(insert_time, item, overlap_policy, insert_policy)
+                .success;
         }
 
         let prefer_cluster_with_video = self.children.get(track_index).is_some_and(|track| {
@@ -296,6 +310,7 @@ impl Stack {
                 None,
             );
         }
+        primary_result.success
     }
 
     fn find_or_create_move_audio_track(
@@ -591,16 +606,18 @@ impl Stack {
         }
     }
 
-    /// Like [`delete_item_targets`], but expands to the whole Tellers group when
-    /// the item belongs to one (including each member's sync partners). Used only
+    /// Like [`delete_item_targets`], but expands to the whole outermost Tellers
+    /// group when the item belongs to one (including each member's sync
+    /// partners). Used only
     /// by the public delete entry points; the internal synced-move path keeps
     /// using the sync-only [`delete_item_targets`].
     fn delete_item_group_targets(&self, item_id: &str) -> Option<Vec<(usize, usize)>> {
         let mut targets = self.delete_item_targets(item_id)?;
-        let (_, _, item) = self.get_item(item_id)?;
+        let index = self.build_index();
+        let (_, _, item) = index.get_item(item_id)?;
         if let Item::Clip(clip) = item {
-            if let Some(group_id) = resolve_tellers_group_id(&clip.metadata) {
-                for member in self.tellers_group_targets(group_id) {
+            if let Some(group_id) = resolve_tellers_root_group_id(&clip.metadata) {
+                for &member in index.tellers_group_members(group_id) {
                     targets.push(member);
                     if let Some(Item::Clip(member_clip)) = self
                         .children
@@ -608,7 +625,7 @@ impl Stack {
                         .and_then(|track| track.items.get(member.1))
                     {
                         if let Some(sync_id) = resolve_sync_clips_id(&member_clip.metadata) {
-                            targets.extend(self.synced_clips_targets(sync_id));
+                            targets.extend_from_slice(index.sync_members(sync_id));
                         }
                     }
                 }
@@ -623,7 +640,7 @@ impl Stack {
         };
         let removed = self.delete_clips_at_indices(targets, true);
         if !removed.is_empty() {
-            self.sanitize();
+            self.sanitize_after_edit();
         }
         removed
     }
@@ -651,9 +668,10 @@ impl Stack {
         sync_groups_behind: &HashSet<i64>,
     ) -> HashSet<usize> {
         let mut tracks: HashSet<usize> = clips_to_delete.iter().map(|(ti, _)| *ti).collect();
+        let index = self.build_index();
         for sync_id in sync_groups_behind {
-            for (ti, _) in self.synced_clips_targets(*sync_id) {
-                tracks.insert(ti);
+            for (ti, _) in index.sync_members(*sync_id) {
+                tracks.insert(*ti);
             }
         }
         tracks
@@ -667,7 +685,7 @@ impl Stack {
         if matches!(item, Item::Gap(_)) {
             let removed = self.delete_clips_at_indices(vec![(track_index, item_index)], false);
             if !removed.is_empty() {
-                self.sanitize();
+                self.sanitize_after_edit();
             }
             return removed;
         }
@@ -679,14 +697,25 @@ impl Stack {
             return Vec::new();
         }
 
-        let column_start = self.children[track_index].start_time_of_item(item_index);
-        let column_end = column_start + item.duration().max(0.0);
+        let item_start = self.children[track_index].start_time_of_item(item_index);
+        let offsets = item_sync_offsets(item);
+        let column_start = item_start - offsets.in_offset;
+        let column_end = item_start + item.duration().max(0.0) - offsets.out_offset;
+        let split_edit = item_link_group_id(item)
+            .is_some_and(|sync_clips_id| self.sync_group_has_offsets(sync_clips_id));
         let sync_groups_behind = self.sync_groups_behind_clips(&clips_to_delete);
         let tracks_to_mutate =
             self.collapse_mutation_tracks(&clips_to_delete, &sync_groups_behind);
 
-        let mut removed = self.delete_clips_at_indices(clips_to_delete, false);
-        let mutated_tracks: HashSet<usize> = removed.iter().map(|(ti, _)| *ti).collect();
+        // With split edits the clips do not span the column: lift them out
+        // and collapse the column itself on every track, so that lead-ins and
+        // tails leave gaps rather than shifting their tracks apart.
+        let mut removed = self.delete_clips_at_indices(clips_to_delete, split_edit);
+        let mutated_tracks: HashSet<usize> = if split_edit {
+            HashSet::new()
+        } else {
+            removed.iter().map(|(ti, _)| *ti).collect()
+        };
 
         let mut pending_range_tracks: Vec<_> = tracks_to_mutate
             .into_iter()
@@ -699,13 +728,17 @@ impl Stack {
             let Some(track) = self.children.get_mut(ti) else {
                 continue;
             };
-            for removed_item in track.delete_range(column_start, column_end, false) {
+            let removed_items = track.delete_range(column_start, column_end, false);
+            if split_edit && removed.iter().any(|(removed_ti, _)| *removed_ti == ti) {
+                continue;
+            }
+            for removed_item in removed_items {
                 removed.push((ti, removed_item));
             }
         }
 
         if !removed.is_empty() {
-            self.sanitize();
+            self.sanitize_after_edit();
         }
         removed
     }
@@ -737,23 +770,10 @@ impl Stack {
         targets
     }
 
-    fn tellers_group_targets(&self, group_id: i64) -> Vec<(usize, usize)> {
-        let mut targets = Vec::new();
-        for (ti, track) in self.children.iter().enumerate() {
-            for (ii, item) in track.items.iter().enumerate() {
-                if let Item::Clip(clip) = item {
-                    if resolve_tellers_group_id(&clip.metadata) == Some(group_id) {
-                        targets.push((ti, ii));
-                    }
-                }
-            }
-        }
-        targets
-    }
-
     /// Build the ordered list of moves for a grouped move of `item_id` to
     /// `dest_track_id` / `dest_time`. Returns `None` when the selected clip is
-    /// not part of a Tellers group.
+    /// not part of a Tellers group. The whole outermost group moves, with every
+    /// group nested in it.
     ///
     /// Each entry is `(representative_item_id, dest_track_id, dest_time)`. A
     /// sub-unit is one sync column (shared Link Group ID) or a single unsynced
@@ -771,13 +791,15 @@ impl Stack {
         dest_track_id: &str,
         dest_time: Seconds,
     ) -> Option<Vec<(String, String, Seconds)>> {
-        let (selected_track_index, selected_item_index, selected_item) = self.get_item(item_id)?;
-        let group_id = item_tellers_group_id(selected_item)?;
+        // Every group member needs its start time; index once instead of
+        // summing durations from the track start for each of them.
+        let index = self.build_index();
+        let (selected_track_index, selected_item_index, selected_item) = index.get_item(item_id)?;
+        let group_id = item_tellers_root_group_id(selected_item)?;
         let Item::Clip(selected_clip) = selected_item else {
             return None;
         };
-        let selected_start =
-            self.children[selected_track_index].start_time_of_item(selected_item_index);
+        let selected_start = index.start_time_of_item(selected_track_index, selected_item_index)?;
         let delta = dest_time - selected_start;
 
         let selected_key = match resolve_sync_clips_id(&selected_clip.metadata) {
@@ -795,7 +817,7 @@ impl Stack {
         )];
         let mut seen_keys = HashSet::new();
         seen_keys.insert(selected_key);
-        for (track_index, item_index) in self.tellers_group_targets(group_id) {
+        for &(track_index, item_index) in index.tellers_group_members(group_id) {
             let Some(Item::Clip(clip)) = self
                 .children
                 .get(track_index)
@@ -816,7 +838,9 @@ impl Stack {
             let Some(track_id) = self.children[track_index].get_id() else {
                 continue;
             };
-            let rep_start = self.children[track_index].start_time_of_item(item_index);
+            let Some(rep_start) = index.start_time_of_item(track_index, item_index) else {
+                continue;
+            };
             moves.push((rep_id, track_id, rep_start + delta, rep_start));
         }
 
@@ -938,7 +962,11 @@ impl Stack {
     fn synced_clip_states(&self) -> HashMap<String, SyncedClipState> {
         let mut states = HashMap::new();
         for (track_index, track) in self.children.iter().enumerate() {
-            for (item_index, item) in track.items.iter().enumerate() {
+            let mut start: Seconds = 0.0;
+            for item in &track.items {
+                let item_start = start;
+                let duration = item.duration().max(0.0);
+                start += duration;
                 let Item::Clip(clip) = item else {
                     continue;
                 };
@@ -952,8 +980,8 @@ impl Stack {
                     id,
                     SyncedClipState {
                         track_index,
-                        start: track.start_time_of_item(item_index),
-                        duration: item.duration().max(0.0),
+                        start: item_start,
+                        duration,
                         sync_clips_id,
                     },
                 );
@@ -963,27 +991,30 @@ impl Stack {
     }
 
     fn cleanup_singleton_sync_clips(&mut self, sync_clips_ids: &[i64]) -> usize {
-        let mut count = 0;
+        // One pass for every group: unsyncing a selection can touch many.
+        let index = self.build_index();
         let mut seen = HashSet::new();
+        let mut singletons = Vec::new();
         for sync_clips_id in sync_clips_ids {
             if !seen.insert(*sync_clips_id) {
                 continue;
             }
-            let targets = self.synced_clips_targets(*sync_clips_id);
-            if targets.len() > 1 {
-                continue;
+            if let [target] = index.sync_members(*sync_clips_id) {
+                singletons.push(*target);
             }
-            for (track_index, item_index) in targets {
-                let Some(Item::Clip(clip)) = self
-                    .children
-                    .get_mut(track_index)
-                    .and_then(|track| track.items.get_mut(item_index))
-                else {
-                    continue;
-                };
-                if remove_resolve_sync_clips_id(&mut clip.metadata) {
-                    count += 1;
-                }
+        }
+
+        let mut count = 0;
+        for (track_index, item_index) in singletons {
+            let Some(Item::Clip(clip)) = self
+                .children
+                .get_mut(track_index)
+                .and_then(|track| track.items.get_mut(item_index))
+            else {
+                continue;
+            };
+            if remove_resolve_sync_clips_id(&mut clip.metadata) {
+                count += 1;
             }
         }
         count
@@ -1000,7 +1031,7 @@ impl Stack {
         }
     }
 
-    fn new_numbered_track(&self, kind: TrackKind) -> Track {
+    pub(crate) fn new_numbered_track(&self, kind: TrackKind) -> Track {
         let prefix = match kind {
             TrackKind::Audio => "A",
             TrackKind::Video => "V",
@@ -1547,85 +1578,75 @@ impl Stack {
     ) -> bool {
         let mut synced_clips = HashSet::new();
         let mut changed_groups = Vec::new();
+        let index = self.build_index();
         for (id, before) in before_states {
             if excluded_ids.contains(id) || !modified_track_indices.contains(&before.track_index) {
                 continue;
             }
-            let Some((track_index, item_index, item)) = self.get_item(id) else {
+            let Some((track_index, item_index, item)) = index.get_item(id) else {
+                continue;
+            };
+            let Some(start) = index.start_time_of_item(track_index, item_index) else {
                 continue;
             };
-            let start = self.children[track_index].start_time_of_item(item_index);
             let duration = item.duration().max(0.0);
             if ((start - before.start).abs() > EPS || (duration - before.duration).abs() > EPS)
                 && synced_clips.insert(before.sync_clips_id)
             {
-                changed_groups.push((before.sync_clips_id, start - before.start, duration));
+                changed_groups.push((
+                    before.sync_clips_id,
+                    start - before.start,
+                    duration,
+                    item_sync_offsets(item).extra_duration(),
+                ));
             }
         }
 
-        for (sync_clips_id, start_delta, duration) in changed_groups {
-            if !self.shift_sync_clips_by_delta(
-                sync_clips_id,
-                before_states,
-                modified_track_indices,
-                start_delta,
-                duration,
-                overlap_policy,
-            ) {
-                return false;
+        // Every partner lands at an absolute time, so all of them are lifted
+        // out first and then placed in start order, rather than rescanning the
+        // stack once per group.
+        let mut moves = Vec::new();
+        {
+            let index = self.build_index();
+            for (sync_clips_id, start_delta, duration, changed_extra) in changed_groups {
+                for &(track_index, item_index) in index.sync_members(sync_clips_id) {
+                    let item = &self.children[track_index].items[item_index];
+                    let Some(id) = item.get_id() else {
+                        return false;
+                    };
+                    let Some(before) = before_states.get(&id) else {
+                        continue;
+                    };
+                    if modified_track_indices.contains(&before.track_index) {
+                        continue;
+                    }
+                    let mut item = item.clone();
+                    item.set_duration(sync_partner_duration(&item, changed_extra, duration));
+                    moves.push((
+                        (track_index, item_index),
+                        before.track_index,
+                        before.start + start_delta,
+                        item,
+                    ));
+                }
             }
         }
-        true
-    }
 
-    fn shift_sync_clips_by_delta(
-        &mut self,
-        sync_clips_id: i64,
-        before_states: &HashMap<String, SyncedClipState>,
-        modified_track_indices: &[usize],
-        start_delta: Seconds,
-        duration: Seconds,
-        overlap_policy: OverlapPolicy,
-    ) -> bool {
-        let mut items = Vec::new();
-        for (track_index, item_index) in self.synced_clips_targets(sync_clips_id) {
-            let Some(item) = self
-                .children
-                .get(track_index)
-                .and_then(|track| track.items.get(item_index))
-                .cloned()
-            else {
-                return false;
-            };
-            let Some(id) = item.get_id() else {
-                return false;
-            };
-            let Some(before) = before_states.get(&id) else {
-                continue;
-            };
-            if modified_track_indices.contains(&before.track_index) {
-                continue;
-            }
-            let mut item = item;
-            item.set_duration(duration);
-            items.push((id, before.track_index, before.start + start_delta, item));
+        let mut removals: Vec<_> = moves.iter().map(|(position, ..)| *position).collect();
+        removals.sort_unstable_by(|a, b| b.cmp(a));
+        removals.dedup();
+        let mut lifted_tracks = Vec::new();
+        for (track_index, item_index) in removals {
+            self.children[track_index].items.remove(item_index);
+            lifted_tracks.push(track_index);
         }
-
-        for (id, _, _, _) in &items {
-            let Some((track_index, item_index, _)) = self.get_item(id) else {
-                return false;
-            };
-            let Some(track) = self.children.get_mut(track_index) else {
-                return false;
-            };
-            if item_index >= track.items.len() {
-                return false;
-            }
-            track.items.remove(item_index);
-            track.sanitize_preserving_all_gap_track();
+        lifted_tracks.dedup();
+        for track_index in lifted_tracks {
+            self.children[track_index].sanitize_preserving_all_gap_track();
         }
 
-        for (_, track_index, start, item) in items {
+        moves.sort_by(|a, b| a.2.total_cmp(&b.2));
+        for (_, track_index, start, item) in moves {
             let Some(track) = self.children.get_mut(track_index) else {
                 return false;
             };
@@ -1634,6 +1655,8 @@ impl Stack {
         true
     }
 
+    /// Insert `item` (and its synced partners) as one column. Leaves the stack
+    /// untouched when the insert fails.
     fn insert_synced_item_at_time(
         &mut self,
         dest_track_index: usize,
@@ -1648,6 +1671,46 @@ impl Stack {
         preferred_audio_track_indices: Option<&[usize]>,
         move_source_track_indices: Option<&[usize]>,
         move_previous_start: Option<Seconds>,
+    ) -> Option<InsertItemAtTimeResult> {
+        let backup = self.clone();
+        let result = self.place_synced_item_at_time(
+            dest_track_index,
+            dest_time,
+            dest_index,
+            item,
+            overlap_policy,
+            insert_policy,
+            synced_audio_clips,
+            synced_video_clip,
+            preferred_video_track_id,
+            preferred_audio_track_indices,
+            move_source_track_indices,
+            move_previous_start,
+        );
+        if result.is_none() {
+            *self = backup;
+        }
+        result
+    }
+
+    /// `insert_synced_item_at_time` without the rollback: on `None` the stack
+    /// may be half edited. For moves, which already hold a backup of the
+    /// state before the source clips were lifted out.
+    #[allow(clippy::too_many_arguments)]
+    fn place_synced_item_at_time(
+        &mut self,
+        dest_track_index: usize,
+        dest_time: Seconds,
+        dest_index: Option<usize>,
+        item: Item,
+        overlap_policy: OverlapPolicy,
+        insert_policy: InsertPolicy,
+        synced_audio_clips: Option<Vec<Item>>,
+        synced_video_clip: Option<Item>,
+        preferred_video_track_id: Option<&str>,
+        preferred_audio_track_indices: Option<&[usize]>,
+        move_source_track_indices: Option<&[usize]>,
+        move_previous_start: Option<Seconds>,
     ) -> Option<InsertItemAtTimeResult> {
         let mut primary_item = item;
         primary_item.clamp_to_active_available_range();
@@ -1684,7 +1747,6 @@ impl Stack {
             )?
         };
 
-        let backup = self.clone();
         let mut used_ids = self.collect_timeline_ids();
         let sync_clips_id = has_synced_clips.then(|| self.next_sync_clips_id());
         let mut created_track_indices = Vec::new();
@@ -1850,10 +1912,7 @@ impl Stack {
                         insert_policy,
                     )
                 });
-            let Some(video_track_index) = video_track_index else {
-                *self = backup;
-                return None;
-            };
+            let video_track_index = video_track_index?;
             if self.children.len() > track_count_before {
                 Self::shift_insert_track_indices_after_create(
                     video_track_index,
@@ -1863,14 +1922,11 @@ impl Stack {
                     &mut created_track_indices,
                 );
             }
-            let Some((video_item, video_id)) = Self::prepare_synced_item_preserve_duration(
+            let (video_item, video_id) = Self::prepare_synced_item_preserve_duration(
                 video_item,
                 sync_clips_id,
                 &mut used_ids,
-            ) else {
-                *self = backup;
-                return None;
-            };
+            )?;
             synced_video_clip_id = Some(video_id);
             column_video = Some((video_track_index, video_item));
         }
@@ -1900,14 +1956,11 @@ impl Stack {
         // Audio clips onto the nearest cluster audio tracks.
         let mut audio_clips = Vec::new();
         for (audio_item, &audio_track_index) in synced_inputs.audio.into_iter().zip(&audio_slots) {
-            let Some((audio_item, audio_id)) = Self::prepare_synced_item_preserve_duration(
+            let (audio_item, audio_id) = Self::prepare_synced_item_preserve_duration(
                 audio_item,
                 sync_clips_id,
                 &mut used_ids,
-            ) else {
-                *self = backup;
-                return None;
-            };
+            )?;
             column.push((audio_track_index, audio_item));
             audio_clips.push((audio_id, audio_track_index));
         }
@@ -1938,7 +1991,6 @@ impl Stack {
                 InsertPolicy::SplitAndInsert,
             );
             if !result.success {
-                *self = backup;
                 return None;
             }
             insert_updates.push((track_index, result));
@@ -1956,7 +2008,6 @@ impl Stack {
             &cluster,
             sync_clips_id,
         ) {
-            *self = backup;
             return None;
         }
         if overlap_policy == OverlapPolicy::Push
@@ -1969,7 +2020,6 @@ impl Stack {
                 move_previous_start,
             )
         {
-            *self = backup;
             return None;
         }
 
@@ -1990,6 +2040,9 @@ impl Stack {
         }
     }
 
+    /// Resize an item to start at `new_start_time` for `new_duration`.
+    /// Moving only its end with `OverlapPolicy::Push`, or shortening it,
+    /// ripples the track and every sync-locked track by the same duration.
     pub fn resize_item(
         &mut self,
         item_id: &str,
@@ -1997,6 +2050,40 @@ impl Stack {
         new_duration: Seconds,
         overlap_policy: OverlapPolicy,
         clamp_to_media: bool,
+    ) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            let old_span = stack.item_span(item_id);
+            stack.rippling_sync_locked_tracks(
+                |stack| {
+                    stack.resize_item_ignoring_locks(
+                        item_id,
+                        new_start_time,
+                        new_duration,
+                        overlap_policy,
+                        clamp_to_media,
+                    )
+                },
+                |stack, _| {
+                    let (old_start, old_duration) = old_span?;
+                    let (start, duration) = stack.item_span(item_id)?;
+                    let rippled = (start - old_start).abs() <= EPS
+                        && (overlap_policy == OverlapPolicy::Push || duration < old_duration);
+                    rippled.then_some(Ripple {
+                        at: start + duration.min(old_duration),
+                        delta: duration - old_duration,
+                    })
+                },
+            )
+        })
+    }
+
+    fn resize_item_ignoring_locks(
+        &mut self,
+        item_id: &str,
+        new_start_time: Seconds,
+        new_duration: Seconds,
+        overlap_policy: OverlapPolicy,
+        clamp_to_media: bool,
     ) -> bool {
         let Some((selected_track_index, selected_item_index, selected_item)) =
             self.get_item(item_id)
@@ -2050,12 +2137,16 @@ impl Stack {
         let selected_start =
             self.children[selected_track_index].start_time_of_item(selected_item_index);
         let start_delta = new_start_time - selected_start;
+        let selected_extra = item_sync_offsets(selected_item).extra_duration();
 
+        // In selected-clip terms: partners with split edits are longer or
+        // shorter by their own offsets.
         let effective_duration = target_ids
             .iter()
             .filter_map(|id| {
                 self.get_item(id).map(|(_, _, item)| {
-                    resize_effective_duration(item, new_duration, clamp_to_media)
+                    let extra = item_sync_offsets(item).extra_duration() - selected_extra;
+                    resize_effective_duration(item, new_duration + extra, clamp_to_media) - extra
                 })
             })
             .fold(new_duration.max(0.0), Seconds::min);
@@ -2079,23 +2170,19 @@ impl Stack {
 
                     let old_start = self.children[track_index].start_time_of_item(item_index);
                     let old_duration = item.duration().max(0.0);
-                    if effective_duration <= old_duration + EPS {
+                    let duration = sync_partner_duration(item, selected_extra, effective_duration);
+                    if duration <= old_duration + EPS {
                         resized_items.clear();
                         modified_track_indices.clear();
                         break;
                     }
 
                     let mut item = item.clone();
-                    item.set_duration(effective_duration);
+                    item.set_duration(duration);
                     if clamp_to_media {
                         item.clamp_to_active_available_range();
                     }
-                    resized_items.push((
-                        track_index,
-                        old_start,
-                        old_start + effective_duration,
-                        item,
-                    ));
+                    resized_items.push((track_index, old_start, old_start + duration, item));
                     modified_track_indices.push(track_index);
                 }
 
@@ -2137,7 +2224,7 @@ impl Stack {
                     *self = backup;
                     return false;
                 };
-                item.set_duration(effective_duration);
+                item.set_duration(sync_partner_duration(item, selected_extra, effective_duration));
                 if clamp_to_media {
                     item.clamp_to_active_available_range();
                 }
@@ -2175,7 +2262,7 @@ impl Stack {
 
                 modified_track_indices.push(track_index);
                 let mut item = item.clone();
-                item.set_duration(effective_duration);
+                item.set_duration(sync_partner_duration(&item, selected_extra, effective_duration));
                 if clamp_to_media {
                     item.clamp_to_active_available_range();
                 }
@@ -2245,7 +2332,7 @@ impl Stack {
             let target_start =
                 self.children[track_index].start_time_of_item(item_index) + start_delta;
             let mut item = item.clone();
-            item.set_duration(effective_duration);
+            item.set_duration(sync_partner_duration(&item, selected_extra, effective_duration));
             if clamp_to_media {
                 item.clamp_to_active_available_range();
             }
@@ -2304,6 +2391,27 @@ impl Stack {
         clamp_to_media: bool,
         resize_from_start: bool,
         push_following: bool,
+    ) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            stack.modify_item_ignoring_locks(
+                item_id,
+                source_start_time,
+                duration,
+                clamp_to_media,
+                resize_from_start,
+                push_following,
+            )
+        })
+    }
+
+    fn modify_item_ignoring_locks(
+        &mut self,
+        item_id: &str,
+        source_start_time: Seconds,
+        duration: Seconds,
+        clamp_to_media: bool,
+        resize_from_start: bool,
+        push_following: bool,
     ) -> bool {
         let Some((track_index, item_index, _)) = self.get_item(item_id) else {
             return false;
@@ -2455,6 +2563,27 @@ impl Stack {
         new_duration: Seconds,
         overlap_policy: OverlapPolicy,
         clamp_to_media: bool,
+    ) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            stack.resize_item_with_source_start_ignoring_locks(
+                item_id,
+                new_start_time,
+                source_start_time,
+                new_duration,
+                overlap_policy,
+                clamp_to_media,
+            )
+        })
+    }
+
+    fn resize_item_with_source_start_ignoring_locks(
+        &mut self,
+        item_id: &str,
+        new_start_time: Seconds,
+        source_start_time: Seconds,
+        new_duration: Seconds,
+        overlap_policy: OverlapPolicy,
+        clamp_to_media: bool,
     ) -> bool {
         let Some((_, _, item)) = self.get_item(item_id) else {
             return false;
@@ -3010,7 +3139,7 @@ impl Stack {
         let has_synced_partners =
             !linked_audio_items.is_empty() || linked_video_item.is_some();
         let insert_result = if !has_synced_partners {
-            self.insert_synced_item_at_time(
+            self.place_synced_item_at_time(
                 dest_track_index,
                 dest_time,
                 None,
@@ -3025,7 +3154,7 @@ impl Stack {
                 Some(primary_start_time),
             )
         } else {
-            self.insert_synced_item_at_time(
+            self.place_synced_item_at_time(
                 dest_track_index,
                 dest_time,
                 None,
@@ -3070,7 +3199,7 @@ impl Stack {
             return false;
         }
 
-        self.sanitize();
+        self.sanitize_after_edit();
         true
     }
 
@@ -3214,7 +3343,7 @@ impl Stack {
         let gap_only_exclude = (!gap_only_audio_tracks.is_empty())
             .then_some(gap_only_audio_tracks.as_slice());
         if self
-            .insert_synced_item_at_time(
+            .place_synced_item_at_time(
                 dest_track_index,
                 dest_time,
                 None,
@@ -3332,7 +3461,7 @@ impl Stack {
 
         let synced_audio_clips = (!synced_audio.is_empty()).then_some(synced_audio);
         if self
-            .insert_synced_item_at_time(
+            .place_synced_item_at_time(
                 dest_track_index,
                 dest_time,
                 None,
@@ -3352,7 +3481,7 @@ impl Stack {
             if !replace_with_gap {
                 self.remove_gaps_by_id(&placeholder_gap_ids);
             }
-            self.sanitize();
+            self.sanitize_after_edit();
             true
         } else {
             *self = backup;
@@ -3728,6 +3857,13 @@ fn clamp_clip_to_active_available_range(clip: &mut Clip) {
     clip.clamp_to_active_available_range();
 }
 
+/// The duration of a synced clip when the clip of its group that is
+/// `extra` longer than their column (see [`SyncOffsets`](crate::SyncOffsets))
+/// lasts `duration`.
+fn sync_partner_duration(item: &Item, extra: Seconds, duration: Seconds) -> Seconds {
+    (duration - extra + item_sync_offsets(item).extra_duration()).max(0.0)
+}
+
 fn resize_effective_duration(
     item: &Item,
     requested_duration: Seconds,
@@ -3765,7 +3901,7 @@ fn set_item_source_start(item: &mut Item, source_start_time: Seconds) {
     }
 }
 
-fn replace_track_range_with_item(
+pub(crate) fn replace_track_range_with_item(
     track: &mut Track,
     range_start: Seconds,
     range_end: Seconds,
@@ -3838,7 +3974,7 @@ enum SubUnitKey {
     Clip(String),
 }
 
-pub(super) fn set_resolve_sync_clips_id(metadata: &mut serde_json::Value, sync_clips_id: i64) {
+pub(crate) fn set_resolve_sync_clips_id(metadata: &mut serde_json::Value, sync_clips_id: i64) {
     if metadata.as_object().is_none() {
         *metadata = serde_json::Value::Object(serde_json::Map::new());
     }
@@ -3855,7 +3991,18 @@ pub(super) fn set_resolve_sync_clips_id(metadata: &mut serde_json::Value, sync_c
     );
 }
 
-fn remove_resolve_sync_clips_id(metadata: &mut serde_json::Value) -> bool {
+/// The group copies of clips from group `old` join, allocating the next id
+/// the first time `old` is seen.
+pub(crate) fn fresh_group(ids: &mut HashMap<i64, i64>, next: &mut i64, old: i64) -> i64 {
+    *ids.entry(old).or_insert_with(|| {
+        *next += 1;
+        *next - 1
+    })
+}
+
+pub(crate) fn remove_resolve_sync_clips_id(metadata: &mut serde_json::Value) -> bool {
+    // Split-edit offsets are relative to the group; they go with it.
+    crate::metadata::remove_sync_offsets(metadata);
     let Some(resolve) = metadata
         .get_mut("Resolve_OTIO")
         .and_then(|value| value.as_object_mut())
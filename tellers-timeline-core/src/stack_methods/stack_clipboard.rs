@@ -0,0 +1,284 @@
+//! Copy and paste of item selections.
+//!
+//! [`Stack::copy_selection`] captures clips with their place relative to the
+//! selection; [`Stack::paste_selection`] puts a copy of them back anywhere,
+//! with fresh timeline ids and fresh link and Tellers groups so the pasted
+//! clips never join the groups they were copied from.
+
+use super::stack_sync_lock::Ripple;
+use super::{fresh_group, replace_track_range_with_item, EPS};
+use crate::metadata::{
+    item_tellers_group_ids, remove_tellers_group_level, resolve_tellers_group_id,
+    resolve_tellers_parent_group_ids, set_tellers_group_id, set_tellers_parent_group_ids,
+};
+use crate::{Gap, IdMetadataExt, Item, MetadataExt, OverlapPolicy, Seconds, Stack, TrackKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Clips copied by [`Stack::copy_selection`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Clipboard {
+    /// In track then time order.
+    pub items: Vec<ClipboardItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardItem {
+    /// Tracks below the first copied track.
+    pub track_offset: usize,
+    /// Kind of the track the clip was copied from, for tracks a paste creates.
+    pub track_kind: TrackKind,
+    /// Start after the earliest copied clip.
+    pub start: Seconds,
+    pub item: Item,
+}
+
+impl Clipboard {
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// From the start of the earliest clip to the end of the latest.
+    pub fn duration(&self) -> Seconds {
+        self.items
+            .iter()
+            .map(|entry| entry.start + entry.item.duration().max(0.0))
+            .fold(0.0, Seconds::max)
+    }
+}
+
+impl Stack {
+    /// Copy the clips `item_ids` and their sync partners. Gaps and unknown ids
+    /// are skipped. Tellers groups only some of whose clips are copied are
+    /// left out of the clipboard.
+    pub fn copy_selection(&self, item_ids: &[String]) -> Clipboard {
+        let index = self.build_index();
+        let mut targets = Vec::new();
+        let mut seen_targets = HashSet::new();
+        for item_id in item_ids {
+            let Some((track_index, item_index, Item::Clip(clip))) = index.get_item(item_id) else {
+                continue;
+            };
+            let partners = clip
+                .sync_clips_id()
+                .map(|sync_clips_id| index.sync_members(sync_clips_id))
+                .unwrap_or_default();
+            for &target in std::iter::once(&(track_index, item_index)).chain(partners) {
+                if seen_targets.insert(target) {
+                    targets.push(target);
+                }
+            }
+        }
+        targets.sort_unstable();
+
+        let starts: Vec<Seconds> = targets
+            .iter()
+            .map(|&(ti, ii)| index.start_time_of_item(ti, ii).unwrap_or_default())
+            .collect();
+        let (Some(first_track), Some(first_start)) = (
+            targets.first().map(|&(ti, _)| ti),
+            starts.iter().copied().reduce(Seconds::min),
+        ) else {
+            return Clipboard::default();
+        };
+
+        let mut items: Vec<ClipboardItem> = targets
+            .iter()
+            .zip(starts)
+            .map(|(&(ti, ii), start)| ClipboardItem {
+                track_offset: ti - first_track,
+                track_kind: self.children[ti].kind.clone(),
+                start: start - first_start,
+                item: self.children[ti].items[ii].clone(),
+            })
+            .collect();
+
+        let mut copied_group_sizes: HashMap<i64, usize> = HashMap::new();
+        for entry in &items {
+            for group_id in item_tellers_group_ids(&entry.item) {
+                *copied_group_sizes.entry(group_id).or_default() += 1;
+            }
+        }
+        let index = self.build_index();
+        for entry in &mut items {
+            for group_id in item_tellers_group_ids(&entry.item) {
+                if copied_group_sizes[&group_id] < index.tellers_group_members(group_id).len() {
+                    remove_tellers_group_level(entry.item.get_metadata_mut(), group_id);
+                }
+            }
+        }
+        Clipboard { items }
+    }
+
+    /// Paste `clipboard` so that its earliest clip starts at `dest_time` and
+    /// its first track lands on `dest_track_index`. Clips landing past the
+    /// last track go to new numbered tracks, after the last track of their
+    /// kind; clips landing on a track of another kind fail the whole paste.
+    /// `Override` overwrites what the clips land on, `Push` first opens room
+    /// for the whole clipboard on the tracks it pastes to (and on sync-locked
+    /// tracks). Every clip gets a fresh timeline id, and each copied link
+    /// group or Tellers group becomes a new group. Returns the ids of the
+    /// pasted clips in clipboard order, or `None` when nothing was pasted.
+    pub fn paste_selection(
+        &mut self,
+        clipboard: &Clipboard,
+        dest_time: Seconds,
+        dest_track_index: usize,
+        overlap_policy: OverlapPolicy,
+    ) -> Option<Vec<String>> {
+        self.refusing_locked_track_changes(|stack| {
+            stack.rippling_sync_locked_tracks(
+                |stack| {
+                    stack.paste_selection_ignoring_locks(
+                        clipboard,
+                        dest_time,
+                        dest_track_index,
+                        overlap_policy,
+                    )
+                },
+                |_, pasted| {
+                    (pasted.is_some() && overlap_policy == OverlapPolicy::Push).then_some(Ripple {
+                        at: dest_time,
+                        delta: clipboard.duration(),
+                    })
+                },
+            )
+        })
+    }
+
+    fn paste_selection_ignoring_locks(
+        &mut self,
+        clipboard: &Clipboard,
+        dest_time: Seconds,
+        dest_track_index: usize,
+        overlap_policy: OverlapPolicy,
+    ) -> Option<Vec<String>> {
+        if clipboard.is_empty()
+            || !dest_time.is_finite()
+            || dest_time < -EPS
+            || dest_track_index > self.children.len()
+        {
+            return None;
+        }
+        let dest_time = dest_time.max(0.0);
+        let track_indices = self.paste_track_indices(clipboard, dest_track_index)?;
+
+        let mut used_ids: HashSet<String> = self
+            .children
+            .iter()
+            .flat_map(|track| {
+                std::iter::once(track.get_id()).chain(track.items.iter().map(IdMetadataExt::get_id))
+            })
+            .flatten()
+            .collect();
+        let mut next_sync_clips_id = self.next_sync_clips_id();
+        let mut next_tellers_group_id = self.next_tellers_group_id();
+        let mut sync_clips_ids: HashMap<i64, i64> = HashMap::new();
+        let mut tellers_group_ids: HashMap<i64, i64> = HashMap::new();
+        let mut pasted = Vec::with_capacity(clipboard.len());
+        for entry in &clipboard.items {
+            let mut item = entry.item.clone();
+            let item_id = loop {
+                let id = crate::types::gen_hex_id_12();
+                if used_ids.insert(id.clone()) {
+                    break id;
+                }
+            };
+            item.set_id(Some(item_id.clone()));
+            if let Item::Clip(clip) = &mut item {
+                if let Some(old) = clip.sync_clips_id() {
+                    let new = fresh_group(&mut sync_clips_ids, &mut next_sync_clips_id, old);
+                    super::set_resolve_sync_clips_id(&mut clip.metadata, new);
+                }
+                if let Some(old) = resolve_tellers_group_id(&clip.metadata) {
+                    let new = fresh_group(&mut tellers_group_ids, &mut next_tellers_group_id, old);
+                    set_tellers_group_id(&mut clip.metadata, new);
+                    let parent_ids: Vec<i64> = resolve_tellers_parent_group_ids(&clip.metadata)
+                        .into_iter()
+                        .map(|old| {
+                            fresh_group(&mut tellers_group_ids, &mut next_tellers_group_id, old)
+                        })
+                        .collect();
+                    set_tellers_parent_group_ids(&mut clip.metadata, &parent_ids);
+                }
+            }
+            pasted.push((track_indices[&entry.track_offset], entry, item, item_id));
+        }
+
+        if overlap_policy == OverlapPolicy::Push {
+            let mut pushed: Vec<usize> = pasted.iter().map(|(ti, ..)| *ti).collect();
+            pushed.sort_unstable();
+            pushed.dedup();
+            for track_index in pushed {
+                self.children[track_index].insert_gap_at_time(dest_time, clipboard.duration());
+            }
+        }
+
+        let mut item_ids = Vec::with_capacity(pasted.len());
+        for (track_index, entry, item, item_id) in pasted {
+            let start = dest_time + entry.start;
+            let end = start + item.duration().max(0.0);
+            let track = &mut self.children[track_index];
+            let track_end = track.total_duration();
+            if start > track_end + EPS {
+                track
+                    .items
+                    .push(Item::Gap(Gap::make_gap(start - track_end)));
+            }
+            replace_track_range_with_item(track, start, end, item);
+            item_ids.push(item_id);
+        }
+        self.sanitize_preserving_all_gap_tracks();
+        Some(item_ids)
+    }
+
+    /// The track each clipboard track offset pastes to. Offsets landing past
+    /// the last track get a new numbered track, placed after the last track of
+    /// their kind. `None`, before anything is created, if an offset lands on a
+    /// track of another kind.
+    fn paste_track_indices(
+        &mut self,
+        clipboard: &Clipboard,
+        dest_track_index: usize,
+    ) -> Option<HashMap<usize, usize>> {
+        let mut track_indices: HashMap<usize, usize> = HashMap::new();
+        let mut missing: Vec<(usize, TrackKind)> = Vec::new();
+        for entry in &clipboard.items {
+            if track_indices.contains_key(&entry.track_offset)
+                || missing
+                    .iter()
+                    .any(|(offset, _)| *offset == entry.track_offset)
+            {
+                continue;
+            }
+            let track_index = dest_track_index + entry.track_offset;
+            match self.children.get(track_index) {
+                Some(track) if track.kind == entry.track_kind => {
+                    track_indices.insert(entry.track_offset, track_index);
+                }
+                Some(_) => return None,
+                None => missing.push((entry.track_offset, entry.track_kind.clone())),
+            }
+        }
+        for (offset, kind) in missing {
+            let insert_at = self
+                .children
+                .iter()
+                .rposition(|track| track.kind == kind)
+                .map_or(self.children.len(), |last| last + 1);
+            let track = self.new_numbered_track(kind);
+            self.children.insert(insert_at, track);
+            for track_index in track_indices.values_mut() {
+                if *track_index >= insert_at {
+                    *track_index += 1;
+                }
+            }
+            track_indices.insert(offset, insert_at);
+        }
+        Some(track_indices)
+    }
+}
@@ -0,0 +1,284 @@
+use crate::metadata::{item_link_group_id, item_tellers_group_ids, item_timeline_id};
+use crate::{Item, ItemInRange, Seconds, Stack, Track};
+use std::cell::OnceCell;
+use std::collections::HashMap;
+
+type Members = HashMap<i64, Vec<(usize, usize)>>;
+
+/// Lookup tables for a [`Stack`], for callers that resolve many ids or times
+/// against the same state.
+///
+/// The plain lookups (`Stack::get_item`, `Track::start_time_of_item`,
+/// `Track::get_item_at_time`, ...) scan from the first track or item on every
+/// call, which adds up when an edit resolves many ids on a timeline with
+/// thousands of clips. The index answers the same questions in constant time
+/// (ids, sync and Tellers group members) or logarithmic time (time → item).
+/// Each table is built by one pass over the stack the first time it is
+/// queried, so an index that only resolves ids never pays for the others.
+///
+/// The index borrows the stack, so the borrow checker guarantees it is never
+/// consulted after an edit; build a new one once the edit is done.
+#[derive(Debug, Clone)]
+pub struct StackIndex<'a> {
+    stack: &'a Stack,
+    positions: OnceCell<HashMap<&'a str, (usize, usize)>>,
+    sync_members: OnceCell<Members>,
+    tellers_group_members: OnceCell<Members>,
+    times: OnceCell<StackTimes>,
+}
+
+impl<'a> StackIndex<'a> {
+    pub fn new(stack: &'a Stack) -> Self {
+        Self {
+            stack,
+            positions: OnceCell::new(),
+            sync_members: OnceCell::new(),
+            tellers_group_members: OnceCell::new(),
+            times: OnceCell::new(),
+        }
+    }
+
+    fn items(&self) -> impl Iterator<Item = (usize, usize, &'a Item)> {
+        self.stack
+            .children
+            .iter()
+            .enumerate()
+            .flat_map(|(ti, track)| {
+                track
+                    .items
+                    .iter()
+                    .enumerate()
+                    .map(move |(ii, it)| (ti, ii, it))
+            })
+    }
+
+    fn positions(&self) -> &HashMap<&'a str, (usize, usize)> {
+        self.positions.get_or_init(|| {
+            let mut positions = HashMap::new();
+            for (ti, ii, item) in self.items() {
+                // Ids are unique after sanitize; otherwise keep the first hit,
+                // like the linear scan does.
+                if let Some(id) = item_timeline_id(item) {
+                    positions.entry(id).or_insert((ti, ii));
+                }
+            }
+            positions
+        })
+    }
+
+    fn members_by<G: IntoIterator<Item = i64>>(&self, groups_of: fn(&Item) -> G) -> Members {
+        let mut members: Members = HashMap::new();
+        for (ti, ii, item) in self.items() {
+            for group_id in groups_of(item) {
+                members.entry(group_id).or_default().push((ti, ii));
+            }
+        }
+        members
+    }
+
+    fn times(&self) -> &StackTimes {
+        self.times.get_or_init(|| StackTimes::new(self.stack))
+    }
+
+    /// The number of items that carry a timeline id.
+    pub fn len(&self) -> usize {
+        self.positions().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions().is_empty()
+    }
+
+    /// Same as `Stack::get_item`: (track_index, item_index, &Item).
+    pub fn get_item(&self, item_id: &str) -> Option<(usize, usize, &'a Item)> {
+        let (ti, ii) = *self.positions().get(item_id)?;
+        Some((ti, ii, &self.stack.children[ti].items[ii]))
+    }
+
+    /// (track_index, item_index) of every clip sharing the Link Group ID, in
+    /// track then item order.
+    pub fn sync_members(&self, sync_clips_id: i64) -> &[(usize, usize)] {
+        self.sync_members
+            .get_or_init(|| self.members_by(item_link_group_id))
+            .get(&sync_clips_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// (track_index, item_index) of every clip in the Tellers group, including
+    /// the groups nested in it, in track then item order.
+    pub fn tellers_group_members(&self, group_id: i64) -> &[(usize, usize)] {
+        self.tellers_group_members
+            .get_or_init(|| self.members_by(item_tellers_group_ids))
+            .get(&group_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Same as `Track::start_time_of_item`; indices past the end give the
+    /// track's total duration. `None` only for an unknown track.
+    pub fn start_time_of_item(&self, track_index: usize, item_index: usize) -> Option<Seconds> {
+        Some(self.times().track(track_index)?.start_time_of_item(item_index))
+    }
+
+    /// Same as `Track::total_duration`.
+    pub fn track_duration(&self, track_index: usize) -> Option<Seconds> {
+        Some(self.times().track(track_index)?.duration())
+    }
+
+    /// Same as `Track::get_item_at_time`, by binary search over start times.
+    pub fn item_at_time(&self, track_index: usize, time: Seconds) -> Option<usize> {
+        self.times().track(track_index)?.item_at_time(time)
+    }
+
+    /// Same as `Track::items_in_range`, but binary-searches for the first
+    /// overlapping item, so the cost is logarithmic plus the number of hits.
+    pub fn track_items_in_range(
+        &self,
+        track_index: usize,
+        start: Seconds,
+        end: Seconds,
+    ) -> Vec<ItemInRange<'a>> {
+        match (
+            self.times().track(track_index),
+            self.stack.children.get(track_index),
+        ) {
+            (Some(times), Some(track)) => times.items_in_range(track, start, end),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Same as `Stack::items_in_range`, using `track_items_in_range` per track.
+    pub fn items_in_range(
+        &self,
+        start: Seconds,
+        end: Seconds,
+        track_filter: Option<&[String]>,
+    ) -> Vec<(usize, ItemInRange<'a>)> {
+        self.times()
+            .items_in_range(self.stack, start, end, track_filter)
+    }
+}
+
+/// Start time of every item on one track, for time lookups by binary search.
+///
+/// Unlike [`StackIndex`] this owns its table, so it can be kept next to a
+/// copy of the track (the Python `StackIndex` does that). It describes the
+/// track it was built from; pass that same track to `items_in_range`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackTimes {
+    /// The start of every item followed by the track's total duration,
+    /// accumulated in the same order as `Track::start_time_of_item`.
+    starts: Vec<Seconds>,
+}
+
+impl TrackTimes {
+    pub fn new(track: &Track) -> Self {
+        let mut starts = Vec::with_capacity(track.items.len() + 1);
+        let mut pos: Seconds = 0.0;
+        for item in &track.items {
+            starts.push(pos);
+            pos += item.duration().max(0.0);
+        }
+        starts.push(pos);
+        Self { starts }
+    }
+
+    /// Same as `Track::start_time_of_item`; indices past the end give the
+    /// track's total duration.
+    pub fn start_time_of_item(&self, item_index: usize) -> Seconds {
+        self.starts[item_index.min(self.starts.len() - 1)]
+    }
+
+    /// Same as `Track::total_duration`.
+    pub fn duration(&self) -> Seconds {
+        self.starts[self.starts.len() - 1]
+    }
+
+    /// Same as `Track::get_item_at_time`.
+    pub fn item_at_time(&self, time: Seconds) -> Option<usize> {
+        let item_count = self.starts.len() - 1;
+        // The last item starting at or before `time`. Zero-length items share
+        // their start with the next item, so they are never picked unless
+        // they end the track, where the `time < end` check rejects them.
+        let index = self.starts[..item_count]
+            .partition_point(|start| *start <= time)
+            .checked_sub(1)?;
+        (time < self.starts[index + 1]).then_some(index)
+    }
+
+    /// Same as `Track::items_in_range` on `track`.
+    pub fn items_in_range<'t>(
+        &self,
+        track: &'t Track,
+        start: Seconds,
+        end: Seconds,
+    ) -> Vec<ItemInRange<'t>> {
+        let starts = &self.starts;
+        // Item `i` ends at `starts[i + 1]`; skip every item ending at or
+        // before the range.
+        let first = starts[1..].partition_point(|item_end| *item_end <= start);
+        let mut hits = Vec::new();
+        for (i, item) in track.items.iter().enumerate().skip(first) {
+            let (Some(&item_start), Some(&item_end)) = (starts.get(i), starts.get(i + 1)) else {
+                break;
+            };
+            if item_start >= end {
+                break;
+            }
+            if item_end > item_start && start < end {
+                hits.push(ItemInRange::clipped(
+                    i, item, item_start, item_end, start, end,
+                ));
+            }
+        }
+        hits
+    }
+}
+
+/// [`TrackTimes`] for every track of a stack, in track order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StackTimes {
+    tracks: Vec<TrackTimes>,
+}
+
+impl StackTimes {
+    pub fn new(stack: &Stack) -> Self {
+        Self {
+            tracks: stack.children.iter().map(TrackTimes::new).collect(),
+        }
+    }
+
+    pub fn track(&self, track_index: usize) -> Option<&TrackTimes> {
+        self.tracks.get(track_index)
+    }
+
+    /// Same as `Stack::items_in_range` on `stack`.
+    pub fn items_in_range<'s>(
+        &self,
+        stack: &'s Stack,
+        start: Seconds,
+        end: Seconds,
+        track_filter: Option<&[String]>,
+    ) -> Vec<(usize, ItemInRange<'s>)> {
+        let mut hits = Vec::new();
+        for ((ti, track), times) in stack.children.iter().enumerate().zip(&self.tracks) {
+            if super::stack_item_get::track_matches_filter(track, track_filter) {
+                hits.extend(
+                    times
+                        .items_in_range(track, start, end)
+                        .into_iter()
+                        .map(|hit| (ti, hit)),
+                );
+            }
+        }
+        hits
+    }
+}
+
+impl Stack {
+    /// Build a [`StackIndex`] for repeated lookups against this stack.
+    pub fn build_index(&self) -> StackIndex<'_> {
+        StackIndex::new(self)
+    }
+}
@@ -2,7 +2,7 @@ use crate::{
     Gap, InsertPolicy, Item, OverlapPolicy, Seconds, SplitClipInfo, Stack,
     TrackInsertResult,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use super::{resolve_sync_clips_id, set_resolve_sync_clips_id, EPS};
 
@@ -242,17 +242,28 @@ impl Stack {
             }
         }
 
+        // Each partner track gets one pass per sync group member it holds. A
+        // pass only reads its own track, and one that inserts nothing changes
+        // nothing, so the passes for a track run back to back and stop at the
+        // first that does nothing, instead of rescanning per group.
+        let mut partner_passes: BTreeMap<usize, usize> = BTreeMap::new();
+        let index = self.build_index();
         for sync_id in sync_groups {
-            for (track_index, _) in self.synced_clips_targets(sync_id) {
-                if !cluster_set.contains(&track_index) || updated_tracks.contains(&track_index) {
-                    continue;
-                }
+            for (track_index, _) in index.sync_members(sync_id) {
+                *partner_passes.entry(*track_index).or_default() += 1;
+            }
+        }
+        for (track_index, passes) in partner_passes {
+            if !cluster_set.contains(&track_index) || updated_tracks.contains(&track_index) {
+                continue;
+            }
+            for _ in 0..passes {
                 let Some(partner_sync_start) = self.first_cluster_sync_clip_start_at_or_after(
                     track_index,
                     insert_start,
                     cluster,
                 ) else {
-                    continue;
+                    break;
                 };
                 let (gap_at, gap_duration) = if partner_sync_start <= insert_start + EPS {
                     (insert_start, aligned_start - insert_start)
@@ -260,12 +271,12 @@ impl Stack {
                     (insert_start, aligned_start - partner_sync_start)
                 };
                 if gap_duration <= EPS {
-                    continue;
+                    break;
                 }
                 if partner_sync_start <= insert_start + EPS
                     && self.track_has_spacer_at(track_index, gap_at, gap_duration)
                 {
-                    continue;
+                    break;
                 }
                 let mut gap = Item::Gap(Gap::make_gap(gap_duration));
                 Self::ensure_unique_item_id(&mut gap, &mut used_ids);
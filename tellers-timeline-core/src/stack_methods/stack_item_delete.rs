@@ -1,16 +1,22 @@
+use super::stack_sync_lock::Ripple;
 use crate::{Item, Stack};
 
 impl Stack {
     /// Delete an item by id. Synced clips in the same link group are deleted
     /// together. When `replace_with_gap` is true, each removed clip is replaced
     /// with a gap of the same duration. When false, the column is collapsed
-    /// across the sync track cluster.
+    /// across the sync track cluster and every sync-locked track.
     /// Returns removed items with their source track indices.
     pub fn delete_item(&mut self, item_id: &str, replace_with_gap: bool) -> Vec<(usize, Item)> {
-        if replace_with_gap {
-            self.delete_item_replace_with_gap(item_id)
-        } else {
-            self.delete_item_collapse(item_id)
-        }
+        self.refusing_locked_track_changes(|stack| {
+            if replace_with_gap {
+                return stack.delete_item_replace_with_gap(item_id);
+            }
+            let span = stack.item_span(item_id);
+            stack.rippling_sync_locked_tracks(
+                |stack| stack.delete_item_collapse(item_id),
+                |_, _| span.map(|(at, duration)| Ripple { at, delta: -duration }),
+            )
+        })
     }
 }
@@ -1,7 +1,10 @@
-use crate::{Item, Stack};
+use crate::{IdMetadataExt, Item, ItemInRange, Seconds, Stack, Track};
 
 impl Stack {
     /// Find an item by id across all tracks. Returns (track_index, item_index, &Item).
+    ///
+    /// Scans the tracks in order; code resolving many ids against one state
+    /// should go through [`Stack::build_index`].
     pub fn get_item(&self, item_id: &str) -> Option<(usize, usize, &Item)> {
         for (ti, track) in self.children.iter().enumerate() {
             if let Some((ii, item)) = track.get_item_by_id(item_id) {
@@ -10,4 +13,31 @@ impl Stack {
         }
         None
     }
+
+    /// Every item overlapping `start..end` on the tracks whose ids are in
+    /// `track_filter` (all tracks when `None`), as (track_index, hit) in track
+    /// then item order. See `Track::items_in_range`.
+    pub fn items_in_range(
+        &self,
+        start: Seconds,
+        end: Seconds,
+        track_filter: Option<&[String]>,
+    ) -> Vec<(usize, ItemInRange<'_>)> {
+        let mut hits = Vec::new();
+        for (ti, track) in self.children.iter().enumerate() {
+            if track_matches_filter(track, track_filter) {
+                hits.extend(
+                    track
+                        .items_in_range(start, end)
+                        .into_iter()
+                        .map(|hit| (ti, hit)),
+                );
+            }
+        }
+        hits
+    }
+}
+
+pub(super) fn track_matches_filter(track: &Track, track_filter: Option<&[String]>) -> bool {
+    track_filter.is_none_or(|ids| track.get_id().is_some_and(|id| ids.contains(&id)))
 }
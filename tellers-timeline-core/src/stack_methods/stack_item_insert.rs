@@ -3,7 +3,8 @@ use crate::{InsertPolicy, Item, OverlapPolicy, Seconds, Stack};
 
 impl Stack {
     /// Insert an item at a given time into the track at `dest_track_index`.
-    /// Returns the inserted item's id if insertion occurred.
+    /// With `OverlapPolicy::Push`, sync-locked tracks are pushed by the same
+    /// duration. Returns the inserted item's id if insertion occurred.
     pub fn insert_item_at_time(
         &mut self,
         dest_track_index: usize,
@@ -17,24 +18,36 @@ impl Stack {
         if dest_track_index >= self.children.len() {
             return None;
         }
-        self.insert_synced_item_at_time(
-            dest_track_index,
-            dest_time,
-            None,
-            item,
-            overlap_policy,
-            insert_policy,
-            synced_audio_clips,
-            synced_video_clip,
-            None::<&str>,
-            None::<&[usize]>,
-            None::<&[usize]>,
-            None,
-        )
+        self.refusing_locked_track_changes(|stack| {
+            stack.rippling_sync_locked_tracks(
+                |stack| {
+                    stack.insert_synced_item_at_time(
+                        dest_track_index,
+                        dest_time,
+                        None,
+                        item,
+                        overlap_policy,
+                        insert_policy,
+                        synced_audio_clips,
+                        synced_video_clip,
+                        None::<&str>,
+                        None::<&[usize]>,
+                        None::<&[usize]>,
+                        None,
+                    )
+                },
+                |stack, result| {
+                    (overlap_policy == OverlapPolicy::Push)
+                        .then(|| stack.inserted_ripple(result))
+                        .flatten()
+                },
+            )
+        })
     }
 
     /// Insert an item at an index into the track with `dest_track_id`.
-    /// Returns the inserted item's id if insertion occurred.
+    /// With `OverlapPolicy::Push`, sync-locked tracks are pushed by the same
+    /// duration. Returns the inserted item's id if insertion occurred.
     pub fn insert_item_at_index(
         &mut self,
         dest_track_id: &str,
@@ -51,19 +64,30 @@ impl Stack {
         if dest_track_index >= self.children.len() {
             return None;
         }
-        self.insert_synced_item_at_time(
-            dest_track_index,
-            0.0,
-            Some(dest_index),
-            item,
-            overlap_policy,
-            InsertPolicy::InsertBefore,
-            synced_audio_clips,
-            synced_video_clip,
-            None::<&str>,
-            None::<&[usize]>,
-            None::<&[usize]>,
-            None,
-        )
+        self.refusing_locked_track_changes(|stack| {
+            stack.rippling_sync_locked_tracks(
+                |stack| {
+                    stack.insert_synced_item_at_time(
+                        dest_track_index,
+                        0.0,
+                        Some(dest_index),
+                        item,
+                        overlap_policy,
+                        InsertPolicy::InsertBefore,
+                        synced_audio_clips,
+                        synced_video_clip,
+                        None::<&str>,
+                        None::<&[usize]>,
+                        None::<&[usize]>,
+                        None,
+                    )
+                },
+                |stack, result| {
+                    (overlap_policy == OverlapPolicy::Push)
+                        .then(|| stack.inserted_ripple(result))
+                        .flatten()
+                },
+            )
+        })
     }
 }
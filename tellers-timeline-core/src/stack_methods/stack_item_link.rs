@@ -3,6 +3,10 @@ use std::collections::HashSet;
 
 impl Stack {
     pub fn unsync_item(&mut self, item_ids: &[String]) -> usize {
+        self.refusing_locked_track_changes(|stack| stack.unsync_item_ignoring_locks(item_ids))
+    }
+
+    fn unsync_item_ignoring_locks(&mut self, item_ids: &[String]) -> usize {
         let mut targets = Vec::new();
         let mut seen_targets = HashSet::new();
         let mut touched_sync_clips = Vec::new();
@@ -41,30 +45,52 @@ impl Stack {
 
     /// Group the given clips together under a fresh Tellers group id. Each
     /// clip's sync partners (Resolve "Link Group ID") are pulled into the group
-    /// as well, so a group always contains whole sync columns. Any prior group
-    /// membership of the selected clips is replaced. Returns the new group id,
-    /// or `None` when fewer than two clips would be grouped.
+    /// as well, so a group always contains whole sync columns. Clips that are
+    /// already grouped bring their outermost group, which nests in the new one
+    /// (see [`Stack::tellers_group_parent`]). Returns the new group id, or
+    /// `None` when fewer than two clips or a single existing group would be
+    /// grouped.
     pub fn group_item(&mut self, item_ids: &[String]) -> Option<i64> {
+        self.refusing_locked_track_changes(|stack| stack.group_item_ignoring_locks(item_ids))
+    }
+
+    fn group_item_ignoring_locks(&mut self, item_ids: &[String]) -> Option<i64> {
         let mut targets = Vec::new();
         let mut seen_targets = HashSet::new();
+        let mut root_group_ids = HashSet::new();
+        let mut ungrouped = 0;
+        let index = self.build_index();
         for item_id in item_ids {
-            let Some(target) = self.clip_target(item_id) else {
+            let Some((track_index, item_index, Item::Clip(clip))) = index.get_item(item_id) else {
                 continue;
             };
-            if seen_targets.insert(target) {
-                targets.push(target);
+            let mut members = vec![(track_index, item_index)];
+            if let Some(sync_clips_id) = super::resolve_sync_clips_id(&clip.metadata) {
+                members.extend_from_slice(index.sync_members(sync_clips_id));
             }
-            if let Item::Clip(clip) = &self.children[target.0].items[target.1] {
-                if let Some(sync_clips_id) = super::resolve_sync_clips_id(&clip.metadata) {
-                    for partner in self.synced_clips_targets(sync_clips_id) {
-                        if seen_targets.insert(partner) {
-                            targets.push(partner);
+            for (ti, ii) in members {
+                if !seen_targets.insert((ti, ii)) {
+                    continue;
+                }
+                let Item::Clip(member) = &self.children[ti].items[ii] else {
+                    continue;
+                };
+                let Some(root_group_id) = crate::resolve_tellers_root_group_id(&member.metadata)
+                else {
+                    ungrouped += 1;
+                    targets.push((ti, ii));
+                    continue;
+                };
+                if root_group_ids.insert(root_group_id) {
+                    for &grouped in index.tellers_group_members(root_group_id) {
+                        if grouped == (ti, ii) || seen_targets.insert(grouped) {
+                            targets.push(grouped);
                         }
                     }
                 }
             }
         }
-        if targets.len() < 2 {
+        if targets.len() < 2 || (ungrouped == 0 && root_group_ids.len() < 2) {
             return None;
         }
 
@@ -77,47 +103,77 @@ impl Stack {
             else {
                 continue;
             };
-            crate::set_tellers_group_id(&mut clip.metadata, group_id);
+            if crate::resolve_tellers_group_id(&clip.metadata).is_some() {
+                let mut parent_ids = crate::resolve_tellers_parent_group_ids(&clip.metadata);
+                parent_ids.push(group_id);
+                crate::set_tellers_parent_group_ids(&mut clip.metadata, &parent_ids);
+            } else {
+                crate::set_tellers_group_id(&mut clip.metadata, group_id);
+            }
         }
         Some(group_id)
     }
 
-    /// Ungroup the whole Tellers group(s) that the given clips belong to. The
-    /// group id is removed from every member, not just the clips passed in.
+    /// Ungroup the outermost Tellers group(s) that the given clips belong to.
+    /// Only that level is dissolved: the groups nested in it become outermost
+    /// and keep their members, while clips directly in it lose their group id.
     /// Sync (Link Group ID) membership is left untouched. Returns the number of
-    /// clips that had a group id removed.
+    /// clips that left a group.
     pub fn ungroup_item(&mut self, item_ids: &[String]) -> usize {
+        self.refusing_locked_track_changes(|stack| stack.ungroup_item_ignoring_locks(item_ids))
+    }
+
+    fn ungroup_item_ignoring_locks(&mut self, item_ids: &[String]) -> usize {
         let mut group_ids = HashSet::new();
+        let mut targets = Vec::new();
+        let index = self.build_index();
         for item_id in item_ids {
-            let Some((track_index, item_index)) = self.clip_target(item_id) else {
+            let Some((_, _, Item::Clip(clip))) = index.get_item(item_id) else {
                 continue;
             };
-            if let Item::Clip(clip) = &self.children[track_index].items[item_index] {
-                if let Some(group_id) = crate::resolve_tellers_group_id(&clip.metadata) {
-                    group_ids.insert(group_id);
+            if let Some(group_id) = crate::resolve_tellers_root_group_id(&clip.metadata) {
+                if group_ids.insert(group_id) {
+                    targets.extend(
+                        index
+                            .tellers_group_members(group_id)
+                            .iter()
+                            .map(|&member| (member, group_id)),
+                    );
                 }
             }
         }
 
         let mut count = 0;
-        for group_id in group_ids {
-            for (track_index, item_index) in self.tellers_group_targets(group_id) {
-                let Some(Item::Clip(clip)) = self
-                    .children
-                    .get_mut(track_index)
-                    .and_then(|track| track.items.get_mut(item_index))
-                else {
-                    continue;
-                };
-                if crate::remove_tellers_group_id(&mut clip.metadata) {
-                    count += 1;
-                }
+        for ((track_index, item_index), group_id) in targets {
+            let Some(Item::Clip(clip)) = self
+                .children
+                .get_mut(track_index)
+                .and_then(|track| track.items.get_mut(item_index))
+            else {
+                continue;
+            };
+            if crate::metadata::remove_tellers_group_level(&mut clip.metadata, group_id) {
+                count += 1;
             }
         }
         count
     }
 
+    /// The Tellers group `group_id` is nested in, or `None` for an outermost
+    /// group or an unknown id.
+    pub fn tellers_group_parent(&self, group_id: i64) -> Option<i64> {
+        let index = self.build_index();
+        let &(track_index, item_index) = index.tellers_group_members(group_id).first()?;
+        let groups = crate::item_tellers_group_ids(&self.children[track_index].items[item_index]);
+        let position = groups.iter().position(|&id| id == group_id)?;
+        groups.get(position + 1).copied()
+    }
+
     pub fn sync_item(&mut self, item_ids: &[String]) -> Option<i64> {
+        self.refusing_locked_track_changes(|stack| stack.sync_item_ignoring_locks(item_ids))
+    }
+
+    fn sync_item_ignoring_locks(&mut self, item_ids: &[String]) -> Option<i64> {
         let mut targets = Vec::new();
         let mut seen_targets = HashSet::new();
         for item_id in item_ids {
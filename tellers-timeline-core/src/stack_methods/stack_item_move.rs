@@ -18,6 +18,27 @@ impl Stack {
         replace_with_gap: bool,
         insert_policy: InsertPolicy,
         overlap_policy: OverlapPolicy,
+    ) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            stack.move_item_at_time_ignoring_locks(
+                item_id,
+                dest_track_id,
+                dest_time,
+                replace_with_gap,
+                insert_policy,
+                overlap_policy,
+            )
+        })
+    }
+
+    fn move_item_at_time_ignoring_locks(
+        &mut self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_time: Seconds,
+        replace_with_gap: bool,
+        insert_policy: InsertPolicy,
+        overlap_policy: OverlapPolicy,
     ) -> bool {
         if let Some(plan) = self.tellers_group_move_plan(item_id, dest_track_id, dest_time) {
             let backup = self.clone();
@@ -63,6 +84,20 @@ impl Stack {
         insert_policy: InsertPolicy,
         overlap_policy: OverlapPolicy,
     ) -> bool {
+        if let Some(sync_clips_id) = self.offset_sync_group_of(item_id) {
+            let Some((dest_track_index, _)) = self.get_track_by_id(dest_track_id) else {
+                return false;
+            };
+            return self.move_offset_sync_group_at_time(
+                item_id,
+                sync_clips_id,
+                dest_track_index,
+                dest_time,
+                replace_with_gap,
+                insert_policy,
+                overlap_policy,
+            );
+        }
         if let Some(items_to_move) = self.synced_move_items(item_id) {
             let dest_track_index = match self.get_track_by_id(dest_track_id) {
                 Some((index, _)) => index,
@@ -97,6 +132,25 @@ impl Stack {
         dest_index: usize,
         replace_with_gap: bool,
         overlap_policy: OverlapPolicy,
+    ) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            stack.move_item_at_index_ignoring_locks(
+                item_id,
+                dest_track_id,
+                dest_index,
+                replace_with_gap,
+                overlap_policy,
+            )
+        })
+    }
+
+    fn move_item_at_index_ignoring_locks(
+        &mut self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_index: usize,
+        replace_with_gap: bool,
+        overlap_policy: OverlapPolicy,
     ) -> bool {
         let item_to_move = match self.get_item(item_id) {
             Some((_ti, _ii, it)) => it.clone(),
@@ -133,7 +187,7 @@ impl Stack {
             )
             .is_some()
         {
-            self.sanitize();
+            self.sanitize_after_edit();
             true
         } else {
             *self = backup;
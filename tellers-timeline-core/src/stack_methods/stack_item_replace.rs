@@ -6,6 +6,17 @@ impl Stack {
         item_id: &str,
         item: Item,
         synced_audio_clips: Option<Vec<Item>>,
+    ) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            stack.replace_item_ignoring_locks(item_id, item, synced_audio_clips)
+        })
+    }
+
+    fn replace_item_ignoring_locks(
+        &mut self,
+        item_id: &str,
+        item: Item,
+        synced_audio_clips: Option<Vec<Item>>,
     ) -> bool {
         let Some((track_index, item_index, existing)) = self.get_item(item_id) else {
             return false;
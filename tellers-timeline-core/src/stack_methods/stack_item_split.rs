@@ -10,6 +10,12 @@ pub(super) enum SyncSplitIdPolicy {
 
 impl Stack {
     pub fn split_item_at_time(&mut self, item_id: &str, split_time: Seconds) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            stack.split_item_at_time_ignoring_locks(item_id, split_time)
+        })
+    }
+
+    fn split_item_at_time_ignoring_locks(&mut self, item_id: &str, split_time: Seconds) -> bool {
         let Some((selected_track_index, selected_item_index, selected_item)) =
             self.get_item(item_id)
         else {
@@ -40,14 +46,14 @@ impl Stack {
                     true,
                 );
                 if ok {
-                    self.sanitize();
+                    self.sanitize_after_edit();
                 }
                 return ok;
             }
         }
 
         self.children[selected_track_index].split_at_time(split_time);
-        self.sanitize();
+        self.sanitize_after_edit();
         true
     }
 
@@ -112,6 +118,9 @@ impl Stack {
         id_policy: SyncSplitIdPolicy,
         require_all_targets: bool,
     ) -> bool {
+        if self.sync_group_has_offsets(sync_clips_id) {
+            return self.split_offset_sync_group_at_time(sync_clips_id, split_time);
+        }
         let targets = self.synced_clips_targets(sync_clips_id);
         if targets.len() <= 1 {
             return true;
@@ -0,0 +1,92 @@
+use crate::{IdMetadataExt, Stack, Track};
+
+/// Return value of an edit, as far as telling success from failure goes:
+/// `false`, `None`, an empty list and a zero count are failures.
+pub trait EditOutcome {
+    fn succeeded(&self) -> bool;
+    fn failed() -> Self;
+}
+
+impl EditOutcome for bool {
+    fn succeeded(&self) -> bool {
+        *self
+    }
+    fn failed() -> Self {
+        false
+    }
+}
+
+impl<T> EditOutcome for Option<T> {
+    fn succeeded(&self) -> bool {
+        self.is_some()
+    }
+    fn failed() -> Self {
+        None
+    }
+}
+
+impl<T> EditOutcome for Vec<T> {
+    fn succeeded(&self) -> bool {
+        !self.is_empty()
+    }
+    fn failed() -> Self {
+        Vec::new()
+    }
+}
+
+impl EditOutcome for usize {
+    fn succeeded(&self) -> bool {
+        *self > 0
+    }
+    fn failed() -> Self {
+        0
+    }
+}
+
+impl Stack {
+    /// Run `edit`, undoing it and reporting failure if it changed a locked
+    /// track. Sync propagation goes through the same edit, so a synced clip
+    /// on a locked track is immovable and takes its partners with it.
+    ///
+    /// Tracks are compared after sanitizing, so the normalization every edit
+    /// applies (merged gaps, trailing gap) does not count as a change.
+    pub(crate) fn refusing_locked_track_changes<R: EditOutcome>(
+        &mut self,
+        edit: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        if !self.children.iter().any(Track::is_locked) {
+            return edit(self);
+        }
+        let backup = self.clone();
+        // The edit's sanitize pass would give id-less tracks an id; do it
+        // first so that locked tracks can be found again afterwards.
+        self.ensure_unique_timeline_ids();
+        let locked: Vec<(Option<String>, Track)> = self
+            .children
+            .iter()
+            .filter(|track| track.is_locked())
+            .map(|track| (track.get_id(), normalized(track)))
+            .collect();
+
+        let outcome = edit(self);
+        let untouched = locked.iter().all(|(id, before)| {
+            id.as_deref()
+                .and_then(|id| self.get_track_by_id(id))
+                .is_some_and(|(_, after)| normalized(after) == *before)
+        });
+        if !outcome.succeeded() || !untouched {
+            *self = backup;
+        }
+        if untouched {
+            outcome
+        } else {
+            R::failed()
+        }
+    }
+}
+
+pub(super) fn normalized(track: &Track) -> Track {
+    let mut track = track.clone();
+    track.sanitize();
+    track
+}
@@ -0,0 +1,360 @@
+//! Dry runs of `Stack` edits.
+//!
+//! A preview applies the edit to a copy of the stack and compares the clips
+//! before and after, so it reports what the edit really does, sync
+//! propagation and overlap handling included, without keeping any of it.
+
+use super::{EditOutcome, InsertItemAtTimeResult};
+use crate::{
+    Clip, DeletedClipInfo, IdMetadataExt, InsertPolicy, Item, MediaReference, OverlapPolicy,
+    Seconds, SplitClipInfo, Stack, TrackInsertResult,
+};
+use std::collections::{HashMap, HashSet};
+
+const EPS: Seconds = super::EPS;
+
+/// What an edit would do to the stack, as reported by the `preview_*`
+/// methods. Ids refer to clips of the stack the preview was taken on.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EditPreview {
+    /// Whether the edit would succeed, and the clips it would delete and
+    /// split. A split whose left piece is overwritten has no `left_clip_id`.
+    pub result: TrackInsertResult,
+    /// Clips that would keep their id but get shorter or longer.
+    pub trimmed_clip_ids: Vec<String>,
+    /// Clips that would move (in time or to another track) without being
+    /// trimmed, e.g. pushed along by an insert.
+    pub pushed_clip_ids: Vec<String>,
+    /// Sync partners of the edited item that would move or change with it.
+    pub moved_sync_partner_ids: Vec<String>,
+    /// Indices, in the edited stack, of the tracks the edit would create.
+    pub created_track_indices: Vec<usize>,
+}
+
+/// Where a clip sits in a stack.
+struct ClipSpan<'a> {
+    track_id: Option<String>,
+    start: Seconds,
+    duration: Seconds,
+    source_start: Seconds,
+    media: Option<&'a MediaReference>,
+    sync_clips_id: Option<i64>,
+}
+
+impl ClipSpan<'_> {
+    fn same_place(&self, other: &ClipSpan<'_>) -> bool {
+        self.track_id == other.track_id && (self.start - other.start).abs() <= EPS
+    }
+
+    fn same_length(&self, other: &ClipSpan<'_>) -> bool {
+        (self.duration - other.duration).abs() <= EPS
+            && (self.source_start - other.source_start).abs() <= EPS
+    }
+
+    /// Whether `piece` plays a part of the media this clip played.
+    fn contains_source_of(&self, piece: &ClipSpan<'_>) -> bool {
+        self.track_id == piece.track_id
+            && self.media.is_some()
+            && self.media == piece.media
+            && piece.source_start >= self.source_start - EPS
+            && piece.source_start + piece.duration <= self.source_start + self.duration + EPS
+    }
+}
+
+fn clip_spans(stack: &Stack) -> Vec<(String, ClipSpan<'_>)> {
+    let mut spans = Vec::new();
+    for track in &stack.children {
+        let mut start = 0.0;
+        for item in &track.items {
+            if let (Item::Clip(clip), Some(id)) = (item, item.get_id()) {
+                spans.push((id, clip_span(clip, track.get_id(), start)));
+            }
+            start += item.duration().max(0.0);
+        }
+    }
+    spans
+}
+
+fn clip_span(clip: &Clip, track_id: Option<String>, start: Seconds) -> ClipSpan<'_> {
+    ClipSpan {
+        track_id,
+        start,
+        duration: clip.source_range.duration.to_seconds().max(0.0),
+        source_start: clip.source_range.start_time.to_seconds(),
+        media: clip.active_media_reference(),
+        sync_clips_id: clip.sync_clips_id(),
+    }
+}
+
+fn inserted_clip_ids(result: &Option<InsertItemAtTimeResult>) -> HashSet<String> {
+    match result {
+        Some(InsertItemAtTimeResult::ItemId(id)) => HashSet::from([id.clone()]),
+        Some(InsertItemAtTimeResult::Synced(synced)) => {
+            let mut ids: HashSet<String> = synced
+                .audio_clips
+                .iter()
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.insert(synced.primary_clip_id.clone());
+            ids.extend(synced.synced_video_clip_id.clone());
+            ids
+        }
+        None => HashSet::new(),
+    }
+}
+
+impl Stack {
+    /// Run `edit` on a copy of the stack and report the difference.
+    /// `item_id` is the edited item, whose sync partners are reported apart;
+    /// `inserted` names the clips the edit adds, which are not split pieces.
+    fn preview_edit<R: EditOutcome>(
+        &self,
+        item_id: Option<&str>,
+        edit: impl FnOnce(&mut Stack) -> R,
+        inserted: impl FnOnce(&R) -> HashSet<String>,
+    ) -> EditPreview {
+        let mut before = self.clone();
+        // Edits give id-less tracks and clips an id; do it up front so that
+        // they can be matched afterwards.
+        before.ensure_unique_timeline_ids();
+        let mut after = before.clone();
+        let outcome = edit(&mut after);
+        if !outcome.succeeded() {
+            return EditPreview::default();
+        }
+        let inserted = inserted(&outcome);
+
+        let old_spans = clip_spans(&before);
+        let new_spans: HashMap<String, ClipSpan<'_>> = clip_spans(&after).into_iter().collect();
+        let sync_group = item_id
+            .and_then(|id| old_spans.iter().find(|(old_id, _)| old_id == id))
+            .and_then(|(_, span)| span.sync_clips_id);
+
+        let mut preview = EditPreview {
+            result: TrackInsertResult {
+                success: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let old_ids: HashSet<&String> = old_spans.iter().map(|(id, _)| id).collect();
+        let changed = |id: &String, old: &ClipSpan<'_>| {
+            new_spans.get(id).is_none_or(|new| !old.same_length(new))
+        };
+        // Each new clip the edit did not insert is a piece of the changed
+        // clip it was cut from: the one playing the same media whose timing
+        // it continues most closely.
+        let mut pieces_by_parent: HashMap<&String, Vec<(&String, &ClipSpan<'_>)>> = HashMap::new();
+        for (piece_id, piece) in &new_spans {
+            if old_ids.contains(piece_id) || inserted.contains(piece_id) {
+                continue;
+            }
+            let drift = |old: &ClipSpan<'_>| {
+                ((piece.start - old.start) - (piece.source_start - old.source_start)).abs()
+            };
+            let parent = old_spans
+                .iter()
+                .filter(|(id, old)| changed(id, old) && old.contains_source_of(piece))
+                .min_by(|a, b| drift(&a.1).total_cmp(&drift(&b.1)));
+            if let Some((parent_id, _)) = parent {
+                pieces_by_parent
+                    .entry(parent_id)
+                    .or_default()
+                    .push((piece_id, piece));
+            }
+        }
+
+        for (id, old) in &old_spans {
+            let mut pieces = pieces_by_parent.remove(id).unwrap_or_default();
+            pieces.sort_by(|a, b| a.1.source_start.total_cmp(&b.1.source_start));
+            let kept = new_spans.get(id);
+            for (piece_id, piece) in &pieces {
+                preview.result.split_clips.push(SplitClipInfo {
+                    old_clip_id: id.clone(),
+                    left_clip_id: kept.map(|_| id.clone()),
+                    right_clip_id: Some((*piece_id).clone()),
+                    sync_clips_id: old.sync_clips_id,
+                    split_time: old.start + piece.source_start - old.source_start,
+                });
+            }
+
+            let Some(new) = kept else {
+                preview.result.deleted_clips.push(DeletedClipInfo {
+                    clip_id: id.clone(),
+                    sync_clips_id: old.sync_clips_id,
+                });
+                continue;
+            };
+            if Some(id.as_str()) == item_id || (old.same_place(new) && old.same_length(new)) {
+                continue;
+            }
+            if sync_group.is_some() && old.sync_clips_id == sync_group {
+                preview.moved_sync_partner_ids.push(id.clone());
+            } else if !old.same_length(new) {
+                if pieces.is_empty() {
+                    preview.trimmed_clip_ids.push(id.clone());
+                }
+            } else {
+                preview.pushed_clip_ids.push(id.clone());
+            }
+        }
+
+        let old_tracks: HashSet<Option<String>> =
+            before.children.iter().map(|track| track.get_id()).collect();
+        preview.created_track_indices = after
+            .children
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| !old_tracks.contains(&track.get_id()))
+            .map(|(index, _)| index)
+            .collect();
+        preview
+    }
+
+    /// What [`Stack::insert_item_at_time`] would do, without doing it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn preview_insert_item_at_time(
+        &self,
+        dest_track_index: usize,
+        dest_time: Seconds,
+        item: Item,
+        overlap_policy: OverlapPolicy,
+        insert_policy: InsertPolicy,
+        synced_audio_clips: Option<Vec<Item>>,
+        synced_video_clip: Option<Item>,
+    ) -> EditPreview {
+        self.preview_edit(
+            None,
+            |stack| {
+                stack.insert_item_at_time(
+                    dest_track_index,
+                    dest_time,
+                    item,
+                    overlap_policy,
+                    insert_policy,
+                    synced_audio_clips,
+                    synced_video_clip,
+                )
+            },
+            inserted_clip_ids,
+        )
+    }
+
+    /// What [`Stack::insert_item_at_index`] would do, without doing it.
+    pub fn preview_insert_item_at_index(
+        &self,
+        dest_track_id: &str,
+        dest_index: usize,
+        item: Item,
+        overlap_policy: OverlapPolicy,
+        synced_audio_clips: Option<Vec<Item>>,
+        synced_video_clip: Option<Item>,
+    ) -> EditPreview {
+        self.preview_edit(
+            None,
+            |stack| {
+                stack.insert_item_at_index(
+                    dest_track_id,
+                    dest_index,
+                    item,
+                    overlap_policy,
+                    synced_audio_clips,
+                    synced_video_clip,
+                )
+            },
+            inserted_clip_ids,
+        )
+    }
+
+    /// What [`Stack::move_item_at_time`] would do, without doing it.
+    pub fn preview_move_item_at_time(
+        &self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_time: Seconds,
+        replace_with_gap: bool,
+        insert_policy: InsertPolicy,
+        overlap_policy: OverlapPolicy,
+    ) -> EditPreview {
+        self.preview_edit(
+            Some(item_id),
+            |stack| {
+                stack.move_item_at_time(
+                    item_id,
+                    dest_track_id,
+                    dest_time,
+                    replace_with_gap,
+                    insert_policy,
+                    overlap_policy,
+                )
+            },
+            |_| HashSet::new(),
+        )
+    }
+
+    /// What [`Stack::move_item_at_index`] would do, without doing it.
+    pub fn preview_move_item_at_index(
+        &self,
+        item_id: &str,
+        dest_track_id: &str,
+        dest_index: usize,
+        replace_with_gap: bool,
+        overlap_policy: OverlapPolicy,
+    ) -> EditPreview {
+        self.preview_edit(
+            Some(item_id),
+            |stack| {
+                stack.move_item_at_index(
+                    item_id,
+                    dest_track_id,
+                    dest_index,
+                    replace_with_gap,
+                    overlap_policy,
+                )
+            },
+            |_| HashSet::new(),
+        )
+    }
+
+    /// What [`Stack::resize_item`] would do, without doing it.
+    pub fn preview_resize_item(
+        &self,
+        item_id: &str,
+        new_start_time: Seconds,
+        new_duration: Seconds,
+        overlap_policy: OverlapPolicy,
+        clamp_to_media: bool,
+    ) -> EditPreview {
+        self.preview_edit(
+            Some(item_id),
+            |stack| {
+                stack.resize_item(
+                    item_id,
+                    new_start_time,
+                    new_duration,
+                    overlap_policy,
+                    clamp_to_media,
+                )
+            },
+            |_| HashSet::new(),
+        )
+    }
+
+    /// What [`Stack::split_item_at_time`] would do, without doing it.
+    pub fn preview_split_item_at_time(&self, item_id: &str, split_time: Seconds) -> EditPreview {
+        self.preview_edit(
+            Some(item_id),
+            |stack| stack.split_item_at_time(item_id, split_time),
+            |_| HashSet::new(),
+        )
+    }
+
+    /// What [`Stack::delete_item`] would do, without doing it.
+    pub fn preview_delete_item(&self, item_id: &str, replace_with_gap: bool) -> EditPreview {
+        self.preview_edit(
+            Some(item_id),
+            |stack| stack.delete_item(item_id, replace_with_gap),
+            |_| HashSet::new(),
+        )
+    }
+}
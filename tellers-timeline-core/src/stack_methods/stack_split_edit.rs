@@ -0,0 +1,275 @@
+//! Split edits (J-cuts and L-cuts) on link groups.
+//!
+//! A link group normally plays one column: every clip starts and ends with
+//! the others. A split edit moves one clip's in or out point away from the
+//! column and records by how much (see [`SyncOffsets`]), so that resizes,
+//! moves, splits and deletes keep the offset instead of treating the group as
+//! misaligned.
+
+use super::{insertion_start_or_end_for_policy, replace_track_range_with_item, EPS};
+use crate::metadata::{item_sync_offsets, resolve_sync_offsets, set_sync_offsets};
+use crate::{Gap, IdMetadataExt, InsertPolicy, Item, OverlapPolicy, Seconds, Stack, SyncOffsets};
+
+/// The edge of a clip a split edit moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncEdge {
+    In,
+    Out,
+}
+
+impl Stack {
+    /// The split-edit offsets of a clip, or `None` when there is no such item.
+    pub fn sync_offsets(&self, item_id: &str) -> Option<SyncOffsets> {
+        self.get_item(item_id)
+            .map(|(_, _, item)| item_sync_offsets(item))
+    }
+
+    /// Move the `edge` of the synced clip `item_id` to `offset` seconds from
+    /// its link group's column (negative is earlier), trimming or extending
+    /// the clip. The rest of the group stays in place and linked; what the
+    /// clip grows over on its track is overwritten, what it leaves is gap.
+    /// Fails for unsynced clips, and when the clip would need media it does
+    /// not have or start before zero.
+    pub fn set_sync_offset(&mut self, item_id: &str, edge: SyncEdge, offset: Seconds) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            stack.set_sync_offset_ignoring_locks(item_id, edge, offset)
+        })
+    }
+
+    fn set_sync_offset_ignoring_locks(
+        &mut self,
+        item_id: &str,
+        edge: SyncEdge,
+        offset: Seconds,
+    ) -> bool {
+        if !offset.is_finite() {
+            return false;
+        }
+        let index = self.build_index();
+        let Some((track_index, item_index, Item::Clip(clip))) = index.get_item(item_id) else {
+            return false;
+        };
+        let Some(sync_clips_id) = clip.sync_clips_id() else {
+            return false;
+        };
+        if index.sync_members(sync_clips_id).len() < 2 {
+            return false;
+        }
+        let Some(start) = index.start_time_of_item(track_index, item_index) else {
+            return false;
+        };
+
+        let duration = clip.source_range.duration.to_seconds().max(0.0);
+        let mut offsets = resolve_sync_offsets(&clip.metadata);
+        let mut clip = clip.clone();
+        let (new_start, new_duration) = match edge {
+            SyncEdge::In => {
+                let delta = offset - offsets.in_offset;
+                offsets.in_offset = offset;
+                let source_start = clip.source_range.start_time.to_seconds() + delta;
+                if source_start < -EPS {
+                    return false;
+                }
+                clip.source_range
+                    .start_time
+                    .set_from_seconds(source_start.max(0.0));
+                (start + delta, duration - delta)
+            }
+            SyncEdge::Out => {
+                let delta = offset - offsets.out_offset;
+                offsets.out_offset = offset;
+                (start, duration + delta)
+            }
+        };
+        if new_start < -EPS || new_duration <= EPS {
+            return false;
+        }
+        clip.source_range.duration.set_from_seconds(new_duration);
+        let requested = clip.source_range.clone();
+        clip.clamp_to_active_available_range();
+        if clip.source_range != requested {
+            return false;
+        }
+        set_sync_offsets(&mut clip.metadata, offsets);
+
+        let track = &mut self.children[track_index];
+        track.items[item_index] = Item::Gap(Gap::make_gap(duration));
+        replace_track_range_with_item(
+            track,
+            new_start.max(0.0),
+            new_start.max(0.0) + new_duration,
+            Item::Clip(clip),
+        );
+        self.sanitize_preserving_all_gap_tracks();
+        true
+    }
+
+    /// Whether any clip of the link group has a split edit.
+    pub(super) fn sync_group_has_offsets(&self, sync_clips_id: i64) -> bool {
+        let index = self.build_index();
+        index
+            .sync_members(sync_clips_id)
+            .iter()
+            .any(|&(ti, ii)| !item_sync_offsets(&self.children[ti].items[ii]).is_zero())
+    }
+
+    /// The link group of `item_id` when it has split edits.
+    pub(super) fn offset_sync_group_of(&self, item_id: &str) -> Option<i64> {
+        let (_, _, Item::Clip(clip)) = self.get_item(item_id)? else {
+            return None;
+        };
+        let sync_clips_id = clip.sync_clips_id()?;
+        self.sync_group_has_offsets(sync_clips_id)
+            .then_some(sync_clips_id)
+    }
+
+    /// Every clip of a link group as `(track_index, item_index, start, end)`.
+    fn sync_member_spans(&self, sync_clips_id: i64) -> Vec<(usize, usize, Seconds, Seconds)> {
+        let index = self.build_index();
+        index
+            .sync_members(sync_clips_id)
+            .iter()
+            .filter_map(|&(ti, ii)| {
+                let start = index.start_time_of_item(ti, ii)?;
+                let end = start + self.children[ti].items[ii].duration().max(0.0);
+                Some((ti, ii, start, end))
+            })
+            .collect()
+    }
+
+    /// The column a link group shares, as `(start, end)`: where its clips
+    /// would start and end without split edits.
+    pub(super) fn sync_column(&self, sync_clips_id: i64) -> Option<(Seconds, Seconds)> {
+        let &(ti, ii, start, end) = self.sync_member_spans(sync_clips_id).first()?;
+        let offsets = item_sync_offsets(&self.children[ti].items[ii]);
+        Some((start - offsets.in_offset, end - offsets.out_offset))
+    }
+
+    /// Split a link group with split edits at `split_time`. Every clip of the
+    /// group containing the time is split. Inside the column, pieces ending
+    /// by `split_time` keep the group and the rest move to a new one, each
+    /// with offsets from its own half of the column. In a lead-in or tail
+    /// outside the column, the piece off the column leaves the group.
+    pub(super) fn split_offset_sync_group_at_time(
+        &mut self,
+        sync_clips_id: i64,
+        split_time: Seconds,
+    ) -> bool {
+        let Some((column_start, column_end)) = self.sync_column(sync_clips_id) else {
+            return false;
+        };
+        let mut split_tracks: Vec<usize> = self
+            .sync_member_spans(sync_clips_id)
+            .into_iter()
+            .filter(|&(_, _, start, end)| split_time > start + EPS && split_time < end - EPS)
+            .map(|(ti, ..)| ti)
+            .collect();
+        split_tracks.sort_unstable();
+        split_tracks.dedup();
+        for track_index in split_tracks {
+            self.children[track_index].split_at_time(split_time);
+        }
+
+        let inside_column = split_time > column_start + EPS && split_time < column_end - EPS;
+        let right_sync_clips_id = inside_column.then(|| self.next_sync_clips_id());
+        for (ti, ii, start, end) in self.sync_member_spans(sync_clips_id) {
+            let Item::Clip(clip) = &mut self.children[ti].items[ii] else {
+                continue;
+            };
+            let ((group_start, group_end), group_id) = match right_sync_clips_id {
+                Some(right) if end > split_time + EPS => ((split_time, column_end), right),
+                Some(_) => ((column_start, split_time), sync_clips_id),
+                None if end <= column_start + EPS || start >= column_end - EPS => {
+                    super::remove_resolve_sync_clips_id(&mut clip.metadata);
+                    continue;
+                }
+                None => ((column_start, column_end), sync_clips_id),
+            };
+            super::set_resolve_sync_clips_id(&mut clip.metadata, group_id);
+            set_sync_offsets(
+                &mut clip.metadata,
+                SyncOffsets {
+                    in_offset: start - group_start,
+                    out_offset: end - group_end,
+                },
+            );
+        }
+        let groups: Vec<i64> = std::iter::once(sync_clips_id)
+            .chain(right_sync_clips_id)
+            .collect();
+        self.cleanup_singleton_sync_clips(&groups);
+        true
+    }
+
+    /// Move the link group of `item_id`, which has split edits, so that the
+    /// item starts at `dest_time` on the track `dest_track_index`, or at the
+    /// edge of the item there that `insert_policy` picks. The other clips keep
+    /// their tracks and their offsets from the item.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn move_offset_sync_group_at_time(
+        &mut self,
+        item_id: &str,
+        sync_clips_id: i64,
+        dest_track_index: usize,
+        dest_time: Seconds,
+        replace_with_gap: bool,
+        insert_policy: InsertPolicy,
+        overlap_policy: OverlapPolicy,
+    ) -> bool {
+        let Some(selected_start) = self.stack_item_start_time(item_id) else {
+            return false;
+        };
+        let members: Vec<(Seconds, String)> = self
+            .sync_member_spans(sync_clips_id)
+            .into_iter()
+            .filter_map(|(ti, ii, start, _)| Some((start, self.children[ti].items[ii].get_id()?)))
+            .collect();
+        let overlap_policy =
+            Self::effective_move_overlap_policy(overlap_policy, Some(selected_start), dest_time);
+
+        let backup = self.clone();
+        let mut lifted = Vec::new();
+        for (start, id) in members {
+            let Some((track_index, item)) = self.delete_one_item(&id, replace_with_gap) else {
+                *self = backup;
+                return false;
+            };
+            let track_index = if id == item_id {
+                dest_track_index
+            } else {
+                track_index
+            };
+            lifted.push((track_index, start, item));
+        }
+        let landing = self
+            .children
+            .get(dest_track_index)
+            .and_then(|track| insertion_start_or_end_for_policy(track, dest_time, insert_policy));
+        let Some(landing) = landing else {
+            *self = backup;
+            return false;
+        };
+        let delta = landing - selected_start;
+        if lifted.iter().any(|(_, start, _)| start + delta < -EPS) {
+            *self = backup;
+            return false;
+        }
+        lifted.sort_by(|a, b| a.1.total_cmp(&b.1));
+        // The policy has placed the item; every clip then lands exactly where
+        // its offset puts it, so none is moved off the item under it.
+        for (track_index, start, item) in lifted {
+            if !self.insert_at_time_with_sync_splits(
+                track_index,
+                (start + delta).max(0.0),
+                item,
+                overlap_policy,
+                InsertPolicy::SplitAndInsert,
+            ) {
+                *self = backup;
+                return false;
+            }
+        }
+        self.sanitize_after_edit();
+        true
+    }
+}
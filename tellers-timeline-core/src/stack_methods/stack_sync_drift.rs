@@ -0,0 +1,215 @@
+//! Drift inside link groups.
+//!
+//! The clips of a link group play one column (see
+//! [`SyncOffsets`](crate::SyncOffsets) for split edits) from the same source
+//! position. Edits made here keep them that way, but hand-edited JSON or
+//! other tools may not: [`Stack::sync_drift`] reports how far each clip is
+//! from its group's anchor, and [`Stack::resync_group`] puts the clips back.
+
+use super::{replace_track_range_with_item, EPS};
+use crate::metadata::item_sync_offsets;
+use crate::{Gap, IdMetadataExt, Item, Seconds, Stack, TrackKind};
+
+/// How far one clip of a link group is from the group's anchor. Offsets are
+/// positive when the clip is later than the anchor, and compare column starts,
+/// so split edits are not drift.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncMemberDrift {
+    pub item_id: Option<String>,
+    pub track_index: usize,
+    /// Timeline offset, in seconds.
+    pub timeline_offset: Seconds,
+    /// Source offset, in seconds.
+    pub source_offset: Seconds,
+    /// `timeline_offset` in frames of the clip's rate.
+    pub timeline_offset_frames: f64,
+    /// `source_offset` in frames of the clip's rate.
+    pub source_offset_frames: f64,
+}
+
+impl SyncMemberDrift {
+    pub fn is_in_sync(&self) -> bool {
+        self.timeline_offset.abs() <= EPS && self.source_offset.abs() <= EPS
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncGroupDrift {
+    pub sync_clips_id: i64,
+    /// The clip the others are measured from: the group's first video clip,
+    /// or its first clip when it has none.
+    pub anchor_item_id: Option<String>,
+    /// Every other clip of the group, in track then item order.
+    pub members: Vec<SyncMemberDrift>,
+}
+
+impl SyncGroupDrift {
+    pub fn is_in_sync(&self) -> bool {
+        self.members.iter().all(SyncMemberDrift::is_in_sync)
+    }
+}
+
+/// Where a synced clip's column starts, on the timeline and in its source.
+#[derive(Debug, Clone, Copy)]
+struct ColumnStart {
+    timeline: Seconds,
+    source: Seconds,
+}
+
+impl Stack {
+    /// Drift of every link group with at least two clips, by Link Group ID.
+    pub fn sync_drift(&self) -> Vec<SyncGroupDrift> {
+        let mut sync_clips_ids: Vec<i64> = self
+            .children
+            .iter()
+            .flat_map(|track| &track.items)
+            .filter_map(|item| match item {
+                Item::Clip(clip) => clip.sync_clips_id(),
+                Item::Gap(_) => None,
+            })
+            .collect();
+        sync_clips_ids.sort_unstable();
+        sync_clips_ids.dedup();
+        sync_clips_ids
+            .into_iter()
+            .filter_map(|sync_clips_id| self.sync_group_drift(sync_clips_id, None))
+            .collect()
+    }
+
+    /// Drift of one link group, measured from `anchor_item_id` when given.
+    /// `None` when the group has fewer than two clips or the anchor is not
+    /// one of them.
+    pub fn sync_group_drift(
+        &self,
+        sync_clips_id: i64,
+        anchor_item_id: Option<&str>,
+    ) -> Option<SyncGroupDrift> {
+        let index = self.build_index();
+        let members = index.sync_members(sync_clips_id);
+        if members.len() < 2 {
+            return None;
+        }
+        let anchor = match anchor_item_id {
+            Some(anchor_item_id) => *members.iter().find(|&&(ti, ii)| {
+                self.children[ti].items[ii].get_id().as_deref() == Some(anchor_item_id)
+            })?,
+            None => members
+                .iter()
+                .copied()
+                .find(|&(ti, _)| self.children[ti].kind == TrackKind::Video)
+                .unwrap_or(members[0]),
+        };
+        let column_start = |ti: usize, ii: usize| -> Option<ColumnStart> {
+            let Item::Clip(clip) = &self.children[ti].items[ii] else {
+                return None;
+            };
+            let in_offset = item_sync_offsets(&self.children[ti].items[ii]).in_offset;
+            Some(ColumnStart {
+                timeline: index.start_time_of_item(ti, ii)? - in_offset,
+                source: clip.source_range.start_time.to_seconds() - in_offset,
+            })
+        };
+        let anchor_column = column_start(anchor.0, anchor.1)?;
+
+        let members = members
+            .iter()
+            .filter(|&&member| member != anchor)
+            .filter_map(|&(ti, ii)| {
+                let column = column_start(ti, ii)?;
+                let Item::Clip(clip) = &self.children[ti].items[ii] else {
+                    return None;
+                };
+                let rate = clip.source_range.start_time.rate;
+                let timeline_offset = column.timeline - anchor_column.timeline;
+                let source_offset = column.source - anchor_column.source;
+                Some(SyncMemberDrift {
+                    item_id: clip.get_id(),
+                    track_index: ti,
+                    timeline_offset,
+                    source_offset,
+                    timeline_offset_frames: to_frames(timeline_offset, rate),
+                    source_offset_frames: to_frames(source_offset, rate),
+                })
+            })
+            .collect();
+        Some(SyncGroupDrift {
+            sync_clips_id,
+            anchor_item_id: self.children[anchor.0].items[anchor.1].get_id(),
+            members,
+        })
+    }
+
+    /// Bring every clip of the link group back in line with `anchor_item_id`:
+    /// clips off the anchor's column move to it, overwriting what they land
+    /// on, and clips reading other source slip to the anchor's. Split edits
+    /// are kept. Fails, leaving the stack untouched, when the anchor is not in
+    /// the group or a clip would need media it does not have or start before
+    /// zero.
+    pub fn resync_group(&mut self, sync_clips_id: i64, anchor_item_id: &str) -> bool {
+        self.refusing_locked_track_changes(|stack| {
+            stack.resync_group_ignoring_locks(sync_clips_id, anchor_item_id)
+        })
+    }
+
+    fn resync_group_ignoring_locks(&mut self, sync_clips_id: i64, anchor_item_id: &str) -> bool {
+        let Some(drift) = self.sync_group_drift(sync_clips_id, Some(anchor_item_id)) else {
+            return false;
+        };
+        let backup = self.clone();
+        for member in drift.members.iter().filter(|member| !member.is_in_sync()) {
+            let resynced = member
+                .item_id
+                .as_deref()
+                .is_some_and(|item_id| self.resync_clip(item_id, member));
+            if !resynced {
+                *self = backup;
+                return false;
+            }
+        }
+        self.sanitize_preserving_all_gap_tracks();
+        true
+    }
+
+    /// Undo `drift` on the clip `item_id` by moving and slipping it.
+    fn resync_clip(&mut self, item_id: &str, drift: &SyncMemberDrift) -> bool {
+        let Some((track_index, item_index, Item::Clip(clip))) = self.get_item(item_id) else {
+            return false;
+        };
+        let Some(start) = self.stack_item_start_time(item_id) else {
+            return false;
+        };
+        let mut clip = clip.clone();
+        let duration = clip.source_range.duration.to_seconds().max(0.0);
+        let new_start = start - drift.timeline_offset;
+        let source_start = clip.source_range.start_time.to_seconds() - drift.source_offset;
+        if new_start < -EPS || source_start < -EPS {
+            return false;
+        }
+        clip.source_range
+            .start_time
+            .set_from_seconds(source_start.max(0.0));
+        let requested = clip.source_range.clone();
+        clip.clamp_to_active_available_range();
+        if clip.source_range != requested {
+            return false;
+        }
+
+        let track = &mut self.children[track_index];
+        track.items[item_index] = Item::Gap(Gap::make_gap(duration));
+        replace_track_range_with_item(
+            track,
+            new_start.max(0.0),
+            new_start.max(0.0) + duration,
+            Item::Clip(clip),
+        );
+        true
+    }
+}
+
+fn to_frames(seconds: Seconds, rate: f64) -> f64 {
+    if rate.abs() > f64::EPSILON {
+        seconds * rate
+    } else {
+        seconds
+    }
+}
@@ -0,0 +1,87 @@
+//! Sync lock: ripple edits made on one track are repeated on every
+//! sync-locked track the edit did not reach by itself, so that those tracks
+//! stay aligned with the edit.
+
+use super::stack_lock::normalized;
+use super::{EditOutcome, InsertItemAtTimeResult};
+use crate::{IdMetadataExt, Seconds, Stack, Track};
+
+/// A span opened (`delta > 0`) or closed (`delta < 0`) at `at` by a ripple
+/// edit. A closed span is `at..at - delta`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Ripple {
+    pub(crate) at: Seconds,
+    pub(crate) delta: Seconds,
+}
+
+impl Stack {
+    /// Run `edit` and, if it succeeds, apply the ripple `ripple` reads from
+    /// the edited stack to every sync-locked track the edit left unchanged.
+    /// Locked tracks are never rippled. Clips straddling the edit point are
+    /// split.
+    pub(crate) fn rippling_sync_locked_tracks<R: EditOutcome>(
+        &mut self,
+        edit: impl FnOnce(&mut Self) -> R,
+        ripple: impl FnOnce(&Self, &R) -> Option<Ripple>,
+    ) -> R {
+        if !self.children.iter().any(Track::is_sync_locked) {
+            return edit(self);
+        }
+        // The edit's sanitize pass would give id-less tracks an id; do it
+        // first so that the sync-locked tracks can be found again afterwards.
+        self.ensure_unique_timeline_ids();
+        let followers: Vec<(Option<String>, Track)> = self
+            .children
+            .iter()
+            .filter(|track| track.is_sync_locked() && !track.is_locked())
+            .map(|track| (track.get_id(), normalized(track)))
+            .collect();
+
+        let outcome = edit(self);
+        if !outcome.succeeded() {
+            return outcome;
+        }
+        let Some(Ripple { at, delta }) = ripple(self, &outcome) else {
+            return outcome;
+        };
+        for (id, before) in followers {
+            let Some((track_index, track)) = id.as_deref().and_then(|id| self.get_track_by_id(id))
+            else {
+                continue;
+            };
+            if normalized(track) != before {
+                continue;
+            }
+            let track = &mut self.children[track_index];
+            if delta > 0.0 {
+                track.insert_gap_at_time(at, delta);
+            } else {
+                track.delete_range(at, at - delta, false);
+            }
+        }
+        self.sanitize_preserving_all_gap_tracks();
+        outcome
+    }
+
+    /// Where the item `item_id` is, as `(start, duration)`.
+    pub(crate) fn item_span(&self, item_id: &str) -> Option<(Seconds, Seconds)> {
+        let (track_index, item_index, item) = self.get_item(item_id)?;
+        Some((
+            self.children[track_index].start_time_of_item(item_index),
+            item.duration().max(0.0),
+        ))
+    }
+
+    /// The span a push insert opened: where its primary clip landed.
+    pub(crate) fn inserted_ripple(
+        &self,
+        result: &Option<InsertItemAtTimeResult>,
+    ) -> Option<Ripple> {
+        let primary_id = match result.as_ref()? {
+            InsertItemAtTimeResult::ItemId(id) => id,
+            InsertItemAtTimeResult::Synced(synced) => &synced.primary_clip_id,
+        };
+        let (at, delta) = self.item_span(primary_id)?;
+        Some(Ripple { at, delta })
+    }
+}
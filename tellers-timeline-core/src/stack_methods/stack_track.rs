@@ -11,14 +11,14 @@ impl Stack {
     /// Append a track to the stack.
     pub fn add_track(&mut self, track: Track) {
         self.children.push(track);
-        self.sanitize();
+        self.sanitize_after_edit();
     }
 
     /// Insert a track at a specific index. Negative indices behave like Python's.
     pub fn add_track_at(&mut self, track: Track, insertion_index: isize) -> bool {
         let idx = super::clamp_insertion_index(self.children.len(), insertion_index);
         self.children.insert(idx, track);
-        self.sanitize();
+        self.sanitize_after_edit();
         true
     }
 
@@ -39,10 +39,27 @@ impl Stack {
             dest_index
         };
         self.children.insert(adjusted_dest_index, track);
-        self.sanitize();
+        self.sanitize_after_edit();
         true
     }
 
+    /// Whether the track at `track_index` is heard / seen in the output: it
+    /// must be enabled and not muted, and while any track of the same kind is
+    /// soloed, it must be soloed too.
+    pub fn is_track_rendered(&self, track_index: usize) -> bool {
+        let Some(track) = self.children.get(track_index) else {
+            return false;
+        };
+        if !track.enabled || track.is_muted() {
+            return false;
+        }
+        track.is_solo()
+            || !self
+                .children
+                .iter()
+                .any(|other| other.kind == track.kind && other.is_solo())
+    }
+
     /// Return sync groups of tracks that share a link group.
     pub fn sync_track_info(&self) -> Vec<SyncTrackInfo> {
         self.track_boundary_ranges()
@@ -61,9 +78,13 @@ impl Stack {
             .collect()
     }
 
-    /// Delete a track by id. Returns the removed track on success.
+    /// Delete a track by id. Returns the removed track on success; locked
+    /// tracks are not deleted.
     pub fn delete_track(&mut self, id: &str) -> Option<Track> {
         let (i, track) = self.get_track_by_id(id)?;
+        if track.is_locked() {
+            return None;
+        }
         let touched_sync_clips_ids: Vec<_> = track
             .items
             .iter()
@@ -107,11 +128,18 @@ impl Stack {
             return Vec::new();
         }
 
+        // Same relation as `tracks_share_sync_clips`, but each track's link
+        // groups are collected once instead of rescanning both tracks for
+        // every (anchor, candidate) pair.
+        let sync_clips_ids: Vec<_> = (0..len).map(|i| self.track_sync_clips_ids(i)).collect();
         let mut groups = Vec::new();
         let mut seen = std::collections::HashSet::new();
         for anchor in 0..len {
             let mut track_indices: Vec<usize> = (0..len)
-                .filter(|&candidate| self.tracks_share_sync_clips(anchor, candidate))
+                .filter(|&candidate| {
+                    candidate == anchor
+                        || !sync_clips_ids[anchor].is_disjoint(&sync_clips_ids[candidate])
+                })
                 .collect();
             track_indices.sort_unstable();
             if seen.insert(track_indices.clone()) {
@@ -0,0 +1,219 @@
+use super::EditOutcome;
+use crate::Stack;
+use std::ops::Deref;
+use std::sync::{Arc, Weak};
+use thiserror::Error;
+
+/// The transactions open on a [`Stack`]. While any is open, its edits skip
+/// their trailing sanitize pass; the last commit runs it once. Not serialized,
+/// not carried over by `clone` (a copy of a stack is outside its
+/// transactions), and ignored by `==`.
+#[derive(Debug, Default)]
+pub struct OpenTransactions {
+    /// One per [`PendingTransaction`]; a dropped handle no longer counts.
+    handles: Vec<Weak<()>>,
+    /// An edit deferred a full `sanitize`, not just the gap-track-preserving
+    /// one.
+    full_sanitize: bool,
+}
+
+impl OpenTransactions {
+    fn any_open(&self) -> bool {
+        self.handles.iter().any(|handle| handle.strong_count() > 0)
+    }
+
+    fn position(&self, handle: &Arc<()>) -> Option<usize> {
+        let handle = Arc::downgrade(handle);
+        self.handles.iter().position(|open| open.ptr_eq(&handle))
+    }
+
+    fn prune(&mut self) {
+        self.handles.retain(|handle| handle.strong_count() > 0);
+        if self.handles.is_empty() {
+            self.full_sanitize = false;
+        }
+    }
+}
+
+impl Clone for OpenTransactions {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for OpenTransactions {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionError {
+    /// The edit at `index` (0-based, in `apply` order) reported failure:
+    /// `false`, `None`, an empty list or a zero count.
+    #[error("edit {index} of the transaction failed")]
+    EditFailed { index: usize },
+    /// A [`PendingTransaction`] was ended on a stack it was not opened on.
+    #[error("the transaction is not open on this stack")]
+    NotOpen,
+}
+
+/// The handle a [`Stack::transaction`] closure edits through. Reads go
+/// straight to the stack (it derefs to [`Stack`]); edits go through
+/// [`StackTransaction::apply`].
+pub struct StackTransaction<'a> {
+    stack: &'a mut Stack,
+    applied: usize,
+    failed: Option<TransactionError>,
+}
+
+impl StackTransaction<'_> {
+    /// Run one edit. The first failing edit dooms the transaction: later
+    /// edits are not run (they return the same error) and the whole batch
+    /// is rolled back, even if the closure ignores the error.
+    pub fn apply<R: EditOutcome>(
+        &mut self,
+        edit: impl FnOnce(&mut Stack) -> R,
+    ) -> Result<R, TransactionError> {
+        if let Some(error) = self.failed {
+            return Err(error);
+        }
+        let index = self.applied;
+        self.applied += 1;
+        let outcome = edit(self.stack);
+        if outcome.succeeded() {
+            Ok(outcome)
+        } else {
+            let error = TransactionError::EditFailed { index };
+            self.failed = Some(error);
+            Err(error)
+        }
+    }
+
+    /// Number of edits applied so far.
+    pub fn applied(&self) -> usize {
+        self.applied
+    }
+}
+
+impl Deref for StackTransaction<'_> {
+    type Target = Stack;
+
+    fn deref(&self) -> &Stack {
+        self.stack
+    }
+}
+
+/// A transaction opened with [`Stack::begin_transaction`], for callers that
+/// cannot put their edits in a closure. It must be ended with
+/// [`PendingTransaction::commit`] or [`PendingTransaction::rollback`] on the
+/// stack it was opened on; dropping it ends the transaction and leaves its
+/// edits unsanitized.
+pub struct PendingTransaction {
+    backup: Stack,
+    handle: Arc<()>,
+}
+
+impl PendingTransaction {
+    /// Keep the edits, and run the sanitize pass they deferred unless another
+    /// transaction is still open on `stack`.
+    pub fn commit(self, stack: &mut Stack) -> Result<(), TransactionError> {
+        let position = stack
+            .transactions
+            .position(&self.handle)
+            .ok_or(TransactionError::NotOpen)?;
+        stack.transactions.handles.remove(position);
+        drop(self.handle);
+        stack.transactions.prune();
+        if !stack.transactions.any_open() {
+            if std::mem::take(&mut stack.transactions.full_sanitize) {
+                stack.sanitize();
+            } else {
+                stack.sanitize_preserving_all_gap_tracks();
+            }
+        }
+        Ok(())
+    }
+
+    /// Put `stack` back the way it was when the transaction was opened.
+    pub fn rollback(self, stack: &mut Stack) -> Result<(), TransactionError> {
+        let position = stack
+            .transactions
+            .position(&self.handle)
+            .ok_or(TransactionError::NotOpen)?;
+        let mut transactions = std::mem::take(&mut stack.transactions);
+        transactions.handles.remove(position);
+        drop(self.handle);
+        transactions.prune();
+        *stack = self.backup;
+        stack.transactions = transactions;
+        Ok(())
+    }
+}
+
+impl Stack {
+    /// Apply a batch of edits atomically. `edits` runs against a
+    /// [`StackTransaction`]; if it returns `Err`, or any of its
+    /// [`StackTransaction::apply`] calls failed, the stack is restored to what
+    /// it was before the call. Otherwise the edits are kept and the stack is
+    /// sanitized once, instead of after every edit.
+    ///
+    /// Transactions nest: an inner one rolls back only its own edits, and
+    /// only the outermost commit sanitizes.
+    pub fn transaction<T, E: From<TransactionError>>(
+        &mut self,
+        edits: impl FnOnce(&mut StackTransaction<'_>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let pending = self.begin_transaction();
+        let result = {
+            let mut tx = StackTransaction {
+                stack: self,
+                applied: 0,
+                failed: None,
+            };
+            match (edits(&mut tx), tx.failed) {
+                (Ok(_), Some(error)) => Err(error.into()),
+                (result, _) => result,
+            }
+        };
+        match result {
+            Ok(value) => {
+                pending.commit(self)?;
+                Ok(value)
+            }
+            Err(error) => {
+                pending.rollback(self)?;
+                Err(error)
+            }
+        }
+    }
+
+    /// Open a transaction without a closure: edits made until the returned
+    /// [`PendingTransaction`] is committed skip their sanitize pass, and a
+    /// rollback undoes all of them.
+    pub fn begin_transaction(&mut self) -> PendingTransaction {
+        let handle = Arc::new(());
+        self.transactions.prune();
+        self.transactions.handles.push(Arc::downgrade(&handle));
+        PendingTransaction {
+            backup: self.clone(),
+            handle,
+        }
+    }
+
+    /// Whether edits should leave sanitizing to a transaction open on this
+    /// stack.
+    pub(crate) fn sanitize_deferred(&self) -> bool {
+        self.transactions.any_open()
+    }
+
+    /// `sanitize` for edits: deferred to the commit while a transaction is
+    /// open on this stack.
+    pub(crate) fn sanitize_after_edit(&mut self) {
+        if self.sanitize_deferred() {
+            self.transactions.full_sanitize = true;
+        } else {
+            self.sanitize();
+        }
+    }
+}
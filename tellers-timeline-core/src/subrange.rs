@@ -0,0 +1,153 @@
+//! Extracting a time window of a timeline as a standalone timeline, e.g. to
+//! turn a 30 s cutdown of a long edit into its own project.
+//!
+//! Every track is cut to the window: clips straddling a boundary are split
+//! there, keeping the part inside with its `source_range` moved accordingly.
+//! Link groups and Tellers groups keep the clips that survive and are dropped
+//! when fewer than two do; split-edit offsets are measured against the part
+//! of the group's column inside the window. Timed words and subtitles in the
+//! timeline metadata are cut and shifted the same way, and every timeline,
+//! stack, track and item gets a new id so the extract never collides with
+//! its source project.
+
+use crate::metadata::{item_sync_offsets, set_sync_offsets, tellers_ai_mut};
+use crate::{IdMetadataExt, Item, Seconds, Stack, SyncOffsets, Timeline};
+use std::collections::HashMap;
+
+const EPS: Seconds = 1e-9;
+
+/// Timeline metadata keys holding `[{ "text", "start", "end" }, ...]` lists
+/// timed against the timeline.
+pub(crate) const TIMED_METADATA_KEYS: [&str; 2] = ["tellers_timed_words", "tellers_subtitles"];
+
+impl Timeline {
+    /// A new timeline playing `start..end` of this one from time zero.
+    /// `None` unless `0 <= start < end`.
+    pub fn subrange(&self, start: Seconds, end: Seconds) -> Option<Timeline> {
+        if !start.is_finite() || !end.is_finite() || start < 0.0 || end <= start + EPS {
+            return None;
+        }
+        let mut timeline = self.clone();
+        timeline.tracks = stack_subrange(&self.tracks, start, end);
+        crate::crdt::drop_singleton_groups(&mut timeline);
+        for key in TIMED_METADATA_KEYS {
+            if let Some(entries) = timeline
+                .metadata
+                .get_mut(key)
+                .and_then(serde_json::Value::as_array_mut)
+            {
+                retime_entries(entries, start, end);
+            }
+        }
+        renew_id(&mut timeline.metadata);
+        Some(timeline)
+    }
+}
+
+fn stack_subrange(source: &Stack, start: Seconds, end: Seconds) -> Stack {
+    // Where each link group with split edits had its column, before cutting.
+    let index = source.build_index();
+    let mut columns: HashMap<i64, (Seconds, Seconds)> = HashMap::new();
+    for (ti, track) in source.children.iter().enumerate() {
+        for (ii, item) in track.items.iter().enumerate() {
+            let Item::Clip(clip) = item else {
+                continue;
+            };
+            let Some(sync_clips_id) = clip.sync_clips_id() else {
+                continue;
+            };
+            if columns.contains_key(&sync_clips_id)
+                || index
+                    .sync_members(sync_clips_id)
+                    .iter()
+                    .all(|&(ti, ii)| item_sync_offsets(&source.children[ti].items[ii]).is_zero())
+            {
+                continue;
+            }
+            let Some(item_start) = index.start_time_of_item(ti, ii) else {
+                continue;
+            };
+            let offsets = item_sync_offsets(item);
+            columns.insert(
+                sync_clips_id,
+                (
+                    item_start - offsets.in_offset,
+                    item_start + item.duration().max(0.0) - offsets.out_offset,
+                ),
+            );
+        }
+    }
+
+    let mut stack = source.clone();
+    for track in &mut stack.children {
+        track.split_at_time(end);
+        track.split_at_time(start);
+        let mut item_end = 0.0;
+        track.items.retain(|item| {
+            let item_start = item_end;
+            item_end += item.duration().max(0.0);
+            item_start >= start - EPS && item_end <= end + EPS
+        });
+        let mut item_start = 0.0;
+        for item in &mut track.items {
+            let duration = item.duration().max(0.0);
+            if let Item::Clip(clip) = item {
+                let column = clip.sync_clips_id().and_then(|id| columns.get(&id));
+                if let Some(&(column_start, column_end)) = column {
+                    let column_start = column_start.max(start) - start;
+                    let column_end = column_end.min(end) - start;
+                    let clip_end = item_start + duration;
+                    if column_end <= column_start + EPS
+                        || clip_end <= column_start + EPS
+                        || item_start >= column_end - EPS
+                    {
+                        crate::stack_methods::remove_resolve_sync_clips_id(&mut clip.metadata);
+                    } else {
+                        set_sync_offsets(
+                            &mut clip.metadata,
+                            SyncOffsets {
+                                in_offset: item_start - column_start,
+                                out_offset: clip_end - column_end,
+                            },
+                        );
+                    }
+                }
+            }
+            item_start += duration;
+        }
+        track.set_id(None);
+        for item in &mut track.items {
+            item.set_id(None);
+        }
+    }
+    renew_id(&mut stack.metadata);
+    stack.sanitize_preserving_all_gap_tracks();
+    stack
+}
+
+/// Keep the entries overlapping `start..end`, cut to it and shifted so that
+/// `start` is time zero. Entries without numeric times are kept as they are.
+fn retime_entries(entries: &mut Vec<serde_json::Value>, start: Seconds, end: Seconds) {
+    entries.retain_mut(|entry| {
+        let times = (
+            entry.get("start").and_then(serde_json::Value::as_f64),
+            entry.get("end").and_then(serde_json::Value::as_f64),
+        );
+        let (Some(entry_start), Some(entry_end)) = times else {
+            return true;
+        };
+        if entry_end <= start + EPS || entry_start >= end - EPS {
+            return false;
+        }
+        entry["start"] = serde_json::Value::from(entry_start.max(start) - start);
+        entry["end"] = serde_json::Value::from(entry_end.min(end) - start);
+        true
+    });
+}
+
+fn renew_id(metadata: &mut serde_json::Value) {
+    tellers_ai_mut(metadata).insert(
+        "timeline_id".to_string(),
+        serde_json::Value::String(crate::types::gen_hex_id_12()),
+    );
+}
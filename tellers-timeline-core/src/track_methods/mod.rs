@@ -1,8 +1,11 @@
+pub mod track_effects;
 pub mod track_item_delete;
 pub mod track_item_get;
 pub mod track_item_insert;
 pub mod track_item_split;
+pub mod track_state;
 
+pub use track_item_get::ItemInRange;
 pub use track_item_insert::{
     DeletedClipInfo, InsertPolicy, OverlapPolicy, SplitClipInfo, TrackInsertResult,
 };
@@ -1,11 +1,45 @@
 use crate::{Item, Seconds, Track};
 
+/// An item overlapping a queried time range: where it sits on its track and
+/// the part of it that falls inside the range (`overlap_start..overlap_end`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ItemInRange<'a> {
+    pub item_index: usize,
+    pub item: &'a Item,
+    pub start: Seconds,
+    pub end: Seconds,
+    pub overlap_start: Seconds,
+    pub overlap_end: Seconds,
+}
+
+impl<'a> ItemInRange<'a> {
+    /// `item` spans `start..end` on its track and is known to overlap the
+    /// query `range_start..range_end`.
+    pub(crate) fn clipped(
+        item_index: usize,
+        item: &'a Item,
+        start: Seconds,
+        end: Seconds,
+        range_start: Seconds,
+        range_end: Seconds,
+    ) -> Self {
+        Self {
+            item_index,
+            item,
+            start,
+            end,
+            overlap_start: start.max(range_start),
+            overlap_end: end.min(range_end),
+        }
+    }
+}
+
 impl Track {
     /// Find an item by id stored at `metadata["tellers.ai"]["timeline_id"]`.
     /// Returns the index and a non-mut reference to the item Some((index, item)).
     pub fn get_item_by_id(&self, id: &str) -> Option<(usize, &Item)> {
         for (i, it) in self.items.iter().enumerate() {
-            if crate::metadata::IdMetadataExt::get_id(it).as_deref() == Some(id) {
+            if crate::metadata::item_timeline_id(it) == Some(id) {
                 return Some((i, it));
             }
         }
@@ -24,4 +58,27 @@ impl Track {
         }
         None
     }
+
+    /// Every item (clips and gaps) overlapping the half-open range
+    /// `start..end`, in track order. Zero-length items never overlap anything,
+    /// matching `get_item_at_time`.
+    ///
+    /// This walks the track from the start; for many queries against the same
+    /// state use `StackIndex::track_items_in_range` or `TrackTimes`, which
+    /// binary-search.
+    pub fn items_in_range(&self, start: Seconds, end: Seconds) -> Vec<ItemInRange<'_>> {
+        let mut hits = Vec::new();
+        let mut pos: Seconds = 0.0;
+        for (i, it) in self.items.iter().enumerate() {
+            if pos >= end {
+                break;
+            }
+            let item_end = pos + it.duration().max(0.0);
+            if item_end > pos && item_end > start && start < end {
+                hits.push(ItemInRange::clipped(i, it, pos, item_end, start, end));
+            }
+            pos = item_end;
+        }
+        hits
+    }
 }
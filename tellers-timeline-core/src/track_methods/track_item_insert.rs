@@ -77,6 +77,30 @@ impl Track {
         self.items.insert(index, item);
     }
 
+    /// Open `duration` seconds of gap at `time`, splitting the item that
+    /// straddles it and pushing everything after it. Nothing happens at or
+    /// past the end of the track, where there is nothing to push.
+    /// Does not run track sanitize.
+    pub(crate) fn insert_gap_at_time(&mut self, time: Seconds, duration: Seconds) {
+        const EPS: Seconds = 1e-9;
+        if duration <= EPS || time >= self.total_duration() - EPS {
+            return;
+        }
+        self.split_at_time(time);
+        let mut start = 0.0;
+        let index = self
+            .items
+            .iter()
+            .position(|item| {
+                let at = start >= time - EPS;
+                start += item.duration().max(0.0);
+                at
+            })
+            .unwrap_or(self.items.len());
+        self.items
+            .insert(index, Item::Gap(crate::types::Gap::make_gap(duration)));
+    }
+
     pub(crate) fn insert_and_override(&mut self, index: usize, mut item: Item) -> TrackInsertResult {
         const EPS: Seconds = 1e-9;
         item.clamp_to_active_available_range();
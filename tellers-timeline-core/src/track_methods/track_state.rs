@@ -0,0 +1,108 @@
+//! Editing state of a track: locked, sync-locked, solo, muted and display
+//! height.
+//!
+//! OTIO only knows `enabled`, so the rest is kept in `metadata["tellers.ai"]`
+//! (`"locked"`, `"sync_locked"`, `"solo"`, `"muted"`, `"height"`).
+//! Clearing a flag removes its key, so tracks that never had one serialize
+//! as before.
+
+use crate::metadata::tellers_ai_mut;
+use crate::Track;
+
+const LOCKED_KEY: &str = "locked";
+const SYNC_LOCKED_KEY: &str = "sync_locked";
+const SOLO_KEY: &str = "solo";
+const MUTED_KEY: &str = "muted";
+const HEIGHT_KEY: &str = "height";
+
+impl Track {
+    /// Whether edits must leave this track untouched. See
+    /// [`Stack`](crate::Stack) edit methods, which fail rather than modify a
+    /// locked track.
+    pub fn is_locked(&self) -> bool {
+        self.state_flag(LOCKED_KEY)
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.set_state_flag(LOCKED_KEY, locked);
+    }
+
+    /// Whether the track follows ripple edits made on other tracks: push
+    /// inserts, collapsing deletes and ripple trims open or close the same
+    /// span on every sync-locked track, so it stays aligned with the edit.
+    pub fn is_sync_locked(&self) -> bool {
+        self.state_flag(SYNC_LOCKED_KEY)
+    }
+
+    pub fn set_sync_locked(&mut self, sync_locked: bool) {
+        self.set_state_flag(SYNC_LOCKED_KEY, sync_locked);
+    }
+
+    /// Whether the track is soloed: while any track of a kind is soloed, only
+    /// the soloed tracks of that kind are rendered.
+    pub fn is_solo(&self) -> bool {
+        self.state_flag(SOLO_KEY)
+    }
+
+    pub fn set_solo(&mut self, solo: bool) {
+        self.set_state_flag(SOLO_KEY, solo);
+    }
+
+    /// Whether the track is muted. Unlike `enabled = false`, muting is an
+    /// editing toggle: the track stays part of the edit but is not rendered.
+    pub fn is_muted(&self) -> bool {
+        self.state_flag(MUTED_KEY)
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.set_state_flag(MUTED_KEY, muted);
+    }
+
+    /// Display height of the track in the editor, if one was set.
+    pub fn height(&self) -> Option<f64> {
+        self.metadata
+            .get("tellers.ai")
+            .and_then(|ai| ai.get(HEIGHT_KEY))
+            .and_then(serde_json::Value::as_f64)
+    }
+
+    /// Set or clear the display height. Non-finite or negative heights clear
+    /// it.
+    pub fn set_height(&mut self, height: Option<f64>) {
+        let height = height.filter(|h| h.is_finite() && *h >= 0.0);
+        match height.and_then(serde_json::Number::from_f64) {
+            Some(number) => {
+                tellers_ai_mut(&mut self.metadata)
+                    .insert(HEIGHT_KEY.to_string(), serde_json::Value::Number(number));
+            }
+            None => self.remove_state_key(HEIGHT_KEY),
+        }
+    }
+
+    fn state_flag(&self, key: &str) -> bool {
+        self.metadata
+            .get("tellers.ai")
+            .and_then(|ai| ai.get(key))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    fn set_state_flag(&mut self, key: &str, value: bool) {
+        if value {
+            tellers_ai_mut(&mut self.metadata)
+                .insert(key.to_string(), serde_json::Value::Bool(true));
+        } else {
+            self.remove_state_key(key);
+        }
+    }
+
+    fn remove_state_key(&mut self, key: &str) {
+        if let Some(ai) = self
+            .metadata
+            .get_mut("tellers.ai")
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            ai.remove(key);
+        }
+    }
+}
@@ -25,7 +25,7 @@ fn default_time_range_schema() -> String {
 fn default_rational_time_schema() -> String {
     "RationalTime.1".to_string()
 }
-fn default_effect_schema() -> String {
+pub(crate) fn default_effect_schema() -> String {
     "Effect.1".to_string()
 }
 fn default_enabled() -> bool {
@@ -160,6 +160,9 @@ pub struct Track {
     pub items: Vec<Item>,
     #[serde(default, deserialize_with = "deserialize_metadata_with_id")]
     pub metadata: serde_json::Value,
+    /// Track-wide effects (gain, pan), applied on top of each clip's own.
+    #[serde(default)]
+    pub effects: Vec<Effect>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -172,6 +175,10 @@ pub struct Stack {
     pub children: Vec<Track>,
     #[serde(default, deserialize_with = "deserialize_metadata_with_id")]
     pub metadata: serde_json::Value,
+    /// Transactions open on this stack, see [`Stack::begin_transaction`].
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub transactions: crate::OpenTransactions,
 }
 
 impl Default for Stack {
@@ -181,6 +188,7 @@ impl Default for Stack {
             name: None,
             children: vec![],
             metadata: serde_json::Value::Object(serde_json::Map::new()),
+            transactions: crate::OpenTransactions::default(),
         }
     }
 }
@@ -407,11 +415,17 @@ impl Clip {
     /// The Rich Text Title HTML of this clip's active media reference, if it is
     /// a Rich generator reference. Mirrors the app's `extractTitleBlobHtml`.
     pub fn get_rich_text(&self) -> Option<String> {
+        self.active_media_reference()?.get_rich_text()
+    }
+
+    /// The media reference this clip plays: the active key, or
+    /// `DEFAULT_MEDIA` when no key is set.
+    pub fn active_media_reference(&self) -> Option<&MediaReference> {
         let key = self
             .active_media_reference_key
             .as_deref()
             .unwrap_or("DEFAULT_MEDIA");
-        self.media_references.get(key)?.get_rich_text()
+        self.media_references.get(key)
     }
 
     pub fn bind_default_media_reference_when_needed(&mut self) {
@@ -523,7 +537,7 @@ impl Clip {
     }
 
     pub fn get_position(&self) -> MediaReferencePosition {
-        let active_media_reference = self.media_references.get(self.active_media_reference_key.as_ref().unwrap()).unwrap();
+        let active_media_reference = self.active_media_reference();
         // MediaReferencePosition uses [-0.5, +0.5] with (0, 0) at the screen center,
         // matching Resolve's transformationPan/Tilt space where 0 is centered.
         let mut x = 0.0;
@@ -531,7 +545,7 @@ impl Clip {
         let mut rotation = 0.0;
         let mut zoom_x = 1.0;
         let mut zoom_y = 1.0;
-        if let MediaReference::GeneratorReference { parameters, .. } = active_media_reference {
+        if let Some(MediaReference::GeneratorReference { parameters, .. }) = active_media_reference {
             if let Some(resolve_otio_effects) = parameters.resolve_otio.as_ref() {
                 for effect in resolve_otio_effects {
                     if effect.effect_name == "Rich Text" {
@@ -1264,7 +1278,7 @@ pub struct Effect {
 }
 
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MediaReferencePosition {
     pub x: f64,
     pub y: f64,
@@ -1273,7 +1287,7 @@ pub struct MediaReferencePosition {
     pub zoom_y: f64,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MediaReferenceCrop {
     pub crop_left: f64,
     pub crop_right: f64,
@@ -1719,6 +1733,7 @@ impl Track {
             name: None,
             items: vec![],
             metadata: serde_json::Value::Object(serde_json::Map::new()),
+            effects: vec![],
         };
         crate::metadata::IdMetadataExt::set_id(&mut t, Some(id.unwrap_or_else(gen_hex_id_12)));
         t
@@ -1746,7 +1761,8 @@ impl Track {
     }
 
     /// The mean volume across the track's clips (gaps ignored). Returns 0.0 when
-    /// the track has no clips. Mirrors the app's `Track.averageVolume`.
+    /// the track has no clips. Mirrors the app's `Track.averageVolume`; the
+    /// track's own gain ([`Track::get_volume`]) is not included.
     pub fn average_volume(&self) -> f64 {
         let mut sum = 0.0;
         let mut count = 0usize;
@@ -1763,16 +1779,6 @@ impl Track {
         }
     }
 
-    /// Set the volume on every item in the track.
-    ///
-    /// A track has no single volume field, so "track volume" is applied by
-    /// setting the given `volume` on each of its items (see
-    /// [`Item::set_volume`]).
-    pub fn set_volume(&mut self, volume: f64) {
-        for item in &mut self.items {
-            item.set_volume(volume);
-        }
-    }
 }
 
 impl Stack {
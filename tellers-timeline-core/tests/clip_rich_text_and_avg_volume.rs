@@ -59,6 +59,10 @@ fn track_average_volume_means_clip_volumes_ignoring_gaps() {
     // Two clips at the default volume (1.0); the gap is ignored.
     assert_eq!(track.average_volume(), 1.0);
 
-    track.set_volume(0.5);
-    assert_eq!(track.average_volume(), 0.5);
+    track.items[0].set_volume(0.5);
+    assert_eq!(track.average_volume(), 0.75);
+
+    // The track gain is not a clip volume.
+    track.set_volume(0.1);
+    assert_eq!(track.average_volume(), 0.75);
 }
@@ -0,0 +1,185 @@
+//! `Stack::copy_selection` / `paste_selection`: copies keep their layout and
+//! sync partners, and get fresh ids and groups.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{item_tellers_group_ids, Clipboard};
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+fn span_of(stack: &Stack, item_id: &str) -> (usize, f64, f64) {
+    let (track_index, item_index, item) = stack.get_item(item_id).unwrap();
+    let start = stack.children[track_index].start_time_of_item(item_index);
+    (track_index, start, item.duration())
+}
+
+// a: [gap 0..1][aud 1..3]
+// v: [gap 0..1][vid 1..3][other 3..5]
+fn stack() -> Stack {
+    let mut a = Track::new(TrackKind::Audio, Some("a".to_string()));
+    a.items.push(Item::Gap(Gap::make_gap(1.0)));
+    a.items.push(synced_clip_item(2.0, "aud", 4));
+    let mut v = Track::new(TrackKind::Video, Some("v".to_string()));
+    v.items.push(Item::Gap(Gap::make_gap(1.0)));
+    v.items.push(synced_clip_item(2.0, "vid", 4));
+    v.items.push(Item::Clip(clip(2.0, Some("other"))));
+    Stack {
+        children: vec![a, v],
+        ..Stack::default()
+    }
+}
+
+#[test]
+fn copying_a_clip_brings_its_sync_partners() {
+    let stack = stack();
+    let clipboard = stack.copy_selection(&ids(&["vid", "missing"]));
+    assert_eq!(clipboard.len(), 2);
+    assert_eq!(clipboard.items[0].track_offset, 0);
+    assert_eq!(clipboard.items[0].track_kind, TrackKind::Audio);
+    assert_eq!(clipboard.items[1].track_offset, 1);
+    assert_eq!(clipboard.items[0].start, 0.0);
+    assert_eq!(clipboard.duration(), 2.0);
+
+    assert!(stack.copy_selection(&ids(&["missing"])).is_empty());
+}
+
+#[test]
+fn paste_gives_fresh_ids_and_a_fresh_link_group() {
+    let mut stack = stack();
+    let before = stack.clone();
+    let clipboard = stack.copy_selection(&ids(&["vid"]));
+    let pasted = stack
+        .paste_selection(&clipboard, 6.0, 0, OverlapPolicy::Override)
+        .unwrap();
+    assert_eq!(pasted.len(), 2);
+    assert!(pasted.iter().all(|id| id != "vid" && id != "aud"));
+
+    assert_eq!(span_of(&stack, &pasted[0]), (0, 6.0, 2.0));
+    assert_eq!(span_of(&stack, &pasted[1]), (1, 6.0, 2.0));
+    let pasted_link = sync_clips_id(stack.get_item(&pasted[0]).unwrap().2);
+    assert!(pasted_link.is_some());
+    assert_ne!(pasted_link, Some(4));
+    assert_eq!(
+        sync_clips_id(stack.get_item(&pasted[1]).unwrap().2),
+        pasted_link
+    );
+    assert_eq!(stack.get_item("vid"), before.get_item("vid"));
+    assert_eq!(span_of(&stack, "other"), (1, 3.0, 2.0));
+
+    // Pasting the same clipboard again makes yet another group.
+    let again = stack
+        .paste_selection(&clipboard, 8.0, 0, OverlapPolicy::Override)
+        .unwrap();
+    let again_link = sync_clips_id(stack.get_item(&again[0]).unwrap().2);
+    assert_ne!(again_link, pasted_link);
+}
+
+#[test]
+fn override_overwrites_and_push_makes_room() {
+    let mut stack = stack();
+    let clipboard = stack.copy_selection(&ids(&["vid"]));
+
+    let mut overwritten = stack.clone();
+    let pasted = overwritten
+        .paste_selection(&clipboard, 2.0, 0, OverlapPolicy::Override)
+        .unwrap();
+    assert_eq!(span_of(&overwritten, &pasted[1]), (1, 2.0, 2.0));
+    assert_eq!(span_of(&overwritten, "vid"), (1, 1.0, 1.0));
+    // Only the tail of "other" is left, as a new piece.
+    assert!(overwritten.get_item("other").is_none());
+    assert_eq!(overwritten.children[1].total_duration(), 5.0);
+
+    let pasted = stack
+        .paste_selection(&clipboard, 3.0, 0, OverlapPolicy::Push)
+        .unwrap();
+    assert_eq!(span_of(&stack, &pasted[1]), (1, 3.0, 2.0));
+    assert_eq!(span_of(&stack, "vid"), (1, 1.0, 2.0));
+    assert_eq!(span_of(&stack, "other"), (1, 5.0, 2.0));
+}
+
+#[test]
+fn pasting_below_the_last_track_creates_numbered_tracks() {
+    let mut stack = stack();
+    let clipboard = stack.copy_selection(&ids(&["aud"]));
+    let pasted = stack
+        .paste_selection(&clipboard, 0.0, 2, OverlapPolicy::Override)
+        .unwrap();
+    // Each new track lands after the last track of its kind.
+    let tracks: Vec<(TrackKind, Option<String>)> = stack
+        .children
+        .iter()
+        .map(|track| (track.kind.clone(), track.name.clone()))
+        .collect();
+    assert_eq!(
+        tracks,
+        vec![
+            (TrackKind::Audio, None),
+            (TrackKind::Audio, Some("A1".to_string())),
+            (TrackKind::Video, None),
+            (TrackKind::Video, Some("V1".to_string())),
+        ]
+    );
+    assert_eq!(span_of(&stack, &pasted[0]), (1, 0.0, 2.0));
+    assert_eq!(span_of(&stack, &pasted[1]), (3, 0.0, 2.0));
+
+    assert!(stack
+        .paste_selection(&clipboard, 0.0, 5, OverlapPolicy::Override)
+        .is_none());
+    assert!(stack
+        .paste_selection(&Clipboard::default(), 0.0, 0, OverlapPolicy::Override)
+        .is_none());
+}
+
+#[test]
+fn pasting_onto_a_track_of_another_kind_is_refused() {
+    let mut stack = stack();
+    let before = stack.clone();
+    let clipboard = stack.copy_selection(&ids(&["aud"]));
+    // Audio would land on "v", video past the end.
+    assert!(stack
+        .paste_selection(&clipboard, 0.0, 1, OverlapPolicy::Override)
+        .is_none());
+    assert_eq!(stack, before);
+
+    let video_only = stack.copy_selection(&ids(&["other"]));
+    assert!(stack
+        .paste_selection(&video_only, 6.0, 0, OverlapPolicy::Push)
+        .is_none());
+    assert_eq!(stack, before);
+}
+
+#[test]
+fn only_wholly_copied_tellers_groups_are_pasted_as_new_groups() {
+    let mut stack = stack();
+    let group = stack.group_item(&ids(&["vid", "other"])).unwrap();
+
+    let whole = stack.copy_selection(&ids(&["vid", "other"]));
+    let pasted = stack
+        .paste_selection(&whole, 10.0, 0, OverlapPolicy::Override)
+        .unwrap();
+    let pasted_groups: Vec<Vec<i64>> = pasted
+        .iter()
+        .map(|id| item_tellers_group_ids(stack.get_item(id).unwrap().2))
+        .collect();
+    assert_eq!(pasted_groups.len(), 3);
+    assert!(pasted_groups
+        .iter()
+        .all(|groups| groups.len() == 1 && groups[0] != group));
+    assert!(pasted_groups.windows(2).all(|pair| pair[0] == pair[1]));
+
+    let part = stack.copy_selection(&ids(&["other"]));
+    assert!(item_tellers_group_ids(&part.items[0].item).is_empty());
+}
+
+#[test]
+fn clipboards_round_trip_through_json() {
+    let stack = stack();
+    let clipboard = stack.copy_selection(&ids(&["vid"]));
+    let json = serde_json::to_string(&clipboard).unwrap();
+    let parsed: Clipboard = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+}
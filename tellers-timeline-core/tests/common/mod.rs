@@ -99,6 +99,36 @@ pub fn audio_clip_with_available_duration(duration: f64, url: &str, available_du
     ))
 }
 
+pub fn media_clip(duration: f64, url: &str, id: &str) -> Clip {
+    let mut c = clip(duration, Some(id));
+    c.media_references
+        .insert("DEFAULT_MEDIA".to_string(), media_ref(url, None));
+    c
+}
+
+pub fn clip_item(duration: f64, url: &str, id: &str) -> Item {
+    Item::Clip(media_clip(duration, url, id))
+}
+
+/// Reads `source_start..source_start + duration` of media available over
+/// 10..70.
+pub fn used_media_clip(
+    url: &str,
+    media_id: Option<&str>,
+    source_start: f64,
+    duration: f64,
+    id: &str,
+) -> Item {
+    let mut reference = media_ref(url, media_id);
+    *reference.available_range_mut() = Some(TimeRange::new(60.0, 10.0));
+    Item::Clip(Clip::new_single_media_reference(
+        TimeRange::new(duration, source_start),
+        reference,
+        None,
+        Some(id.to_string()),
+    ))
+}
+
 pub fn clip_with_media_range(
     duration: f64,
     source_start: f64,
@@ -0,0 +1,167 @@
+//! Flattened composition: layers at an instant and contiguous segments.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{CompositionLayer, MediaReferenceCrop, MediaReferencePosition};
+
+// a1:  [A 0..6 (source starts at 10)]
+// v1:  [V1 0..4][gap 4..5][V2 5..8]
+// v2:  [gap 0..2][T 2..3]
+// a2:  [gap 0..1][gap 1..2.5]          (unsanitized, never audible)
+// fx:  [O 0..8]                        (Other track, ignored)
+fn composed_timeline() -> Timeline {
+    let mut a1 = Track::new(TrackKind::Audio, Some("a1".to_string()));
+    let mut a = media_clip(6.0, "file:///a.wav", "A");
+    a.source_range.start_time.value = 10.0;
+    a1.items.push(Item::Clip(a));
+
+    let mut v1 = Track::new(TrackKind::Video, Some("v1".to_string()));
+    v1.items
+        .push(Item::Clip(media_clip(4.0, "file:///v1.mov", "V1")));
+    v1.items.push(Item::Gap(Gap::make_gap(1.0)));
+    v1.items
+        .push(Item::Clip(media_clip(3.0, "file:///v2.mov", "V2")));
+
+    let mut v2 = Track::new(TrackKind::Video, Some("v2".to_string()));
+    v2.items.push(Item::Gap(Gap::make_gap(2.0)));
+    let mut title = media_clip(1.0, "file:///t.png", "T");
+    title.set_crop(MediaReferenceCrop {
+        crop_left: 0.1,
+        crop_right: 0.2,
+        crop_top: 0.0,
+        crop_bottom: 0.3,
+    });
+    title.set_volume(0.5);
+    v2.items.push(Item::Clip(title));
+
+    let mut a2 = Track::new(TrackKind::Audio, Some("a2".to_string()));
+    a2.items.push(Item::Gap(Gap::make_gap(1.0)));
+    a2.items.push(Item::Gap(Gap::make_gap(1.5)));
+
+    let mut other = Track::new(TrackKind::Other, Some("fx".to_string()));
+    other
+        .items
+        .push(Item::Clip(media_clip(8.0, "file:///o.mov", "O")));
+
+    Timeline {
+        tracks: Stack {
+            children: vec![a1, v1, v2, a2, other],
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    }
+}
+
+fn ids(layers: &[CompositionLayer]) -> Vec<&str> {
+    layers
+        .iter()
+        .map(|layer| layer.clip_id.as_deref().unwrap())
+        .collect()
+}
+
+#[test]
+fn composition_lists_top_video_first_and_every_audio_clip() {
+    let timeline = composed_timeline();
+
+    let composition = timeline.composition_at(2.5);
+    assert_eq!(ids(&composition.video), vec!["T", "V1"]);
+    assert_eq!(ids(&composition.audio), vec!["A"]);
+
+    let title = &composition.video[0];
+    assert_eq!(title.track_index, 2);
+    assert_eq!(title.item_index, 1);
+    assert_eq!(title.media_url.as_deref(), Some("file:///t.png"));
+    assert_eq!((title.start, title.end), (2.0, 3.0));
+    assert_eq!(title.source_time, 0.5);
+    assert_eq!(title.crop.crop_right, 0.2);
+    assert_eq!(title.volume, 0.5);
+    assert_eq!(
+        title.position,
+        MediaReferencePosition {
+            x: 0.0,
+            y: 0.0,
+            rotation: 0.0,
+            zoom_x: 1.0,
+            zoom_y: 1.0,
+        }
+    );
+
+    let audio = &composition.audio[0];
+    assert_eq!(audio.source_time, 12.5);
+    assert_eq!(audio.volume, 1.0);
+
+    assert!(timeline.composition_at(4.5).video.is_empty());
+    assert_eq!(timeline.composition_at(8.0), Default::default());
+}
+
+#[test]
+fn layers_carry_the_track_gain() {
+    let mut timeline = composed_timeline();
+    timeline.tracks.children[2].set_volume(0.5);
+    timeline.tracks.children[2].set_pan(0.3);
+
+    let title = &timeline.composition_at(2.5).video[0];
+    assert_eq!(title.volume, 0.5);
+    assert_eq!((title.track_volume, title.track_pan), (0.5, 0.3));
+    assert_eq!(title.effective_volume(), 0.25);
+    assert_eq!(timeline.tracks.effective_volume("T"), Some(0.25));
+    assert_eq!(timeline.tracks.effective_volume("V1"), Some(1.0));
+}
+
+#[test]
+fn disabled_clips_and_tracks_are_reported_but_inactive() {
+    let mut timeline = composed_timeline();
+    timeline.tracks.children[2].enabled = false;
+    timeline.tracks.children[1].items[0].set_enabled(false);
+
+    let composition = timeline.composition_at(2.5);
+    assert_eq!(ids(&composition.video), vec!["T", "V1"]);
+    assert!(!composition.video[0].track_enabled);
+    assert!(composition.video[0].clip_enabled);
+    assert!(!composition.video[1].clip_enabled);
+    assert!(composition.video.iter().all(|layer| !layer.is_active()));
+    assert!(composition.audio[0].is_active());
+}
+
+#[test]
+fn segments_tile_the_timeline_where_layers_change() {
+    let timeline = composed_timeline();
+    let segments: Vec<_> = timeline.segments().collect();
+
+    let spans: Vec<_> = segments.iter().map(|s| (s.start, s.end)).collect();
+    // 1.0 and 2.5 only separate gaps on a2, so they don't split anything.
+    assert_eq!(
+        spans,
+        vec![
+            (0.0, 2.0),
+            (2.0, 3.0),
+            (3.0, 4.0),
+            (4.0, 5.0),
+            (5.0, 6.0),
+            (6.0, 8.0)
+        ]
+    );
+    for segment in &segments {
+        assert_eq!(
+            segment.composition,
+            timeline.composition_at(segment.start),
+            "segment at {}",
+            segment.start
+        );
+    }
+    assert_eq!(ids(&segments[3].composition.video), Vec::<&str>::new());
+    assert_eq!(ids(&segments[5].composition.video), vec!["V2"]);
+    assert!(segments[5].composition.audio.is_empty());
+}
+
+#[test]
+fn empty_timeline_has_no_segments() {
+    assert_eq!(Timeline::default().segments().count(), 0);
+    let mut timeline = Timeline::default();
+    timeline
+        .tracks
+        .children
+        .push(Track::new(TrackKind::Video, None));
+    assert_eq!(timeline.segments().count(), 0);
+}
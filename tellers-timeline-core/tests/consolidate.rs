@@ -0,0 +1,160 @@
+//! `Timeline::consolidate`: trimming every media to its used span plus
+//! handles, rewriting the clips, and listing the transcode jobs.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::ConsolidateOptions;
+
+fn timeline(items: Vec<Item>) -> Timeline {
+    let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+    video.items = items;
+    Timeline {
+        tracks: Stack {
+            children: vec![video],
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    }
+}
+
+fn clip_at(timeline: &Timeline, index: usize) -> &Clip {
+    match &timeline.tracks.children[0].items[index] {
+        Item::Clip(clip) => clip,
+        Item::Gap(_) => panic!("expected a clip at {index}"),
+    }
+}
+
+fn options(handles: f64) -> ConsolidateOptions {
+    ConsolidateOptions {
+        handles,
+        target_dir: "s3://archive/project/".to_string(),
+        ..ConsolidateOptions::default()
+    }
+}
+
+#[test]
+fn trims_each_media_to_its_used_span_plus_handles() {
+    let mut timeline = timeline(vec![
+        used_media_clip("s3://rushes/a.mov?sig=1", Some("asset-a"), 20.0, 2.0, "a1"),
+        used_media_clip("s3://rushes/a.mov?sig=2", Some("asset-a"), 40.0, 5.0, "a2"),
+        used_media_clip("file:///cards/b.mxf", None, 10.5, 1.0, "b1"),
+    ]);
+
+    let report = timeline.consolidate(&options(2.0));
+
+    assert_eq!(report.jobs.len(), 2);
+    let a = &report.jobs[0];
+    assert_eq!(a.media_id.as_deref(), Some("asset-a"));
+    assert_eq!(a.source_url, "s3://rushes/a.mov?sig=1");
+    assert_eq!((a.source_in, a.source_out), (18.0, 47.0));
+    assert_eq!(a.target_url, "s3://archive/project/asset-a_18000-47000.mov");
+    // b starts 0.5s into the available range: the head handle is clamped.
+    let b = &report.jobs[1];
+    assert_eq!((b.source_in, b.source_out), (10.0, 13.5));
+    assert!(b.target_url.starts_with("s3://archive/project/b_"));
+    assert!(b.target_url.ends_with("_10000-13500.mxf"));
+
+    // Clips now read from the trimmed files, which start at 0.
+    let a2 = clip_at(&timeline, 1);
+    assert_eq!(a2.source_range.start_time.to_seconds(), 22.0);
+    assert_eq!(a2.source_range.duration.to_seconds(), 5.0);
+    assert_eq!(
+        active_target_url(&timeline.tracks.children[0].items[1]),
+        Some(a.target_url.as_str())
+    );
+    let reference = a2.active_media_reference().unwrap();
+    assert_eq!(reference.media_start(), 0.0);
+    assert_eq!(reference.media_duration(), Some(29.0));
+}
+
+#[test]
+fn names_are_deterministic_and_keep_same_named_sources_apart() {
+    let build = || {
+        timeline(vec![
+            used_media_clip("file:///day1/clip.mov", None, 20.0, 2.0, "x"),
+            used_media_clip("file:///day2/clip.mov", None, 20.0, 2.0, "y"),
+        ])
+    };
+
+    let first = build().consolidate(&options(0.0));
+    let second = build().consolidate(&options(0.0));
+
+    assert_eq!(first, second);
+    assert_ne!(first.jobs[0].target_url, first.jobs[1].target_url);
+}
+
+#[test]
+fn preserving_timecode_keeps_source_ranges() {
+    let mut timeline = timeline(vec![used_media_clip("file:///a.mov", None, 30.0, 2.0, "a")]);
+
+    let report = timeline.consolidate(&ConsolidateOptions {
+        extension: Some("mp4".to_string()),
+        preserve_timecode: true,
+        ..options(1.0)
+    });
+
+    assert!(report.jobs[0].target_url.ends_with("_29000-33000.mp4"));
+    let clip = clip_at(&timeline, 0);
+    assert_eq!(clip.source_range.start_time.to_seconds(), 30.0);
+    let reference = clip.active_media_reference().unwrap();
+    assert_eq!(reference.media_start(), 29.0);
+    assert_eq!(reference.media_duration(), Some(4.0));
+}
+
+#[test]
+fn media_without_url_is_skipped() {
+    let mut timeline = timeline(vec![used_media_clip("", Some("asset-a"), 20.0, 2.0, "a")]);
+
+    let report = timeline.consolidate(&options(1.0));
+
+    assert!(report.jobs.is_empty());
+    assert_eq!(report.skipped_media_ids, vec!["asset-a".to_string()]);
+    assert_eq!(
+        clip_at(&timeline, 0).source_range.start_time.to_seconds(),
+        20.0
+    );
+}
+
+#[test]
+fn clips_with_other_renditions_are_skipped_unless_timecode_is_kept() {
+    let with_proxy = || {
+        let mut item = used_media_clip("file:///proxy/a.mp4", None, 30.0, 2.0, "proxied");
+        if let Item::Clip(clip) = &mut item {
+            let proxy = clip.media_references.remove("DEFAULT_MEDIA").unwrap();
+            clip.media_references.insert("proxy".to_string(), proxy);
+            clip.media_references.insert(
+                "DEFAULT_MEDIA".to_string(),
+                media_ref("file:///rushes/a.mov", None),
+            );
+            clip.active_media_reference_key = Some("proxy".to_string());
+        }
+        timeline(vec![
+            item,
+            used_media_clip("file:///proxy/a.mp4", None, 40.0, 2.0, "plain"),
+        ])
+    };
+
+    let mut shifted = with_proxy();
+    let before = clip_at(&shifted, 0).clone();
+    let report = shifted.consolidate(&options(0.0));
+    assert_eq!(report.skipped_clip_ids, vec!["proxied".to_string()]);
+    assert_eq!(*clip_at(&shifted, 0), before);
+    // The skipped clip no longer widens the span.
+    assert_eq!(report.jobs.len(), 1);
+    assert_eq!(
+        (report.jobs[0].source_in, report.jobs[0].source_out),
+        (40.0, 42.0)
+    );
+
+    let mut kept = with_proxy();
+    let report = kept.consolidate(&ConsolidateOptions {
+        preserve_timecode: true,
+        ..options(0.0)
+    });
+    assert!(report.skipped_clip_ids.is_empty());
+    assert_eq!(
+        (report.jobs[0].source_in, report.jobs[0].source_out),
+        (30.0, 42.0)
+    );
+}
@@ -0,0 +1,343 @@
+//! CRDT replicas: concurrent edits converge and keep the grouping invariants.
+
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+use tellers_timeline_core::{
+    item_link_group_id, item_tellers_group_id, CrdtOp, Replica, SyncRepair,
+};
+
+fn origin_timeline() -> Timeline {
+    let mut timeline = Timeline {
+        tracks: two_synced_clips(),
+        ..Timeline::default()
+    };
+    let mut free = Track::new(TrackKind::Video, Some("v2".to_string()));
+    free.items.push(Item::Clip(clip(3.0, Some("c"))));
+    free.items.push(Item::Clip(clip(3.0, Some("d"))));
+    timeline.tracks.children.push(free);
+    timeline
+}
+
+fn ids(timeline: &Timeline, track_id: &str) -> Vec<String> {
+    let (_, track) = timeline.tracks.get_track_by_id(track_id).expect("track");
+    track.items.iter().filter_map(|i| i.get_id()).collect()
+}
+
+fn assert_no_singleton_groups(timeline: &Timeline) {
+    let mut link: HashMap<i64, usize> = HashMap::new();
+    let mut tellers: HashMap<i64, usize> = HashMap::new();
+    for item in timeline.tracks.children.iter().flat_map(|t| &t.items) {
+        if let Some(group) = item_link_group_id(item) {
+            *link.entry(group).or_default() += 1;
+        }
+        if let Some(group) = item_tellers_group_id(item) {
+            *tellers.entry(group).or_default() += 1;
+        }
+    }
+    assert!(
+        link.values().all(|n| *n > 1),
+        "singleton link group: {link:?}"
+    );
+    assert!(
+        tellers.values().all(|n| *n > 1),
+        "singleton tellers group: {tellers:?}"
+    );
+    assert_sync_clips_track_aligned(&timeline.tracks, "crdt");
+}
+
+/// Deliver `a_ops` and `b_ops` to fresh copies of both replicas in opposite
+/// orders and check that everyone ends up with the same timeline.
+fn exchange(a: &mut Replica, b: &mut Replica, a_ops: &[CrdtOp], b_ops: &[CrdtOp]) {
+    a.apply_remote(b_ops);
+    b.apply_remote(a_ops);
+    assert_eq!(a.timeline(), b.timeline());
+    assert_no_singleton_groups(a.timeline());
+}
+
+#[test]
+fn local_edit_matches_plain_stack_edit() {
+    let mut replica = Replica::new(1, &origin_timeline());
+    let mut expected = replica.timeline().clone();
+    expected.tracks.delete_item("c", false);
+
+    let (_, ops) = replica.edit(|stack| stack.delete_item("c", false));
+    assert!(!ops.is_empty());
+    assert_eq!(replica.timeline(), &expected);
+}
+
+#[test]
+fn concurrent_inserts_on_same_track_converge() {
+    let mut a = Replica::new(1, &origin_timeline());
+    let mut b = a.fork(2);
+
+    let (_, a_ops) = a.edit(|stack| {
+        let (index, _) = stack.get_track_by_id("v2").unwrap();
+        stack.children[index]
+            .items
+            .insert(1, Item::Clip(clip(1.0, Some("from-a"))));
+    });
+    let (_, b_ops) = b.edit(|stack| {
+        let (index, _) = stack.get_track_by_id("v2").unwrap();
+        stack.children[index]
+            .items
+            .insert(1, Item::Clip(clip(2.0, Some("from-b"))));
+    });
+    exchange(&mut a, &mut b, &a_ops, &b_ops);
+
+    // Both insertions anchor after "c"; the later stamp (replica 2) goes first.
+    assert_eq!(ids(a.timeline(), "v2"), vec!["c", "from-b", "from-a", "d"]);
+}
+
+#[test]
+fn concurrent_moves_of_same_item_keep_a_single_copy() {
+    let mut a = Replica::new(1, &origin_timeline());
+    let mut b = a.fork(2);
+
+    let (moved, a_ops) = a.edit(|stack| {
+        stack.move_item_at_time(
+            "c",
+            "v2",
+            10.0,
+            true,
+            InsertPolicy::InsertBefore,
+            OverlapPolicy::Override,
+        )
+    });
+    assert!(moved);
+    let (moved, b_ops) = b.edit(|stack| {
+        stack.move_item_at_time(
+            "c",
+            "v",
+            20.0,
+            true,
+            InsertPolicy::InsertBefore,
+            OverlapPolicy::Override,
+        )
+    });
+    assert!(moved);
+    exchange(&mut a, &mut b, &a_ops, &b_ops);
+
+    let copies = a
+        .timeline()
+        .tracks
+        .children
+        .iter()
+        .flat_map(|t| &t.items)
+        .filter(|i| i.get_id().as_deref() == Some("c"))
+        .count();
+    assert_eq!(copies, 1);
+    // Replica 2 wins the tie on the Lamport counter.
+    assert_eq!(ids(a.timeline(), "v").last().map(String::as_str), Some("c"));
+}
+
+#[test]
+fn concurrent_move_and_delete_of_synced_clips_converge() {
+    let mut a = Replica::new(1, &origin_timeline());
+    let mut b = a.fork(2);
+
+    let (moved, a_ops) = a.edit(|stack| {
+        stack.move_item_at_time(
+            "vB",
+            "v",
+            12.0,
+            true,
+            InsertPolicy::InsertBefore,
+            OverlapPolicy::Override,
+        )
+    });
+    assert!(moved);
+    let (_, b_ops) = b.edit(|stack| {
+        stack.unsync_item(&["aB".to_string()]);
+        stack.delete_item("aB", true)
+    });
+    exchange(&mut a, &mut b, &a_ops, &b_ops);
+
+    // The move lands, and vB stays unsynced with its partner gone.
+    let (_, _, vb) = a.timeline().tracks.get_item("vB").unwrap();
+    assert_eq!(item_link_group_id(vb), None);
+    assert!(a.timeline().tracks.get_item("aB").is_none());
+}
+
+#[test]
+fn sync_and_concurrent_delete_leave_no_singleton_group() {
+    let mut a = Replica::new(1, &origin_timeline());
+    let mut b = a.fork(2);
+
+    let (group, a_ops) = a.edit(|stack| {
+        stack.unsync_item(&["vA".to_string()]);
+        stack.sync_item(&["c".to_string(), "d".to_string()])
+    });
+    assert!(group.is_some());
+    let (_, b_ops) = b.edit(|stack| stack.delete_item("d", true));
+    exchange(&mut a, &mut b, &a_ops, &b_ops);
+
+    let (_, _, c) = a.timeline().tracks.get_item("c").unwrap();
+    assert_eq!(item_link_group_id(c), None);
+}
+
+fn item_start(timeline: &Timeline, id: &str) -> f64 {
+    let (ti, ii, _) = timeline.tracks.get_item(id).expect("item");
+    timeline.tracks.children[ti].start_time_of_item(ii)
+}
+
+#[test]
+fn concurrent_shifts_that_break_a_sync_layout_realign_the_group() {
+    let timeline = Timeline {
+        tracks: stack_with_synced_audio_below_video(),
+        ..Timeline::default()
+    };
+    let mut a = Replica::new(1, &timeline);
+    let mut b = a.fork(2);
+
+    // Each side nudges one member by a second, which is a valid (if unusual)
+    // layout on its own; together the members no longer match either layout.
+    let (_, a_ops) = a.edit(|stack| {
+        let index = track_index_by_id(stack, "a");
+        stack.children[index]
+            .items
+            .insert(1, Item::Gap(Gap::make_gap(1.0)));
+    });
+    let (_, b_ops) = b.edit(|stack| {
+        let index = track_index_by_id(stack, "v");
+        stack.children[index]
+            .items
+            .insert(1, Item::Gap(Gap::make_gap(1.0)));
+    });
+    exchange(&mut a, &mut b, &a_ops, &b_ops);
+
+    // Replica 2's layout wins; the audio is moved back under it and the
+    // pair stays linked.
+    let merged = a.timeline();
+    let group = |id| item_link_group_id(merged.tracks.get_item(id).unwrap().2);
+    assert!(group("linked-video").is_some());
+    assert_eq!(group("linked-video"), group("linked-audio"));
+    assert_eq!(item_start(merged, "linked-video"), 3.0);
+    assert_eq!(item_start(merged, "linked-audio"), 2.0);
+    assert_eq!(
+        a.sync_repairs(),
+        [SyncRepair::Realigned {
+            group_id: group("linked-audio").unwrap(),
+            item_id: "linked-audio".to_string(),
+        }]
+    );
+    assert_eq!(a.sync_repairs(), b.sync_repairs());
+}
+
+#[test]
+fn sync_member_whose_place_is_taken_is_unlinked_and_reported() {
+    let timeline = Timeline {
+        tracks: stack_with_synced_audio_below_video(),
+        ..Timeline::default()
+    };
+    let mut a = Replica::new(1, &timeline);
+    let mut b = a.fork(2);
+    let (_, _, video) = timeline.tracks.get_item("linked-video").unwrap();
+    let group_id = item_link_group_id(video).unwrap();
+
+    // Ours drops a clip right where the audio sat and pushes the audio on.
+    let (_, a_ops) = a.edit(|stack| {
+        let index = track_index_by_id(stack, "a");
+        stack.children[index]
+            .items
+            .insert(1, Item::Clip(clip(1.0, Some("blocker"))));
+    });
+    let (_, b_ops) = b.edit(|stack| {
+        let index = track_index_by_id(stack, "v");
+        stack.children[index]
+            .items
+            .insert(1, Item::Gap(Gap::make_gap(1.0)));
+    });
+    exchange(&mut a, &mut b, &a_ops, &b_ops);
+
+    assert_eq!(
+        a.sync_repairs(),
+        [SyncRepair::Unlinked {
+            group_id,
+            item_id: "linked-audio".to_string(),
+        }]
+    );
+    for id in ["linked-audio", "linked-video", "blocker"] {
+        let (_, _, item) = a.timeline().tracks.get_item(id).unwrap();
+        assert_eq!(item_link_group_id(item), None, "{id} still synced");
+    }
+    assert_eq!(item_start(a.timeline(), "blocker"), 2.0);
+}
+
+#[test]
+fn ops_are_idempotent_and_tolerate_reordering() {
+    let origin = Replica::new(1, &origin_timeline());
+    let mut a = origin.clone();
+    let (_, first) = a.edit(|stack| {
+        let (index, _) = stack.get_track_by_id("v2").unwrap();
+        stack.children[index]
+            .items
+            .push(Item::Clip(clip(1.0, Some("e"))));
+    });
+    let (_, second) = a.edit(|stack| {
+        let (index, _) = stack.get_track_by_id("v2").unwrap();
+        stack.children[index]
+            .items
+            .push(Item::Clip(clip(1.0, Some("f"))));
+    });
+
+    // "f" anchors on "e": delivered first, it waits.
+    let mut b = origin.fork(2);
+    b.apply_remote(&second);
+    assert!(b.pending_ops() > 0);
+    b.apply_remote(&first);
+    assert_eq!(b.pending_ops(), 0);
+    assert_eq!(b.timeline(), a.timeline());
+
+    b.apply_remote(&first);
+    b.apply_remote(&second);
+    assert_eq!(b.timeline(), a.timeline());
+}
+
+#[test]
+fn ops_round_trip_through_json() {
+    let mut a = Replica::new(1, &origin_timeline());
+    let mut b = a.fork(2);
+    let (_, ops) = a.edit(|stack| {
+        stack.move_item_at_time(
+            "d",
+            "v2",
+            0.0,
+            true,
+            InsertPolicy::InsertBefore,
+            OverlapPolicy::Override,
+        )
+    });
+    let wire = serde_json::to_string(&ops).unwrap();
+    let decoded: Vec<CrdtOp> = serde_json::from_str(&wire).unwrap();
+    assert_eq!(decoded, ops);
+    b.apply_remote(&decoded);
+    assert_eq!(b.timeline(), a.timeline());
+}
+
+#[test]
+fn replicas_of_an_id_less_timeline_start_identical() {
+    let mut timeline = origin_timeline();
+    for track in &mut timeline.tracks.children {
+        track.set_id(None);
+        for item in &mut track.items {
+            item.set_id(None);
+        }
+    }
+    let mut a = Replica::new(1, &timeline);
+    let mut b = Replica::new(2, &timeline);
+    assert_eq!(a.timeline(), b.timeline());
+    let track_id = a.timeline().tracks.children[0].get_id().unwrap();
+    let item_id = a.timeline().tracks.children[0].items[0].get_id().unwrap();
+
+    // Ops made on one replica find their targets on the other.
+    let (_, a_ops) = a.edit(|stack| stack.delete_item(&item_id, false));
+    let (_, b_ops) = b.edit(|stack| stack.reorder_track(&track_id, 3));
+    exchange(&mut a, &mut b, &a_ops, &b_ops);
+    assert!(a.timeline().tracks.get_item(&item_id).is_none());
+    assert_eq!(
+        a.timeline().tracks.children.last().unwrap().get_id(),
+        Some(track_id)
+    );
+}
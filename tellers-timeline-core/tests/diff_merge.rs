@@ -0,0 +1,365 @@
+//! Structural diff and three-way merge keyed on `tellers.ai.timeline_id`.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{
+    diff_timelines, merge_timelines, ItemConflictAspect, ItemProperties, MergeConflict, MergeSide,
+    MetadataExt, TimelineChange, TrackConflictAspect, TrackProperties,
+};
+
+fn base_timeline() -> Timeline {
+    let mut video = Track::new(TrackKind::Video, Some("v1".to_string()));
+    video.items.push(Item::Clip(clip(4.0, Some("c1"))));
+    video.items.push(Item::Gap(Gap::make_gap(2.0)));
+    video.items.push(Item::Clip(clip(4.0, Some("c2"))));
+    let mut audio = Track::new(TrackKind::Audio, Some("a1".to_string()));
+    audio.items.push(Item::Clip(clip(6.0, Some("m1"))));
+    let mut timeline = Timeline::default();
+    timeline.tracks.children.push(audio);
+    timeline.tracks.children.push(video);
+    timeline
+}
+
+fn item_start(timeline: &Timeline, id: &str) -> (String, f64) {
+    let (track_index, item_index, _) = timeline.tracks.get_item(id).expect("item exists");
+    let track = &timeline.tracks.children[track_index];
+    (track.get_id().unwrap(), track.start_time_of_item(item_index))
+}
+
+#[test]
+fn identical_timelines_have_empty_diff() {
+    let timeline = base_timeline();
+    assert!(diff_timelines(&timeline, &timeline.clone()).is_empty());
+}
+
+#[test]
+fn diff_reports_added_removed_moved_and_trimmed_items() {
+    let before = base_timeline();
+    let mut after = before.clone();
+    assert!(after.tracks.move_item_at_time(
+        "c2",
+        "v1",
+        12.0,
+        true,
+        InsertPolicy::InsertBefore,
+        OverlapPolicy::Override,
+    ));
+    after.tracks.delete_item("m1", true);
+    let (ti, ii, _) = after.tracks.get_item("c1").unwrap();
+    let mut trimmed = after.tracks.children[ti].items[ii].get_source_range();
+    trimmed.set_duration(3.0);
+    after.tracks.children[ti].items[ii].set_source_range(trimmed);
+    after.tracks.children[ti]
+        .items
+        .push(Item::Clip(clip(1.0, Some("new"))));
+
+    let diff = diff_timelines(&before, &after);
+    assert!(diff.changes.iter().any(|c| matches!(
+        c,
+        TimelineChange::ItemRemoved { item_id, track_id, .. } if item_id == "m1" && track_id == "a1"
+    )));
+    assert!(diff.changes.iter().any(|c| matches!(
+        c,
+        TimelineChange::ItemAdded { item_id, .. } if item_id == "new"
+    )));
+    assert!(diff.changes.iter().any(|c| matches!(
+        c,
+        TimelineChange::ItemMoved { item_id, from_start, to_start, .. }
+            if item_id == "c2" && (*from_start - 6.0).abs() < 1e-9 && (*to_start - 11.0).abs() < 1e-9
+    )));
+    assert!(diff.changes.iter().any(|c| matches!(
+        c,
+        TimelineChange::ItemTrimmed { item_id, after, .. }
+            if item_id == "c1" && (after.get_duration() - 3.0).abs() < 1e-9
+    )));
+}
+
+#[test]
+fn diff_reports_track_reorder_and_metadata_and_effect_changes() {
+    let before = base_timeline();
+    let mut after = before.clone();
+    assert!(after.reorder_track("v1", 0));
+    after.tracks.children[0].name = Some("Main".to_string());
+    after.tracks.children[0].get_metadata_mut()["note"] = serde_json::json!("x");
+    let (ti, ii, _) = after.tracks.get_item("c1").unwrap();
+    after.tracks.children[ti].items[ii].set_volume(0.5);
+    after.tracks.children[ti].items[ii].get_metadata_mut()["tag"] = serde_json::json!(1);
+
+    let changes = diff_timelines(&before, &after).changes;
+    assert!(changes.contains(&TimelineChange::TracksReordered {
+        before: vec!["a1".to_string(), "v1".to_string()],
+        after: vec!["v1".to_string(), "a1".to_string()],
+    }));
+    assert!(changes.contains(&TimelineChange::TrackPropertiesChanged {
+        track_id: "v1".to_string(),
+        before: TrackProperties {
+            name: None,
+            kind: TrackKind::Video,
+            enabled: true,
+        },
+        after: TrackProperties {
+            name: Some("Main".to_string()),
+            kind: TrackKind::Video,
+            enabled: true,
+        },
+    }));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, TimelineChange::TrackMetadataChanged { track_id, .. } if track_id == "v1")));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, TimelineChange::ItemEffectsChanged { item_id, .. } if item_id == "c1")));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, TimelineChange::ItemMetadataChanged { item_id, .. } if item_id == "c1")));
+    // Nothing moved: reordering tracks does not move the clips on them.
+    assert!(!changes
+        .iter()
+        .any(|c| matches!(c, TimelineChange::ItemMoved { .. })));
+}
+
+#[test]
+fn merge_applies_non_conflicting_changes_from_both_sides() {
+    let base = base_timeline();
+
+    let mut ours = base.clone();
+    let (ti, ii, _) = ours.tracks.get_item("c1").unwrap();
+    ours.tracks.children[ti].items[ii].set_volume(0.25);
+
+    let mut theirs = base.clone();
+    assert!(theirs.tracks.move_item_at_time(
+        "c2",
+        "v1",
+        12.0,
+        true,
+        InsertPolicy::InsertBefore,
+        OverlapPolicy::Override,
+    ));
+    theirs.tracks.delete_item("m1", true);
+
+    let result = merge_timelines(&base, &ours, &theirs);
+    assert!(result.is_clean(), "{:?}", result.conflicts);
+    let merged = &result.timeline;
+    assert!(merged.tracks.get_item("m1").is_none());
+    assert_eq!(item_start(merged, "c2"), ("v1".to_string(), 12.0));
+    let (_, _, c1) = merged.tracks.get_item("c1").unwrap();
+    assert!((c1.get_volume() - 0.25).abs() < 1e-9);
+}
+
+#[test]
+fn merge_reports_overlapping_edits_on_same_track_range() {
+    let base = base_timeline();
+
+    // Ours drops a new clip into the gap at 4..6 on v1.
+    let mut ours = base.clone();
+    let v1 = track_index_by_id(&ours.tracks, "v1");
+    ours.tracks.children[v1].items[1] = Item::Clip(clip(2.0, Some("ours-new")));
+
+    // Theirs extends c1 over the same gap.
+    let mut theirs = base.clone();
+    let v1 = track_index_by_id(&theirs.tracks, "v1");
+    theirs.tracks.children[v1].items[0].set_duration(6.0);
+    theirs.tracks.children[v1].items.remove(1);
+
+    let result = merge_timelines(&base, &ours, &theirs);
+    assert_eq!(
+        result.conflicts,
+        vec![MergeConflict::OverlappingRange {
+            track_id: "v1".to_string(),
+            start: 4.0,
+            end: 6.0,
+            kept_item_id: "ours-new".to_string(),
+            reverted_item_id: "c1".to_string(),
+        }]
+    );
+    let merged = &result.timeline;
+    let (_, _, c1) = merged.tracks.get_item("c1").unwrap();
+    assert!((c1.duration() - 4.0).abs() < 1e-9);
+    assert_eq!(item_start(merged, "ours-new"), ("v1".to_string(), 4.0));
+    assert_eq!(item_start(merged, "c2"), ("v1".to_string(), 6.0));
+}
+
+#[test]
+fn merge_reports_concurrent_edits_and_keeps_ours() {
+    let base = base_timeline();
+    let mut ours = base.clone();
+    let mut theirs = base.clone();
+    for (timeline, volume) in [(&mut ours, 0.2), (&mut theirs, 0.8)] {
+        let (ti, ii, _) = timeline.tracks.get_item("c2").unwrap();
+        timeline.tracks.children[ti].items[ii].set_volume(volume);
+    }
+    // Ours deletes m1 while theirs edits it.
+    ours.tracks.delete_item("m1", true);
+    let (ti, ii, _) = theirs.tracks.get_item("m1").unwrap();
+    theirs.tracks.children[ti].items[ii].set_enabled(false);
+
+    let result = merge_timelines(&base, &ours, &theirs);
+    assert!(result.conflicts.contains(&MergeConflict::Item {
+        item_id: "c2".to_string(),
+        aspect: ItemConflictAspect::Content,
+    }));
+    assert!(result.conflicts.contains(&MergeConflict::Item {
+        item_id: "m1".to_string(),
+        aspect: ItemConflictAspect::Presence,
+    }));
+    let (_, _, c2) = result.timeline.tracks.get_item("c2").unwrap();
+    assert!((c2.get_volume() - 0.2).abs() < 1e-9);
+    assert!(result.timeline.tracks.get_item("m1").is_none());
+}
+
+#[test]
+fn merge_keeps_track_removed_by_theirs_when_ours_edited_it() {
+    let base = base_timeline();
+    let mut ours = base.clone();
+    let (ti, ii, _) = ours.tracks.get_item("m1").unwrap();
+    ours.tracks.children[ti].items[ii].set_volume(0.5);
+    let mut theirs = base.clone();
+    theirs.delete_track("a1").unwrap();
+
+    let result = merge_timelines(&base, &ours, &theirs);
+    assert!(result.conflicts.contains(&MergeConflict::Track {
+        track_id: "a1".to_string(),
+        aspect: TrackConflictAspect::Presence,
+        removed_by: Some(MergeSide::Theirs),
+    }));
+    assert!(result.timeline.tracks.get_item("m1").is_some());
+
+    // Without the edit on ours, the removal merges cleanly.
+    let result = merge_timelines(&base, &base, &theirs);
+    assert!(result.is_clean());
+    assert!(result.timeline.tracks.get_track_by_id("a1").is_none());
+}
+
+#[test]
+fn diff_reports_item_properties_before_and_after() {
+    let before = base_timeline();
+    let mut after = before.clone();
+    let (ti, ii, _) = after.tracks.get_item("c2").unwrap();
+    after.tracks.children[ti].items[ii].set_enabled(false);
+
+    let changes = diff_timelines(&before, &after).changes;
+    let Some(TimelineChange::ItemPropertiesChanged {
+        item_id,
+        before,
+        after,
+    }) = changes
+        .iter()
+        .find(|c| matches!(c, TimelineChange::ItemPropertiesChanged { .. }))
+    else {
+        panic!("no property change in {changes:?}");
+    };
+    assert_eq!(item_id, "c2");
+    assert!(before.enabled);
+    assert_eq!(
+        after,
+        &ItemProperties {
+            enabled: false,
+            ..before.clone()
+        }
+    );
+}
+
+#[test]
+fn ripple_on_ours_and_trim_on_theirs_merge_cleanly() {
+    let mut base = Timeline::default();
+    let mut video = Track::new(TrackKind::Video, Some("v1".to_string()));
+    for id in ["c1", "c2", "c3"] {
+        video.items.push(Item::Clip(clip(4.0, Some(id))));
+    }
+    base.tracks.children.push(video);
+
+    let mut ours = base.clone();
+    ours.tracks.delete_item("c1", false);
+    let mut theirs = base.clone();
+    let (ti, ii, _) = theirs.tracks.get_item("c3").unwrap();
+    theirs.tracks.children[ti].items[ii].set_duration(2.0);
+
+    let result = merge_timelines(&base, &ours, &theirs);
+    assert!(result.is_clean(), "{:?}", result.conflicts);
+    let merged = &result.timeline;
+    assert!(merged.tracks.get_item("c1").is_none());
+    assert_eq!(item_start(merged, "c3"), ("v1".to_string(), 4.0));
+    let (_, _, c3) = merged.tracks.get_item("c3").unwrap();
+    assert!((c3.duration() - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn concurrent_moves_and_trims_conflict_separately() {
+    let base = base_timeline();
+    let mut ours = base.clone();
+    let mut theirs = base.clone();
+    for (timeline, duration) in [(&mut ours, 3.0), (&mut theirs, 2.0)] {
+        let (ti, ii, _) = timeline.tracks.get_item("c2").unwrap();
+        timeline.tracks.children[ti].items[ii].set_duration(duration);
+    }
+
+    let result = merge_timelines(&base, &ours, &theirs);
+    assert_eq!(
+        result.conflicts,
+        vec![MergeConflict::Item {
+            item_id: "c2".to_string(),
+            aspect: ItemConflictAspect::SourceRange,
+        }]
+    );
+    let (_, _, c2) = result.timeline.tracks.get_item("c2").unwrap();
+    assert!((c2.duration() - 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn merge_reports_conflicting_track_reorders() {
+    let mut base = base_timeline();
+    base.tracks
+        .children
+        .push(Track::new(TrackKind::Video, Some("v2".to_string())));
+
+    let mut ours = base.clone();
+    assert!(ours.reorder_track("v2", 0));
+    let mut theirs = base.clone();
+    assert!(theirs.reorder_track("v1", 0));
+
+    let result = merge_timelines(&base, &ours, &theirs);
+    assert_eq!(
+        result.conflicts,
+        vec![MergeConflict::TrackOrder {
+            ours: vec!["v2".to_string(), "a1".to_string(), "v1".to_string()],
+            theirs: vec!["v1".to_string(), "a1".to_string(), "v2".to_string()],
+        }]
+    );
+    let order: Vec<_> = result
+        .timeline
+        .tracks
+        .children
+        .iter()
+        .map(|track| track.get_id().unwrap())
+        .collect();
+    assert_eq!(order, vec!["v2", "a1", "v1"]);
+
+    // The same reorder on both sides is not a conflict.
+    assert!(merge_timelines(&base, &ours, &ours).is_clean());
+}
+
+#[test]
+fn tracks_and_clips_without_ids_survive_diff_and_merge() {
+    let mut timeline = base_timeline();
+    let mut loose = Track::new(TrackKind::Video, None);
+    loose.set_id(None);
+    let mut untracked = clip(3.0, None);
+    untracked.set_id(None);
+    loose.items.push(Item::Gap(Gap::make_gap(1.0)));
+    loose.items.push(Item::Clip(untracked));
+    timeline.tracks.children.push(loose);
+    assert!(timeline.tracks.children[2].get_id().is_none());
+
+    assert!(diff_timelines(&timeline, &timeline.clone()).is_empty());
+    let merged = merge_timelines(&timeline, &timeline, &timeline);
+    assert!(merged.conflicts.is_empty());
+    assert_eq!(merged.timeline.tracks.children.len(), 3);
+    let loose = &merged.timeline.tracks.children[2];
+    assert_eq!(loose.items.len(), 2);
+    assert_eq!(loose.items[1].duration(), 3.0);
+
+    let mut trimmed = timeline.clone();
+    trimmed.tracks.children[2].items[1].set_duration(2.0);
+    assert_eq!(diff_timelines(&timeline, &trimmed).changes.len(), 1);
+}
@@ -0,0 +1,143 @@
+//! `Stack::preview_*`: what an edit would split, trim, delete, push and
+//! create, reported without touching the stack.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::EditPreview;
+
+// v: [A 0..4][B 4..6]
+fn stack() -> Stack {
+    let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+    video.items.push(clip_item(4.0, "file:///a.mov", "A"));
+    video.items.push(clip_item(2.0, "file:///b.mov", "B"));
+    Stack {
+        children: vec![video],
+        ..Stack::default()
+    }
+}
+
+#[test]
+fn an_override_insert_reports_the_split_it_makes() {
+    let stack = stack();
+    let before = stack.clone();
+
+    let preview = stack.preview_insert_item_at_time(
+        0,
+        1.0,
+        clip_item(1.0, "file:///n.mov", "N"),
+        OverlapPolicy::Override,
+        InsertPolicy::SplitAndInsert,
+        None,
+        None,
+    );
+
+    assert_eq!(stack, before);
+    assert!(preview.result.success);
+    assert!(preview.result.deleted_clips.is_empty());
+    let [split] = preview.result.split_clips.as_slice() else {
+        panic!("expected one split, got {:?}", preview.result.split_clips);
+    };
+    assert_eq!(split.old_clip_id, "A");
+    assert_eq!(split.left_clip_id.as_deref(), Some("A"));
+    assert_eq!(split.split_time, 2.0);
+
+    assert!(preview.trimmed_clip_ids.is_empty() && preview.pushed_clip_ids.is_empty());
+}
+
+#[test]
+fn pushed_trimmed_and_deleted_clips_are_told_apart() {
+    let stack = stack();
+
+    let push = stack.preview_insert_item_at_time(
+        0,
+        4.0,
+        clip_item(1.0, "file:///n.mov", "N"),
+        OverlapPolicy::Push,
+        InsertPolicy::InsertBefore,
+        None,
+        None,
+    );
+    assert_eq!(push.pushed_clip_ids, vec!["B"]);
+    assert!(push.result.split_clips.is_empty());
+
+    // B growing over A's tail trims A in place.
+    let trim = stack.preview_resize_item("B", 3.0, 3.0, OverlapPolicy::Override, false);
+    assert!(trim.result.success);
+    assert_eq!(trim.trimmed_clip_ids, vec!["A"]);
+    assert!(trim.result.split_clips.is_empty() && trim.pushed_clip_ids.is_empty());
+
+    // A growing over B's head leaves only a new piece of B.
+    let cut = stack.preview_resize_item("A", 0.0, 5.0, OverlapPolicy::Override, false);
+    assert_eq!(cut.result.deleted_clips.len(), 1);
+    assert_eq!(cut.result.deleted_clips[0].clip_id, "B");
+    assert_eq!(cut.result.split_clips[0].old_clip_id, "B");
+    assert_eq!(cut.result.split_clips[0].left_clip_id, None);
+    assert_eq!(cut.result.split_clips[0].split_time, 5.0);
+    assert!(cut.trimmed_clip_ids.is_empty());
+
+    let delete = stack.preview_delete_item("A", true);
+    let deleted: Vec<_> = delete
+        .result
+        .deleted_clips
+        .iter()
+        .map(|clip| clip.clip_id.as_str())
+        .collect();
+    assert_eq!(deleted, vec!["A"]);
+    assert!(delete.pushed_clip_ids.is_empty());
+}
+
+#[test]
+fn failing_edits_preview_as_unsuccessful() {
+    let stack = stack();
+    assert_eq!(
+        stack.preview_split_item_at_time("missing", 1.0),
+        EditPreview::default()
+    );
+    assert!(stack.preview_split_item_at_time("A", 1.0).result.success);
+}
+
+#[test]
+fn sync_partners_and_created_tracks_are_reported() {
+    let stack = stack_with_synced_audio_below_video();
+    let before = stack.clone();
+
+    let preview = stack.preview_move_item_at_time(
+        "linked-video",
+        "v",
+        6.0,
+        true,
+        InsertPolicy::InsertBefore,
+        OverlapPolicy::Override,
+    );
+    assert_eq!(stack, before);
+    assert!(preview.result.success);
+    assert_eq!(preview.moved_sync_partner_ids, vec!["linked-audio"]);
+    assert!(preview.pushed_clip_ids.is_empty());
+
+    let mut video_only = Stack::default();
+    video_only
+        .children
+        .push(Track::new(TrackKind::Video, Some("v".to_string())));
+    let audio = vec![
+        audio_clip(2.0, "file:///a1.wav", None),
+        audio_clip(2.0, "file:///a2.wav", None),
+    ];
+    let preview = video_only.preview_insert_item_at_time(
+        0,
+        0.0,
+        Item::Clip(clip(2.0, Some("V"))),
+        OverlapPolicy::Override,
+        InsertPolicy::InsertBefore,
+        Some(audio.clone()),
+        None,
+    );
+    assert_eq!(video_only.children.len(), 1);
+    let inserted = insert_with_audio(&mut video_only, 0, 0.0, clip(2.0, Some("V")), audio).unwrap();
+    assert_eq!(
+        preview.created_track_indices,
+        inserted.created_track_indices
+    );
+    assert!(!preview.created_track_indices.is_empty());
+    assert!(preview.result.split_clips.is_empty());
+}
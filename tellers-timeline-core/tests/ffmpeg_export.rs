@@ -0,0 +1,173 @@
+//! ffmpeg export: filtergraph text checked against golden render scripts.
+//!
+//! Set `UPDATE_GOLDEN=1` to rewrite the files under tests/fixtures/ffmpeg
+//! after an intended change, then review the diff.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{
+    FfmpegExportOptions, FfmpegInput, MediaReferenceCrop, MediaReferencePosition,
+};
+
+fn assert_golden(name: &str, actual: &str) {
+    let path = fixture_path("ffmpeg").join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).expect("write golden");
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).expect("golden file");
+    assert_eq!(
+        actual,
+        expected,
+        "{} differs; rerun with UPDATE_GOLDEN=1",
+        path.display()
+    );
+}
+
+// a1:  [A 0..6 (source 10..16, volume 0.5)]
+// v1:  [V1 0..4][gap 4..5][off 5..6 (disabled)][V2 6..8]
+// v2:  [gap 0..2][T 2..3 (cropped, moved, zoomed, rotated)]
+// v3:  [gap 0..8]                      (nothing to render)
+fn composed_timeline() -> Timeline {
+    let mut a1 = Track::new(TrackKind::Audio, Some("a1".to_string()));
+    let mut a = media_clip(6.0, "file:///media/a.wav", "A");
+    a.source_range.start_time.value = 10.0;
+    a.set_volume(0.5);
+    a1.items.push(Item::Clip(a));
+
+    let mut v1 = Track::new(TrackKind::Video, Some("v1".to_string()));
+    v1.items
+        .push(Item::Clip(media_clip(4.0, "file:///media/v1.mov", "V1")));
+    v1.items.push(Item::Gap(Gap::make_gap(1.0)));
+    let mut off = media_clip(1.0, "file:///media/v1.mov", "off");
+    off.enabled = false;
+    v1.items.push(Item::Clip(off));
+    v1.items
+        .push(Item::Clip(media_clip(2.0, "file:///media/v1.mov", "V2")));
+
+    let mut v2 = Track::new(TrackKind::Video, Some("v2".to_string()));
+    v2.items.push(Item::Gap(Gap::make_gap(2.0)));
+    let mut title = media_clip(1.0, "file:///media/logo.png", "T");
+    title.set_crop(MediaReferenceCrop {
+        crop_left: 0.1,
+        crop_right: 0.2,
+        crop_top: 0.0,
+        crop_bottom: 0.25,
+    });
+    title.set_position(MediaReferencePosition {
+        x: 0.25,
+        y: 0.1,
+        rotation: 90.0,
+        zoom_x: 0.5,
+        zoom_y: 0.5,
+    });
+    v2.items.push(Item::Clip(title));
+
+    let mut v3 = Track::new(TrackKind::Video, Some("v3".to_string()));
+    v3.items.push(Item::Gap(Gap::make_gap(8.0)));
+
+    Timeline {
+        tracks: Stack {
+            children: vec![a1, v1, v2, v3],
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    }
+}
+
+#[test]
+fn composed_timeline_matches_golden_script() {
+    let export = composed_timeline().to_ffmpeg(&FfmpegExportOptions::default());
+    assert_eq!(export.duration, 8.0);
+    assert_eq!(
+        export.inputs,
+        vec![
+            FfmpegInput {
+                url: "file:///media/a.wav".to_string(),
+                still_image: false,
+            },
+            FfmpegInput {
+                url: "file:///media/v1.mov".to_string(),
+                still_image: false,
+            },
+            FfmpegInput {
+                url: "file:///media/logo.png".to_string(),
+                still_image: true,
+            },
+        ]
+    );
+    assert_golden("composed.sh", &export.to_shell_script("out.mp4"));
+}
+
+#[test]
+fn resolve_project_matches_golden_script() {
+    let json = std::fs::read_to_string(fixture_path("new_project_delete.otio")).expect("fixture");
+    let mut timeline: Timeline = serde_json::from_str(&json).expect("parse");
+    timeline.sanitize();
+
+    let options = FfmpegExportOptions {
+        width: 1280,
+        height: 720,
+        frame_rate: 25.0,
+        sample_rate: 44100,
+    };
+    let export = timeline.to_ffmpeg(&options);
+    assert_golden(
+        "new_project_delete.sh",
+        &export.to_shell_script("preview.mp4"),
+    );
+}
+
+#[test]
+fn disabled_tracks_are_left_out() {
+    let mut timeline = composed_timeline();
+    timeline.tracks.children[0].enabled = false;
+    timeline.tracks.children[2].enabled = false;
+
+    let export = timeline.to_ffmpeg(&FfmpegExportOptions::default());
+    let urls: Vec<_> = export
+        .inputs
+        .iter()
+        .map(|input| input.url.as_str())
+        .collect();
+    assert_eq!(urls, vec!["file:///media/v1.mov"]);
+    assert!(!export.filter_complex.contains("amix"));
+    assert!(export
+        .filter_complex
+        .contains("[0:v]trim=start=0:duration=4"));
+    assert!(!export.filter_complex.contains("[vt2]"));
+    // The duration still covers every track, so the output length is stable
+    // while tracks are toggled.
+    assert_eq!(export.duration, 8.0);
+}
+
+#[test]
+fn track_gain_and_pan_apply_after_the_clip_volume() {
+    let mut timeline = composed_timeline();
+    timeline.tracks.children[0].set_volume(0.5);
+    timeline.tracks.children[0].set_pan(-0.25);
+
+    let export = timeline.to_ffmpeg(&FfmpegExportOptions::default());
+    assert!(export
+        .filter_complex
+        .contains("[a0_0]concat=n=1:v=0:a=1,volume=0.5,stereotools=balance_out=-0.25[at0];"));
+    // The clip's own volume is still applied on the clip.
+    assert!(export.filter_complex.contains(",volume=0.5[a0_0]"));
+}
+
+#[test]
+fn empty_timeline_renders_black_and_silence() {
+    let export = Timeline::default().to_ffmpeg(&FfmpegExportOptions::default());
+    assert!(export.inputs.is_empty());
+    assert_eq!(
+        export.filter_complex,
+        "color=c=black:s=1920x1080:r=30:d=0[vbase];\n\
+         [vbase]format=yuv420p[vout];\n\
+         anullsrc=r=48000:cl=stereo,atrim=duration=0,aresample=48000,aformat=sample_fmts=fltp:channel_layouts=stereo[aout]"
+    );
+    assert_eq!(
+        export.to_args("out.mp4")[..3],
+        ["-y", "-filter_complex", export.filter_complex.as_str()]
+    );
+}
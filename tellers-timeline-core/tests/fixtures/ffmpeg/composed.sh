@@ -0,0 +1,32 @@
+#!/bin/sh
+ffmpeg \
+  -y \
+  -i file:///media/a.wav \
+  -i file:///media/v1.mov \
+  -loop 1 \
+  -i file:///media/logo.png \
+  -filter_complex '[0:a]atrim=start=10:duration=6,asetpts=PTS-STARTPTS,aresample=48000,aformat=sample_fmts=fltp:channel_layouts=stereo,volume=0.5[a0_0];
+[a0_0]concat=n=1:v=0:a=1[at0];
+[1:v]trim=start=0:duration=4,setpts=PTS-STARTPTS,fps=30,format=rgba,scale=1920:1080:force_original_aspect_ratio=decrease[v1_0];
+color=c=black@0.0:s=1920x1080:r=30:d=4,format=rgba[vcanvas1_0];
+[vcanvas1_0][v1_0]overlay=x=(W-w)/2:y=(H-h)/2:eof_action=pass[vclip1_0];
+color=c=black@0.0:s=1920x1080:r=30:d=2,format=rgba[vblank1_1];
+[1:v]trim=start=0:duration=2,setpts=PTS-STARTPTS,fps=30,format=rgba,scale=1920:1080:force_original_aspect_ratio=decrease[v1_3];
+color=c=black@0.0:s=1920x1080:r=30:d=2,format=rgba[vcanvas1_3];
+[vcanvas1_3][v1_3]overlay=x=(W-w)/2:y=(H-h)/2:eof_action=pass[vclip1_3];
+[vclip1_0][vblank1_1][vclip1_3]concat=n=3:v=1:a=0[vt1];
+color=c=black@0.0:s=1920x1080:r=30:d=2,format=rgba[vblank2_0];
+[2:v]trim=start=0:duration=1,setpts=PTS-STARTPTS,fps=30,format=rgba,crop=w=iw*0.7:h=ih*0.75:x=iw*0.1:y=ih*0,scale=1920:1080:force_original_aspect_ratio=decrease,scale=iw*0.5:ih*0.5,rotate=a=-1.570796:ow=rotw(-1.570796):oh=roth(-1.570796):c=none[v2_1];
+color=c=black@0.0:s=1920x1080:r=30:d=1,format=rgba[vcanvas2_1];
+[vcanvas2_1][v2_1]overlay=x=(W-w)/2+480:y=(H-h)/2-108:eof_action=pass[vclip2_1];
+[vblank2_0][vclip2_1]concat=n=2:v=1:a=0[vt2];
+color=c=black:s=1920x1080:r=30:d=8[vbase];
+[vbase][vt1]overlay=eof_action=pass[vmix0];
+[vmix0][vt2]overlay=eof_action=pass[vmix1];
+[vmix1]format=yuv420p[vout];
+anullsrc=r=48000:cl=stereo,atrim=duration=8,aresample=48000,aformat=sample_fmts=fltp:channel_layouts=stereo[abase];
+[abase][at0]amix=inputs=2:duration=first:normalize=0[aout]' \
+  -map '[vout]' \
+  -map '[aout]' \
+  -t 8 \
+  out.mp4
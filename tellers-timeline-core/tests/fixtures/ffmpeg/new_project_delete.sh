@@ -0,0 +1,31 @@
+#!/bin/sh
+ffmpeg \
+  -y \
+  -loop 1 \
+  -i '_DSC9756 R.jpg' \
+  -i MACIF_PGEE_50sec.mp4 \
+  -filter_complex 'color=c=black@0.0:s=1280x720:r=25:d=5,format=rgba[vblank3_0];
+[0:v]trim=start=3.04:duration=43.96,setpts=PTS-STARTPTS,fps=25,format=rgba,scale=1280:720:force_original_aspect_ratio=decrease,scale=iw*1:ih*1.185185[v3_1];
+color=c=black@0.0:s=1280x720:r=25:d=43.96,format=rgba[vcanvas3_1];
+[vcanvas3_1][v3_1]overlay=x=(W-w)/2:y=(H-h)/2:eof_action=pass[vclip3_1];
+[vblank3_0][vclip3_1]concat=n=2:v=1:a=0[vt3];
+anullsrc=r=44100:cl=stereo,atrim=duration=5,aresample=44100,aformat=sample_fmts=fltp:channel_layouts=stereo[ablank4_0];
+[1:a]atrim=start=2.88:duration=47,asetpts=PTS-STARTPTS,aresample=44100,aformat=sample_fmts=fltp:channel_layouts=stereo[a4_1];
+[ablank4_0][a4_1]concat=n=2:v=0:a=1[at4];
+[0:v]trim=start=0:duration=5,setpts=PTS-STARTPTS,fps=25,format=rgba,scale=1280:720:force_original_aspect_ratio=decrease[v5_0];
+color=c=black@0.0:s=1280x720:r=25:d=5,format=rgba[vcanvas5_0];
+[vcanvas5_0][v5_0]overlay=x=(W-w)/2:y=(H-h)/2:eof_action=pass[vclip5_0];
+[1:v]trim=start=2.88:duration=47,setpts=PTS-STARTPTS,fps=25,format=rgba,scale=1280:720:force_original_aspect_ratio=decrease[v5_1];
+color=c=black@0.0:s=1280x720:r=25:d=47,format=rgba[vcanvas5_1];
+[vcanvas5_1][v5_1]overlay=x=(W-w)/2:y=(H-h)/2:eof_action=pass[vclip5_1];
+[vclip5_0][vclip5_1]concat=n=2:v=1:a=0[vt5];
+color=c=black:s=1280x720:r=25:d=52[vbase];
+[vbase][vt3]overlay=eof_action=pass[vmix0];
+[vmix0][vt5]overlay=eof_action=pass[vmix1];
+[vmix1]format=yuv420p[vout];
+anullsrc=r=44100:cl=stereo,atrim=duration=52,aresample=44100,aformat=sample_fmts=fltp:channel_layouts=stereo[abase];
+[abase][at4]amix=inputs=2:duration=first:normalize=0[aout]' \
+  -map '[vout]' \
+  -map '[aout]' \
+  -t 52 \
+  preview.mp4
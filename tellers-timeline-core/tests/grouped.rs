@@ -104,7 +104,7 @@ fn group_returns_none_for_fewer_than_two_members() {
 }
 
 #[test]
-fn group_reassigns_existing_membership() {
+fn group_nests_existing_groups() {
     let mut stack = Stack::default();
     stack.children.push(audio_track("t1", vec![clip_item(2.0, "A")]));
     stack.children.push(audio_track("t2", vec![clip_item(2.0, "B")]));
@@ -117,10 +117,13 @@ fn group_reassigns_existing_membership() {
         .group_item(&["B".to_string(), "C".to_string()])
         .unwrap();
 
+    // B brings its whole group, which nests in the new one.
     assert_ne!(g1, g2);
     assert_eq!(group_id(&stack, "A"), Some(g1));
-    assert_eq!(group_id(&stack, "B"), Some(g2));
+    assert_eq!(group_id(&stack, "B"), Some(g1));
     assert_eq!(group_id(&stack, "C"), Some(g2));
+    assert_eq!(stack.tellers_group_parent(g1), Some(g2));
+    assert_eq!(stack.tellers_group_parent(g2), None);
 }
 
 #[test]
@@ -0,0 +1,132 @@
+//! Range queries: which items overlap `start..end`, and by how much.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{ItemInRange, StackTimes};
+
+fn ranged_stack() -> Stack {
+    let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+    video.items.push(Item::Clip(clip(2.0, Some("v0")))); // 0..2
+    video.items.push(Item::Gap(Gap::make_gap(1.0))); // 2..3
+    video.items.push(Item::Clip(clip(0.0, Some("empty")))); // 3..3
+    video.items.push(Item::Clip(clip(4.0, Some("v1")))); // 3..7
+    let mut audio = Track::new(TrackKind::Audio, Some("a".to_string()));
+    audio.items.push(Item::Clip(clip(5.0, Some("a0")))); // 0..5
+    Stack {
+        children: vec![audio, video],
+        ..Stack::default()
+    }
+}
+
+fn summary(hits: &[ItemInRange<'_>]) -> Vec<(usize, f64, f64, f64, f64)> {
+    hits.iter()
+        .map(|hit| {
+            (
+                hit.item_index,
+                hit.start,
+                hit.end,
+                hit.overlap_start,
+                hit.overlap_end,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn track_range_reports_absolute_span_and_clipped_overlap() {
+    let stack = ranged_stack();
+    let video = &stack.children[1];
+
+    let hits = video.items_in_range(1.5, 4.0);
+    assert_eq!(
+        summary(&hits),
+        vec![
+            (0, 0.0, 2.0, 1.5, 2.0),
+            (1, 2.0, 3.0, 2.0, 3.0),
+            (3, 3.0, 7.0, 3.0, 4.0)
+        ]
+    );
+    assert_eq!(hits[2].item.get_id().as_deref(), Some("v1"));
+}
+
+#[test]
+fn range_bounds_are_half_open() {
+    let stack = ranged_stack();
+    let video = &stack.children[1];
+
+    // Ends exactly where the gap starts: only v0.
+    assert_eq!(summary(&video.items_in_range(0.0, 2.0)).len(), 1);
+    // Starts exactly where v0 ends: v0 is excluded.
+    assert_eq!(video.items_in_range(2.0, 2.5)[0].item_index, 1);
+    assert!(video.items_in_range(7.0, 9.0).is_empty());
+    assert!(video.items_in_range(4.0, 4.0).is_empty());
+    assert!(video.items_in_range(5.0, 1.0).is_empty());
+}
+
+#[test]
+fn stack_range_filters_tracks_by_id() {
+    let stack = ranged_stack();
+
+    let all: Vec<_> = stack
+        .items_in_range(4.5, 6.0, None)
+        .into_iter()
+        .map(|(ti, hit)| (ti, hit.item_index))
+        .collect();
+    assert_eq!(all, vec![(0, 0), (1, 3)]);
+
+    let video_only: Vec<_> = stack
+        .items_in_range(4.5, 6.0, Some(&["v".to_string()]))
+        .into_iter()
+        .map(|(ti, hit)| (ti, hit.item_index))
+        .collect();
+    assert_eq!(video_only, vec![(1, 3)]);
+    assert!(stack
+        .items_in_range(0.0, 10.0, Some(&["missing".to_string()]))
+        .is_empty());
+}
+
+#[test]
+fn indexed_range_matches_track_walk() {
+    let stack = ranged_stack();
+    let index = stack.build_index();
+    let bounds = [-1.0, 0.0, 0.5, 2.0, 2.5, 3.0, 4.0, 5.0, 7.0, 8.0];
+    for &start in &bounds {
+        for &end in &bounds {
+            for (ti, track) in stack.children.iter().enumerate() {
+                assert_eq!(
+                    index.track_items_in_range(ti, start, end),
+                    track.items_in_range(start, end),
+                    "track {ti} {start}..{end}"
+                );
+            }
+            assert_eq!(
+                index.items_in_range(start, end, None),
+                stack.items_in_range(start, end, None)
+            );
+        }
+    }
+    assert!(index.track_items_in_range(9, 0.0, 1.0).is_empty());
+}
+
+#[test]
+fn owned_times_outlive_the_borrow_and_match_track_walk() {
+    let stack = ranged_stack();
+    // Built once and kept while the stack is read again, as the Python
+    // index does with its snapshot.
+    let times = StackTimes::new(&stack);
+    let filter = ["v".to_string()];
+    for &(start, end) in &[(1.5, 4.0), (0.0, 2.0), (3.0, 3.5), (6.5, 9.0)] {
+        assert_eq!(
+            times.items_in_range(&stack, start, end, Some(&filter)),
+            stack.items_in_range(start, end, Some(&filter))
+        );
+    }
+    let video = times.track(1).unwrap();
+    assert_eq!(video.duration(), 7.0);
+    assert_eq!(video.start_time_of_item(3), 3.0);
+    assert_eq!(video.start_time_of_item(99), 7.0);
+    assert_eq!(video.item_at_time(3.0), Some(3));
+    assert_eq!(video.item_at_time(7.0), None);
+    assert!(times.track(2).is_none());
+}
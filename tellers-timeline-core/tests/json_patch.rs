@@ -0,0 +1,186 @@
+//! RFC 6902 JSON Patch export from stack edits and `Timeline::apply_patch`.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{apply_json_patch, json_patch_between, JsonPatch, JsonPatchOp, PatchError};
+
+fn timeline() -> Timeline {
+    let mut video = Track::new(TrackKind::Video, Some("v1".to_string()));
+    video.items.push(Item::Clip(clip(4.0, Some("c1"))));
+    video.items.push(Item::Clip(clip(4.0, Some("c2"))));
+    video.items.push(Item::Clip(clip(4.0, Some("c3"))));
+    let mut timeline = Timeline::default();
+    timeline.tracks.children.push(video);
+    // Round-trip once so the in-memory form matches what a client parses
+    // (deserialization fills in the stack and timeline ids).
+    serde_json::from_str(&timeline.to_json().unwrap()).unwrap()
+}
+
+#[test]
+fn stack_edit_emits_patch_that_replays_on_client_copy() {
+    let mut server = timeline();
+    let mut client = server.clone();
+
+    let (moved, patch) = server.edit_with_patch(|stack| {
+        stack.move_item_at_time(
+            "c1",
+            "v1",
+            20.0,
+            true,
+            InsertPolicy::InsertBefore,
+            OverlapPolicy::Override,
+        )
+    })
+    .unwrap();
+    assert!(moved);
+    assert!(!patch.is_empty());
+    assert!(patch.ops().iter().all(|op| match op {
+        JsonPatchOp::Add { path, .. }
+        | JsonPatchOp::Remove { path }
+        | JsonPatchOp::Replace { path, .. } => path.starts_with("/tracks/children/0"),
+        _ => false,
+    }));
+
+    client.apply_patch(&patch).unwrap();
+    assert_eq!(client, server);
+}
+
+#[test]
+fn inserting_a_clip_is_a_single_add() {
+    let mut server = timeline();
+    let (_, patch) = server.edit_with_patch(|stack| {
+        stack.insert_item_at_index(
+            "v1",
+            1,
+            Item::Clip(clip(2.0, Some("new"))),
+            OverlapPolicy::Push,
+            None,
+            None,
+        )
+    })
+    .unwrap();
+    assert_eq!(patch.ops().len(), 1, "{patch:?}");
+    assert!(matches!(
+        &patch.ops()[0],
+        JsonPatchOp::Add { path, .. } if path == "/tracks/children/0/children/1"
+    ));
+}
+
+#[test]
+fn patch_serializes_as_rfc6902_array() {
+    let patch = JsonPatch(vec![
+        JsonPatchOp::Replace {
+            path: "/name".to_string(),
+            value: serde_json::json!("Cut 2"),
+        },
+        JsonPatchOp::Remove {
+            path: "/metadata/a~1b".to_string(),
+        },
+    ]);
+    let json = serde_json::to_value(&patch).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([
+            {"op": "replace", "path": "/name", "value": "Cut 2"},
+            {"op": "remove", "path": "/metadata/a~1b"}
+        ])
+    );
+    let back: JsonPatch = serde_json::from_value(json).unwrap();
+    assert_eq!(back, patch);
+}
+
+#[test]
+fn json_patch_between_round_trips_arbitrary_documents() {
+    let before = serde_json::json!({"a": [1, 2, 3], "b": {"c/d": true}, "e": "x"});
+    let after = serde_json::json!({"a": [1, 3, 4], "b": {"c/d": false}, "f": null});
+    let patch = json_patch_between(&before, &after);
+    let mut doc = before.clone();
+    apply_json_patch(&mut doc, &patch).unwrap();
+    assert_eq!(doc, after);
+}
+
+#[test]
+fn keyed_and_unkeyed_array_elements_replay_after_reorders() {
+    let keyed = |id: usize| {
+        serde_json::json!({
+            "metadata": {"tellers.ai": {"timeline_id": format!("k{id}")}},
+            "v": id
+        })
+    };
+    let mut old: Vec<serde_json::Value> = (0..3000).map(keyed).collect();
+    old.insert(10, serde_json::json!("loose"));
+    old.insert(500, serde_json::json!(7));
+    let mut new = old.clone();
+    new.swap(20, 2500);
+    new.remove(100);
+    new.insert(1200, keyed(9999));
+    new.insert(11, serde_json::json!("added"));
+    new[300]["v"] = serde_json::json!("edited");
+
+    let before = serde_json::json!({ "items": old });
+    let after = serde_json::json!({ "items": new });
+    let patch = json_patch_between(&before, &after);
+    // Keyed elements pair up by id, so only the touched ones appear.
+    assert!(patch.ops().len() <= 8, "{} ops", patch.ops().len());
+    let mut doc = before.clone();
+    apply_json_patch(&mut doc, &patch).unwrap();
+    assert_eq!(doc, after);
+}
+
+#[test]
+fn patch_to_matches_an_edit() {
+    let before = timeline();
+    let mut after = before.clone();
+    after.tracks.delete_item("c2", false);
+    let mut client = before.clone();
+    client.apply_patch(&before.patch_to(&after).unwrap()).unwrap();
+    assert_eq!(client, after);
+}
+
+#[test]
+fn failing_patch_leaves_timeline_unchanged() {
+    let mut tl = timeline();
+    let original = tl.clone();
+    let patch = JsonPatch(vec![
+        JsonPatchOp::Replace {
+            path: "/name".to_string(),
+            value: serde_json::json!("renamed"),
+        },
+        JsonPatchOp::Test {
+            path: "/name".to_string(),
+            value: serde_json::json!("other"),
+        },
+    ]);
+    assert_eq!(
+        tl.apply_patch(&patch),
+        Err(PatchError::TestFailed("/name".to_string()))
+    );
+    assert_eq!(tl, original);
+
+    let patch = JsonPatch(vec![JsonPatchOp::Remove {
+        path: "/tracks/children/7".to_string(),
+    }]);
+    assert!(matches!(tl.apply_patch(&patch), Err(PatchError::PathNotFound(_))));
+}
+
+#[test]
+fn move_and_copy_ops_apply() {
+    let mut doc = serde_json::json!({"a": [1, 2], "b": {}});
+    let patch = JsonPatch(vec![
+        JsonPatchOp::Copy {
+            from: "/a/0".to_string(),
+            path: "/b/x".to_string(),
+        },
+        JsonPatchOp::Move {
+            from: "/a/1".to_string(),
+            path: "/a/-".to_string(),
+        },
+        JsonPatchOp::Move {
+            from: "/a/0".to_string(),
+            path: "/b/y".to_string(),
+        },
+    ]);
+    apply_json_patch(&mut doc, &patch).unwrap();
+    assert_eq!(doc, serde_json::json!({"a": [2], "b": {"x": 1, "y": 1}}));
+}
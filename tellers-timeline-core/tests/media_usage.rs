@@ -0,0 +1,146 @@
+//! `Timeline::media_usage`: clips grouped per media, merged used intervals,
+//! handles, and the JSON / CSV manifests.
+
+mod common;
+
+use common::*;
+
+fn usage_timeline() -> Timeline {
+    let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+    video.items = vec![
+        // 12..15
+        used_media_clip(
+            "https://signed/a.mov?sig=1",
+            Some("asset-a"),
+            12.0,
+            3.0,
+            "a1",
+        ),
+        Item::Gap(Gap::make_gap(1.0)),
+        used_media_clip("file:///b.mov", None, 20.0, 2.0, "b1"),
+        // Same asset under another URL, 14..18: overlaps a1.
+        used_media_clip(
+            "https://signed/a.mov?sig=2",
+            Some("asset-a"),
+            14.0,
+            4.0,
+            "a2",
+        ),
+        Item::Clip(Clip::new_single_media_reference(
+            range(1.0),
+            MediaReference::create_rich_text_reference("<p>Title</p>".to_string()),
+            None,
+            Some("title".to_string()),
+        )),
+    ];
+    let mut audio = Track::new(TrackKind::Audio, Some("a".to_string()));
+    // 22..27, touching b1's 20..22.
+    audio.items = vec![used_media_clip("file:///b.mov", None, 22.0, 5.0, "b2")];
+    Timeline {
+        tracks: Stack {
+            children: vec![video, audio],
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    }
+}
+
+#[test]
+fn groups_clips_by_media_id_then_url() {
+    let usage = usage_timeline().media_usage();
+
+    assert_eq!(usage.media.len(), 2);
+    let asset = &usage.media[0];
+    assert_eq!(asset.media_id.as_deref(), Some("asset-a"));
+    assert_eq!(
+        asset.target_urls,
+        vec!["https://signed/a.mov?sig=1", "https://signed/a.mov?sig=2"]
+    );
+    let clips: Vec<_> = asset
+        .clips
+        .iter()
+        .map(|c| (c.location.clip_id.as_deref().unwrap(), c.timeline_start))
+        .collect();
+    assert_eq!(clips, vec![("a1", 0.0), ("a2", 6.0)]);
+
+    let by_url = &usage.media[1];
+    assert_eq!(by_url.media_id, None);
+    assert_eq!(by_url.target_urls, vec!["file:///b.mov"]);
+    assert_eq!(by_url.clips[1].location.track_index, 1);
+}
+
+#[test]
+fn used_intervals_merge_and_handles_measure_the_rest() {
+    let usage = usage_timeline().media_usage();
+
+    let asset = &usage.media[0];
+    assert_eq!(asset.used_intervals, vec![(12.0, 18.0)]);
+    assert_eq!(asset.used_duration, 6.0);
+    assert_eq!(asset.available_range, Some((10.0, 70.0)));
+    assert_eq!(asset.head_handle, Some(2.0));
+    assert_eq!(asset.tail_handle, Some(52.0));
+
+    // Touching intervals merge too.
+    assert_eq!(usage.media[1].used_intervals, vec![(20.0, 27.0)]);
+}
+
+#[test]
+fn media_without_url_or_id_is_not_listed() {
+    let mut timeline = usage_timeline();
+    timeline.clear_target_urls();
+
+    let usage = timeline.media_usage();
+
+    assert_eq!(usage.media.len(), 1);
+    assert_eq!(usage.media[0].media_id.as_deref(), Some("asset-a"));
+    assert!(usage.media[0].target_urls.is_empty());
+}
+
+#[test]
+fn manifests_list_every_media() {
+    let usage = usage_timeline().media_usage();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&usage.to_manifest_json(false).unwrap()).unwrap();
+    assert_eq!(json["media"][0]["media_id"], "asset-a");
+    assert_eq!(json["media"][0]["clips"][1]["location"]["clip_id"], "a2");
+    assert_eq!(json["media"][1]["used_duration"], 7.0);
+
+    let csv = usage.to_manifest_csv();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "media_id,target_urls,clip_count,used_duration,used_intervals,available_start,available_end,head_handle,tail_handle",
+            "asset-a,https://signed/a.mov?sig=1;https://signed/a.mov?sig=2,2,6.000,12.000-18.000,10.000,70.000,2.000,52.000",
+            ",file:///b.mov,2,7.000,20.000-27.000,10.000,70.000,10.000,43.000",
+        ]
+    );
+}
+
+#[test]
+fn csv_quotes_fields_with_separators() {
+    let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+    video.items = vec![used_media_clip(
+        "file:///a, \"b\".mov",
+        None,
+        10.0,
+        1.0,
+        "c",
+    )];
+    let timeline = Timeline {
+        tracks: Stack {
+            children: vec![video],
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    };
+
+    let csv = timeline.media_usage().to_manifest_csv();
+
+    assert!(csv
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with(",\"file:///a, \"\"b\"\".mov\",1,"));
+}
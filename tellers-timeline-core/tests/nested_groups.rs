@@ -0,0 +1,158 @@
+//! Nested Tellers groups: grouping groups, moves and deletes acting on the
+//! whole tree, and ungroup dissolving one level at a time.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{
+    item_tellers_group_ids, remove_tellers_group_id, resolve_tellers_parent_group_ids,
+    resolve_tellers_root_group_id,
+};
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+fn groups_of(stack: &Stack, item_id: &str) -> Vec<i64> {
+    item_tellers_group_ids(stack.get_item(item_id).unwrap().2)
+}
+
+fn start_of(stack: &Stack, item_id: &str) -> f64 {
+    let (track_index, item_index, _) = stack.get_item(item_id).unwrap();
+    stack.children[track_index].start_time_of_item(item_index)
+}
+
+// t1: [A 0..2][gap][C 6..8]
+// t2: [B 0..2][gap][D 6..8]
+// t3: [E 0..2]
+fn stack() -> Stack {
+    let mut t1 = Track::new(TrackKind::Video, Some("t1".to_string()));
+    t1.items.push(Item::Clip(clip(2.0, Some("A"))));
+    t1.items.push(Item::Gap(Gap::make_gap(4.0)));
+    t1.items.push(Item::Clip(clip(2.0, Some("C"))));
+    let mut t2 = Track::new(TrackKind::Video, Some("t2".to_string()));
+    t2.items.push(Item::Clip(clip(2.0, Some("B"))));
+    t2.items.push(Item::Gap(Gap::make_gap(4.0)));
+    t2.items.push(Item::Clip(clip(2.0, Some("D"))));
+    let mut t3 = Track::new(TrackKind::Video, Some("t3".to_string()));
+    t3.items.push(Item::Clip(clip(2.0, Some("E"))));
+    Stack {
+        children: vec![t1, t2, t3],
+        ..Stack::default()
+    }
+}
+
+// "AB" and "CD" grouped into "scene", with E loose.
+fn scene() -> (Stack, i64, i64, i64) {
+    let mut stack = stack();
+    let ab = stack.group_item(&ids(&["A", "B"])).unwrap();
+    let cd = stack.group_item(&ids(&["C", "D"])).unwrap();
+    let scene = stack.group_item(&ids(&["A", "D"])).unwrap();
+    (stack, ab, cd, scene)
+}
+
+#[test]
+fn grouping_groups_nests_them() {
+    let (stack, ab, cd, scene) = scene();
+    assert_eq!(groups_of(&stack, "A"), vec![ab, scene]);
+    assert_eq!(groups_of(&stack, "B"), vec![ab, scene]);
+    assert_eq!(groups_of(&stack, "C"), vec![cd, scene]);
+    assert_eq!(groups_of(&stack, "E"), Vec::<i64>::new());
+    assert_eq!(stack.tellers_group_parent(ab), Some(scene));
+    assert_eq!(stack.tellers_group_parent(cd), Some(scene));
+    assert_eq!(stack.tellers_group_parent(scene), None);
+
+    let index = stack.build_index();
+    assert_eq!(index.tellers_group_members(scene).len(), 4);
+    assert_eq!(index.tellers_group_members(ab).len(), 2);
+}
+
+#[test]
+fn loose_clips_join_the_new_outer_group_directly() {
+    let (mut stack, ab, _, scene) = scene();
+    let outer = stack.group_item(&ids(&["B", "E"])).unwrap();
+    assert_eq!(groups_of(&stack, "A"), vec![ab, scene, outer]);
+    assert_eq!(groups_of(&stack, "E"), vec![outer]);
+
+    // A tree on its own has nothing to be grouped with.
+    assert_eq!(stack.group_item(&ids(&["A", "C"])), None);
+    assert_eq!(groups_of(&stack, "E"), vec![outer]);
+}
+
+#[test]
+fn moving_any_clip_shifts_the_whole_tree() {
+    let (mut stack, ..) = scene();
+    assert!(stack.move_item_at_time(
+        "B",
+        "t2",
+        1.0,
+        true,
+        InsertPolicy::SplitAndInsert,
+        OverlapPolicy::Override,
+    ));
+    assert_eq!(start_of(&stack, "A"), 1.0);
+    assert_eq!(start_of(&stack, "B"), 1.0);
+    assert_eq!(start_of(&stack, "C"), 7.0);
+    assert_eq!(start_of(&stack, "D"), 7.0);
+    assert_eq!(start_of(&stack, "E"), 0.0);
+}
+
+#[test]
+fn ungroup_dissolves_only_the_outer_level() {
+    let (mut stack, ab, cd, scene) = scene();
+    assert_eq!(stack.ungroup_item(&ids(&["C"])), 4);
+    assert_eq!(groups_of(&stack, "A"), vec![ab]);
+    assert_eq!(groups_of(&stack, "D"), vec![cd]);
+    assert_eq!(stack.tellers_group_parent(ab), None);
+    assert!(stack.build_index().tellers_group_members(scene).is_empty());
+
+    // The inner groups move on their own again.
+    assert!(stack.move_item_at_time(
+        "A",
+        "t1",
+        3.0,
+        true,
+        InsertPolicy::SplitAndInsert,
+        OverlapPolicy::Override,
+    ));
+    assert_eq!(start_of(&stack, "B"), 3.0);
+    assert_eq!(start_of(&stack, "C"), 6.0);
+
+    assert_eq!(stack.ungroup_item(&ids(&["A"])), 2);
+    assert!(groups_of(&stack, "A").is_empty());
+    assert_eq!(groups_of(&stack, "C"), vec![cd]);
+}
+
+#[test]
+fn deleting_a_clip_deletes_the_whole_tree() {
+    let (mut stack, ..) = scene();
+    stack.delete_item("A", true);
+    for id in ["A", "B", "C", "D"] {
+        assert!(stack.get_item(id).is_none(), "{id} should be deleted");
+    }
+    assert!(stack.get_item("E").is_some());
+}
+
+#[test]
+fn parent_ids_live_in_tellers_metadata() {
+    let (stack, ab, _, scene) = scene();
+    let Item::Clip(a) = stack.get_item("A").unwrap().2 else {
+        panic!("A is a clip");
+    };
+    assert_eq!(
+        a.metadata["tellers.ai"]["Tellers Parent Group IDs"],
+        serde_json::json!([scene])
+    );
+    assert_eq!(resolve_tellers_root_group_id(&a.metadata), Some(scene));
+
+    let mut metadata = serde_json::json!({
+        "tellers.ai": {
+            "Tellers Group ID": ab,
+            "Tellers Parent Group IDs": ["7", 9]
+        }
+    });
+    assert_eq!(resolve_tellers_parent_group_ids(&metadata), vec![7, 9]);
+    assert_eq!(resolve_tellers_root_group_id(&metadata), Some(9));
+    assert!(remove_tellers_group_id(&mut metadata));
+    assert_eq!(metadata, serde_json::json!({ "tellers.ai": {} }));
+}
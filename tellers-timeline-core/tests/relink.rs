@@ -0,0 +1,217 @@
+//! Relinking media references: prefix and regex rules, a resolver callback,
+//! relative URLs against a base, and switching the active reference key.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{RelinkOptions, RelinkRule, UnresolvedReason};
+
+fn single(url: &str, id: &str) -> Item {
+    Item::Clip(Clip::new_single_media_reference(
+        range(2.0),
+        media_ref(url, Some(&format!("media-{id}"))),
+        None,
+        Some(id.to_string()),
+    ))
+}
+
+fn timeline(items: Vec<Item>) -> Timeline {
+    let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+    video.items = items;
+    Timeline {
+        tracks: Stack {
+            children: vec![video],
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    }
+}
+
+fn urls(timeline: &Timeline) -> Vec<Option<&str>> {
+    timeline.tracks.children[0]
+        .items
+        .iter()
+        .map(active_target_url)
+        .collect()
+}
+
+#[test]
+fn first_matching_rule_rewrites_the_url() {
+    let mut timeline = timeline(vec![
+        single("s3://bucket/raw/a.mov?X-Amz-Signature=abc", "a"),
+        single("https://cdn.example.com/b.mov", "b"),
+        single("file:///elsewhere/c.mov", "c"),
+    ]);
+    let options = RelinkOptions {
+        rules: vec![
+            RelinkRule::regex(r"^s3://bucket/raw/([^?]+)(\?.*)?$", "/proxies/$1").unwrap(),
+            RelinkRule::prefix("https://cdn.example.com/", "/cache/"),
+            RelinkRule::prefix("s3://", "/never/"),
+        ],
+        ..RelinkOptions::default()
+    };
+
+    let report = timeline.relink(&options);
+
+    assert_eq!(
+        urls(&timeline),
+        vec![
+            Some("/proxies/a.mov"),
+            Some("/cache/b.mov"),
+            Some("file:///elsewhere/c.mov")
+        ]
+    );
+    assert_eq!(report.relinked.len(), 2);
+    assert_eq!(report.relinked[0].location.clip_id.as_deref(), Some("a"));
+    assert_eq!(report.relinked[0].location.reference_key, "DEFAULT_MEDIA");
+    assert_eq!(
+        report.relinked[0].old_url,
+        "s3://bucket/raw/a.mov?X-Amz-Signature=abc"
+    );
+    assert!(report.unresolved.is_empty());
+}
+
+#[test]
+fn invalid_pattern_is_an_error() {
+    assert!(RelinkRule::regex("(", "x").is_err());
+}
+
+#[test]
+fn resolver_is_asked_only_when_no_rule_matches() {
+    let mut timeline = timeline(vec![
+        single("s3://bucket/a.mov", "a"),
+        single("s3://other/b.mov", "b"),
+    ]);
+    let options = RelinkOptions {
+        rules: vec![RelinkRule::prefix("s3://bucket/", "/local/")],
+        ..RelinkOptions::default()
+    };
+    let mut asked = Vec::new();
+
+    timeline.relink_with(&options, |reference| {
+        let media_id = reference.metadata()["tellers.ai"]["media_id"]
+            .as_str()?
+            .to_string();
+        asked.push(media_id.clone());
+        Some(format!("/by-id/{media_id}.mov"))
+    });
+
+    assert_eq!(asked, vec!["media-b".to_string()]);
+    assert_eq!(
+        urls(&timeline),
+        vec![Some("/local/a.mov"), Some("/by-id/media-b.mov")]
+    );
+}
+
+#[test]
+fn relative_urls_resolve_against_the_base() {
+    let mut timeline = timeline(vec![
+        single("media/a.mov", "a"),
+        single("./b.mov", "b"),
+        single("../../../c.mov", "c"),
+        single("/abs/d.mov", "d"),
+    ]);
+    let options = RelinkOptions {
+        base_url: Some("file:///mnt/project/".to_string()),
+        ..RelinkOptions::default()
+    };
+
+    let report = timeline.relink(&options);
+
+    assert_eq!(
+        urls(&timeline),
+        vec![
+            Some("file:///mnt/project/media/a.mov"),
+            Some("file:///mnt/project/b.mov"),
+            Some("file:///c.mov"),
+            Some("/abs/d.mov"),
+        ]
+    );
+    assert_eq!(report.relinked.len(), 3);
+    assert!(report.unresolved.is_empty());
+}
+
+#[test]
+fn reports_references_left_unresolved() {
+    let mut timeline = timeline(vec![
+        single("", "missing"),
+        single("media/a.mov", "relative"),
+        single("https://expiring.example.com/b.mov", "unmatched"),
+        single("s3://bucket/c.mov", "moved"),
+    ]);
+    let options = RelinkOptions {
+        rules: vec![RelinkRule::prefix("s3://bucket/", "/local/")],
+        require_match: true,
+        ..RelinkOptions::default()
+    };
+
+    let report = timeline.relink(&options);
+
+    let unresolved: Vec<_> = report
+        .unresolved
+        .iter()
+        .map(|u| (u.location.clip_id.as_deref().unwrap(), u.reason))
+        .collect();
+    assert_eq!(
+        unresolved,
+        vec![
+            ("missing", UnresolvedReason::MissingUrl),
+            ("relative", UnresolvedReason::RelativeUrl),
+            ("unmatched", UnresolvedReason::NoMatch),
+        ]
+    );
+    assert_eq!(report.unresolved[1].target_url, "media/a.mov");
+}
+
+#[test]
+fn reference_keys_limit_the_relink() {
+    let mut timeline = timeline(vec![Item::Clip(clip_with_references(
+        2.0,
+        Some("DEFAULT_MEDIA"),
+        Some("c"),
+    ))]);
+    let options = RelinkOptions {
+        rules: vec![RelinkRule::prefix("file:///", "/mnt/")],
+        reference_keys: Some(vec!["ALT".to_string()]),
+        ..RelinkOptions::default()
+    };
+
+    let report = timeline.relink(&options);
+
+    assert_eq!(report.relinked.len(), 1);
+    assert_eq!(report.relinked[0].new_url, "/mnt/replacement-alt.mov");
+    assert_eq!(
+        urls(&timeline),
+        vec![Some("file:///replacement-default.mov")]
+    );
+}
+
+#[test]
+fn switching_the_reference_key_reports_clips_without_it() {
+    let mut timeline = timeline(vec![
+        Item::Clip(clip_with_references(
+            2.0,
+            Some("DEFAULT_MEDIA"),
+            Some("both"),
+        )),
+        single("file:///only-default.mov", "single"),
+        Item::Gap(Gap::make_gap(1.0)),
+    ]);
+
+    let missing = timeline.switch_media_reference_key("ALT");
+
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].clip_id.as_deref(), Some("single"));
+    assert_eq!(
+        urls(&timeline),
+        vec![
+            Some("file:///replacement-alt.mov"),
+            Some("file:///only-default.mov"),
+            None
+        ]
+    );
+    assert!(timeline
+        .switch_media_reference_key("DEFAULT_MEDIA")
+        .is_empty());
+    assert_eq!(urls(&timeline)[0], Some("file:///replacement-default.mov"));
+}
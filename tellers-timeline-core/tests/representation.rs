@@ -0,0 +1,174 @@
+//! Switching every clip between `DEFAULT_MEDIA` and named renditions with
+//! `Timeline::set_active_representation`.
+
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+use tellers_timeline_core::{Representation, RepresentationIssueKind};
+
+fn reference(url: &str, start: f64, duration: f64) -> MediaReference {
+    MediaReference::ExternalReference {
+        target_url: url.to_string(),
+        available_range: Some(TimeRange::new(duration, start)),
+        name: None,
+        available_image_bounds: None,
+        metadata: serde_json::json!({}),
+    }
+}
+
+/// A clip reading `source_start..source_start + 2` from the original, with
+/// the given extra renditions.
+fn clip_with(id: &str, source_start: f64, renditions: Vec<(&str, MediaReference)>) -> Item {
+    let mut refs = HashMap::new();
+    refs.insert(
+        "DEFAULT_MEDIA".to_string(),
+        reference(&format!("file:///{id}.mov"), 3600.0, 100.0),
+    );
+    for (key, rendition) in renditions {
+        refs.insert(key.to_string(), rendition);
+    }
+    Item::Clip(Clip::new(
+        TimeRange::new(2.0, source_start),
+        refs,
+        None,
+        None,
+        Some(id.to_string()),
+    ))
+}
+
+fn timeline(items: Vec<Item>) -> Timeline {
+    let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+    video.items = items;
+    Timeline {
+        tracks: Stack {
+            children: vec![video],
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    }
+}
+
+fn active_urls(timeline: &Timeline) -> Vec<Option<&str>> {
+    timeline.tracks.children[0]
+        .items
+        .iter()
+        .map(active_target_url)
+        .collect()
+}
+
+#[test]
+fn switches_every_clip_having_the_representation() {
+    let mut timeline = timeline(vec![
+        clip_with(
+            "a",
+            3610.0,
+            vec![("proxy", reference("file:///a-proxy.mp4", 3600.0, 100.0))],
+        ),
+        Item::Gap(Gap::make_gap(1.0)),
+        clip_with(
+            "b",
+            3620.0,
+            vec![("proxy", reference("file:///b-proxy.mp4", 3600.0, 100.0))],
+        ),
+    ]);
+
+    let report = timeline.set_active_representation(Representation::Proxy);
+
+    assert_eq!(report.switched, 2);
+    assert!(report.issues.is_empty());
+    assert_eq!(
+        active_urls(&timeline),
+        vec![
+            Some("file:///a-proxy.mp4"),
+            None,
+            Some("file:///b-proxy.mp4")
+        ]
+    );
+    assert_eq!(
+        timeline.active_representation(),
+        Some(Representation::Proxy)
+    );
+
+    let back = timeline.set_active_representation("DEFAULT_MEDIA");
+    assert_eq!(back.switched, 2);
+    assert_eq!(active_urls(&timeline)[0], Some("file:///a.mov"));
+    assert_eq!(
+        timeline.active_representation(),
+        Some(Representation::Custom("DEFAULT_MEDIA".to_string()))
+    );
+}
+
+#[test]
+fn clips_without_the_representation_fall_back_and_are_reported() {
+    let mut timeline = timeline(vec![
+        clip_with("plain", 3610.0, vec![]),
+        clip_with(
+            "hr",
+            3610.0,
+            vec![("high_res", reference("file:///hr-4k.mov", 3600.0, 100.0))],
+        ),
+    ]);
+    // The plain clip was playing something else: it still falls back to the
+    // original rather than staying on a rendition of the wrong kind.
+    if let Item::Clip(clip) = &mut timeline.tracks.children[0].items[0] {
+        clip.media_references.insert(
+            "proxy".to_string(),
+            reference("file:///plain-proxy.mp4", 3600.0, 100.0),
+        );
+        clip.active_media_reference_key = Some("proxy".to_string());
+    }
+
+    let report = timeline.set_active_representation("high_res");
+
+    assert_eq!(report.switched, 1);
+    assert_eq!(report.issues.len(), 1);
+    let issue = &report.issues[0];
+    assert_eq!(issue.location.clip_id.as_deref(), Some("plain"));
+    assert_eq!(issue.location.reference_key, "high_res");
+    assert_eq!(issue.kind, RepresentationIssueKind::Missing);
+    assert_eq!(issue.active_key.as_deref(), Some("DEFAULT_MEDIA"));
+    assert_eq!(
+        active_urls(&timeline),
+        vec![Some("file:///plain.mov"), Some("file:///hr-4k.mov")]
+    );
+}
+
+#[test]
+fn refuses_renditions_that_do_not_cover_the_source_range() {
+    // Proxy transcoded without the source timecode: it starts at 0, so the
+    // clip's 3610s in-point is past its end.
+    let mut timeline = timeline(vec![clip_with(
+        "tc",
+        3610.0,
+        vec![("proxy", reference("file:///tc-proxy.mp4", 0.0, 100.0))],
+    )]);
+
+    let report = timeline.set_active_representation(Representation::Proxy);
+
+    assert_eq!(report.switched, 0);
+    assert_eq!(
+        report.issues[0].kind,
+        RepresentationIssueKind::IncompatibleRange {
+            available_start: 0.0,
+            available_end: 100.0
+        }
+    );
+    assert_eq!(active_urls(&timeline), vec![Some("file:///tc.mov")]);
+}
+
+#[test]
+fn generator_clips_are_skipped() {
+    let title = Item::Clip(Clip::new_single_media_reference(
+        range(2.0),
+        MediaReference::create_rich_text_reference("<p>Title</p>".to_string()),
+        None,
+        Some("title".to_string()),
+    ));
+    let mut timeline = timeline(vec![title]);
+
+    let report = timeline.set_active_representation(Representation::Proxy);
+
+    assert_eq!(report.switched, 0);
+    assert!(report.issues.is_empty());
+}
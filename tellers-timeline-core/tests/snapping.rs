@@ -0,0 +1,134 @@
+//! `Stack::snap`: drags pulled to clip edges, the playhead and markers, with
+//! the moving item's sync partners snapping along and its groups ignored.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{SnapMoving, SnapSources, SnapTarget};
+
+// v1: [A 0..2][gap][B 5..7]
+// v2: [gap][M 1..2]           M and Ma are synced
+// a:  [gap][Ma 1.5..3]
+fn stack() -> Stack {
+    let mut v1 = Track::new(TrackKind::Video, Some("v1".to_string()));
+    v1.items.push(Item::Clip(clip(2.0, Some("A"))));
+    v1.items.push(Item::Gap(Gap::make_gap(3.0)));
+    v1.items.push(Item::Clip(clip(2.0, Some("B"))));
+    let mut v2 = Track::new(TrackKind::Video, Some("v2".to_string()));
+    v2.items.push(Item::Gap(Gap::make_gap(1.0)));
+    v2.items.push(synced_clip_item(1.0, "M", 7));
+    let mut a = Track::new(TrackKind::Audio, Some("a".to_string()));
+    a.items.push(Item::Gap(Gap::make_gap(1.5)));
+    a.items.push(synced_clip_item(1.5, "Ma", 7));
+    Stack {
+        children: vec![v1, v2, a],
+        ..Stack::default()
+    }
+}
+
+fn moving(id: &str) -> SnapMoving {
+    SnapMoving::Item(id.to_string())
+}
+
+fn own_edges_only() -> SnapSources {
+    SnapSources {
+        sync_partner_edges: false,
+        ..SnapSources::default()
+    }
+}
+
+#[test]
+fn the_closest_edge_of_the_moving_item_snaps() {
+    let stack = stack();
+
+    // M would cover 3.92..4.92: its end is 0.08 from B's start.
+    let snap = stack
+        .snap(&moving("M"), 3.92, 0.2, &SnapSources::default())
+        .unwrap();
+    assert_eq!(snap.start, 4.0);
+    assert_eq!(snap.time, 5.0);
+    assert_eq!(snap.edge_offset, 1.0);
+    assert_eq!(
+        snap.target,
+        SnapTarget::ClipStart {
+            track_index: 0,
+            item_index: 2,
+            item_id: Some("B".to_string()),
+        }
+    );
+
+    assert_eq!(
+        stack.snap(&moving("M"), 3.5, 0.2, &SnapSources::default()),
+        None
+    );
+    assert_eq!(
+        stack.snap(&moving("missing"), 0.0, 10.0, &SnapSources::default()),
+        None
+    );
+}
+
+#[test]
+fn sync_partner_edges_snap_when_enabled() {
+    let stack = stack();
+
+    // Ma sits 0.5..2.0 after M's start: at 3.1 its end is 0.1 from B's start.
+    let snap = stack
+        .snap(&moving("M"), 3.1, 0.2, &SnapSources::default())
+        .unwrap();
+    assert_eq!(snap.edge_offset, 2.0);
+    assert_eq!(snap.start, 3.0);
+    assert_eq!(stack.snap(&moving("M"), 3.1, 0.2, &own_edges_only()), None);
+}
+
+#[test]
+fn clips_moving_along_are_not_targets() {
+    let mut stack = stack();
+
+    // Ma's current start (1.5) is right next to M's, but Ma moves with M.
+    assert_eq!(stack.snap(&moving("M"), 1.45, 0.1, &own_edges_only()), None);
+
+    let near_a = stack.snap(&moving("M"), -0.05, 0.1, &own_edges_only());
+    assert!(matches!(
+        near_a.map(|snap| snap.target),
+        Some(SnapTarget::ClipStart {
+            item_index: 0,
+            track_index: 0,
+            ..
+        })
+    ));
+    stack
+        .group_item(&["M".to_string(), "A".to_string()])
+        .unwrap();
+    assert_eq!(
+        stack.snap(&moving("M"), -0.05, 0.1, &own_edges_only()),
+        None
+    );
+}
+
+#[test]
+fn ranges_snap_to_the_playhead_and_markers() {
+    let stack = stack();
+    let range = SnapMoving::Range {
+        start: 0.0,
+        end: 1.0,
+    };
+    let mut sources = SnapSources {
+        clip_edges: false,
+        playhead: Some(3.0),
+        markers: vec![3.0, 4.05],
+        ..SnapSources::default()
+    };
+
+    // Ties go to the playhead, then to markers.
+    let snap = stack.snap(&range, 2.95, 0.2, &sources).unwrap();
+    assert_eq!((snap.start, snap.target), (3.0, SnapTarget::Playhead));
+    sources.playhead = None;
+    let snap = stack.snap(&range, 2.95, 0.2, &sources).unwrap();
+    assert_eq!(snap.target, SnapTarget::Marker { index: 0 });
+
+    // The range's end is closer to the second marker.
+    let snap = stack.snap(&range, 3.04, 0.2, &sources).unwrap();
+    assert_eq!(snap.target, SnapTarget::Marker { index: 1 });
+    assert_eq!(snap.edge_offset, 1.0);
+    assert!(stack.snap(&range, 1.5, 0.2, &sources).is_none());
+}
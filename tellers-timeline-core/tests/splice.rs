@@ -0,0 +1,240 @@
+//! `Timeline::append` / `splice_at`: assembling a timeline from segments,
+//! with tracks matched by kind and name and group ids kept unique.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::item_tellers_group_ids;
+
+fn named_track(kind: TrackKind, name: &str, items: Vec<Item>) -> Track {
+    let mut track = Track::new(kind, None);
+    track.name = Some(name.to_string());
+    track.items = items;
+    track
+}
+
+fn timeline(children: Vec<Track>) -> Timeline {
+    Timeline {
+        tracks: Stack {
+            children,
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    }
+}
+
+fn span_of(timeline: &Timeline, item_id: &str) -> (usize, f64, f64) {
+    let (track_index, item_index, item) = timeline.tracks.get_item(item_id).unwrap();
+    let start = timeline.tracks.children[track_index].start_time_of_item(item_index);
+    (track_index, start, item.duration())
+}
+
+// A1: [aud 0..4], V1: [vid 0..4], linked.
+fn episode() -> Timeline {
+    timeline(vec![
+        named_track(
+            TrackKind::Audio,
+            "A1",
+            vec![synced_clip_item(4.0, "aud", 1)],
+        ),
+        named_track(
+            TrackKind::Video,
+            "V1",
+            vec![synced_clip_item(4.0, "vid", 1)],
+        ),
+    ])
+}
+
+// A1: [seg-aud 0..2], Music: [gap 0..1][music 1..2], V1: [seg-vid 0..2],
+// with seg-aud and seg-vid linked under the same id as the episode.
+fn segment() -> Timeline {
+    timeline(vec![
+        named_track(
+            TrackKind::Audio,
+            "A1",
+            vec![synced_clip_item(2.0, "seg-aud", 1)],
+        ),
+        named_track(
+            TrackKind::Audio,
+            "Music",
+            vec![
+                Item::Gap(Gap::make_gap(1.0)),
+                Item::Clip(clip(1.0, Some("music"))),
+            ],
+        ),
+        named_track(
+            TrackKind::Video,
+            "V1",
+            vec![synced_clip_item(2.0, "seg-vid", 1)],
+        ),
+    ])
+}
+
+#[test]
+fn append_matches_tracks_and_numbers_new_ones() {
+    let mut timeline = episode();
+    timeline.append(&segment());
+
+    assert_eq!(span_of(&timeline, "seg-aud"), (0, 4.0, 2.0));
+    assert_eq!(span_of(&timeline, "seg-vid"), (2, 4.0, 2.0));
+    // The unmatched audio track lands after the last audio track, keeping
+    // its name under a numbered id.
+    let music = &timeline.tracks.children[1];
+    assert_eq!(music.kind, TrackKind::Audio);
+    assert_eq!(music.name.as_deref(), Some("Music"));
+    assert_eq!(music.get_id().as_deref(), Some("A2"));
+    assert_eq!(span_of(&timeline, "music"), (1, 5.0, 1.0));
+    assert_eq!(span_of(&timeline, "vid"), (2, 0.0, 4.0));
+}
+
+#[test]
+fn splicing_pushes_every_track_and_splits_what_it_cuts() {
+    let mut timeline = episode();
+    timeline.tracks.children.push(named_track(
+        TrackKind::Video,
+        "V2",
+        vec![Item::Clip(clip(4.0, Some("title")))],
+    ));
+    assert!(timeline.splice_at(1.0, &segment()));
+
+    assert_eq!(span_of(&timeline, "seg-vid"), (2, 1.0, 2.0));
+    assert_eq!(span_of(&timeline, "vid"), (2, 0.0, 1.0));
+    let video = &timeline.tracks.children[2];
+    assert_item_span(video, 2, 3.0, 3.0);
+    assert!((source_start(&video.items[2]) - 1.0).abs() < 1e-9);
+
+    // Tracks the segment has nothing for are pushed too.
+    let titles = &timeline.tracks.children[3];
+    assert_eq!(titles.name.as_deref(), Some("V2"));
+    assert_item_span(titles, 0, 0.0, 1.0);
+    assert!(matches!(titles.items[1], Item::Gap(_)));
+    assert_item_span(titles, 2, 3.0, 3.0);
+}
+
+#[test]
+fn group_ids_and_colliding_item_ids_are_renumbered() {
+    let mut timeline = episode();
+    let episode_group = timeline.tracks.group_item(&["vid".to_string()]).unwrap();
+    let mut segment = segment();
+    let segment_group = segment
+        .tracks
+        .group_item(&["seg-vid".to_string(), "music".to_string()])
+        .unwrap();
+    assert_eq!(segment_group, episode_group);
+    segment.tracks.children[0].items[0].set_id(Some("aud".to_string()));
+
+    timeline.append(&segment);
+
+    let link = |id: &str| sync_clips_id(timeline.tracks.get_item(id).unwrap().2);
+    assert_eq!(link("vid"), Some(1));
+    let segment_link = link("seg-vid");
+    assert!(segment_link.is_some_and(|id| id != 1));
+
+    let groups = |id: &str| item_tellers_group_ids(timeline.tracks.get_item(id).unwrap().2);
+    assert_eq!(groups("vid"), vec![episode_group]);
+    let segment_groups = groups("seg-vid");
+    assert_eq!(segment_groups.len(), 1);
+    assert_ne!(segment_groups, vec![episode_group]);
+    assert_eq!(groups("music"), segment_groups);
+
+    // The episode keeps its ids; the segment's clashing clip gets a new one.
+    assert_eq!(span_of(&timeline, "aud"), (0, 0.0, 4.0));
+    let appended_audio = &timeline.tracks.children[0].items[1];
+    assert_ne!(appended_audio.get_id().as_deref(), Some("aud"));
+    assert_eq!(sync_clips_id(appended_audio), segment_link);
+}
+
+#[test]
+fn timed_words_are_merged_on_the_same_clock() {
+    let mut timeline = episode();
+    timeline.metadata["tellers_timed_words"] = serde_json::json!([
+        { "text": "before", "start": 0.5, "end": 1.0 },
+        { "text": "after", "start": 3.0, "end": 3.5 }
+    ]);
+    let episode_id = timeline.metadata["tellers.ai"]["timeline_id"].clone();
+    let mut segment = segment();
+    segment.metadata["tellers_timed_words"] = serde_json::json!([
+        { "text": "inserted", "start": 0.0, "end": 1.0 }
+    ]);
+    segment.metadata["tellers_subtitles"] = serde_json::json!([
+        { "text": "sub", "start": 1.0, "end": 2.0 }
+    ]);
+    segment.metadata["source"] = serde_json::json!("segment");
+
+    assert!(timeline.splice_at(2.0, &segment));
+    assert_eq!(
+        timeline.metadata["tellers_timed_words"],
+        serde_json::json!([
+            { "text": "before", "start": 0.5, "end": 1.0 },
+            { "text": "inserted", "start": 2.0, "end": 3.0 },
+            { "text": "after", "start": 5.0, "end": 5.5 }
+        ])
+    );
+    assert_eq!(
+        timeline.metadata["tellers_subtitles"],
+        serde_json::json!([{ "text": "sub", "start": 3.0, "end": 4.0 }])
+    );
+    assert_eq!(timeline.metadata["source"], serde_json::json!("segment"));
+    assert_eq!(timeline.metadata["tellers.ai"]["timeline_id"], episode_id);
+}
+
+#[test]
+fn new_tracks_keep_the_source_metadata_and_effects() {
+    let mut timeline = episode();
+    let mut segment = segment();
+    let music = &mut segment.tracks.children[1];
+    music.set_id(Some("music-track".to_string()));
+    music.metadata["source"] = serde_json::json!("library");
+    music.effects.push(
+        serde_json::from_value(serde_json::json!({ "name": "Fade", "effect_name": "fade" }))
+            .unwrap(),
+    );
+    timeline.append(&segment);
+
+    let music = &timeline.tracks.children[1];
+    assert_eq!(music.get_id().as_deref(), Some("A2"));
+    assert_eq!(music.metadata["source"], serde_json::json!("library"));
+    assert_eq!(music.effects, segment.tracks.children[1].effects);
+}
+
+#[test]
+fn splicing_through_a_locked_track_is_refused() {
+    let mut timeline = episode();
+    timeline.tracks.children[1].set_locked(true);
+    let before = timeline.clone();
+    assert!(!timeline.splice_at(1.0, &segment()));
+    assert_eq!(timeline, before);
+
+    // Appending leaves the locked track alone: its segment track goes to a
+    // new track instead.
+    timeline.append(&segment());
+    assert_eq!(timeline.tracks.children[2], before.tracks.children[1]);
+    assert_eq!(span_of(&timeline, "seg-vid"), (3, 4.0, 2.0));
+    assert_eq!(timeline.tracks.children[3].name.as_deref(), Some("V1"));
+}
+
+#[test]
+fn timed_entries_across_the_splice_point_are_split() {
+    let mut timeline = episode();
+    timeline.metadata["tellers_subtitles"] = serde_json::json!([
+        { "text": "across", "start": 1.0, "end": 3.0 }
+    ]);
+    assert!(timeline.splice_at(2.0, &segment()));
+    assert_eq!(
+        timeline.metadata["tellers_subtitles"],
+        serde_json::json!([
+            { "text": "across", "start": 1.0, "end": 2.0 },
+            { "text": "across", "start": 4.0, "end": 5.0 }
+        ])
+    );
+}
+
+#[test]
+fn invalid_times_leave_the_timeline_untouched() {
+    let mut timeline = episode();
+    let before = timeline.clone();
+    assert!(!timeline.splice_at(-1.0, &segment()));
+    assert!(!timeline.splice_at(f64::NAN, &segment()));
+    assert!(timeline.splice_at(2.0, &Timeline::default()));
+    assert_eq!(timeline, before);
+}
@@ -0,0 +1,187 @@
+//! Split edits (J-cuts and L-cuts): offsetting one clip of a link group from
+//! the group's column, and resizes, moves, splits and deletes keeping it.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{item_sync_offsets, MetadataExt, SyncEdge, SyncOffsets};
+
+fn span(stack: &Stack, id: &str) -> (f64, f64) {
+    let (track_index, item_index, item) = stack.get_item(id).unwrap();
+    let start = stack.children[track_index].start_time_of_item(item_index);
+    (start, start + item.duration())
+}
+
+// v: [gap 0..1][vid 1..5][next-v 5..8]
+// a: [gap 0..1][aud 1..5][next-a 5..8]
+fn linked() -> Stack {
+    let mut v = Track::new(TrackKind::Video, Some("v".to_string()));
+    v.items.push(Item::Gap(Gap::make_gap(1.0)));
+    v.items
+        .push(synced_clip_item_with_source_start(4.0, 1.0, "vid", 7));
+    v.items.push(Item::Clip(clip(3.0, Some("next-v"))));
+    let mut a = Track::new(TrackKind::Audio, Some("a".to_string()));
+    a.items.push(Item::Gap(Gap::make_gap(1.0)));
+    a.items
+        .push(synced_clip_item_with_source_start(4.0, 1.0, "aud", 7));
+    a.items.push(Item::Clip(clip(3.0, Some("next-a"))));
+    Stack {
+        children: vec![v, a],
+        ..Stack::default()
+    }
+}
+
+// The audio leads in a second early (J-cut) and runs a second late (L-cut).
+fn split_edit() -> Stack {
+    let mut stack = linked();
+    assert!(stack.set_sync_offset("aud", SyncEdge::In, -1.0));
+    assert!(stack.set_sync_offset("aud", SyncEdge::Out, 1.0));
+    stack
+}
+
+#[test]
+fn offsets_are_recorded_and_the_clip_stays_linked() {
+    let stack = split_edit();
+    assert_eq!(span(&stack, "vid"), (1.0, 5.0));
+    assert_eq!(span(&stack, "aud"), (0.0, 6.0));
+    assert_eq!(source_start(stack.get_item("aud").unwrap().2), 0.0);
+    assert_eq!(
+        stack.sync_offsets("aud"),
+        Some(SyncOffsets {
+            in_offset: -1.0,
+            out_offset: 1.0
+        })
+    );
+    assert_eq!(stack.sync_offsets("vid"), Some(SyncOffsets::default()));
+    let (_, _, audio) = stack.get_item("aud").unwrap();
+    assert_eq!(sync_clips_id(audio), Some(7));
+    assert_eq!(
+        audio.get_metadata()["tellers.ai"]["sync_offset"]["in"],
+        -1.0
+    );
+    // The tail overwrote the head of the next audio clip.
+    let audio = &stack.children[1];
+    assert_item_span(audio, audio.items.len() - 1, 6.0, 2.0);
+
+    let mut stack = split_edit();
+    assert!(stack.set_sync_offset("aud", SyncEdge::In, 0.0));
+    assert_eq!(span(&stack, "aud"), (1.0, 6.0));
+    assert_eq!(
+        item_sync_offsets(stack.get_item("aud").unwrap().2).in_offset,
+        0.0
+    );
+}
+
+#[test]
+fn offsets_need_a_link_group_and_media() {
+    let mut stack = linked();
+    assert!(!stack.set_sync_offset("next-v", SyncEdge::In, -1.0));
+    // The audio's source range starts one second into its media.
+    assert!(!stack.set_sync_offset("aud", SyncEdge::In, -2.0));
+    assert!(!stack.set_sync_offset("aud", SyncEdge::In, 4.0));
+    assert_eq!(span(&stack, "aud"), (1.0, 5.0));
+
+    // Unlinking drops the offsets with the group.
+    let mut stack = split_edit();
+    assert_eq!(stack.unsync_item(&["aud".to_string()]), 2);
+    assert!(stack.sync_offsets("aud").unwrap().is_zero());
+}
+
+#[test]
+fn resizes_and_moves_keep_the_offsets() {
+    let mut stack = split_edit();
+    assert!(stack.resize_item("vid", 1.0, 2.0, OverlapPolicy::Override, false));
+    assert_eq!(span(&stack, "vid"), (1.0, 3.0));
+    assert_eq!(span(&stack, "aud"), (0.0, 4.0));
+
+    let mut stack = split_edit();
+    assert!(stack.move_item_at_time(
+        "aud",
+        "a",
+        10.0,
+        true,
+        InsertPolicy::SplitAndInsert,
+        OverlapPolicy::Override,
+    ));
+    assert_eq!(span(&stack, "aud"), (10.0, 16.0));
+    assert_eq!(span(&stack, "vid"), (11.0, 15.0));
+    assert_eq!(sync_clips_id(stack.get_item("vid").unwrap().2), Some(7));
+    assert_eq!(sync_clips_id(stack.get_item("aud").unwrap().2), Some(7));
+    assert_eq!(stack.sync_offsets("aud").unwrap().in_offset, -1.0);
+}
+
+#[test]
+fn moves_place_the_item_by_the_insert_policy() {
+    let mut stack = split_edit();
+    assert!(stack.move_item_at_time(
+        "vid",
+        "v",
+        6.0,
+        true,
+        InsertPolicy::InsertAfter,
+        OverlapPolicy::Override,
+    ));
+    assert_eq!(span(&stack, "next-v"), (5.0, 8.0));
+    assert_eq!(span(&stack, "vid"), (8.0, 12.0));
+    assert_eq!(span(&stack, "aud"), (7.0, 13.0));
+
+    // Snapping back to 0 would push the lead-in before the start.
+    let mut stack = split_edit();
+    let before = stack.clone();
+    assert!(!stack.move_item_at_time(
+        "vid",
+        "v",
+        0.5,
+        true,
+        InsertPolicy::InsertBefore,
+        OverlapPolicy::Override,
+    ));
+    assert_eq!(stack, before);
+}
+
+#[test]
+fn splits_divide_the_column_and_unlink_lead_ins() {
+    let mut stack = split_edit();
+    assert!(stack.split_item_at_time("vid", 3.0));
+    let (_, _, left_audio) = stack.get_item("aud").unwrap();
+    assert_eq!(span(&stack, "aud"), (0.0, 3.0));
+    assert_eq!(
+        item_sync_offsets(left_audio),
+        SyncOffsets {
+            in_offset: -1.0,
+            out_offset: 0.0
+        }
+    );
+    let right_audio = &stack.children[1].items[1];
+    assert_eq!(
+        item_sync_offsets(right_audio),
+        SyncOffsets {
+            in_offset: 0.0,
+            out_offset: 1.0
+        }
+    );
+    let right_video = &stack.children[0].items[2];
+    assert_eq!(sync_clips_id(right_audio), sync_clips_id(right_video));
+    assert_ne!(sync_clips_id(right_audio), Some(7));
+
+    // Cutting the lead-in leaves its head off the group.
+    let mut stack = split_edit();
+    assert!(stack.split_item_at_time("aud", 0.5));
+    let head = &stack.children[1].items[0];
+    assert_eq!(sync_clips_id(head), None);
+    assert!(item_sync_offsets(head).is_zero());
+    let tail = &stack.children[1].items[1];
+    assert_eq!(sync_clips_id(tail), Some(7));
+    assert_eq!(item_sync_offsets(tail).in_offset, -0.5);
+}
+
+#[test]
+fn collapsing_deletes_close_the_column_on_every_track() {
+    let mut stack = split_edit();
+    assert_eq!(stack.delete_item("vid", false).len(), 2);
+    // Both tracks lose the four seconds of the column: the audio keeps a
+    // gap for its lead-in and tail.
+    assert_eq!(span(&stack, "next-v").0, 1.0);
+    let audio = &stack.children[1];
+    assert_item_span(audio, audio.items.len() - 1, 2.0, 2.0);
+}
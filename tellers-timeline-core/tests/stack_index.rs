@@ -0,0 +1,123 @@
+//! StackIndex answers exactly what the linear lookups answer.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{item_link_group_id, item_tellers_group_id, StackIndex};
+
+fn indexed_stack() -> Stack {
+    let mut stack = two_synced_clips();
+    let mut free = Track::new(TrackKind::Video, Some("v2".to_string()));
+    free.items.push(Item::Gap(Gap::make_gap(1.5)));
+    free.items.push(Item::Clip(clip(2.0, Some("c"))));
+    // Zero-length items never contain a time; keep one mid-track and one at
+    // the end to check the binary search skips them like the scan does.
+    free.items.push(Item::Clip(clip(0.0, Some("empty"))));
+    free.items.push(Item::Clip(clip(3.0, Some("d"))));
+    free.items.push(Item::Clip(clip(0.0, Some("empty-tail"))));
+    stack.children.push(free);
+    stack
+        .children
+        .push(Track::new(TrackKind::Audio, Some("silent".to_string())));
+    stack
+        .group_item(&["c".to_string(), "vA".to_string()])
+        .unwrap();
+    stack
+}
+
+fn all_ids(stack: &Stack) -> Vec<String> {
+    stack
+        .children
+        .iter()
+        .flat_map(|t| &t.items)
+        .filter_map(|i| i.get_id())
+        .collect()
+}
+
+#[test]
+fn get_item_matches_linear_lookup() {
+    let stack = indexed_stack();
+    let index = stack.build_index();
+    assert_eq!(index.len(), all_ids(&stack).len());
+    for id in all_ids(&stack)
+        .iter()
+        .map(String::as_str)
+        .chain(["missing"])
+    {
+        let linear = stack.get_item(id).map(|(ti, ii, _)| (ti, ii));
+        let indexed = index.get_item(id).map(|(ti, ii, _)| (ti, ii));
+        assert_eq!(indexed, linear, "{id}");
+    }
+}
+
+#[test]
+fn start_times_and_item_at_time_match_track_methods() {
+    let stack = indexed_stack();
+    let index = StackIndex::new(&stack);
+    for (ti, track) in stack.children.iter().enumerate() {
+        for ii in 0..=track.items.len() + 1 {
+            assert_eq!(
+                index.start_time_of_item(ti, ii),
+                Some(track.start_time_of_item(ii)),
+                "track {ti} item {ii}"
+            );
+        }
+        assert_eq!(index.track_duration(ti), Some(track.total_duration()));
+
+        let mut t = -0.5;
+        while t <= track.total_duration() + 1.0 {
+            assert_eq!(
+                index.item_at_time(ti, t),
+                track.get_item_at_time(t),
+                "track {ti} at {t}"
+            );
+            t += 0.25;
+        }
+    }
+    assert_eq!(index.start_time_of_item(stack.children.len(), 0), None);
+    assert_eq!(index.item_at_time(stack.children.len(), 0.0), None);
+}
+
+#[test]
+fn group_members_match_metadata() {
+    let stack = indexed_stack();
+    let index = stack.build_index();
+    let members_of = |group_of: fn(&Item) -> Option<i64>, group_id: i64| {
+        let mut members = Vec::new();
+        for (ti, track) in stack.children.iter().enumerate() {
+            for (ii, item) in track.items.iter().enumerate() {
+                if group_of(item) == Some(group_id) {
+                    members.push((ti, ii));
+                }
+            }
+        }
+        members
+    };
+
+    let (_, _, va) = stack.get_item("vA").unwrap();
+    let sync_id = item_link_group_id(va).unwrap();
+    let group_id = item_tellers_group_id(va).unwrap();
+    assert_eq!(
+        index.sync_members(sync_id),
+        members_of(item_link_group_id, sync_id)
+    );
+    assert_eq!(index.sync_members(sync_id).len(), 2);
+    assert_eq!(
+        index.tellers_group_members(group_id),
+        members_of(item_tellers_group_id, group_id)
+    );
+    // Grouping pulls vA's sync partner aA in as well.
+    assert_eq!(index.tellers_group_members(group_id).len(), 3);
+    assert!(index.sync_members(sync_id + 1000).is_empty());
+}
+
+#[test]
+fn ungroup_through_the_index_clears_every_member() {
+    let mut stack = indexed_stack();
+    assert_eq!(stack.ungroup_item(&["c".to_string(), "vA".to_string()]), 3);
+    assert!(stack
+        .children
+        .iter()
+        .flat_map(|t| &t.items)
+        .all(|item| item_tellers_group_id(item).is_none()));
+}
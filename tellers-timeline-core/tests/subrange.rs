@@ -0,0 +1,211 @@
+//! `Timeline::subrange`: cutting a time window out of a timeline as a new
+//! timeline with its own ids.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{
+    item_sync_offsets, item_tellers_group_ids, set_sync_offsets, SyncOffsets,
+};
+
+fn timeline(children: Vec<Track>) -> Timeline {
+    Timeline {
+        tracks: Stack {
+            children,
+            ..Stack::default()
+        },
+        ..Timeline::default()
+    }
+}
+
+fn timeline_id(metadata: &serde_json::Value) -> Option<&str> {
+    metadata["tellers.ai"]["timeline_id"].as_str()
+}
+
+fn clips(track: &Track) -> Vec<&Item> {
+    track
+        .items
+        .iter()
+        .filter(|item| matches!(item, Item::Clip(_)))
+        .collect()
+}
+
+// v: [V1 0..4][V2 4..8]
+// a: [A1 0..4][A2 4..8]
+// V1/A1 and V2/A2 are linked.
+fn linked() -> Timeline {
+    let mut v = Track::new(TrackKind::Video, Some("v".to_string()));
+    v.items.push(synced_clip_item(4.0, "V1", 3));
+    v.items.push(synced_clip_item(4.0, "V2", 5));
+    let mut a = Track::new(TrackKind::Audio, Some("a".to_string()));
+    a.items.push(synced_clip_item(4.0, "A1", 3));
+    a.items.push(synced_clip_item(4.0, "A2", 5));
+    timeline(vec![a, v])
+}
+
+#[test]
+fn clips_are_split_at_the_window_with_their_source_moved() {
+    let source = linked();
+    let before = source.clone();
+    let cut = source.subrange(2.0, 6.0).unwrap();
+    assert_eq!(source, before);
+
+    for track in &cut.tracks.children {
+        assert_eq!(track.items.len(), 2);
+        assert_item_span(track, 0, 0.0, 2.0);
+        assert_item_span(track, 1, 2.0, 2.0);
+        assert_eq!(source_start(&track.items[0]), 2.0);
+        assert_eq!(source_start(&track.items[1]), 0.0);
+    }
+    assert_eq!(sync_clips_id(&cut.tracks.children[0].items[0]), Some(3));
+    assert_eq!(sync_clips_id(&cut.tracks.children[1].items[1]), Some(5));
+
+    // A window past the end leaves empty tracks.
+    let late = source.subrange(10.0, 12.0).unwrap();
+    assert_eq!(late.tracks.children.len(), 2);
+    assert!(late
+        .tracks
+        .children
+        .iter()
+        .all(|track| clips(track).is_empty()));
+}
+
+#[test]
+fn every_id_is_regenerated() {
+    let source = linked();
+    let cut = source.subrange(0.0, 8.0).unwrap();
+    assert!(timeline_id(&cut.metadata).is_some());
+    assert_ne!(timeline_id(&cut.metadata), timeline_id(&source.metadata));
+    assert!(timeline_id(&cut.tracks.metadata).is_some());
+    assert_ne!(
+        timeline_id(&cut.tracks.metadata),
+        timeline_id(&source.tracks.metadata)
+    );
+
+    let mut seen = std::collections::HashSet::new();
+    for track in &cut.tracks.children {
+        let track_id = track.get_id().unwrap();
+        assert!(track_id != "v" && track_id != "a");
+        assert!(seen.insert(track_id));
+        for item in &track.items {
+            let item_id = item.get_id().unwrap();
+            assert!(source.tracks.get_item(&item_id).is_none());
+            assert!(seen.insert(item_id));
+        }
+    }
+}
+
+#[test]
+fn groups_left_with_one_clip_are_dropped() {
+    // Only V1 and A1 are left: their link survives.
+    let source = linked();
+    let first = source.subrange(1.0, 3.0).unwrap();
+    assert_eq!(sync_clips_id(&first.tracks.children[0].items[0]), Some(3));
+    assert_eq!(sync_clips_id(&first.tracks.children[1].items[0]), Some(3));
+
+    // V1 without A1 has no partner left.
+    let mut lone = linked();
+    lone.tracks.children[0].items[0] = Item::Gap(Gap::make_gap(4.0));
+    let cut = lone.subrange(1.0, 3.0).unwrap();
+    assert_eq!(sync_clips_id(&cut.tracks.children[1].items[0]), None);
+
+    let mut v = Track::new(TrackKind::Video, Some("v".to_string()));
+    v.items.push(Item::Clip(clip(4.0, Some("C1"))));
+    v.items.push(Item::Clip(clip(4.0, Some("C2"))));
+    let mut grouped = timeline(vec![v]);
+    let group = grouped
+        .tracks
+        .group_item(&["C1".to_string(), "C2".to_string()])
+        .unwrap();
+
+    let both = grouped.subrange(3.0, 5.0).unwrap();
+    let video = &both.tracks.children[0];
+    assert_eq!(item_tellers_group_ids(&video.items[0]), vec![group]);
+    assert_eq!(item_tellers_group_ids(&video.items[1]), vec![group]);
+
+    let first = grouped.subrange(1.0, 3.0).unwrap();
+    assert!(item_tellers_group_ids(&first.tracks.children[0].items[0]).is_empty());
+}
+
+#[test]
+fn split_edit_offsets_are_measured_inside_the_window() {
+    // v: [V 0..4], a: [gap 0..1][A 1..5], with A's edges 1s off the column.
+    let mut v = Track::new(TrackKind::Video, Some("v".to_string()));
+    v.items.push(synced_clip_item(4.0, "V", 3));
+    let mut a = Track::new(TrackKind::Audio, Some("a".to_string()));
+    a.items.push(Item::Gap(Gap::make_gap(1.0)));
+    let mut audio = synced_clip_item(4.0, "A", 3);
+    if let Item::Clip(clip) = &mut audio {
+        set_sync_offsets(
+            &mut clip.metadata,
+            SyncOffsets {
+                in_offset: 1.0,
+                out_offset: 1.0,
+            },
+        );
+    }
+    a.items.push(audio);
+    let source = timeline(vec![a, v]);
+
+    let tail = source.subrange(2.0, 5.0).unwrap();
+    let audio = clips(&tail.tracks.children[0])[0];
+    assert_eq!(
+        item_sync_offsets(audio),
+        SyncOffsets {
+            in_offset: 0.0,
+            out_offset: 1.0
+        }
+    );
+    let video = clips(&tail.tracks.children[1])[0];
+    assert!(item_sync_offsets(video).is_zero());
+
+    let head = source.subrange(0.5, 3.0).unwrap();
+    let audio = clips(&head.tracks.children[0])[0];
+    assert_eq!(
+        item_sync_offsets(audio),
+        SyncOffsets {
+            in_offset: 0.5,
+            out_offset: 0.0
+        }
+    );
+    assert_eq!(sync_clips_id(audio), Some(3));
+}
+
+#[test]
+fn timed_words_and_subtitles_are_cut_and_shifted() {
+    let mut source = linked();
+    source.metadata["tellers_timed_words"] = serde_json::json!([
+        { "text": "one", "start": 0.5, "end": 1.5 },
+        { "text": "two", "start": 3.0, "end": 4.0 },
+        { "text": "three", "start": 7.0, "end": 8.0 }
+    ]);
+    source.metadata["tellers_subtitles"] = serde_json::json!([
+        { "text": "all of it", "start": 0.0, "end": 8.0 },
+        { "text": "untimed" }
+    ]);
+
+    let cut = source.subrange(1.0, 5.0).unwrap();
+    assert_eq!(
+        cut.metadata["tellers_timed_words"],
+        serde_json::json!([
+            { "text": "one", "start": 0.0, "end": 0.5 },
+            { "text": "two", "start": 2.0, "end": 3.0 }
+        ])
+    );
+    assert_eq!(
+        cut.metadata["tellers_subtitles"],
+        serde_json::json!([
+            { "text": "all of it", "start": 0.0, "end": 4.0 },
+            { "text": "untimed" }
+        ])
+    );
+}
+
+#[test]
+fn empty_or_negative_windows_are_rejected() {
+    let source = linked();
+    assert!(source.subrange(2.0, 2.0).is_none());
+    assert!(source.subrange(3.0, 2.0).is_none());
+    assert!(source.subrange(-1.0, 2.0).is_none());
+    assert!(source.subrange(0.0, f64::NAN).is_none());
+}
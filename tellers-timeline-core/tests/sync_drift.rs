@@ -0,0 +1,132 @@
+//! Drift inside link groups: reporting how far clips are from their group's
+//! anchor, and `resync_group` moving and slipping them back.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::{SyncEdge, SyncMemberDrift};
+
+fn only_member(stack: &Stack, sync_clips_id: i64) -> SyncMemberDrift {
+    let drift = stack.sync_group_drift(sync_clips_id, None).unwrap();
+    let [member] = drift.members.as_slice() else {
+        panic!("expected one partner, got {:?}", drift.members);
+    };
+    member.clone()
+}
+
+// v: [vid 0..4]          source 10..14
+// a: [gap 0..1][aud 1..5] source 12..16, at 24 fps
+fn drifted() -> Stack {
+    let mut v = Track::new(TrackKind::Video, Some("v".to_string()));
+    v.items
+        .push(synced_clip_item_with_rate(4.0, 10.0, "vid", 3, 24.0));
+    let mut a = Track::new(TrackKind::Audio, Some("a".to_string()));
+    a.items.push(Item::Gap(Gap::make_gap(1.0)));
+    a.items
+        .push(synced_clip_item_with_rate(4.0, 12.0, "aud", 3, 24.0));
+    Stack {
+        children: vec![a, v],
+        ..Stack::default()
+    }
+}
+
+#[test]
+fn drift_is_reported_in_seconds_and_frames_from_the_video_clip() {
+    let stack = drifted();
+    let drift = stack.sync_drift();
+    assert_eq!(drift.len(), 1);
+    assert_eq!(drift[0].sync_clips_id, 3);
+    assert_eq!(drift[0].anchor_item_id.as_deref(), Some("vid"));
+    assert!(!drift[0].is_in_sync());
+
+    let member = only_member(&stack, 3);
+    assert_eq!(member.item_id.as_deref(), Some("aud"));
+    assert_eq!(member.track_index, 0);
+    assert!((member.timeline_offset - 1.0).abs() < 1e-9);
+    assert!((member.source_offset - 2.0).abs() < 1e-9);
+    assert!((member.timeline_offset_frames - 24.0).abs() < 1e-9);
+    assert!((member.source_offset_frames - 48.0).abs() < 1e-9);
+
+    let from_audio = stack.sync_group_drift(3, Some("aud")).unwrap();
+    assert_eq!(from_audio.members[0].item_id.as_deref(), Some("vid"));
+    assert!((from_audio.members[0].timeline_offset + 1.0).abs() < 1e-9);
+    assert!(stack.sync_group_drift(3, Some("missing")).is_none());
+}
+
+#[test]
+fn aligned_groups_and_split_edits_are_in_sync() {
+    let mut stack = stack_with_synced_audio_below_video();
+    let drift = stack.sync_drift();
+    assert_eq!(drift.len(), 1);
+    assert!(drift[0].is_in_sync());
+
+    for track in &mut stack.children {
+        if let Item::Clip(clip) = &mut track.items[1] {
+            clip.source_range.start_time.value = 5.0;
+        }
+    }
+    assert!(stack.set_sync_offset("linked-audio", SyncEdge::In, -1.0));
+    assert!(stack.sync_drift()[0].is_in_sync());
+
+    // A lone clip has nothing to drift from.
+    let mut lone = Stack::default();
+    let mut v = Track::new(TrackKind::Video, Some("v".to_string()));
+    v.items.push(synced_clip_item(2.0, "solo", 9));
+    lone.children.push(v);
+    assert!(lone.sync_drift().is_empty());
+}
+
+#[test]
+fn resync_moves_and_slips_partners_to_the_anchor() {
+    let mut stack = drifted();
+    assert!(stack.resync_group(3, "vid"));
+
+    assert!(stack.sync_drift()[0].is_in_sync());
+    let audio = &stack.children[0];
+    let (_, item_index, item) = stack.get_item("aud").unwrap();
+    assert_item_span(audio, item_index, 0.0, 4.0);
+    assert!((source_start(item) - 10.0 * 24.0).abs() < 1e-9);
+    let (_, _, video) = stack.get_item("vid").unwrap();
+    assert!((source_start(video) - 10.0 * 24.0).abs() < 1e-9);
+}
+
+#[test]
+fn resync_can_anchor_on_any_clip_and_overwrites_what_it_lands_on() {
+    let mut stack = drifted();
+    stack.children[1]
+        .items
+        .push(Item::Clip(clip(3.0, Some("after"))));
+    assert!(stack.resync_group(3, "aud"));
+
+    let video = &stack.children[1];
+    let (_, item_index, item) = stack.get_item("vid").unwrap();
+    assert_item_span(video, item_index, 1.0, 4.0);
+    assert!((source_start(item) - 12.0 * 24.0).abs() < 1e-9);
+    // "after" (4..7) lost its first second to the moved clip.
+    assert_item_span(video, item_index + 1, 5.0, 2.0);
+    assert!(stack.sync_drift()[0].is_in_sync());
+}
+
+#[test]
+fn resync_fails_without_touching_the_stack() {
+    let mut stack = drifted();
+    let before = stack.clone();
+    assert!(!stack.resync_group(3, "missing"));
+    assert!(!stack.resync_group(4, "vid"));
+
+    // Slipping the video to the audio's source would need media past the
+    // end of its 100s.
+    let mut late = drifted();
+    if let Item::Clip(clip) = &mut late.children[0].items[1] {
+        clip.source_range.start_time.value = 98.0 * 24.0;
+    }
+    let late_before = late.clone();
+    assert!(!late.resync_group(3, "aud"));
+    assert_eq!(late, late_before);
+    assert_eq!(stack, before);
+
+    stack.children[0].set_locked(true);
+    assert!(!stack.resync_group(3, "vid"));
+    stack.children[0].set_locked(false);
+    assert_eq!(stack, before);
+}
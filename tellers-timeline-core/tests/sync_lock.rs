@@ -0,0 +1,122 @@
+//! Sync-locked tracks follow push inserts, collapsing deletes and ripple
+//! trims made on other tracks, splitting clips at the edit point.
+
+mod common;
+
+use common::*;
+
+fn ids(track: &Track) -> Vec<String> {
+    track.items.iter().filter_map(Item::get_id).collect()
+}
+
+// v1:     [A 0..2][B 2..4]
+// titles: [T 0..6]           (sync-locked)
+// music:  [M 0..6]
+fn stack() -> Stack {
+    let mut v1 = Track::new(TrackKind::Video, Some("v1".to_string()));
+    v1.items.push(clip_item(2.0, "file:///a.mov", "A"));
+    v1.items.push(clip_item(2.0, "file:///b.mov", "B"));
+    let mut titles = Track::new(TrackKind::Video, Some("titles".to_string()));
+    titles.items.push(clip_item(6.0, "file:///t.mov", "T"));
+    titles.set_sync_locked(true);
+    let mut music = Track::new(TrackKind::Video, Some("music".to_string()));
+    music.items.push(clip_item(6.0, "file:///m.mov", "M"));
+    Stack {
+        children: vec![v1, titles, music],
+        ..Stack::default()
+    }
+}
+
+#[test]
+fn sync_lock_is_stored_under_tellers_ai() {
+    let mut track = Track::new(TrackKind::Audio, Some("a".to_string()));
+    assert!(!track.is_sync_locked());
+    track.set_sync_locked(true);
+    assert_eq!(track.metadata["tellers.ai"]["sync_locked"], true);
+    track.set_sync_locked(false);
+    assert!(track.metadata["tellers.ai"].get("sync_locked").is_none());
+}
+
+#[test]
+fn push_insert_opens_the_same_span_on_sync_locked_tracks() {
+    let mut stack = stack();
+    let result = stack.insert_item_at_time(
+        0,
+        2.0,
+        clip_item(1.0, "file:///n.mov", "N"),
+        OverlapPolicy::Push,
+        InsertPolicy::InsertBefore,
+        None,
+        None,
+    );
+    assert!(result.is_some());
+    assert_eq!(ids(&stack.children[0]), vec!["A", "N", "B"]);
+
+    let titles = &stack.children[1];
+    assert_eq!(titles.items.len(), 3);
+    assert_item_span(titles, 0, 0.0, 2.0);
+    assert!(matches!(titles.items[1], Item::Gap(_)));
+    assert_item_span(titles, 1, 2.0, 1.0);
+    assert_item_span(titles, 2, 3.0, 4.0);
+    assert_eq!(source_start(&titles.items[2]), 2.0);
+
+    // Tracks without sync lock keep their place.
+    assert_eq!(stack.children[2].items.len(), 1);
+    assert_eq!(stack.children[2].total_duration(), 6.0);
+
+    // Override inserts do not ripple.
+    let mut stack = self::stack();
+    stack.insert_item_at_time(
+        0,
+        2.0,
+        clip_item(1.0, "file:///n.mov", "N"),
+        OverlapPolicy::Override,
+        InsertPolicy::InsertBefore,
+        None,
+        None,
+    );
+    assert_eq!(ids(&stack.children[1]), vec!["T"]);
+}
+
+#[test]
+fn collapsing_delete_closes_the_span_on_sync_locked_tracks() {
+    let mut stack = stack();
+    assert_eq!(stack.delete_item("A", false).len(), 1);
+
+    let titles = &stack.children[1];
+    assert_eq!(titles.items.len(), 1);
+    assert_item_span(titles, 0, 0.0, 4.0);
+    assert_eq!(source_start(&titles.items[0]), 2.0);
+    assert_eq!(stack.children[2].total_duration(), 6.0);
+
+    let mut stack = self::stack();
+    stack.delete_item("A", true);
+    assert_eq!(stack.children[1].total_duration(), 6.0);
+}
+
+#[test]
+fn ripple_trims_follow_and_locked_tracks_do_not() {
+    let mut stack = stack();
+    assert!(stack.resize_item("A", 0.0, 1.5, OverlapPolicy::Override, false));
+    assert_eq!(stack.children[0].total_duration(), 3.5);
+    let titles = &stack.children[1];
+    assert_eq!(titles.items.len(), 2);
+    assert_item_span(titles, 0, 0.0, 1.5);
+    assert_item_span(titles, 1, 1.5, 4.0);
+    assert_eq!(source_start(&titles.items[1]), 2.0);
+
+    let mut stack = self::stack();
+    assert!(stack.resize_item("A", 0.0, 3.0, OverlapPolicy::Push, false));
+    let titles = &stack.children[1];
+    assert_item_span(titles, 0, 0.0, 2.0);
+    assert!(matches!(titles.items[1], Item::Gap(_)));
+    assert_item_span(titles, 2, 3.0, 4.0);
+
+    // A track that is both locked and sync-locked is left alone, and the
+    // edit still succeeds.
+    let mut stack = self::stack();
+    stack.children[1].set_locked(true);
+    assert_eq!(stack.delete_item("A", false).len(), 1);
+    assert_eq!(ids(&stack.children[1]), vec!["T"]);
+    assert_eq!(stack.children[1].total_duration(), 6.0);
+}
@@ -0,0 +1,152 @@
+//! Track lock, solo, mute and height: persistence in `tellers.ai`, edits
+//! refusing to touch locked tracks, and what solo/mute do to rendering.
+
+mod common;
+
+use common::*;
+
+// v1: [V1 0..2][V2 2..4]
+// v2: [W 0..3]
+fn two_video_tracks() -> Stack {
+    let mut v1 = Track::new(TrackKind::Video, Some("v1".to_string()));
+    v1.items.push(clip_item(2.0, "file:///v1.mov", "V1"));
+    v1.items.push(clip_item(2.0, "file:///v2.mov", "V2"));
+    let mut v2 = Track::new(TrackKind::Video, Some("v2".to_string()));
+    v2.items.push(clip_item(3.0, "file:///w.mov", "W"));
+    Stack {
+        children: vec![v1, v2],
+        ..Stack::default()
+    }
+}
+
+#[test]
+fn state_is_stored_under_tellers_ai_and_cleared_keys_are_removed() {
+    let mut track = Track::new(TrackKind::Audio, Some("a".to_string()));
+    assert!(!track.is_locked() && !track.is_solo() && !track.is_muted());
+    assert_eq!(track.height(), None);
+
+    track.set_locked(true);
+    track.set_solo(true);
+    track.set_muted(true);
+    track.set_height(Some(48.0));
+    assert_eq!(track.metadata["tellers.ai"]["locked"], true);
+    assert_eq!(track.metadata["tellers.ai"]["height"], 48.0);
+
+    let json = serde_json::to_string(&track).unwrap();
+    let back: Track = serde_json::from_str(&json).unwrap();
+    assert!(back.is_locked() && back.is_solo() && back.is_muted());
+    assert_eq!(back.height(), Some(48.0));
+
+    track.set_locked(false);
+    track.set_solo(false);
+    track.set_muted(false);
+    track.set_height(Some(f64::NAN));
+    let ai = track.metadata["tellers.ai"].as_object().unwrap();
+    assert_eq!(ai.keys().collect::<Vec<_>>(), vec!["timeline_id"]);
+}
+
+#[test]
+fn edits_on_a_locked_track_are_refused() {
+    let mut stack = two_video_tracks();
+    stack.children[0].set_locked(true);
+    let before = stack.clone();
+
+    assert!(stack.delete_item("V1", true).is_empty());
+    assert!(!stack.split_item_at_time("V2", 3.0));
+    assert!(!stack.resize_item("V1", 0.0, 1.0, OverlapPolicy::Override, false));
+    assert!(!stack.move_item_at_time(
+        "V1",
+        "v2",
+        5.0,
+        true,
+        InsertPolicy::InsertBefore,
+        OverlapPolicy::Override,
+    ));
+    assert!(stack
+        .insert_item_at_time(
+            0,
+            1.0,
+            Item::Clip(clip(1.0, Some("new"))),
+            OverlapPolicy::Override,
+            InsertPolicy::SplitAndInsert,
+            None,
+            None,
+        )
+        .is_none());
+    assert!(stack.delete_track("v1").is_none());
+    assert_eq!(stack, before);
+}
+
+#[test]
+fn other_tracks_stay_editable() {
+    let mut stack = two_video_tracks();
+    stack.children[0].set_locked(true);
+
+    assert!(stack.split_item_at_time("W", 1.0));
+    assert_eq!(stack.children[1].items.len(), 2);
+    // Moving a track does not change it.
+    assert!(stack.reorder_track("v1", 2));
+    assert_eq!(stack.children[1].get_id().as_deref(), Some("v1"));
+    assert!(stack.delete_track("v2").is_some());
+}
+
+#[test]
+fn synced_clips_on_a_locked_track_hold_their_partners() {
+    let mut stack = stack_with_synced_audio_below_video();
+    stack.children[0].set_locked(true);
+    let before = stack.clone();
+
+    assert!(!stack.move_item_at_time(
+        "linked-video",
+        "v",
+        4.0,
+        true,
+        InsertPolicy::InsertBefore,
+        OverlapPolicy::Override,
+    ));
+    assert!(!stack.resize_item("linked-video", 2.0, 1.0, OverlapPolicy::Override, false));
+    assert!(stack.delete_item("linked-video", true).is_empty());
+    assert_eq!(stack, before);
+
+    stack.children[0].set_locked(false);
+    assert_eq!(stack.delete_item("linked-video", true).len(), 2);
+}
+
+#[test]
+fn solo_and_mute_decide_which_tracks_render() {
+    let mut video = two_video_tracks();
+    let mut audio = Track::new(TrackKind::Audio, Some("a".to_string()));
+    audio.items.push(clip_item(4.0, "file:///a.wav", "A"));
+    video.children.push(audio);
+    let mut timeline = Timeline {
+        tracks: video,
+        ..Timeline::default()
+    };
+    assert!((0..3).all(|i| timeline.tracks.is_track_rendered(i)));
+
+    // Solo only silences tracks of the same kind.
+    timeline.tracks.children[0].set_solo(true);
+    let rendered: Vec<_> = (0..3)
+        .map(|i| timeline.tracks.is_track_rendered(i))
+        .collect();
+    assert_eq!(rendered, vec![true, false, true]);
+
+    let composition = timeline.composition_at(1.0);
+    let active: Vec<_> = composition
+        .video
+        .iter()
+        .map(|layer| (layer.clip_id.as_deref().unwrap(), layer.is_active()))
+        .collect();
+    assert_eq!(active, vec![("W", false), ("V1", true)]);
+    assert!(composition.video[0].track_enabled);
+
+    timeline.tracks.children[2].set_muted(true);
+    assert!(!timeline.composition_at(1.0).audio[0].is_active());
+    let urls: Vec<_> = timeline
+        .to_ffmpeg(&Default::default())
+        .inputs
+        .into_iter()
+        .map(|input| input.url)
+        .collect();
+    assert_eq!(urls, vec!["file:///v1.mov", "file:///v2.mov"]);
+}
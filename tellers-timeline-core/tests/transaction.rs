@@ -0,0 +1,229 @@
+//! `Stack::transaction`: several edits applied atomically, rolled back on the
+//! first failure, sanitized once at commit.
+
+mod common;
+
+use common::*;
+use tellers_timeline_core::TransactionError;
+
+// v: [A 0..2][B 2..4][C 4..6]
+fn stack() -> Stack {
+    let mut video = Track::new(TrackKind::Video, Some("v".to_string()));
+    for id in ["A", "B", "C"] {
+        video.items.push(Item::Clip(clip(2.0, Some(id))));
+    }
+    Stack {
+        children: vec![video],
+        ..Stack::default()
+    }
+}
+
+fn ids(stack: &Stack) -> Vec<Option<String>> {
+    stack.children[0]
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::Clip(_) => item.get_id(),
+            Item::Gap(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn edits_commit_together_and_sanitize_once() {
+    let mut stack = stack();
+    // An unsanitized track: any sanitize pass merges its two gaps.
+    let mut other = Track::new(TrackKind::Video, Some("w".to_string()));
+    other.items = vec![
+        Item::Gap(Gap::make_gap(1.0)),
+        Item::Gap(Gap::make_gap(1.0)),
+        Item::Clip(clip(1.0, Some("X"))),
+    ];
+    stack.children.push(other);
+
+    let applied = stack
+        .transaction(|tx| {
+            tx.apply(|s| s.resize_item("A", 0.0, 1.5, OverlapPolicy::Override, false))?;
+            tx.apply(|s| s.resize_item("C", 4.0, 1.0, OverlapPolicy::Override, false))?;
+            assert_eq!(tx.children[1].items.len(), 3);
+            Ok::<_, TransactionError>(tx.applied())
+        })
+        .unwrap();
+
+    assert_eq!(applied, 2);
+    assert_eq!(stack.children[1].items.len(), 2);
+    assert_eq!(stack.children[1].items[0].duration(), 2.0);
+    assert_eq!(stack.children[0].items[0].duration(), 1.5);
+
+    // Outside a transaction the same edit sanitizes right away.
+    let mut direct = stack.clone();
+    direct.children[1]
+        .items
+        .insert(0, Item::Gap(Gap::make_gap(1.0)));
+    assert!(direct.resize_item("A", 0.0, 1.0, OverlapPolicy::Override, false));
+    assert_eq!(direct.children[1].items.len(), 2);
+}
+
+#[test]
+fn the_first_failure_rolls_everything_back() {
+    let mut stack = stack();
+    let before = stack.clone();
+
+    let result = stack.transaction(|tx| {
+        tx.apply(|s| s.delete_item("A", false))?;
+        tx.apply(|s| s.split_item_at_time("missing", 1.0))?;
+        tx.apply(|s| s.delete_item("B", false))
+    });
+
+    assert_eq!(result, Err(TransactionError::EditFailed { index: 1 }));
+    assert_eq!(stack, before);
+}
+
+#[test]
+fn an_ignored_failure_still_rolls_back() {
+    let mut stack = stack();
+    let before = stack.clone();
+
+    let result = stack.transaction(|tx| {
+        let _ = tx.apply(|s| s.split_item_at_time("missing", 1.0));
+        // Later edits do not run once the transaction has failed.
+        assert!(tx.apply(|s| s.delete_item("A", false)).is_err());
+        Ok::<_, TransactionError>(())
+    });
+
+    assert_eq!(result, Err(TransactionError::EditFailed { index: 0 }));
+    assert_eq!(stack, before);
+}
+
+#[derive(Debug, PartialEq)]
+enum AppError {
+    Edit(TransactionError),
+    Rejected,
+}
+
+impl From<TransactionError> for AppError {
+    fn from(error: TransactionError) -> Self {
+        AppError::Edit(error)
+    }
+}
+
+#[test]
+fn caller_errors_roll_back_and_inner_transactions_nest() {
+    let mut stack = stack();
+    let before = stack.clone();
+
+    let result = stack.transaction(|tx| {
+        tx.apply(|s| s.delete_item("A", false))?;
+        Err::<(), _>(AppError::Rejected)
+    });
+    assert_eq!(result, Err(AppError::Rejected));
+    assert_eq!(stack, before);
+
+    stack
+        .transaction(|tx| {
+            tx.apply(|s| s.delete_item("A", false))?;
+            // The inner batch fails and only undoes its own edits.
+            tx.apply(|s| {
+                s.transaction(|inner| {
+                    inner.apply(|s| s.delete_item("B", false))?;
+                    inner.apply(|s| s.delete_item("missing", false))
+                })
+                .is_err()
+            })?;
+            Ok::<_, TransactionError>(())
+        })
+        .unwrap();
+    assert_eq!(
+        ids(&stack),
+        vec![Some("B".to_string()), Some("C".to_string())]
+    );
+}
+
+#[test]
+fn begun_transactions_commit_or_roll_back_explicitly() {
+    let mut stack = stack();
+    let before = stack.clone();
+
+    let pending = stack.begin_transaction();
+    assert_eq!(stack.delete_item("A", false).len(), 1);
+    pending.rollback(&mut stack).unwrap();
+    assert_eq!(stack, before);
+
+    let pending = stack.begin_transaction();
+    assert_eq!(stack.delete_item("A", false).len(), 1);
+    pending.commit(&mut stack).unwrap();
+    assert_eq!(
+        ids(&stack),
+        vec![Some("B".to_string()), Some("C".to_string())]
+    );
+}
+
+// `stack()` plus an unsanitized track: any sanitize pass merges its gaps.
+fn stack_with_unsanitized_track() -> Stack {
+    let mut stack = stack();
+    let mut other = Track::new(TrackKind::Video, Some("w".to_string()));
+    other.items = vec![
+        Item::Gap(Gap::make_gap(1.0)),
+        Item::Gap(Gap::make_gap(1.0)),
+        Item::Clip(clip(1.0, Some("X"))),
+    ];
+    stack.children.push(other);
+    stack
+}
+
+#[test]
+fn only_the_stack_in_the_transaction_defers_sanitizing() {
+    let mut stack = stack_with_unsanitized_track();
+    let pending = stack.begin_transaction();
+    assert!(stack.split_item_at_time("A", 1.0));
+    assert_eq!(stack.children[1].items.len(), 3);
+
+    let mut unrelated = stack_with_unsanitized_track();
+    assert!(unrelated.split_item_at_time("A", 1.0));
+    assert_eq!(unrelated.children[1].items.len(), 2);
+    // A copy is outside the transaction too.
+    let mut copy = stack.clone();
+    assert!(copy.split_item_at_time("B", 3.0));
+    assert_eq!(copy.children[1].items.len(), 2);
+
+    let timeline = Timeline {
+        tracks: stack_with_unsanitized_track(),
+        ..Timeline::default()
+    };
+    let cut = timeline.subrange(0.0, 3.0).unwrap();
+    assert!(cut
+        .tracks
+        .children
+        .iter()
+        .flat_map(|track| &track.items)
+        .all(|item| item.get_id().is_some()));
+
+    pending.commit(&mut stack).unwrap();
+    assert_eq!(stack.children[1].items.len(), 2);
+}
+
+#[test]
+fn pending_transactions_end_only_on_their_own_stack() {
+    let mut stack = stack_with_unsanitized_track();
+    let mut other = stack.clone();
+    let before = other.clone();
+
+    let pending = stack.begin_transaction();
+    assert!(stack.split_item_at_time("A", 1.0));
+    assert_eq!(pending.commit(&mut other), Err(TransactionError::NotOpen));
+    assert_eq!(other, before);
+
+    let pending = stack.begin_transaction();
+    assert_eq!(pending.rollback(&mut other), Err(TransactionError::NotOpen));
+    assert_eq!(other, before);
+}
+
+#[test]
+fn dropping_a_pending_transaction_on_another_thread_ends_it() {
+    let mut stack = stack_with_unsanitized_track();
+    let pending = stack.begin_transaction();
+    std::thread::spawn(move || drop(pending)).join().unwrap();
+
+    assert!(stack.split_item_at_time("A", 1.0));
+    assert_eq!(stack.children[1].items.len(), 2);
+}
@@ -11,8 +11,7 @@ fn clip_json() -> &'static str {
     }"#
 }
 
-#[test]
-fn track_set_volume_applies_to_every_clip() {
+fn audio_track() -> Track {
     let track_json = format!(
         r#"{{
             "OTIO_SCHEMA": "Track.1",
@@ -21,17 +20,43 @@ fn track_set_volume_applies_to_every_clip() {
         }}"#,
         c = clip_json(),
     );
-    let mut track: Track = serde_json::from_str(&track_json).unwrap();
+    serde_json::from_str(&track_json).unwrap()
+}
 
-    // Clips start at the default volume.
-    for item in &track.items {
-        assert_eq!(item.get_volume(), 1.0);
-    }
+#[test]
+fn track_set_volume_keeps_clip_volumes() {
+    let mut track = audio_track();
+    track.items[1].set_volume(0.5);
+    assert_eq!(track.get_volume(), 1.0);
 
     track.set_volume(0.25);
 
-    // Every item now reflects the track volume.
-    for item in &track.items {
-        assert_eq!(item.get_volume(), 0.25);
-    }
+    // The per-clip mix is untouched; the track gain multiplies it.
+    assert_eq!(track.get_volume(), 0.25);
+    assert_eq!(track.items[0].get_volume(), 1.0);
+    assert_eq!(track.items[1].get_volume(), 0.5);
+    assert_eq!(track.effective_volume(0), Some(0.25));
+    assert_eq!(track.effective_volume(1), Some(0.125));
+    assert_eq!(track.effective_volume(2), None);
+
+    // Setting it again replaces the effect rather than stacking another.
+    track.set_volume(0.5);
+    assert_eq!(track.effects.len(), 1);
+    assert_eq!(track.effective_volume(1), Some(0.25));
+}
+
+#[test]
+fn track_pan_is_clamped_and_round_trips_with_the_gain() {
+    let mut track = audio_track();
+    assert_eq!(track.get_pan(), 0.0);
+
+    track.set_pan(-3.0);
+    track.set_volume(0.8);
+    assert_eq!(track.get_pan(), -1.0);
+
+    let json = serde_json::to_string(&track).unwrap();
+    let back: Track = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.get_pan(), -1.0);
+    assert_eq!(back.get_volume(), 0.8);
+    assert_eq!(back.effects, track.effects);
 }